@@ -0,0 +1,532 @@
+"""Client for the axionax Faucet API
+
+Generated by tools/sdk-gen from tools/faucet/openapi.json. Do not edit.
+"""
+
+import json
+import urllib.error
+import urllib.request
+from typing import Any, Dict, List, Literal, Optional, TypedDict
+from urllib.parse import quote, urlencode
+
+__version__ = "1.0.0"
+
+
+class HealthResponse(TypedDict):
+    status: str
+    service: str
+    version: str
+
+
+class _ReadyResponseRequired(TypedDict):
+    ready: bool
+    role: Literal["standalone", "leader", "follower"]
+
+
+class ReadyResponse(_ReadyResponseRequired, total=False):
+    replica_id: str
+    leader: str
+    load_shedding: "SheddingStatus"
+
+
+class _SheddingStatusRequired(TypedDict):
+    shedding: bool
+    percent: int
+    shed_total: int
+
+
+class SheddingStatus(_SheddingStatusRequired, total=False):
+    rpc_p95_ms: Optional[int]
+    threshold_ms: int
+
+
+class ReceiptDomain(TypedDict):
+    name: str
+    version: str
+    chainId: int
+
+
+class ReceiptInfo(TypedDict):
+    signer: str
+    domain: "ReceiptDomain"
+    type: str
+
+
+class _InfoResponseRequired(TypedDict):
+    chain_id: int
+    amount: str
+    cooldown_hours: int
+    network: str
+    symbol: str
+    maintenance: Optional["Maintenance"]
+    receipts: "ReceiptInfo"
+
+
+class InfoResponse(_InfoResponseRequired, total=False):
+    explorer_tx_url: Optional[str]
+    support_url: Optional[str]
+    logo_url: Optional[str]
+    config_version: Optional[int]
+
+
+class PowSolution(TypedDict):
+    challenge: str
+    nonce: int
+
+
+class _FaucetRequestRequired(TypedDict):
+    address: str
+
+
+class FaucetRequest(_FaucetRequestRequired, total=False):
+    captcha_token: str
+    pow: "PowSolution"
+    referral: str
+    allow_contract: bool
+    fingerprint: str
+    identity: "IdentityProof"
+    chain_id: int
+
+
+class _IdentityProofRequired(TypedDict):
+    provider: Literal["brightid", "worldid"]
+
+
+class IdentityProof(_IdentityProofRequired, total=False):
+    """Proof of personhood for larger drips and shorter cooldowns"""
+
+    proof: Dict[str, Any]
+
+
+ChallengeKind = Literal["captcha", "pow"]
+
+
+class SignedReceipt(TypedDict):
+    claim_id: str
+    recipient: str
+    amount: str
+    timestamp: int
+    tx_hash: str
+    chain_id: int
+    signer: str
+    signature: str
+
+
+class _FaucetResponseRequired(TypedDict):
+    success: bool
+
+
+class FaucetResponse(_FaucetResponseRequired, total=False):
+    tx_hash: str
+    explorer_url: str
+    amount: str
+    resolved_address: str
+    message: str
+    error: str
+    kind: "ErrorKind"
+    code: str
+    challenge: "ChallengeKind"
+    receipt: "SignedReceipt"
+    eta: int
+    errors: List["FieldError"]
+    support_url: str
+
+
+class FieldError(TypedDict):
+    field: str
+    code: Literal["required", "invalid_type", "invalid_address", "invalid_name", "unknown_chain"]
+    message: str
+
+
+ErrorKind = Literal["validation", "unauthorized", "forbidden", "not_found", "rate_limit", "unavailable", "rpc", "signing", "storage", "internal"]
+
+
+class _ClaimEventRequired(TypedDict):
+    claim_id: str
+    tx_hash: str
+    status: Literal["pending", "confirmed", "failed"]
+
+
+class ClaimEvent(_ClaimEventRequired, total=False):
+    explorer_url: str
+    block_number: int
+
+
+class _MaintenanceRequired(TypedDict):
+    message: str
+    since: int
+
+
+class Maintenance(_MaintenanceRequired, total=False):
+    eta: int
+
+
+class MaintenanceRequest(TypedDict, total=False):
+    message: str
+    eta: int
+
+
+class MaintenanceResponse(TypedDict):
+    maintenance: Optional["Maintenance"]
+
+
+class _RolloutRequired(TypedDict):
+    percent: int
+
+
+class Rollout(_RolloutRequired, total=False):
+    partners: List[str]
+
+
+class FlagStatus(TypedDict):
+    name: str
+    percent: int
+    partners: List[str]
+    source: Literal["env", "bundle", "admin"]
+
+
+class FlagsResponse(TypedDict):
+    flags: List["FlagStatus"]
+
+
+class PowChallenge(TypedDict):
+    challenge: str
+    difficulty: int
+    expires_in_secs: int
+
+
+class StatsResponse(TypedDict):
+    total_requests: int
+    total_distributed: str
+    faucet_balance: str
+    cooldown_hours: int
+    amount_per_request: str
+    total_top_ups: int
+    total_topped_up: str
+
+
+class TaskStatus(TypedDict):
+    id: str
+    description: str
+    completed: bool
+
+
+class _BadgeRequired(TypedDict):
+    address: str
+    status: Literal["queued", "minted", "failed"]
+    queued: int
+
+
+class Badge(_BadgeRequired, total=False):
+    tx_hash: str
+    explorer_url: str
+
+
+Bucket = Literal["hour", "day"]
+
+
+class BucketStats(TypedDict):
+    start: int
+    requests: int
+    grants: int
+    rejects: int
+    distributed: str
+
+
+class TimeseriesResponse(TypedDict):
+    bucket: "Bucket"
+    series: List["BucketStats"]
+
+
+class _RunwayRequired(TypedDict):
+    balance: str
+    window_days: int
+    distributed: str
+    burn_per_day: str
+
+
+class Runway(_RunwayRequired, total=False):
+    runway_days: float
+    depletes_at: int
+
+
+Ranking = Literal["contracts", "transactions"]
+
+
+class LeaderboardEntry(TypedDict):
+    rank: int
+    address: str
+    first_drip: int
+    transactions: int
+    contracts: int
+
+
+class LeaderboardResponse(TypedDict):
+    by: "Ranking"
+    days: int
+    entries: List["LeaderboardEntry"]
+
+
+class _GeoAggregateRequired(TypedDict):
+    requests: int
+    grants: int
+
+
+class GeoAggregate(_GeoAggregateRequired, total=False):
+    country: str
+    asn: int
+    as_org: str
+
+
+class PartnerStats(TypedDict):
+    partner: str
+    requests: int
+    grants: int
+    distributed: str
+    daily_budget: str
+    budget_remaining: str
+
+
+class _SloStatusRequired(TypedDict):
+    route: str
+    threshold_ms: int
+    target: float
+    requests: int
+    good: int
+    met: bool
+
+
+class SloStatus(_SloStatusRequired, total=False):
+    compliance: Optional[float]
+
+
+class SloResponse(TypedDict):
+    window_minutes: int
+    objectives: List["SloStatus"]
+
+
+class AdminStatsResponse(TypedDict):
+    window_hours: int
+    countries: List["GeoAggregate"]
+    asns: List["GeoAggregate"]
+    partners: List["PartnerStats"]
+    alias_clusters: List["AliasCluster"]
+
+
+class AliasCluster(TypedDict):
+    size: int
+    sessions: int
+    grants: int
+    addresses: List[str]
+
+
+class _AuditEntryRequired(TypedDict):
+    id: int
+    timestamp: int
+    actor: str
+    action: str
+    payload: Any
+
+
+class AuditEntry(_AuditEntryRequired, total=False):
+    ip: str
+
+
+class AuditResponse(TypedDict):
+    entries: List["AuditEntry"]
+
+
+class _AddressLabelRequired(TypedDict):
+    address: str
+    label: str
+    added_by: str
+    added: int
+
+
+class AddressLabel(_AddressLabelRequired, total=False):
+    note: str
+
+
+class LabelRequest(TypedDict, total=False):
+    note: str
+
+
+class LabelsResponse(TypedDict):
+    labels: List["AddressLabel"]
+
+
+class _WhyCheckRequired(TypedDict):
+    check: str
+    passed: bool
+
+
+class WhyCheck(_WhyCheckRequired, total=False):
+    code: str
+    message: str
+
+
+class _WhyResponseRequired(TypedDict):
+    address: str
+    ip: str
+    eligible: bool
+    amount: str
+    banned: bool
+    cooldown: Dict[str, Any]
+    ip_limit: Dict[str, Any]
+    lifetime: Dict[str, Any]
+    checks: List["WhyCheck"]
+
+
+class WhyResponse(_WhyResponseRequired, total=False):
+    name: str
+    rejected_by: "WhyCheck"
+    labels: List["AddressLabel"]
+
+
+class ApiError(Exception):
+    """Non-2xx response; `body` holds the decoded error payload"""
+
+    def __init__(self, status: int, body: Any):
+        super().__init__(f"HTTP {status}")
+        self.status = status
+        self.body = body
+
+
+class FaucetClient:
+    """Client for the axionax Faucet API"""
+
+    def __init__(self, base_url: str, token: Optional[str] = None, timeout: float = 30.0):
+        self.base_url = base_url.rstrip("/")
+        self.token = token
+        self.timeout = timeout
+
+    def health(self) -> "HealthResponse":
+        """Health check"""
+        return self._request("GET", "/health", None, None, False)
+
+    def readyz(self) -> "ReadyResponse":
+        """Readiness and replica role"""
+        return self._request("GET", "/readyz", None, None, False)
+
+    def info(self) -> "InfoResponse":
+        """Faucet network, drip amount and receipt signing domain"""
+        return self._request("GET", "/v1/info", None, None, False)
+
+    def request_tokens(self, body: "FaucetRequest") -> "FaucetResponse":
+        """Request a drip to an address or name"""
+        return self._request("POST", "/v1/request", None, body, False)
+
+    def pow_challenge(self) -> "PowChallenge":
+        """Issue a proof-of-work challenge"""
+        return self._request("GET", "/v1/challenge", None, None, False)
+
+    def stats(self) -> "StatsResponse":
+        """Totals distributed and faucet balance"""
+        return self._request("GET", "/v1/stats", None, None, False)
+
+    def get_receipt(self, claim_id: str) -> "SignedReceipt":
+        """Look up a signed drip receipt"""
+        return self._request("GET", f"/v1/receipt/{quote(claim_id, safe='')}", None, None, False)
+
+    def task_status(self, address: str) -> List["TaskStatus"]:
+        """Completion of the on-chain tasks required before a drip"""
+        return self._request("GET", f"/v1/tasks/{quote(address, safe='')}", None, None, False)
+
+    def badge(self, address: str) -> "Badge":
+        """Builder badge minted to an address after its first drip; 404 when none was queued"""
+        return self._request("GET", f"/v1/badges/{quote(address, safe='')}", None, None, False)
+
+    def why(self, address: str, ip: Optional[str] = None) -> "WhyResponse":
+        """Explain the grant decision for an address without sending anything"""
+        return self._request("GET", f"/v1/why/{quote(address, safe='')}", {"ip": ip}, None, True)
+
+    def runway(self, days: Optional[int] = None) -> "Runway":
+        """Burn rate and projected days of runway for the current balance"""
+        return self._request("GET", "/v1/stats/runway", {"days": days}, None, False)
+
+    def timeseries(self, bucket: Optional["Bucket"] = None, buckets: Optional[int] = None) -> "TimeseriesResponse":
+        """Request, grant, reject and distribution counts per time bucket"""
+        return self._request("GET", "/v1/stats/timeseries", {"bucket": bucket, "buckets": buckets}, None, False)
+
+    def leaderboard(self, by: Optional["Ranking"] = None, days: Optional[int] = None, limit: Optional[int] = None) -> "LeaderboardResponse":
+        """Most active drip recipients"""
+        return self._request("GET", "/v1/leaderboard", {"by": by, "days": days, "limit": limit}, None, False)
+
+    def metrics(self) -> str:
+        """Per-route request counters and latency histograms (Prometheus text format)"""
+        return self._request("GET", "/metrics", None, None, False)
+
+    def slo(self) -> "SloResponse":
+        """Rolling compliance with the latency SLOs"""
+        return self._request("GET", "/v1/slo", None, None, False)
+
+    def admin_stats(self, hours: Optional[int] = None) -> "AdminStatsResponse":
+        """Request aggregates per country, ASN and partner"""
+        return self._request("GET", "/v1/admin/stats", {"hours": hours}, None, True)
+
+    def start_maintenance(self, body: "MaintenanceRequest") -> "MaintenanceResponse":
+        """Pause drips with a message and ETA, or update an ongoing pause"""
+        return self._request("PUT", "/v1/admin/maintenance", None, body, True)
+
+    def end_maintenance(self) -> "MaintenanceResponse":
+        """Resume drips"""
+        return self._request("DELETE", "/v1/admin/maintenance", None, None, True)
+
+    def admin_flags(self) -> "FlagsResponse":
+        """Feature flags with their effective rollout and its source"""
+        return self._request("GET", "/v1/admin/flags", None, None, True)
+
+    def set_flag(self, name: str, body: "Rollout") -> "FlagsResponse":
+        """Override the rollout of a feature flag on every replica"""
+        return self._request("PUT", f"/v1/admin/flags/{quote(name, safe='')}", None, body, True)
+
+    def clear_flag(self, name: str) -> "FlagsResponse":
+        """Drop the override of a feature flag"""
+        return self._request("DELETE", f"/v1/admin/flags/{quote(name, safe='')}", None, None, True)
+
+    def admin_labeled(self, label: Optional[str] = None) -> "LabelsResponse":
+        """Addresses carrying a label, most recently labeled first"""
+        return self._request("GET", "/v1/admin/labels", {"label": label}, None, True)
+
+    def admin_labels(self, address: str) -> "LabelsResponse":
+        """Labels of an address"""
+        return self._request("GET", f"/v1/admin/labels/{quote(address, safe='')}", None, None, True)
+
+    def set_label(self, address: str, label: str, body: "LabelRequest") -> "LabelsResponse":
+        """Attach a label to an address, or update its note"""
+        return self._request("PUT", f"/v1/admin/labels/{quote(address, safe='')}/{quote(label, safe='')}", None, body, True)
+
+    def remove_label(self, address: str, label: str) -> "LabelsResponse":
+        """Detach a label from an address; not_found if it wasn't attached"""
+        return self._request("DELETE", f"/v1/admin/labels/{quote(address, safe='')}/{quote(label, safe='')}", None, None, True)
+
+    def admin_audit(self, actor: Optional[str] = None, action: Optional[str] = None, since: Optional[int] = None, limit: Optional[int] = None) -> "AuditResponse":
+        """Audit log of admin actions, newest first; the query itself is audited"""
+        return self._request("GET", "/v1/admin/audit", {"actor": actor, "action": action, "since": since, "limit": limit}, None, True)
+
+    def _request(self, method: str, path: str, query: Optional[Dict[str, Any]],
+                 body: Any, auth: bool) -> Any:
+        url = self.base_url + path
+        params = {k: v for k, v in (query or {}).items() if v is not None}
+        if params:
+            url += "?" + urlencode(params)
+        headers = {"Accept": "application/json"}
+        data = None
+        if body is not None:
+            headers["Content-Type"] = "application/json"
+            data = json.dumps(body).encode()
+        if auth and self.token:
+            headers["Authorization"] = f"Bearer {self.token}"
+        request = urllib.request.Request(url, data=data, headers=headers, method=method)
+        try:
+            with urllib.request.urlopen(request, timeout=self.timeout) as response:
+                return _decode(response.headers.get("Content-Type", ""), response.read())
+        except urllib.error.HTTPError as e:
+            raise ApiError(e.code, _decode(e.headers.get("Content-Type", ""), e.read())) from None
+
+
+def _decode(content_type: str, data: bytes) -> Any:
+    text = data.decode()
+    if not text:
+        return None
+    return json.loads(text) if "json" in content_type else text
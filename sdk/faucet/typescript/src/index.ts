@@ -0,0 +1,556 @@
+// Generated by tools/sdk-gen from tools/faucet/openapi.json. Do not edit.
+
+export interface HealthResponse {
+  status: string;
+  service: string;
+  version: string;
+}
+
+export interface ReadyResponse {
+  ready: boolean;
+  role: "standalone" | "leader" | "follower";
+  replica_id?: string;
+  /** Replica currently holding the sender lease */
+  leader?: string;
+  load_shedding?: SheddingStatus;
+}
+
+export interface SheddingStatus {
+  /** Whether /request traffic is being shed for slow RPC */
+  shedding: boolean;
+  /** p95 RPC latency over the window, null with too few calls */
+  rpc_p95_ms?: number | null;
+  threshold_ms?: number;
+  /** Share of non-priority requests shed */
+  percent: number;
+  /** Requests shed since startup */
+  shed_total: number;
+}
+
+export interface ReceiptDomain {
+  name: string;
+  version: string;
+  chainId: number;
+}
+
+export interface ReceiptInfo {
+  /** Address that signs receipts */
+  signer: string;
+  domain: ReceiptDomain;
+  /** EIP-712 type of a receipt */
+  type: string;
+}
+
+export interface InfoResponse {
+  chain_id: number;
+  amount: string;
+  cooldown_hours: number;
+  /** Network name, NETWORK_NAME */
+  network: string;
+  /** Token symbol used in amounts, TOKEN_SYMBOL */
+  symbol: string;
+  /** Explorer link template with a {tx} placeholder */
+  explorer_tx_url?: string | null;
+  support_url?: string | null;
+  logo_url?: string | null;
+  /** Ongoing maintenance window; drips are paused while set */
+  maintenance: Maintenance | null;
+  /** Version of the applied config bundle, null on local configuration */
+  config_version?: number | null;
+  receipts: ReceiptInfo;
+}
+
+export interface PowSolution {
+  challenge: string;
+  nonce: number;
+}
+
+export interface FaucetRequest {
+  /** Recipient address or name such as alice.axx */
+  address: string;
+  captcha_token?: string;
+  pow?: PowSolution;
+  /** Signed referral token from a partner dApp */
+  referral?: string;
+  /** Send to the address even if it is a contract */
+  allow_contract?: boolean;
+  /** Client session fingerprint; addresses requested from one session share a cooldown */
+  fingerprint?: string;
+  identity?: IdentityProof;
+  /** Chain the client expects; rejected if the faucet sends on another */
+  chain_id?: number;
+}
+
+/** Proof of personhood for larger drips and shorter cooldowns */
+export interface IdentityProof {
+  provider: "brightid" | "worldid";
+  /** Provider-specific proof, e.g. the IDKit result for worldid; unused by brightid */
+  proof?: unknown;
+}
+
+export type ChallengeKind = "captcha" | "pow";
+
+export interface SignedReceipt {
+  claim_id: string;
+  recipient: string;
+  /** Amount in wei, as a decimal string */
+  amount: string;
+  timestamp: number;
+  tx_hash: string;
+  chain_id: number;
+  signer: string;
+  signature: string;
+}
+
+export interface FaucetResponse {
+  success: boolean;
+  tx_hash?: string;
+  /** Explorer link of tx_hash, when EXPLORER_TX_URL is set */
+  explorer_url?: string;
+  amount?: string;
+  /** Address a requested name resolved to */
+  resolved_address?: string;
+  message?: string;
+  error?: string;
+  kind?: ErrorKind;
+  /** Machine-readable error code, e.g. too_soon */
+  code?: string;
+  challenge?: ChallengeKind;
+  receipt?: SignedReceipt;
+  /** Expected end of maintenance (unix seconds) */
+  eta?: number;
+  /** Every invalid field of a rejected request body */
+  errors?: FieldError[];
+  /** Where users can get help, on errors */
+  support_url?: string;
+}
+
+export interface FieldError {
+  /** Path of the field, e.g. pow.nonce; empty for the body itself */
+  field: string;
+  code: "required" | "invalid_type" | "invalid_address" | "invalid_name" | "unknown_chain";
+  message: string;
+}
+
+/** Error class, which determines the HTTP status */
+export type ErrorKind = "validation" | "unauthorized" | "forbidden" | "not_found" | "rate_limit" | "unavailable" | "rpc" | "signing" | "storage" | "internal";
+
+export interface ClaimEvent {
+  claim_id: string;
+  tx_hash: string;
+  /** Explorer link of tx_hash, when EXPLORER_TX_URL is set */
+  explorer_url?: string;
+  status: "pending" | "confirmed" | "failed";
+  /** Block the transaction was mined in */
+  block_number?: number;
+}
+
+export interface Maintenance {
+  /** Message shown to requesters */
+  message: string;
+  /** Expected end (unix seconds) */
+  eta?: number;
+  /** When maintenance started (unix seconds) */
+  since: number;
+}
+
+export interface MaintenanceRequest {
+  /** Message shown to requesters */
+  message?: string;
+  /** Expected end (unix seconds) */
+  eta?: number;
+}
+
+export interface MaintenanceResponse {
+  maintenance: Maintenance | null;
+}
+
+export interface Rollout {
+  /** Share of clients the flag is on for */
+  percent: number;
+  /** Partners the flag is always on for */
+  partners?: string[];
+}
+
+export interface FlagStatus {
+  name: string;
+  percent: number;
+  partners: string[];
+  source: "env" | "bundle" | "admin";
+}
+
+export interface FlagsResponse {
+  flags: FlagStatus[];
+}
+
+export interface PowChallenge {
+  challenge: string;
+  difficulty: number;
+  expires_in_secs: number;
+}
+
+export interface StatsResponse {
+  total_requests: number;
+  total_distributed: string;
+  faucet_balance: string;
+  cooldown_hours: number;
+  amount_per_request: string;
+  total_top_ups: number;
+  total_topped_up: string;
+}
+
+export interface TaskStatus {
+  id: string;
+  description: string;
+  completed: boolean;
+}
+
+export interface Badge {
+  address: string;
+  status: "queued" | "minted" | "failed";
+  /** Mint transaction, once sent */
+  tx_hash?: string;
+  /** Explorer link of tx_hash, when EXPLORER_TX_URL is set */
+  explorer_url?: string;
+  /** Unix time the badge was queued */
+  queued: number;
+}
+
+export type Bucket = "hour" | "day";
+
+export interface BucketStats {
+  start: number;
+  requests: number;
+  grants: number;
+  rejects: number;
+  distributed: string;
+}
+
+export interface TimeseriesResponse {
+  bucket: Bucket;
+  series: BucketStats[];
+}
+
+export interface Runway {
+  balance: string;
+  window_days: number;
+  /** Drips sent within the window */
+  distributed: string;
+  burn_per_day: string;
+  /** Days until the balance runs out at the current burn rate; absent without drips */
+  runway_days?: number;
+  /** Projected unix time the balance runs out */
+  depletes_at?: number;
+}
+
+export type Ranking = "contracts" | "transactions";
+
+export interface LeaderboardEntry {
+  rank: number;
+  address: string;
+  first_drip: number;
+  transactions: number;
+  contracts: number;
+}
+
+export interface LeaderboardResponse {
+  by: Ranking;
+  days: number;
+  entries: LeaderboardEntry[];
+}
+
+export interface GeoAggregate {
+  country?: string;
+  asn?: number;
+  as_org?: string;
+  requests: number;
+  grants: number;
+}
+
+export interface PartnerStats {
+  partner: string;
+  requests: number;
+  grants: number;
+  distributed: string;
+  daily_budget: string;
+  budget_remaining: string;
+}
+
+export interface SloStatus {
+  route: string;
+  threshold_ms: number;
+  /** Required share of good requests, in percent */
+  target: number;
+  requests: number;
+  good: number;
+  /** Share of good requests in percent, null without traffic */
+  compliance?: number | null;
+  met: boolean;
+}
+
+export interface SloResponse {
+  window_minutes: number;
+  objectives: SloStatus[];
+}
+
+export interface AdminStatsResponse {
+  window_hours: number;
+  countries: GeoAggregate[];
+  asns: GeoAggregate[];
+  partners: PartnerStats[];
+  /** Largest clusters of linked addresses granted within the window */
+  alias_clusters: AliasCluster[];
+}
+
+export interface AliasCluster {
+  /** Addresses in the cluster */
+  size: number;
+  /** Sessions linking the cluster */
+  sessions: number;
+  /** Grants to the cluster's addresses within the window */
+  grants: number;
+  /** Up to 10 addresses of the cluster */
+  addresses: string[];
+}
+
+export interface AuditEntry {
+  id: number;
+  /** Unix seconds */
+  timestamp: number;
+  actor: string;
+  action: string;
+  /** Parameters of the action */
+  payload: unknown;
+  ip?: string;
+}
+
+export interface AuditResponse {
+  entries: AuditEntry[];
+}
+
+export interface AddressLabel {
+  address: string;
+  label: string;
+  note?: string;
+  /** Admin who attached the label */
+  added_by: string;
+  /** Unix seconds */
+  added: number;
+}
+
+export interface LabelRequest {
+  note?: string;
+}
+
+export interface LabelsResponse {
+  labels: AddressLabel[];
+}
+
+export interface WhyCheck {
+  /** maintenance, chain_id, leader or a policy stage name */
+  check: string;
+  passed: boolean;
+  /** Error code a request would get */
+  code?: string;
+  message?: string;
+}
+
+export interface WhyResponse {
+  address: string;
+  name?: string;
+  ip: string;
+  eligible: boolean;
+  rejected_by?: WhyCheck;
+  amount: string;
+  /** The address or IP is on the blocklist */
+  banned: boolean;
+  /** Operator labels of the address, for admins only */
+  labels?: AddressLabel[];
+  cooldown: { last_grant: number | null; remaining_secs: number };
+  ip_limit: { key: string; available: number; burst: number; retry_after_secs?: number };
+  lifetime: { drips: number; total_received: string; first_drip: number | null; last_drip: number | null };
+  checks: WhyCheck[];
+}
+
+/** Non-2xx response; `body` holds the decoded error payload */
+export class ApiError extends Error {
+  constructor(public readonly status: number, public readonly body: unknown) {
+    super(`HTTP ${status}`);
+    this.name = "ApiError";
+  }
+}
+
+export interface FaucetClientOptions {
+  /** Bearer token for authenticated endpoints */
+  token?: string;
+  /** Extra headers sent with every request */
+  headers?: Record<string, string>;
+  /** fetch implementation, defaults to the global one */
+  fetch?: typeof fetch;
+}
+
+/** Client for the axionax Faucet API */
+export class FaucetClient {
+  constructor(
+    private readonly baseUrl: string,
+    private readonly options: FaucetClientOptions = {},
+  ) {}
+
+  /** Health check */
+  health(): Promise<HealthResponse> {
+    return this.request<HealthResponse>("GET", `/health`, undefined, undefined, false);
+  }
+
+  /** Readiness and replica role */
+  readyz(): Promise<ReadyResponse> {
+    return this.request<ReadyResponse>("GET", `/readyz`, undefined, undefined, false);
+  }
+
+  /** Faucet network, drip amount and receipt signing domain */
+  info(): Promise<InfoResponse> {
+    return this.request<InfoResponse>("GET", `/v1/info`, undefined, undefined, false);
+  }
+
+  /** Request a drip to an address or name */
+  requestTokens(body: FaucetRequest): Promise<FaucetResponse> {
+    return this.request<FaucetResponse>("POST", `/v1/request`, undefined, body, false);
+  }
+
+  /** Issue a proof-of-work challenge */
+  powChallenge(): Promise<PowChallenge> {
+    return this.request<PowChallenge>("GET", `/v1/challenge`, undefined, undefined, false);
+  }
+
+  /** Totals distributed and faucet balance */
+  stats(): Promise<StatsResponse> {
+    return this.request<StatsResponse>("GET", `/v1/stats`, undefined, undefined, false);
+  }
+
+  /** Look up a signed drip receipt */
+  getReceipt(claimId: string): Promise<SignedReceipt> {
+    return this.request<SignedReceipt>("GET", `/v1/receipt/${encodeURIComponent(claimId)}`, undefined, undefined, false);
+  }
+
+  /** Completion of the on-chain tasks required before a drip */
+  taskStatus(address: string): Promise<TaskStatus[]> {
+    return this.request<TaskStatus[]>("GET", `/v1/tasks/${encodeURIComponent(address)}`, undefined, undefined, false);
+  }
+
+  /** Builder badge minted to an address after its first drip; 404 when none was queued */
+  badge(address: string): Promise<Badge> {
+    return this.request<Badge>("GET", `/v1/badges/${encodeURIComponent(address)}`, undefined, undefined, false);
+  }
+
+  /** Explain the grant decision for an address without sending anything */
+  why(address: string, query: { ip?: string } = {}): Promise<WhyResponse> {
+    return this.request<WhyResponse>("GET", `/v1/why/${encodeURIComponent(address)}`, query, undefined, true);
+  }
+
+  /** Burn rate and projected days of runway for the current balance */
+  runway(query: { days?: number } = {}): Promise<Runway> {
+    return this.request<Runway>("GET", `/v1/stats/runway`, query, undefined, false);
+  }
+
+  /** Request, grant, reject and distribution counts per time bucket */
+  timeseries(query: { bucket?: Bucket; buckets?: number } = {}): Promise<TimeseriesResponse> {
+    return this.request<TimeseriesResponse>("GET", `/v1/stats/timeseries`, query, undefined, false);
+  }
+
+  /** Most active drip recipients */
+  leaderboard(query: { by?: Ranking; days?: number; limit?: number } = {}): Promise<LeaderboardResponse> {
+    return this.request<LeaderboardResponse>("GET", `/v1/leaderboard`, query, undefined, false);
+  }
+
+  /** Per-route request counters and latency histograms (Prometheus text format) */
+  metrics(): Promise<string> {
+    return this.request<string>("GET", `/metrics`, undefined, undefined, false);
+  }
+
+  /** Rolling compliance with the latency SLOs */
+  slo(): Promise<SloResponse> {
+    return this.request<SloResponse>("GET", `/v1/slo`, undefined, undefined, false);
+  }
+
+  /** Request aggregates per country, ASN and partner */
+  adminStats(query: { hours?: number } = {}): Promise<AdminStatsResponse> {
+    return this.request<AdminStatsResponse>("GET", `/v1/admin/stats`, query, undefined, true);
+  }
+
+  /** Pause drips with a message and ETA, or update an ongoing pause */
+  startMaintenance(body: MaintenanceRequest): Promise<MaintenanceResponse> {
+    return this.request<MaintenanceResponse>("PUT", `/v1/admin/maintenance`, undefined, body, true);
+  }
+
+  /** Resume drips */
+  endMaintenance(): Promise<MaintenanceResponse> {
+    return this.request<MaintenanceResponse>("DELETE", `/v1/admin/maintenance`, undefined, undefined, true);
+  }
+
+  /** Feature flags with their effective rollout and its source */
+  adminFlags(): Promise<FlagsResponse> {
+    return this.request<FlagsResponse>("GET", `/v1/admin/flags`, undefined, undefined, true);
+  }
+
+  /** Override the rollout of a feature flag on every replica */
+  setFlag(name: string, body: Rollout): Promise<FlagsResponse> {
+    return this.request<FlagsResponse>("PUT", `/v1/admin/flags/${encodeURIComponent(name)}`, undefined, body, true);
+  }
+
+  /** Drop the override of a feature flag */
+  clearFlag(name: string): Promise<FlagsResponse> {
+    return this.request<FlagsResponse>("DELETE", `/v1/admin/flags/${encodeURIComponent(name)}`, undefined, undefined, true);
+  }
+
+  /** Addresses carrying a label, most recently labeled first */
+  adminLabeled(query: { label: string } = {}): Promise<LabelsResponse> {
+    return this.request<LabelsResponse>("GET", `/v1/admin/labels`, query, undefined, true);
+  }
+
+  /** Labels of an address */
+  adminLabels(address: string): Promise<LabelsResponse> {
+    return this.request<LabelsResponse>("GET", `/v1/admin/labels/${encodeURIComponent(address)}`, undefined, undefined, true);
+  }
+
+  /** Attach a label to an address, or update its note */
+  setLabel(address: string, label: string, body: LabelRequest): Promise<LabelsResponse> {
+    return this.request<LabelsResponse>("PUT", `/v1/admin/labels/${encodeURIComponent(address)}/${encodeURIComponent(label)}`, undefined, body, true);
+  }
+
+  /** Detach a label from an address; not_found if it wasn't attached */
+  removeLabel(address: string, label: string): Promise<LabelsResponse> {
+    return this.request<LabelsResponse>("DELETE", `/v1/admin/labels/${encodeURIComponent(address)}/${encodeURIComponent(label)}`, undefined, undefined, true);
+  }
+
+  /** Audit log of admin actions, newest first; the query itself is audited */
+  adminAudit(query: { actor?: string; action?: string; since?: number; limit?: number } = {}): Promise<AuditResponse> {
+    return this.request<AuditResponse>("GET", `/v1/admin/audit`, query, undefined, true);
+  }
+
+  private async request<T>(
+    method: string,
+    path: string,
+    query: Record<string, string | number | boolean | undefined> | undefined,
+    body: unknown,
+    auth: boolean,
+  ): Promise<T> {
+    const url = new URL(this.baseUrl.replace(/\/$/, "") + path);
+    for (const [key, value] of Object.entries(query ?? {})) {
+      if (value !== undefined) url.searchParams.set(key, String(value));
+    }
+    const headers: Record<string, string> = { ...this.options.headers };
+    if (body !== undefined) headers["content-type"] = "application/json";
+    if (auth && this.options.token) {
+      headers.authorization = `Bearer ${this.options.token}`;
+    }
+    const response = await (this.options.fetch ?? fetch)(url, {
+      method,
+      headers,
+      body: body === undefined ? undefined : JSON.stringify(body),
+    });
+    const text = await response.text();
+    const json = response.headers.get("content-type")?.includes("json");
+    const payload = text && json ? JSON.parse(text) : text || undefined;
+    if (!response.ok) throw new ApiError(response.status, payload);
+    return payload as T;
+  }
+}
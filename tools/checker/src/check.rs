@@ -0,0 +1,216 @@
+//! Light validation of a block against its transactions and receipts
+//!
+//! Recomputes what the header commits to from the RPC data: transaction
+//! hashes, the transaction and receipt roots, receipt and block blooms, and
+//! the cumulative gas accounting. Nothing is re-executed.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::encode::{self, EncodeError, BLOOM_BYTES};
+use crate::trie;
+
+/// A commitment that doesn't match the data it commits to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Mismatch {
+    pub block: u64,
+    /// Name of the failed check, e.g. `receipts_root`
+    pub check: &'static str,
+    pub detail: String,
+}
+
+/// Outcome of checking one block
+#[derive(Debug, Default, Serialize)]
+pub struct BlockReport {
+    pub block: u64,
+    pub transactions: usize,
+    pub mismatches: Vec<Mismatch>,
+    /// Checks that couldn't run, such as roots over unsupported transaction types
+    pub skipped: Vec<String>,
+}
+
+impl BlockReport {
+    fn mismatch(&mut self, check: &'static str, detail: String) {
+        self.mismatches.push(Mismatch {
+            block: self.block,
+            check,
+            detail,
+        });
+    }
+
+    /// Compare a recomputed 32-byte commitment with the header's
+    fn compare(
+        &mut self,
+        check: &'static str,
+        field: &'static str,
+        header: &Value,
+        computed: &[u8],
+    ) {
+        match encode::hex_bytes(header, field) {
+            Ok(expected) if expected == computed => {}
+            Ok(_) => self.mismatch(
+                check,
+                format!(
+                    "header {} is {}, computed 0x{}",
+                    field,
+                    header.as_str().unwrap_or_default(),
+                    hex::encode(computed)
+                ),
+            ),
+            Err(e) => self.mismatch(check, e.to_string()),
+        }
+    }
+}
+
+/// Check a block fetched with full transactions against its receipts, in order
+pub fn check_block(block: &Value, receipts: &[Value]) -> Result<BlockReport, EncodeError> {
+    let transactions = block["transactions"]
+        .as_array()
+        .ok_or(EncodeError::Missing("transactions"))?;
+    let mut report = BlockReport {
+        block: encode::quantity(&block["number"], "number")?,
+        transactions: transactions.len(),
+        ..Default::default()
+    };
+
+    // Transaction hashes and root
+    let mut encoded = Vec::with_capacity(transactions.len());
+    for tx in transactions {
+        let hash = tx["hash"].as_str().unwrap_or_default();
+        match encode::transaction(tx) {
+            Ok(bytes) => {
+                let computed = trie::keccak(&bytes);
+                if encode::hex_bytes(&tx["hash"], "hash").ok().as_deref() != Some(&computed[..]) {
+                    report.mismatch(
+                        "tx_hash",
+                        format!("tx {} hashes to 0x{}", hash, hex::encode(computed)),
+                    );
+                }
+                encoded.push(bytes);
+            }
+            Err(e) => report.skipped.push(format!("tx {}: {}", hash, e)),
+        }
+    }
+    if encoded.len() == transactions.len() {
+        let root = trie::ordered_root(&encoded);
+        report.compare(
+            "transactions_root",
+            "transactionsRoot",
+            &block["transactionsRoot"],
+            &root,
+        );
+    } else {
+        report
+            .skipped
+            .push("transactions_root: not every transaction could be encoded".to_string());
+    }
+
+    if receipts.len() != transactions.len() {
+        report.mismatch(
+            "receipt_count",
+            format!(
+                "{} transactions but {} receipts",
+                transactions.len(),
+                receipts.len()
+            ),
+        );
+        return Ok(report);
+    }
+
+    let mut encoded = Vec::with_capacity(receipts.len());
+    let mut block_bloom = [0u8; BLOOM_BYTES];
+    let mut cumulative = 0u64;
+    let mut log_index = 0u64;
+    for (i, (tx, receipt)) in transactions.iter().zip(receipts).enumerate() {
+        let hash = tx["hash"].as_str().unwrap_or_default();
+        if receipt["transactionHash"] != tx["hash"] {
+            report.mismatch(
+                "receipt_order",
+                format!(
+                    "receipt {} is for {}, expected {}",
+                    i, receipt["transactionHash"], hash
+                ),
+            );
+        }
+
+        // Bloom of the receipt's logs
+        let bloom = encode::logs_bloom(&receipt["logs"])?;
+        if encode::hex_bytes(&receipt["logsBloom"], "logsBloom")? != bloom {
+            report.mismatch(
+                "receipt_bloom",
+                format!("receipt of {} has a logsBloom not matching its logs", hash),
+            );
+        }
+        block_bloom
+            .iter_mut()
+            .zip(bloom)
+            .for_each(|(acc, b)| *acc |= b);
+
+        // Cumulative gas must grow by each transaction's gas
+        let gas_used = encode::quantity(&receipt["gasUsed"], "gasUsed")?;
+        let receipt_cumulative =
+            encode::quantity(&receipt["cumulativeGasUsed"], "cumulativeGasUsed")?;
+        cumulative = cumulative.saturating_add(gas_used);
+        if receipt_cumulative != cumulative {
+            report.mismatch(
+                "cumulative_gas",
+                format!(
+                    "receipt of {} has cumulativeGasUsed {}, expected {}",
+                    hash, receipt_cumulative, cumulative
+                ),
+            );
+            cumulative = receipt_cumulative;
+        }
+
+        // Log indexes run through the whole block
+        for log in receipt["logs"].as_array().into_iter().flatten() {
+            let index = encode::quantity(&log["logIndex"], "logIndex")?;
+            if index != log_index {
+                report.mismatch(
+                    "log_index",
+                    format!(
+                        "log of {} has logIndex {}, expected {}",
+                        hash, index, log_index
+                    ),
+                );
+            }
+            log_index = index + 1;
+        }
+
+        match encode::receipt(receipt) {
+            Ok(bytes) => encoded.push(bytes),
+            Err(e) => report.skipped.push(format!("receipt of {}: {}", hash, e)),
+        }
+    }
+
+    let header_gas = encode::quantity(&block["gasUsed"], "gasUsed")?;
+    if header_gas != cumulative {
+        report.mismatch(
+            "gas_used",
+            format!(
+                "header gasUsed is {}, receipts add up to {}",
+                header_gas, cumulative
+            ),
+        );
+    }
+    report.compare(
+        "block_bloom",
+        "logsBloom",
+        &block["logsBloom"],
+        &block_bloom,
+    );
+    if encoded.len() == receipts.len() {
+        let root = trie::ordered_root(&encoded);
+        report.compare(
+            "receipts_root",
+            "receiptsRoot",
+            &block["receiptsRoot"],
+            &root,
+        );
+    } else {
+        report
+            .skipped
+            .push("receipts_root: not every receipt could be encoded".to_string());
+    }
+    Ok(report)
+}
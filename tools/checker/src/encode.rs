@@ -0,0 +1,243 @@
+//! Consensus encodings of transactions and receipts as returned by JSON-RPC
+//!
+//! Covers legacy transactions and the EIP-2718 types 1 (access list),
+//! 2 (EIP-1559), 3 (blob) and 4 (set code).
+
+use serde_json::Value;
+use std::fmt;
+
+use crate::rlp::Item;
+use crate::trie::keccak;
+
+/// Size of a logs bloom filter
+pub const BLOOM_BYTES: usize = 256;
+
+/// Why a JSON-RPC object couldn't be encoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// A required field is missing or null
+    Missing(&'static str),
+    /// A field is not `0x`-prefixed hex
+    InvalidHex(&'static str),
+    /// Transaction type this checker doesn't know
+    UnsupportedType(u64),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Missing(field) => write!(f, "missing field {}", field),
+            EncodeError::InvalidHex(field) => write!(f, "field {} is not hex", field),
+            EncodeError::UnsupportedType(kind) => {
+                write!(f, "unsupported transaction type {}", kind)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Decode a `0x`-prefixed hex value
+pub fn hex_bytes(value: &Value, field: &'static str) -> Result<Vec<u8>, EncodeError> {
+    let text = value
+        .as_str()
+        .ok_or(EncodeError::Missing(field))?
+        .trim_start_matches("0x");
+    // Quantities may have an odd number of digits
+    let padded = if text.len() % 2 == 1 {
+        format!("0{}", text)
+    } else {
+        text.to_string()
+    };
+    hex::decode(padded).map_err(|_| EncodeError::InvalidHex(field))
+}
+
+/// Decode a quantity that fits in 64 bits
+pub fn quantity(value: &Value, field: &'static str) -> Result<u64, EncodeError> {
+    let bytes = hex_bytes(value, field)?;
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    if bytes.len() - start > 8 {
+        return Err(EncodeError::InvalidHex(field));
+    }
+    Ok(bytes[start..]
+        .iter()
+        .fold(0, |acc, b| (acc << 8) | u64::from(*b)))
+}
+
+/// Transaction type, 0 for legacy transactions
+pub fn transaction_type(tx: &Value) -> Result<u64, EncodeError> {
+    match &tx["type"] {
+        Value::Null => Ok(0),
+        kind => quantity(kind, "type"),
+    }
+}
+
+/// Signed transaction as included in the transaction trie
+pub fn transaction(tx: &Value) -> Result<Vec<u8>, EncodeError> {
+    let kind = transaction_type(tx)?;
+    let uint = |field: &'static str| hex_bytes(&tx[field], field).map(|b| Item::uint(&b));
+    let bytes = |field: &'static str| hex_bytes(&tx[field], field).map(Item::Bytes);
+    let to = match &tx["to"] {
+        Value::Null => Item::Bytes(Vec::new()),
+        to => Item::Bytes(hex_bytes(to, "to")?),
+    };
+    // Typed transactions carry the parity bit; older nodes only return `v`
+    let y_parity = || match &tx["yParity"] {
+        Value::Null => uint("v"),
+        parity => hex_bytes(parity, "yParity").map(|b| Item::uint(&b)),
+    };
+
+    let fields = match kind {
+        0 => vec![
+            uint("nonce")?,
+            uint("gasPrice")?,
+            uint("gas")?,
+            to,
+            uint("value")?,
+            bytes("input")?,
+            uint("v")?,
+            uint("r")?,
+            uint("s")?,
+        ],
+        1 => vec![
+            uint("chainId")?,
+            uint("nonce")?,
+            uint("gasPrice")?,
+            uint("gas")?,
+            to,
+            uint("value")?,
+            bytes("input")?,
+            access_list(&tx["accessList"])?,
+            y_parity()?,
+            uint("r")?,
+            uint("s")?,
+        ],
+        2..=4 => {
+            let mut fields = vec![
+                uint("chainId")?,
+                uint("nonce")?,
+                uint("maxPriorityFeePerGas")?,
+                uint("maxFeePerGas")?,
+                uint("gas")?,
+                to,
+                uint("value")?,
+                bytes("input")?,
+                access_list(&tx["accessList"])?,
+            ];
+            if kind == 3 {
+                fields.push(uint("maxFeePerBlobGas")?);
+                fields.push(list(&tx["blobVersionedHashes"], |hash| {
+                    hex_bytes(hash, "blobVersionedHashes").map(Item::Bytes)
+                })?);
+            }
+            if kind == 4 {
+                fields.push(list(&tx["authorizationList"], authorization)?);
+            }
+            fields.extend([y_parity()?, uint("r")?, uint("s")?]);
+            fields
+        }
+        other => return Err(EncodeError::UnsupportedType(other)),
+    };
+
+    let mut out = Vec::new();
+    if kind > 0 {
+        out.push(kind as u8);
+    }
+    out.extend(Item::List(fields).encode());
+    Ok(out)
+}
+
+/// Receipt as included in the receipt trie
+pub fn receipt(receipt: &Value) -> Result<Vec<u8>, EncodeError> {
+    // Pre-Byzantium receipts carry a state root instead of a status
+    let outcome = match &receipt["status"] {
+        Value::Null => Item::Bytes(hex_bytes(&receipt["root"], "status")?),
+        status => Item::uint(&hex_bytes(status, "status")?),
+    };
+    let logs = list(&receipt["logs"], |log| {
+        Ok(Item::List(vec![
+            Item::Bytes(hex_bytes(&log["address"], "address")?),
+            list(&log["topics"], |topic| {
+                hex_bytes(topic, "topics").map(Item::Bytes)
+            })?,
+            Item::Bytes(hex_bytes(&log["data"], "data")?),
+        ]))
+    })?;
+    let fields = vec![
+        outcome,
+        Item::uint(&hex_bytes(
+            &receipt["cumulativeGasUsed"],
+            "cumulativeGasUsed",
+        )?),
+        Item::Bytes(hex_bytes(&receipt["logsBloom"], "logsBloom")?),
+        logs,
+    ];
+
+    let kind = transaction_type(receipt)?;
+    let mut out = Vec::new();
+    if kind > 0 {
+        out.push(kind as u8);
+    }
+    out.extend(Item::List(fields).encode());
+    Ok(out)
+}
+
+/// Bloom filter of a receipt's logs
+pub fn logs_bloom(logs: &Value) -> Result<[u8; BLOOM_BYTES], EncodeError> {
+    let mut bloom = [0u8; BLOOM_BYTES];
+    for log in logs.as_array().ok_or(EncodeError::Missing("logs"))? {
+        accrue(&mut bloom, &hex_bytes(&log["address"], "address")?);
+        for topic in log["topics"]
+            .as_array()
+            .ok_or(EncodeError::Missing("topics"))?
+        {
+            accrue(&mut bloom, &hex_bytes(topic, "topics")?);
+        }
+    }
+    Ok(bloom)
+}
+
+/// Set the three bits selected by the hash of `data`
+fn accrue(bloom: &mut [u8; BLOOM_BYTES], data: &[u8]) {
+    let hash = keccak(data);
+    for i in [0, 2, 4] {
+        let bit = ((usize::from(hash[i]) << 8) | usize::from(hash[i + 1])) & 2047;
+        bloom[BLOOM_BYTES - 1 - bit / 8] |= 1 << (bit % 8);
+    }
+}
+
+fn access_list(value: &Value) -> Result<Item, EncodeError> {
+    list(value, |entry| {
+        Ok(Item::List(vec![
+            Item::Bytes(hex_bytes(&entry["address"], "accessList")?),
+            list(&entry["storageKeys"], |key| {
+                hex_bytes(key, "accessList").map(Item::Bytes)
+            })?,
+        ]))
+    })
+}
+
+fn authorization(value: &Value) -> Result<Item, EncodeError> {
+    let uint = |field: &'static str| hex_bytes(&value[field], field).map(|b| Item::uint(&b));
+    Ok(Item::List(vec![
+        uint("chainId")?,
+        Item::Bytes(hex_bytes(&value["address"], "authorizationList")?),
+        uint("nonce")?,
+        uint("yParity")?,
+        uint("r")?,
+        uint("s")?,
+    ]))
+}
+
+/// List item from a JSON array; a missing array is an empty list
+fn list(
+    value: &Value,
+    item: impl Fn(&Value) -> Result<Item, EncodeError>,
+) -> Result<Item, EncodeError> {
+    let items = match value {
+        Value::Null => Vec::new(),
+        Value::Array(values) => values.iter().map(item).collect::<Result<_, _>>()?,
+        _ => return Err(EncodeError::Missing("list")),
+    };
+    Ok(Item::List(items))
+}
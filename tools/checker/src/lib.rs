@@ -0,0 +1,7 @@
+//! Library surface of the checker, shared with its tests
+
+pub mod check;
+pub mod encode;
+pub mod trie;
+
+pub use axionax_tx::rlp;
@@ -0,0 +1,131 @@
+//! Walks a block range of a node and reports blocks whose header doesn't
+//! match the transactions and receipts the node serves for it
+
+use anyhow::{anyhow, bail};
+use axionax_checker::check::{self, BlockReport};
+use axionax_rpc::{Client, Retry};
+use clap::Parser;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Command-line options
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Check transaction, receipt and bloom consistency of a node"
+)]
+struct Cli {
+    /// JSON-RPC endpoint of the node under test
+    #[arg(long, env = "RPC_URL", default_value = "http://localhost:8545")]
+    rpc_url: String,
+
+    /// First block to check (default: the latest block)
+    #[arg(long)]
+    from: Option<u64>,
+
+    /// Last block to check (default: `--from`)
+    #[arg(long)]
+    to: Option<u64>,
+
+    /// Blocks fetched and checked at once
+    #[arg(long, default_value_t = 4)]
+    concurrency: usize,
+
+    /// Print a JSON report instead of one line per mismatch
+    #[arg(long)]
+    json: bool,
+}
+
+/// Block with full transactions and its receipts in transaction order
+async fn fetch(rpc: &Client, number: u64) -> anyhow::Result<(Value, Vec<Value>)> {
+    let block = rpc
+        .get_block_by_number(number, true)
+        .await?
+        .ok_or_else(|| anyhow!("block {} not found", number))?;
+    let receipts = rpc.get_block_receipts(number).await?;
+    Ok((block, receipts))
+}
+
+/// Summary printed with `--json`
+#[derive(Serialize)]
+struct Report {
+    from: u64,
+    to: u64,
+    transactions: usize,
+    blocks: Vec<BlockReport>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    // Long ranges shouldn't abort on a node's momentary hiccup
+    let rpc = Client::builder()
+        .endpoint(&cli.rpc_url)
+        .retry(Retry {
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(5),
+        })
+        .build();
+
+    let from = match cli.from {
+        Some(from) => from,
+        None => rpc.get_block_number().await?,
+    };
+    let to = cli.to.unwrap_or(from);
+    if to < from {
+        bail!("--to {} is before --from {}", to, from);
+    }
+
+    let mut results = stream::iter(from..=to)
+        .map(|number| {
+            let rpc = &rpc;
+            async move {
+                let (block, receipts) = fetch(rpc, number).await?;
+                check::check_block(&block, &receipts)
+                    .map_err(|e| anyhow!("block {}: {}", number, e))
+            }
+        })
+        .buffered(cli.concurrency.max(1));
+
+    let mut blocks = Vec::new();
+    while let Some(report) = results.next().await {
+        let report = report?;
+        if !cli.json {
+            for mismatch in &report.mismatches {
+                println!(
+                    "block {}: {}: {}",
+                    mismatch.block, mismatch.check, mismatch.detail
+                );
+            }
+            for skipped in &report.skipped {
+                eprintln!("block {}: skipped {}", report.block, skipped);
+            }
+        }
+        blocks.push(report);
+    }
+
+    let mismatches: usize = blocks.iter().map(|b| b.mismatches.len()).sum();
+    let transactions = blocks.iter().map(|b| b.transactions).sum();
+    if cli.json {
+        let report = Report {
+            from,
+            to,
+            transactions,
+            blocks,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "Checked blocks {}-{} ({} transactions): {} mismatches",
+            from, to, transactions, mismatches
+        );
+    }
+
+    if mismatches > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
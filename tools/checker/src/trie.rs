@@ -0,0 +1,111 @@
+//! Roots of Merkle Patricia tries, as used for transaction and receipt roots
+
+use sha3::{Digest, Keccak256};
+
+use crate::rlp::Item;
+
+/// Root of the trie holding `entries`; later duplicates of a key win
+pub fn root(entries: &[(Vec<u8>, Vec<u8>)]) -> [u8; 32] {
+    let mut nodes: Vec<(Vec<u8>, &[u8])> = entries
+        .iter()
+        .map(|(key, value)| (nibbles(key), value.as_slice()))
+        .collect();
+    nodes.reverse();
+    nodes.sort_by(|a, b| a.0.cmp(&b.0));
+    nodes.dedup_by(|a, b| a.0 == b.0);
+
+    if nodes.is_empty() {
+        return keccak(&Item::Bytes(Vec::new()).encode());
+    }
+    keccak(&node(&nodes, 0).encode())
+}
+
+/// Root of a block's transaction or receipt trie, keyed by RLP-encoded index
+pub fn ordered_root(values: &[Vec<u8>]) -> [u8; 32] {
+    let entries: Vec<(Vec<u8>, Vec<u8>)> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (Item::from(i as u64).encode(), value.clone()))
+        .collect();
+    root(&entries)
+}
+
+/// Node for sorted, distinct `entries` that share their first `depth` nibbles
+fn node(entries: &[(Vec<u8>, &[u8])], depth: usize) -> Item {
+    if let [(key, value)] = entries {
+        return Item::List(vec![
+            Item::Bytes(hex_prefix(&key[depth..], true)),
+            Item::Bytes(value.to_vec()),
+        ]);
+    }
+
+    let first = &entries[0].0;
+    let shared = entries
+        .iter()
+        .map(|(key, _)| {
+            key[depth..]
+                .iter()
+                .zip(&first[depth..])
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+        .min()
+        .unwrap_or(0);
+    if shared > 0 {
+        return Item::List(vec![
+            Item::Bytes(hex_prefix(&first[depth..depth + shared], false)),
+            reference(node(entries, depth + shared)),
+        ]);
+    }
+
+    // Sorting puts a key ending at this depth first; it is the branch's value
+    let (value, rest) = match entries.split_first() {
+        Some(((key, value), rest)) if key.len() == depth => (value.to_vec(), rest),
+        _ => (Vec::new(), entries),
+    };
+    let mut branch: Vec<Item> = (0..16u8)
+        .map(|nibble| {
+            let start = rest.partition_point(|(key, _)| key[depth] < nibble);
+            let end = rest.partition_point(|(key, _)| key[depth] <= nibble);
+            match end - start {
+                0 => Item::Bytes(Vec::new()),
+                _ => reference(node(&rest[start..end], depth + 1)),
+            }
+        })
+        .collect();
+    branch.push(Item::Bytes(value));
+    Item::List(branch)
+}
+
+/// How a parent refers to a child: inline when its encoding is under 32 bytes
+fn reference(node: Item) -> Item {
+    let encoded = node.encode();
+    if encoded.len() < 32 {
+        node
+    } else {
+        Item::Bytes(keccak(&encoded).to_vec())
+    }
+}
+
+/// Compact (hex-prefix) encoding of a key path
+fn hex_prefix(path: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 };
+    let mut out = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        out.push(((flag + 1) << 4) | path[0]);
+        &path[1..]
+    } else {
+        out.push(flag << 4);
+        path
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+pub fn keccak(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
@@ -0,0 +1,172 @@
+//! Transaction encoding and block consistency checks
+
+use axionax_checker::check::check_block;
+use axionax_checker::encode;
+use axionax_checker::trie::{keccak, ordered_root};
+use serde_json::{json, Value};
+
+/// Signed legacy transaction from the EIP-155 example
+fn transaction() -> Value {
+    json!({
+        "nonce": "0x9",
+        "gasPrice": "0x4a817c800",
+        "gas": "0x5208",
+        "to": "0x3535353535353535353535353535353535353535",
+        "value": "0xde0b6b3a7640000",
+        "input": "0x",
+        "v": "0x25",
+        "r": "0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276",
+        "s": "0x67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// A consistent block holding the example transaction and a receipt with one log
+fn block() -> (Value, Vec<Value>) {
+    let mut tx = transaction();
+    tx["hash"] = json!(hex(&keccak(&encode::transaction(&tx).unwrap())));
+
+    let logs = json!([{
+        "address": "0x3535353535353535353535353535353535353535",
+        "topics": [hex(&keccak(b"Transfer(address,address,uint256)"))],
+        "data": "0x",
+        "logIndex": "0x0",
+    }]);
+    let bloom = encode::logs_bloom(&logs).unwrap();
+    let receipt = json!({
+        "transactionHash": tx["hash"],
+        "status": "0x1",
+        "gasUsed": "0x5208",
+        "cumulativeGasUsed": "0x5208",
+        "logsBloom": hex(&bloom),
+        "logs": logs,
+    });
+
+    let block = json!({
+        "number": "0x10",
+        "gasUsed": "0x5208",
+        "logsBloom": hex(&bloom),
+        "transactionsRoot": hex(&ordered_root(&[encode::transaction(&tx).unwrap()])),
+        "receiptsRoot": hex(&ordered_root(&[encode::receipt(&receipt).unwrap()])),
+        "transactions": [tx],
+    });
+    (block, vec![receipt])
+}
+
+fn failed_checks(block: &Value, receipts: &[Value]) -> Vec<&'static str> {
+    check_block(block, receipts)
+        .unwrap()
+        .mismatches
+        .iter()
+        .map(|m| m.check)
+        .collect()
+}
+
+#[test]
+fn legacy_transaction_encodes_to_signed_rlp() {
+    assert_eq!(
+        hex(&encode::transaction(&transaction()).unwrap()),
+        "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000\
+         8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f\
+         761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+    );
+}
+
+#[test]
+fn typed_transactions_are_prefixed_with_their_type() {
+    let mut tx = transaction();
+    tx["type"] = json!("0x2");
+    tx["chainId"] = json!("0x1");
+    tx["maxPriorityFeePerGas"] = json!("0x1");
+    tx["maxFeePerGas"] = json!("0x4a817c800");
+    tx["accessList"] = json!([]);
+    tx["yParity"] = json!("0x0");
+    assert_eq!(encode::transaction(&tx).unwrap()[0], 0x02);
+
+    tx["type"] = json!("0x7f");
+    assert_eq!(
+        encode::transaction(&tx),
+        Err(encode::EncodeError::UnsupportedType(0x7f))
+    );
+}
+
+#[test]
+fn empty_block_is_consistent() {
+    let empty_root = hex(&ordered_root(&[]));
+    let block = json!({
+        "number": "0x0",
+        "gasUsed": "0x0",
+        "logsBloom": hex(&[0; encode::BLOOM_BYTES]),
+        "transactionsRoot": empty_root,
+        "receiptsRoot": empty_root,
+        "transactions": [],
+    });
+    let report = check_block(&block, &[]).unwrap();
+    assert_eq!(report.block, 0);
+    assert!(report.mismatches.is_empty());
+}
+
+#[test]
+fn consistent_block_passes() {
+    let (block, receipts) = block();
+    let report = check_block(&block, &receipts).unwrap();
+    assert_eq!(report.block, 16);
+    assert_eq!(report.transactions, 1);
+    assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+    assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+}
+
+#[test]
+fn tampered_transaction_breaks_hash_and_root() {
+    let (mut block, receipts) = block();
+    block["transactions"][0]["value"] = json!("0x1");
+    assert_eq!(
+        failed_checks(&block, &receipts),
+        ["tx_hash", "transactions_root"]
+    );
+}
+
+#[test]
+fn gas_accounting_is_checked() {
+    let (mut block, receipts) = block();
+    block["gasUsed"] = json!("0x5209");
+    assert_eq!(failed_checks(&block, &receipts), ["gas_used"]);
+
+    let (block, mut receipts) = self::block();
+    receipts[0]["gasUsed"] = json!("0x5000");
+    assert_eq!(failed_checks(&block, &receipts), ["cumulative_gas"]);
+}
+
+#[test]
+fn blooms_are_checked() {
+    let (mut block, mut receipts) = block();
+    let zero = hex(&[0; encode::BLOOM_BYTES]);
+    block["logsBloom"] = json!(zero);
+    assert_eq!(failed_checks(&block, &receipts), ["block_bloom"]);
+
+    // A receipt bloom is part of the receipt, so its root changes too
+    receipts[0]["logsBloom"] = json!(zero);
+    assert_eq!(
+        failed_checks(&block, &receipts),
+        ["receipt_bloom", "block_bloom", "receipts_root"]
+    );
+}
+
+#[test]
+fn receipts_must_match_transactions() {
+    let (block, mut receipts) = block();
+    assert_eq!(failed_checks(&block, &[]), ["receipt_count"]);
+
+    receipts[0]["logs"][0]["logIndex"] = json!("0x3");
+    receipts[0]["status"] = json!("0x0");
+    assert_eq!(
+        failed_checks(&block, &receipts),
+        ["log_index", "receipts_root"]
+    );
+
+    receipts[0]["transactionHash"] = json!(hex(&[0; 32]));
+    assert_eq!(failed_checks(&block, &receipts)[0], "receipt_order");
+}
@@ -0,0 +1,69 @@
+//! RLP and trie roots against published test vectors
+
+use axionax_checker::rlp::Item;
+use axionax_checker::trie::{ordered_root, root};
+
+fn entries(pairs: &[(&str, &str)]) -> Vec<(Vec<u8>, Vec<u8>)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
+        .collect()
+}
+
+#[test]
+fn rlp_encodes_strings_and_lists() {
+    let dog = || Item::Bytes(b"dog".to_vec());
+    assert_eq!(dog().encode(), b"\x83dog");
+    assert_eq!(
+        Item::List(vec![Item::Bytes(b"cat".to_vec()), dog()]).encode(),
+        b"\xc8\x83cat\x83dog"
+    );
+    assert_eq!(Item::Bytes(Vec::new()).encode(), [0x80]);
+    assert_eq!(Item::List(Vec::new()).encode(), [0xc0]);
+    assert_eq!(Item::from(0).encode(), [0x80]);
+    assert_eq!(Item::from(15).encode(), [0x0f]);
+    assert_eq!(Item::from(1024).encode(), [0x82, 0x04, 0x00]);
+
+    let long = Item::Bytes(b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec());
+    assert_eq!(&long.encode()[..2], [0xb8, 0x38]);
+}
+
+#[test]
+fn empty_trie_root() {
+    assert_eq!(
+        hex::encode(root(&[])),
+        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+    );
+    assert_eq!(ordered_root(&[]), root(&[]));
+}
+
+#[test]
+fn trie_roots_match_test_vectors() {
+    let puppy = entries(&[
+        ("do", "verb"),
+        ("horse", "stallion"),
+        ("doge", "coin"),
+        ("dog", "puppy"),
+    ]);
+    assert_eq!(
+        hex::encode(root(&puppy)),
+        "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+    );
+
+    let dogs = entries(&[
+        ("doe", "reindeer"),
+        ("dog", "puppy"),
+        ("dogglesworth", "cat"),
+    ]);
+    assert_eq!(
+        hex::encode(root(&dogs)),
+        "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+    );
+}
+
+#[test]
+fn later_duplicates_win() {
+    let replaced = entries(&[("dog", "cat"), ("dog", "puppy"), ("doe", "reindeer")]);
+    let expected = entries(&[("doe", "reindeer"), ("dog", "puppy")]);
+    assert_eq!(root(&replaced), root(&expected));
+}
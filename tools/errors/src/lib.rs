@@ -0,0 +1,138 @@
+//! Error taxonomy shared by the axionax tools
+//!
+//! Every error has a [`Kind`], which fixes its HTTP status and whether a
+//! client should retry, and a machine-readable `code` naming the specific
+//! failure within that kind. Clients branch on `kind` and `code`; `message`
+//! is for people.
+
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Broad class of an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// The request is malformed or names something invalid
+    Validation,
+    /// Credentials are missing or wrong
+    Unauthorized,
+    /// The request is understood but refused by policy
+    Forbidden,
+    NotFound,
+    /// The caller must wait before trying again
+    RateLimit,
+    /// The service can't serve requests right now, e.g. out of funds or paused
+    Unavailable,
+    /// A JSON-RPC node failed or returned something unusable
+    Rpc,
+    /// Signing or signature recovery failed
+    Signing,
+    /// The database failed
+    Storage,
+    Internal,
+}
+
+impl Kind {
+    /// HTTP status of errors of this kind
+    pub fn status(self) -> StatusCode {
+        match self {
+            Kind::Validation => StatusCode::BAD_REQUEST,
+            Kind::Unauthorized => StatusCode::UNAUTHORIZED,
+            Kind::Forbidden => StatusCode::FORBIDDEN,
+            Kind::NotFound => StatusCode::NOT_FOUND,
+            Kind::RateLimit => StatusCode::TOO_MANY_REQUESTS,
+            Kind::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Kind::Rpc => StatusCode::BAD_GATEWAY,
+            Kind::Signing | Kind::Storage | Kind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Whether the same request may succeed later without changes
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Kind::RateLimit | Kind::Unavailable | Kind::Rpc)
+    }
+
+    /// Name of the kind as serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Kind::Validation => "validation",
+            Kind::Unauthorized => "unauthorized",
+            Kind::Forbidden => "forbidden",
+            Kind::NotFound => "not_found",
+            Kind::RateLimit => "rate_limit",
+            Kind::Unavailable => "unavailable",
+            Kind::Rpc => "rpc",
+            Kind::Signing => "signing",
+            Kind::Storage => "storage",
+            Kind::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error with a kind, a machine-readable code and a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Error {
+    pub kind: Kind,
+    /// Specific failure, e.g. `rpc_unavailable`; stable across releases
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(kind: Kind, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// A node failed; code `rpc_error`
+    pub fn rpc(message: impl Into<String>) -> Self {
+        Self::new(Kind::Rpc, "rpc_error", message)
+    }
+
+    /// Signing failed; code `signing_error`
+    pub fn signing(message: impl Into<String>) -> Self {
+        Self::new(Kind::Signing, "signing_error", message)
+    }
+
+    /// The database failed; code `storage_error`
+    pub fn storage(message: impl Into<String>) -> Self {
+        Self::new(Kind::Storage, "storage_error", message)
+    }
+
+    /// Anything else that isn't the caller's fault; code `internal_error`
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(Kind::Internal, "internal_error", message)
+    }
+
+    /// Replace the code with a more specific one
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.kind.status()
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.kind.is_retryable()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
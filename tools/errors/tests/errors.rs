@@ -0,0 +1,44 @@
+//! Status mapping and serialization of shared errors
+
+use axionax_errors::{Error, Kind};
+use http::StatusCode;
+use serde_json::json;
+
+#[test]
+fn kinds_map_to_statuses() {
+    let cases = [
+        (Kind::Validation, StatusCode::BAD_REQUEST, false),
+        (Kind::Unauthorized, StatusCode::UNAUTHORIZED, false),
+        (Kind::Forbidden, StatusCode::FORBIDDEN, false),
+        (Kind::NotFound, StatusCode::NOT_FOUND, false),
+        (Kind::RateLimit, StatusCode::TOO_MANY_REQUESTS, true),
+        (Kind::Unavailable, StatusCode::SERVICE_UNAVAILABLE, true),
+        (Kind::Rpc, StatusCode::BAD_GATEWAY, true),
+        (Kind::Signing, StatusCode::INTERNAL_SERVER_ERROR, false),
+        (Kind::Storage, StatusCode::INTERNAL_SERVER_ERROR, false),
+        (Kind::Internal, StatusCode::INTERNAL_SERVER_ERROR, false),
+    ];
+    for (kind, status, retryable) in cases {
+        assert_eq!(kind.status(), status, "{}", kind);
+        assert_eq!(kind.is_retryable(), retryable, "{}", kind);
+        assert_eq!(json!(kind), json!(kind.as_str()));
+    }
+}
+
+#[test]
+fn errors_serialize_kind_code_and_message() {
+    let error = Error::rpc("connection refused").with_code("rpc_unavailable");
+    assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(error.to_string(), "connection refused");
+    assert_eq!(
+        json!(error),
+        json!({ "kind": "rpc", "code": "rpc_unavailable", "message": "connection refused" })
+    );
+
+    assert_eq!(Error::storage("disk full").code, "storage_error");
+    assert_eq!(Error::signing("bad key").kind, Kind::Signing);
+    assert_eq!(
+        Error::internal("boom"),
+        Error::new(Kind::Internal, "internal_error", "boom")
+    );
+}
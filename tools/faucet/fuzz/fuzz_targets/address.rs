@@ -0,0 +1,11 @@
+#![no_main]
+
+use axionax_faucet::validation::{excerpt, parse_address, to_checksum_address};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = excerpt(input);
+    if let Ok(address) = parse_address(input) {
+        assert_eq!(parse_address(&to_checksum_address(&address)), Ok(address));
+    }
+});
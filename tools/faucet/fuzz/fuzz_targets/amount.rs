@@ -0,0 +1,8 @@
+#![no_main]
+
+use axionax_faucet::validation::parse_amount;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = parse_amount(input);
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use axionax_faucet::validation::{check_request, parse_json, RequestRules};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|body: &[u8]| {
+    if let Ok(body) = parse_json::<serde_json::Value>(body) {
+        let rules = RequestRules {
+            chain_id: 86137,
+            names: true,
+            captcha: true,
+        };
+        let _ = check_request(&body, &rules);
+    }
+});
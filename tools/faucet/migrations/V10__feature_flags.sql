@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name TEXT PRIMARY KEY,
+    percent INTEGER NOT NULL,
+    partners TEXT NOT NULL,
+    updated_by TEXT NOT NULL,
+    updated INTEGER NOT NULL
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS badges (
+    address TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    tx_hash TEXT,
+    error TEXT,
+    queued INTEGER NOT NULL,
+    updated INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS badges_status_queued ON badges (status, queued);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS identities (
+    address TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    verified INTEGER NOT NULL,
+    UNIQUE (provider, subject)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS address_labels (
+    address TEXT NOT NULL,
+    label TEXT NOT NULL,
+    note TEXT,
+    added_by TEXT NOT NULL,
+    added INTEGER NOT NULL,
+    PRIMARY KEY (address, label)
+);
+CREATE INDEX IF NOT EXISTS address_labels_label ON address_labels (label);
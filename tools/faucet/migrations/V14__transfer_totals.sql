@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS transfer_totals (
+    kind TEXT PRIMARY KEY,
+    amount TEXT NOT NULL
+);
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS transfers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    address TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transfers_kind_timestamp ON transfers (kind, timestamp);
+
+CREATE TABLE IF NOT EXISTS requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL,
+    ip TEXT NOT NULL,
+    granted INTEGER NOT NULL,
+    reason TEXT,
+    timestamp INTEGER NOT NULL,
+    country TEXT,
+    asn INTEGER,
+    as_org TEXT
+);
+CREATE INDEX IF NOT EXISTS requests_timestamp ON requests (timestamp);
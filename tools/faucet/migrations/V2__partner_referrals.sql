@@ -0,0 +1,3 @@
+ALTER TABLE requests ADD COLUMN partner TEXT;
+ALTER TABLE transfers ADD COLUMN partner TEXT;
+CREATE INDEX IF NOT EXISTS transfers_partner_timestamp ON transfers (partner, timestamp);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS receipts (
+    claim_id TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    chain_id INTEGER NOT NULL,
+    signature TEXT NOT NULL
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS recipient_activity (
+    address TEXT PRIMARY KEY,
+    first_drip INTEGER NOT NULL,
+    nonce_at_drip INTEGER NOT NULL,
+    scanned_nonce INTEGER NOT NULL,
+    transactions INTEGER NOT NULL DEFAULT 0,
+    contracts INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS recipient_activity_first_drip ON recipient_activity (first_drip);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS address_links (
+    address TEXT NOT NULL,
+    linked TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    created INTEGER NOT NULL,
+    PRIMARY KEY (address, linked)
+);
+CREATE INDEX IF NOT EXISTS address_links_linked ON address_links (linked);
+
+CREATE TABLE IF NOT EXISTS alias_scan (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    block INTEGER NOT NULL
+);
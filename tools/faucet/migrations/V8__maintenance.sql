@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS maintenance (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    message TEXT NOT NULL,
+    eta INTEGER,
+    since INTEGER NOT NULL
+);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS claim_status (
+    claim_id TEXT PRIMARY KEY REFERENCES receipts (claim_id),
+    status TEXT NOT NULL,
+    block INTEGER,
+    updated INTEGER NOT NULL
+);
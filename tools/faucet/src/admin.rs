@@ -0,0 +1,476 @@
+//! Admin API, authenticated with `Authorization: Bearer <token>`
+//!
+//! Tokens come from `ADMIN_TOKEN` (actor `admin`) and `ADMIN_TOKENS`, a
+//! comma-separated list of `actor:token` pairs. Every admin action is appended
+//! to the `admin_audit` table with its actor, time, client IP and payload.
+
+use axionax_faucet::validation;
+use axionax_tx::U256;
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Json, Path, Query, State},
+    http::{header, request::Parts},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+use crate::aliases::{self, AliasCluster};
+use crate::bundle::FlagStatus;
+use crate::flags::{self, Rollout};
+use crate::labels::{self, MAX_NOTE_LEN};
+use crate::ledger::{from_unix, to_unix, AddressLabel, AuditEntry, GeoAggregate, Maintenance};
+use crate::{axx, client_ip, FaucetError, FaucetState, COOLDOWN_HOURS};
+
+/// Actor recorded for `ADMIN_TOKEN`
+const DEFAULT_ACTOR: &str = "admin";
+
+/// Most audit entries returned by one `/admin/audit` query
+const MAX_AUDIT_LIMIT: usize = 1000;
+
+/// Message shown during maintenance when none is given
+const DEFAULT_MAINTENANCE_MESSAGE: &str = "The faucet is paused for maintenance.";
+
+/// Bearer token of a named admin
+#[derive(Debug, Clone)]
+pub struct AdminToken {
+    pub actor: String,
+    token: String,
+}
+
+/// Load admin tokens from `ADMIN_TOKEN` and `ADMIN_TOKENS`
+pub fn tokens_from_env() -> anyhow::Result<Vec<AdminToken>> {
+    let mut tokens = Vec::new();
+    if let Ok(token) = std::env::var("ADMIN_TOKEN") {
+        tokens.push(AdminToken {
+            actor: DEFAULT_ACTOR.to_string(),
+            token,
+        });
+    }
+    if let Ok(list) = std::env::var("ADMIN_TOKENS") {
+        for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once(':') {
+                Some((actor, token)) if !actor.is_empty() && !token.is_empty() => {
+                    tokens.push(AdminToken {
+                        actor: actor.to_string(),
+                        token: token.to_string(),
+                    })
+                }
+                _ => anyhow::bail!("Invalid ADMIN_TOKENS entry, expected actor:token"),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Extractor guarding admin routes, identifying the admin behind the token
+pub struct AdminAuth {
+    pub actor: String,
+    ip: Option<String>,
+}
+
+impl AdminAuth {
+    /// Append `action` to the audit log; the action must not proceed if this fails
+    pub async fn audit(
+        &self,
+        state: &FaucetState,
+        action: &str,
+        payload: serde_json::Value,
+    ) -> Result<(), FaucetError> {
+        state
+            .ledger
+            .lock()
+            .await
+            .record_audit(&self.actor, action, &payload, self.ip.as_deref())
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<FaucetState> for AdminAuth {
+    type Rejection = FaucetError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &FaucetState,
+    ) -> Result<Self, Self::Rejection> {
+        let provided = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(FaucetError::Unauthorized)?;
+
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| client_ip::normalize(peer.ip()).to_string());
+
+        // Rejections aren't audited so they can't flood the append-only table
+        match state
+            .admin_tokens
+            .iter()
+            .find(|t| t.token.as_bytes() == provided.as_bytes())
+        {
+            Some(t) => Ok(AdminAuth {
+                actor: t.actor.clone(),
+                ip,
+            }),
+            None => {
+                warn!(
+                    "Rejected admin token from {}",
+                    ip.as_deref().unwrap_or("unknown")
+                );
+                Err(FaucetError::Unauthorized)
+            }
+        }
+    }
+}
+
+/// Query parameters for `/admin/stats`
+#[derive(Debug, Deserialize)]
+pub struct AdminStatsQuery {
+    /// Window to aggregate over, in hours
+    #[serde(default = "default_hours")]
+    hours: u64,
+}
+
+fn default_hours() -> u64 {
+    24
+}
+
+/// Admin stats response
+#[derive(Debug, Serialize)]
+pub struct AdminStatsResponse {
+    window_hours: u64,
+    countries: Vec<GeoAggregate>,
+    asns: Vec<GeoAggregate>,
+    partners: Vec<PartnerStats>,
+    /// Largest clusters of linked addresses granted within the window
+    alias_clusters: Vec<AliasCluster>,
+}
+
+/// Referral activity and remaining budget of a partner
+#[derive(Debug, Serialize)]
+pub struct PartnerStats {
+    partner: String,
+    requests: u64,
+    grants: u64,
+    distributed: String,
+    daily_budget: String,
+    budget_remaining: String,
+}
+
+/// Get request aggregates per country and ASN
+pub async fn stats(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Query(query): Query<AdminStatsQuery>,
+) -> Result<Json<AdminStatsResponse>, FaucetError> {
+    auth.audit(&state, "stats", json!({ "hours": query.hours }))
+        .await?;
+    let since = SystemTime::now() - Duration::from_secs(query.hours * 3600);
+    let ledger = state.ledger.lock().await;
+
+    let mut partners = Vec::new();
+    if let Some(configured) = state.partners.as_ref() {
+        let aggregates = ledger.partner_aggregates(since)?;
+        let budget_since = SystemTime::now() - Duration::from_secs(COOLDOWN_HOURS * 3600);
+        for (id, partner) in configured.iter() {
+            let aggregate = aggregates.iter().find(|a| a.partner == id);
+            let spent = ledger.partner_spent_since(id, budget_since)?;
+            partners.push(PartnerStats {
+                partner: id.to_string(),
+                requests: aggregate.map_or(0, |a| a.requests),
+                grants: aggregate.map_or(0, |a| a.grants),
+                distributed: axx(aggregate.map_or(U256::zero(), |a| a.distributed)),
+                daily_budget: axx(partner.budget),
+                budget_remaining: axx(partner.budget.saturating_sub(spent)),
+            });
+        }
+        partners.sort_by(|a, b| b.requests.cmp(&a.requests).then(a.partner.cmp(&b.partner)));
+    }
+
+    Ok(Json(AdminStatsResponse {
+        window_hours: query.hours,
+        countries: ledger.country_aggregates(since)?,
+        asns: ledger.asn_aggregates(since)?,
+        partners,
+        alias_clusters: aliases::clusters(&ledger, since, state.aliases.max_fanout)?,
+    }))
+}
+
+/// Query parameters for `/admin/audit`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuditQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<String>,
+    /// Only entries at or after this time (unix seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    since: Option<i64>,
+    #[serde(default = "default_audit_limit")]
+    limit: usize,
+}
+
+fn default_audit_limit() -> usize {
+    100
+}
+
+/// Audit log response
+#[derive(Debug, Serialize)]
+pub struct AuditResponse {
+    entries: Vec<AuditEntry>,
+}
+
+/// Get audit log entries, newest first
+pub async fn audit(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditResponse>, FaucetError> {
+    // Reading the trail is itself audited
+    auth.audit(&state, "audit", json!(query)).await?;
+
+    let entries = state.ledger.lock().await.audit_log(
+        query.actor.as_deref(),
+        query.action.as_deref(),
+        query.since.map(from_unix),
+        query.limit.min(MAX_AUDIT_LIMIT),
+    )?;
+    Ok(Json(AuditResponse { entries }))
+}
+
+/// Body of `PUT /admin/maintenance`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MaintenanceRequest {
+    /// Message shown to requesters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// Expected end (unix seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eta: Option<i64>,
+}
+
+/// Maintenance state after a change
+#[derive(Debug, Serialize)]
+pub struct MaintenanceResponse {
+    maintenance: Option<Maintenance>,
+}
+
+/// Pause drips, or update the message and ETA of an ongoing pause
+pub async fn start_maintenance(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Json(request): Json<MaintenanceRequest>,
+) -> Result<Json<MaintenanceResponse>, FaucetError> {
+    auth.audit(&state, "start_maintenance", json!(request))
+        .await?;
+
+    let maintenance = Maintenance {
+        message: request
+            .message
+            .filter(|m| !m.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_MAINTENANCE_MESSAGE.to_string()),
+        eta: request.eta,
+        since: to_unix(SystemTime::now()),
+    };
+    let ledger = state.ledger.lock().await;
+    ledger.set_maintenance(Some(&maintenance))?;
+    warn!(
+        "Maintenance started by {}: {}",
+        auth.actor, maintenance.message
+    );
+    Ok(Json(MaintenanceResponse {
+        maintenance: ledger.maintenance()?,
+    }))
+}
+
+/// Resume drips
+pub async fn end_maintenance(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+) -> Result<Json<MaintenanceResponse>, FaucetError> {
+    auth.audit(&state, "end_maintenance", json!({})).await?;
+    state.ledger.lock().await.set_maintenance(None)?;
+    warn!("Maintenance ended by {}", auth.actor);
+    Ok(Json(MaintenanceResponse { maintenance: None }))
+}
+
+/// Feature flags with their effective rollouts
+#[derive(Debug, Serialize)]
+pub struct FlagsResponse {
+    flags: Vec<FlagStatus>,
+}
+
+/// Get every feature flag with its effective rollout
+pub async fn flags(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+) -> Result<Json<FlagsResponse>, FaucetError> {
+    auth.audit(&state, "flags", json!({})).await?;
+    Ok(Json(FlagsResponse {
+        flags: state.config.flags(),
+    }))
+}
+
+/// Override the rollout of a flag on every replica
+pub async fn set_flag(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Path(name): Path<String>,
+    Json(rollout): Json<Rollout>,
+) -> Result<Json<FlagsResponse>, FaucetError> {
+    let name = flags::valid_name(&name)
+        .ok_or_else(|| FaucetError::InvalidPayload(format!("invalid flag name {:?}", name)))?;
+    rollout.validate().map_err(FaucetError::InvalidPayload)?;
+    auth.audit(
+        &state,
+        "set_flag",
+        json!({ "name": name, "rollout": rollout }),
+    )
+    .await?;
+
+    let ledger = state.ledger.lock().await;
+    ledger.set_flag_override(name, Some(&rollout), &auth.actor)?;
+    state.config.set_overrides(ledger.flag_overrides()?);
+    warn!(
+        "Flag {} rolled out to {}% by {}",
+        name, rollout.percent, auth.actor
+    );
+    Ok(Json(FlagsResponse {
+        flags: state.config.flags(),
+    }))
+}
+
+/// Drop the override of a flag, falling back to env and bundle
+pub async fn clear_flag(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Path(name): Path<String>,
+) -> Result<Json<FlagsResponse>, FaucetError> {
+    auth.audit(&state, "clear_flag", json!({ "name": name }))
+        .await?;
+
+    let ledger = state.ledger.lock().await;
+    ledger.set_flag_override(&name, None, &auth.actor)?;
+    state.config.set_overrides(ledger.flag_overrides()?);
+    warn!("Flag {} override cleared by {}", name, auth.actor);
+    Ok(Json(FlagsResponse {
+        flags: state.config.flags(),
+    }))
+}
+
+/// Query parameters for `/admin/labels`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LabeledQuery {
+    label: String,
+}
+
+/// Body of `PUT /admin/labels/:address/:label`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LabelRequest {
+    /// Why the label was attached
+    note: Option<String>,
+}
+
+/// Labels response
+#[derive(Debug, Serialize)]
+pub struct LabelsResponse {
+    labels: Vec<AddressLabel>,
+}
+
+/// Parse the path parameters of a label route
+fn label_path(address: &str, label: &str) -> Result<(String, String), FaucetError> {
+    let address = validation::parse_address(address).map_err(|_| FaucetError::InvalidAddress)?;
+    let label = labels::valid_label(label)
+        .ok_or_else(|| FaucetError::InvalidPayload(format!("invalid label {:?}", label)))?;
+    Ok((address, label.to_string()))
+}
+
+/// Get every address carrying a label
+pub async fn labeled(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Query(query): Query<LabeledQuery>,
+) -> Result<Json<LabelsResponse>, FaucetError> {
+    auth.audit(&state, "labeled", json!(query)).await?;
+    Ok(Json(LabelsResponse {
+        labels: state.ledger.lock().await.labeled(&query.label)?,
+    }))
+}
+
+/// Get the labels of an address
+pub async fn labels(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Path(address): Path<String>,
+) -> Result<Json<LabelsResponse>, FaucetError> {
+    let address = validation::parse_address(&address).map_err(|_| FaucetError::InvalidAddress)?;
+    auth.audit(&state, "labels", json!({ "address": address }))
+        .await?;
+    Ok(Json(LabelsResponse {
+        labels: state.ledger.lock().await.labels(&address)?,
+    }))
+}
+
+/// Attach a label to an address, or update its note
+pub async fn set_label(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Path((address, label)): Path<(String, String)>,
+    Json(request): Json<LabelRequest>,
+) -> Result<Json<LabelsResponse>, FaucetError> {
+    let (address, label) = label_path(&address, &label)?;
+    let note = request
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+    if note.is_some_and(|n| n.len() > MAX_NOTE_LEN) {
+        return Err(FaucetError::InvalidPayload(format!(
+            "note must be at most {} bytes",
+            MAX_NOTE_LEN
+        )));
+    }
+    auth.audit(
+        &state,
+        "set_label",
+        json!({ "address": address, "label": label, "note": note }),
+    )
+    .await?;
+
+    let ledger = state.ledger.lock().await;
+    ledger.set_label(&address, &label, note, &auth.actor)?;
+    warn!("{} labeled {} by {}", address, label, auth.actor);
+    Ok(Json(LabelsResponse {
+        labels: ledger.labels(&address)?,
+    }))
+}
+
+/// Detach a label from an address
+pub async fn remove_label(
+    auth: AdminAuth,
+    State(state): State<FaucetState>,
+    Path((address, label)): Path<(String, String)>,
+) -> Result<Json<LabelsResponse>, FaucetError> {
+    let (address, label) = label_path(&address, &label)?;
+    auth.audit(
+        &state,
+        "remove_label",
+        json!({ "address": address, "label": label }),
+    )
+    .await?;
+
+    let ledger = state.ledger.lock().await;
+    if !ledger.remove_label(&address, &label)? {
+        return Err(FaucetError::NotFound);
+    }
+    warn!("Label {} removed from {} by {}", label, address, auth.actor);
+    Ok(Json(LabelsResponse {
+        labels: ledger.labels(&address)?,
+    }))
+}
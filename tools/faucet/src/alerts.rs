@@ -0,0 +1,40 @@
+//! Operator notifications
+//!
+//! Events are routed to Slack, Discord, PagerDuty or email by the channels
+//! and routes in `NOTIFICATIONS_FILE`; without it nothing is sent.
+
+use axionax_notify::{Event, Notifications};
+
+use crate::FaucetState;
+
+/// Faucet balance fell below `LOW_BALANCE_AXX`
+pub const LOW_BALANCE: &str = "low_balance";
+/// Projected runway of the faucet balance fell below `RUNWAY_ALERT_DAYS`
+pub const LOW_RUNWAY: &str = "low_runway";
+/// A drip transaction couldn't be sent
+pub const SEND_FAILED: &str = "send_failed";
+/// A drip was sent but couldn't be recorded in the ledger
+pub const RECORD_FAILED: &str = "record_failed";
+/// The treasury topped up the faucet
+pub const TOP_UP: &str = "top_up";
+/// The RPC endpoint reports another chain; drips are paused
+pub const CHAIN_MISMATCH: &str = "chain_mismatch";
+/// The claim confirmation loop hasn't completed a cycle within
+/// `HEARTBEAT_MAX_STALL_SECS`
+pub const WORKER_STALLED: &str = "worker_stalled";
+/// A config bundle failed verification or validation and was not applied
+pub const CONFIG_REJECTED: &str = "config_rejected";
+
+/// Load `NOTIFICATIONS_FILE`
+pub fn from_env() -> anyhow::Result<Notifications> {
+    match std::env::var("NOTIFICATIONS_FILE") {
+        Ok(path) => Ok(Notifications::load(path)?),
+        Err(_) => Ok(Notifications::default()),
+    }
+}
+
+/// Deliver `event` in the background, so no request waits on a webhook
+pub fn send(state: &FaucetState, event: Event) {
+    let notifications = state.notifications.clone();
+    tokio::spawn(async move { notifications.notify(event).await });
+}
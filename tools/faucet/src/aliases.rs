@@ -0,0 +1,188 @@
+//! Address alias clustering
+//!
+//! Farms request through many derived addresses. Addresses are linked when
+//! one funds the other on-chain (found by the `link_aliases` job) or when
+//! they are granted to the same client session (the `fingerprint` of a
+//! request). The `alias` policy stage applies the cooldown to every address
+//! in a cluster, so a grant to one member holds back the rest.
+//!
+//! Wallets linked to more than `ALIAS_MAX_FANOUT` addresses, such as
+//! exchanges or bridges, are kept in clusters but never expanded, so they
+//! can't merge unrelated users.
+
+use serde::Serialize;
+use sha3::{Digest, Sha3_256};
+use std::collections::{HashMap, HashSet};
+use std::time::SystemTime;
+use tracing::info;
+
+use crate::ledger::Ledger;
+use crate::rpc::parse_quantity;
+use crate::{FaucetError, FaucetState};
+
+/// Prefix of session nodes in the link graph
+const SESSION_PREFIX: &str = "session:";
+
+/// Most nodes loaded for one cluster
+pub const MAX_CLUSTER_NODES: u32 = 200;
+
+/// Clusters listed in admin stats
+const MAX_LISTED_CLUSTERS: usize = 20;
+
+/// Addresses listed per cluster in admin stats
+const MAX_LISTED_ADDRESSES: usize = 10;
+
+/// Alias clustering settings
+pub struct Aliases {
+    /// Nodes linked to more addresses than this are not expanded
+    pub max_fanout: u32,
+    /// Most blocks scanned for funding links per job run
+    pub max_blocks: u64,
+}
+
+impl Aliases {
+    /// Load settings from `ALIAS_MAX_FANOUT` and `ALIAS_SCAN_BLOCKS`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let max_fanout = match std::env::var("ALIAS_MAX_FANOUT") {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid ALIAS_MAX_FANOUT: {:?}", value))?,
+            Err(_) => 20,
+        };
+        let max_blocks = match std::env::var("ALIAS_SCAN_BLOCKS") {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid ALIAS_SCAN_BLOCKS: {:?}", value))?,
+            Err(_) => 200,
+        };
+        Ok(Self {
+            max_fanout,
+            max_blocks,
+        })
+    }
+}
+
+/// Link graph node of a client session; the fingerprint itself isn't stored
+pub fn session_node(fingerprint: &str) -> String {
+    let digest = Sha3_256::digest(fingerprint.as_bytes());
+    format!("{}{}", SESSION_PREFIX, hex::encode(&digest[..16]))
+}
+
+/// Whether a link graph node is a session rather than an address
+pub fn is_session(node: &str) -> bool {
+    node.starts_with(SESSION_PREFIX)
+}
+
+/// Link recipients to the wallets that fund them or that they fund
+///
+/// Scans blocks from where the last run stopped; the first run starts at the
+/// chain head. Only transfers touching an address known to the faucet are
+/// linked, and transfers to or from the faucet itself are ignored.
+pub async fn link_funding(state: &FaucetState) -> Result<(), FaucetError> {
+    let head = state.rpc.get_block_number().await?;
+    let last = state.ledger.lock().await.alias_scan_block()?;
+    let Some(last) = last else {
+        state.ledger.lock().await.set_alias_scan_block(head)?;
+        return Ok(());
+    };
+    let start = last + 1;
+    if start > head {
+        return Ok(());
+    }
+    let end = head.min(start + state.aliases.max_blocks.max(1) - 1);
+    let faucet = state.address.to_lowercase();
+
+    let mut linked = 0;
+    for number in start..=end {
+        let block = state
+            .rpc
+            .get_block_by_number(number, true)
+            .await?
+            .ok_or_else(|| axionax_rpc::Error::EmptyResult("eth_getBlockByNumber".to_string()))?;
+        let transactions = block["transactions"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+
+        let ledger = state.ledger.lock().await;
+        for tx in &transactions {
+            let (Some(from), Some(to)) = (tx["from"].as_str(), tx["to"].as_str()) else {
+                continue;
+            };
+            let value = tx["value"].as_str().map(parse_quantity).transpose()?;
+            let (from, to) = (from.to_lowercase(), to.to_lowercase());
+            if value.unwrap_or_default() == 0 || from == faucet || to == faucet || from == to {
+                continue;
+            }
+            if ledger.is_known_address(&to)? || ledger.is_known_address(&from)? {
+                ledger.link_address(&to, &from, "funding")?;
+                linked += 1;
+            }
+        }
+        ledger.set_alias_scan_block(number)?;
+    }
+    if linked > 0 {
+        info!(
+            "Linked {} funding transfers in blocks {}-{}",
+            linked, start, end
+        );
+    }
+    Ok(())
+}
+
+/// A cluster of addresses believed to belong to one user
+#[derive(Debug, Serialize)]
+pub struct AliasCluster {
+    /// Addresses in the cluster
+    size: usize,
+    /// Sessions linking the cluster
+    sessions: usize,
+    /// Grants to the cluster's addresses within the window
+    grants: u64,
+    /// Up to 10 addresses of the cluster
+    addresses: Vec<String>,
+}
+
+/// Largest clusters among addresses granted since `since`
+pub fn clusters(
+    ledger: &Ledger,
+    since: SystemTime,
+    max_fanout: u32,
+) -> Result<Vec<AliasCluster>, FaucetError> {
+    let mut grants: HashMap<String, u64> = HashMap::new();
+    let mut order = Vec::new();
+    for (address, _, _) in ledger.grants_since(since)? {
+        let count = grants.entry(address.clone()).or_default();
+        if *count == 0 {
+            order.push(address);
+        }
+        *count += 1;
+    }
+
+    let mut seen = HashSet::new();
+    let mut clusters = Vec::new();
+    for address in order {
+        if seen.contains(&address) {
+            continue;
+        }
+        let nodes = ledger.linked_nodes(&address, max_fanout, MAX_CLUSTER_NODES)?;
+        let sessions = nodes.iter().filter(|n| is_session(n)).count();
+        let mut addresses: Vec<String> = nodes.into_iter().filter(|n| !is_session(n)).collect();
+        addresses.push(address);
+        addresses.sort();
+        seen.extend(addresses.iter().cloned());
+        if addresses.len() < 2 {
+            continue;
+        }
+
+        clusters.push(AliasCluster {
+            size: addresses.len(),
+            sessions,
+            grants: addresses.iter().filter_map(|a| grants.get(a)).sum(),
+            addresses: addresses.into_iter().take(MAX_LISTED_ADDRESSES).collect(),
+        });
+    }
+    clusters.sort_by(|a, b| b.grants.cmp(&a.grants).then(b.size.cmp(&a.size)));
+    clusters.truncate(MAX_LISTED_CLUSTERS);
+    Ok(clusters)
+}
@@ -0,0 +1,185 @@
+//! Time-bucketed usage analytics and the budget runway forecast
+
+use axionax_tx::U256;
+use axum::extract::{Json, Query, State};
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+use crate::ledger::{to_unix, EntryKind};
+use crate::{axx, FaucetError, FaucetState};
+
+const MAX_BUCKETS: u32 = 1000;
+
+/// Days the burn rate is averaged over when `/stats/runway` has no `days`
+pub const DEFAULT_RUNWAY_WINDOW_DAYS: u32 = 7;
+
+const MAX_RUNWAY_WINDOW_DAYS: u32 = 90;
+
+/// Bucket width for the time series
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    #[default]
+    Hour,
+    Day,
+}
+
+impl Bucket {
+    /// Bucket length in seconds
+    pub fn width(self) -> i64 {
+        match self {
+            Bucket::Hour => 3600,
+            Bucket::Day => 86400,
+        }
+    }
+
+    fn default_count(self) -> u32 {
+        match self {
+            Bucket::Hour => 48,
+            Bucket::Day => 30,
+        }
+    }
+}
+
+/// Query parameters for `/stats/timeseries`
+#[derive(Debug, Deserialize)]
+pub struct TimeseriesQuery {
+    #[serde(default)]
+    bucket: Bucket,
+    /// Number of buckets to return, ending with the current one
+    buckets: Option<u32>,
+}
+
+/// One bucket of the time series
+#[derive(Debug, Default, Serialize)]
+pub struct BucketStats {
+    /// Bucket start (unix seconds)
+    start: i64,
+    requests: u64,
+    grants: u64,
+    rejects: u64,
+    distributed: String,
+}
+
+/// Time series response
+#[derive(Debug, Serialize)]
+pub struct TimeseriesResponse {
+    bucket: Bucket,
+    series: Vec<BucketStats>,
+}
+
+/// Get request, grant, reject and distribution counts per time bucket
+pub async fn timeseries(
+    State(state): State<FaucetState>,
+    Query(query): Query<TimeseriesQuery>,
+) -> Result<Json<TimeseriesResponse>, FaucetError> {
+    let width = query.bucket.width();
+    let count = query
+        .buckets
+        .unwrap_or_else(|| query.bucket.default_count())
+        .clamp(1, MAX_BUCKETS) as i64;
+
+    let now = to_unix(SystemTime::now());
+    let first = (now / width - (count - 1)) * width;
+
+    let (requests, distributed) = {
+        let ledger = state.ledger.lock().await;
+        (
+            ledger.request_buckets(first, width)?,
+            ledger.transfer_buckets(EntryKind::Drip, first, width)?,
+        )
+    };
+    let mut series: Vec<BucketStats> = (0..count)
+        .map(|i| {
+            let start = first + i * width;
+            BucketStats {
+                start,
+                distributed: axx(distributed.get(&start).copied().unwrap_or_default()),
+                ..Default::default()
+            }
+        })
+        .collect();
+    for bucket in requests {
+        if let Some(stats) = series.get_mut(((bucket.start - first) / width) as usize) {
+            stats.requests = bucket.requests;
+            stats.grants = bucket.grants;
+            stats.rejects = bucket.requests - bucket.grants;
+        }
+    }
+
+    Ok(Json(TimeseriesResponse {
+        bucket: query.bucket,
+        series,
+    }))
+}
+
+/// Query parameters for `/stats/runway`
+#[derive(Debug, Deserialize)]
+pub struct RunwayQuery {
+    /// Days of drips the burn rate is averaged over
+    days: Option<u32>,
+}
+
+/// Burn rate and projected runway of the faucet balance
+#[derive(Debug, Serialize)]
+pub struct Runway {
+    pub balance: String,
+    window_days: u32,
+    /// Drips sent within the window
+    distributed: String,
+    pub burn_per_day: String,
+    /// Days until the balance runs out at the current burn rate; unset without drips
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runway_days: Option<f64>,
+    /// Projected unix time the balance runs out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depletes_at: Option<i64>,
+}
+
+/// Get the burn rate and projected days of runway for the current balance
+pub async fn runway(
+    State(state): State<FaucetState>,
+    Query(query): Query<RunwayQuery>,
+) -> Result<Json<Runway>, FaucetError> {
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_RUNWAY_WINDOW_DAYS)
+        .clamp(1, MAX_RUNWAY_WINDOW_DAYS);
+    forecast(&state, days).await.map(Json)
+}
+
+/// Project the runway of the balance from drips of the last `window_days` days
+///
+/// Fees aren't counted, so the balance runs out slightly sooner.
+pub async fn forecast(state: &FaucetState, window_days: u32) -> Result<Runway, FaucetError> {
+    let cached = *state.balance.read().await;
+    let balance = match cached {
+        Some(balance) => balance,
+        None => state.rpc.get_balance(&state.address).await?,
+    };
+    let day = Bucket::Day.width();
+    let now = to_unix(SystemTime::now());
+    let window = window_days as i64 * day;
+    // The whole window as a single bucket
+    let distributed = state
+        .ledger
+        .lock()
+        .await
+        .transfer_buckets(EntryKind::Drip, now - window, window)?
+        .into_values()
+        .fold(U256::zero(), U256::saturating_add);
+
+    let burn_per_day = distributed / window_days;
+    let runway_days = (!burn_per_day.is_zero()).then(|| {
+        let hundredths = balance.saturating_mul(100.into()) / burn_per_day;
+        hundredths.min(U256::from(u64::MAX)).as_u64() as f64 / 100.0
+    });
+    Ok(Runway {
+        balance: axx(balance),
+        window_days,
+        distributed: axx(distributed),
+        burn_per_day: axx(burn_per_day),
+        depletes_at: runway_days.map(|days| now + (days * day as f64) as i64),
+        runway_days,
+    })
+}
@@ -0,0 +1,153 @@
+//! Versioned public API
+//!
+//! Every public route is served under `/v1`. The same routes are still
+//! answered at their original unversioned paths so existing integrations keep
+//! working, with `Deprecation` (RFC 9745), `Sunset` (RFC 8594) and a `Link` to
+//! the `/v1` successor on every response. `LEGACY_API_SUNSET` sets the sunset
+//! as an HTTP date. Probes and scraping (`/health`, `/readyz`, `/metrics`)
+//! stay unversioned.
+//!
+//! A new version with different response shapes gets its own router nested
+//! next to [`v1`], reusing the handlers whose shape doesn't change. Rate limits
+//! and metrics are keyed by the route without its version, so `RATE_LIMITS`
+//! and `SLO_OBJECTIVES` entries like `/request` cover every version.
+
+use axionax_faucet::validation;
+use axum::{
+    extract::{DefaultBodyLimit, MatchedPath, Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+    routing::{get, post, put},
+    Router,
+};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::metrics::Metrics;
+use crate::{
+    admin, analytics, badges, challenge, claims, get_receipt, info, leaderboard, metrics,
+    request_tokens, stats, stats_stream, task_status, why, FaucetState,
+};
+
+/// Prefix of the current API version
+pub const V1: &str = "/v1";
+
+/// When the unversioned paths were deprecated (2026-10-16T00:00:00Z)
+const LEGACY_DEPRECATED_AT: u64 = 1_792_108_800;
+
+/// Time between deprecation and sunset when `LEGACY_API_SUNSET` is unset
+const DEFAULT_SUNSET_AFTER: Duration = Duration::from_secs(180 * 24 * 3600);
+
+/// Routes of API version 1, relative to [`V1`]
+pub fn v1() -> Router<FaucetState> {
+    Router::new()
+        .route("/info", get(info))
+        .route(
+            "/request",
+            post(request_tokens).layer(DefaultBodyLimit::max(validation::MAX_PAYLOAD_BYTES)),
+        )
+        .route("/challenge", get(challenge::pow_challenge))
+        .route("/stats", get(stats))
+        .route("/stats/stream", get(stats_stream))
+        .route("/receipt/:claim_id", get(get_receipt))
+        .route("/ws", get(claims::subscribe))
+        .route("/tasks/:address", get(task_status))
+        .route("/badges/:address", get(badges::badge))
+        .route("/why/:address", get(why::why))
+        .route("/stats/timeseries", get(analytics::timeseries))
+        .route("/stats/runway", get(analytics::runway))
+        .route("/leaderboard", get(leaderboard::leaderboard))
+        .route("/admin/stats", get(admin::stats))
+        .route("/admin/audit", get(admin::audit))
+        .route(
+            "/admin/maintenance",
+            put(admin::start_maintenance).delete(admin::end_maintenance),
+        )
+        .route("/admin/flags", get(admin::flags))
+        .route(
+            "/admin/flags/:name",
+            put(admin::set_flag).delete(admin::clear_flag),
+        )
+        .route("/admin/labels", get(admin::labeled))
+        .route("/admin/labels/:address", get(admin::labels))
+        .route(
+            "/admin/labels/:address/:label",
+            put(admin::set_label).delete(admin::remove_label),
+        )
+        .route("/slo", get(metrics::slo))
+}
+
+/// Version 1 routes at their unversioned paths, marked deprecated
+pub fn legacy(metrics: Arc<Metrics>) -> anyhow::Result<Router<FaucetState>> {
+    let deprecation = Deprecation::from_env(metrics)?;
+    Ok(v1().layer(axum::middleware::from_fn_with_state(
+        Arc::new(deprecation),
+        deprecated,
+    )))
+}
+
+/// Route without its version prefix, e.g. `/request` for `/v1/request`
+pub fn unversioned(route: &str) -> &str {
+    let Some(rest) = route.strip_prefix("/v") else {
+        return route;
+    };
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    match rest[digits..].strip_prefix('/') {
+        Some(_) if digits > 0 => &rest[digits..],
+        _ => route,
+    }
+}
+
+/// Header values sent with every response on a deprecated path
+struct Deprecation {
+    deprecation: HeaderValue,
+    sunset: HeaderValue,
+    metrics: Arc<Metrics>,
+}
+
+impl Deprecation {
+    fn from_env(metrics: Arc<Metrics>) -> anyhow::Result<Self> {
+        let deprecated_at = SystemTime::UNIX_EPOCH + Duration::from_secs(LEGACY_DEPRECATED_AT);
+        let sunset = match std::env::var("LEGACY_API_SUNSET") {
+            Ok(date) => httpdate::parse_http_date(&date).map_err(|_| {
+                anyhow::anyhow!(
+                    "Invalid LEGACY_API_SUNSET {:?}, expected an HTTP date such as {:?}",
+                    date,
+                    httpdate::fmt_http_date(deprecated_at + DEFAULT_SUNSET_AFTER)
+                )
+            })?,
+            Err(_) => deprecated_at + DEFAULT_SUNSET_AFTER,
+        };
+        Ok(Self {
+            deprecation: HeaderValue::from_str(&format!("@{}", LEGACY_DEPRECATED_AT))?,
+            sunset: HeaderValue::from_str(&httpdate::fmt_http_date(sunset))?,
+            metrics,
+        })
+    }
+}
+
+/// Mark a response to an unversioned path as deprecated and point to `/v1`
+async fn deprecated(
+    State(deprecation): State<Arc<Deprecation>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(route) = request.extensions().get::<MatchedPath>() {
+        deprecation.metrics.record_deprecated(route.as_str());
+    }
+    let successor = format!(
+        "<{}{}>; rel=\"successor-version\"",
+        V1,
+        request.uri().path()
+    );
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", deprecation.deprecation.clone());
+    headers.insert("sunset", deprecation.sunset.clone());
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.append(header::LINK, link);
+    }
+    response
+}
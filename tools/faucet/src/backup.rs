@@ -0,0 +1,552 @@
+//! Portable snapshots of the faucet ledger
+//!
+//! `axionax-faucet backup <file>` writes every transfer, request and receipt to
+//! a JSON snapshot with a SHA3-256 checksum; `restore <file>` verifies the
+//! checksum and loads it into an empty database. Cooldowns and IP windows are
+//! rebuilt from the restored grants on startup. The admin audit log is
+//! append-only: restoring adds missing audit entries but never replaces any.
+
+use anyhow::{bail, Context};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::ledger::to_unix;
+
+/// Snapshot file format version
+const FORMAT_VERSION: u32 = 1;
+
+/// Snapshot file: the snapshot plus a checksum over its serialized form
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    checksum: String,
+    snapshot: Snapshot,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    format: u32,
+    created_at: i64,
+    schema_version: Option<u32>,
+    transfers: Vec<TransferRow>,
+    requests: Vec<RequestRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    receipts: Vec<ReceiptRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    recipient_activity: Vec<ActivityRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    admin_audit: Vec<AuditRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    address_links: Vec<LinkRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    maintenance: Vec<MaintenanceRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    feature_flags: Vec<FlagRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    badges: Vec<BadgeRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    identities: Vec<IdentityRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    address_labels: Vec<LabelRow>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TransferRow {
+    id: i64,
+    kind: String,
+    address: String,
+    amount: String,
+    tx_hash: String,
+    timestamp: i64,
+    // Omitted when empty so snapshots taken before partners existed keep their checksum
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    partner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RequestRow {
+    id: i64,
+    address: String,
+    ip: String,
+    granted: bool,
+    reason: Option<String>,
+    timestamp: i64,
+    country: Option<String>,
+    asn: Option<u32>,
+    as_org: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    partner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReceiptRow {
+    claim_id: String,
+    address: String,
+    amount: String,
+    tx_hash: String,
+    timestamp: i64,
+    chain_id: i64,
+    signature: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ActivityRow {
+    address: String,
+    first_drip: i64,
+    nonce_at_drip: i64,
+    scanned_nonce: i64,
+    transactions: i64,
+    contracts: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AuditRow {
+    id: i64,
+    timestamp: i64,
+    actor: String,
+    action: String,
+    payload: String,
+    ip: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LinkRow {
+    address: String,
+    linked: String,
+    kind: String,
+    created: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MaintenanceRow {
+    id: i64,
+    message: String,
+    eta: Option<i64>,
+    since: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FlagRow {
+    name: String,
+    percent: i64,
+    partners: String,
+    updated_by: String,
+    updated: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BadgeRow {
+    address: String,
+    status: String,
+    attempts: i64,
+    tx_hash: Option<String>,
+    error: Option<String>,
+    queued: i64,
+    updated: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IdentityRow {
+    address: String,
+    provider: String,
+    subject: String,
+    verified: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LabelRow {
+    address: String,
+    label: String,
+    note: Option<String>,
+    added_by: String,
+    added: i64,
+}
+
+impl Snapshot {
+    fn checksum(&self) -> anyhow::Result<String> {
+        let bytes = serde_json::to_vec(self)?;
+        Ok(format!("0x{}", hex::encode(Sha3_256::digest(bytes))))
+    }
+}
+
+/// Write a snapshot of the database at `db_path` to `file`
+pub fn backup(db_path: &str, file: &Path) -> anyhow::Result<()> {
+    let mut conn = crate::db::open(db_path)?;
+    crate::db::migrate(&mut conn)?;
+
+    let snapshot = Snapshot {
+        format: FORMAT_VERSION,
+        created_at: to_unix(SystemTime::now()),
+        schema_version: crate::db::schema_version(&mut conn)?,
+        transfers: conn
+            .prepare(
+                "SELECT id, kind, address, amount, tx_hash, timestamp, partner
+                 FROM transfers ORDER BY id",
+            )?
+            .query_map([], |row| {
+                Ok(TransferRow {
+                    id: row.get(0)?,
+                    kind: row.get(1)?,
+                    address: row.get(2)?,
+                    amount: row.get(3)?,
+                    tx_hash: row.get(4)?,
+                    timestamp: row.get(5)?,
+                    partner: row.get(6)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        requests: conn
+            .prepare(
+                "SELECT id, address, ip, granted, reason, timestamp, country, asn, as_org, partner
+                 FROM requests ORDER BY id",
+            )?
+            .query_map([], |row| {
+                Ok(RequestRow {
+                    id: row.get(0)?,
+                    address: row.get(1)?,
+                    ip: row.get(2)?,
+                    granted: row.get(3)?,
+                    reason: row.get(4)?,
+                    timestamp: row.get(5)?,
+                    country: row.get(6)?,
+                    asn: row.get(7)?,
+                    as_org: row.get(8)?,
+                    partner: row.get(9)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        receipts: conn
+            .prepare(
+                "SELECT claim_id, address, amount, tx_hash, timestamp, chain_id, signature
+                 FROM receipts ORDER BY timestamp, claim_id",
+            )?
+            .query_map([], |row| {
+                Ok(ReceiptRow {
+                    claim_id: row.get(0)?,
+                    address: row.get(1)?,
+                    amount: row.get(2)?,
+                    tx_hash: row.get(3)?,
+                    timestamp: row.get(4)?,
+                    chain_id: row.get(5)?,
+                    signature: row.get(6)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        recipient_activity: conn
+            .prepare(
+                "SELECT address, first_drip, nonce_at_drip, scanned_nonce, transactions, contracts
+                 FROM recipient_activity ORDER BY address",
+            )?
+            .query_map([], |row| {
+                Ok(ActivityRow {
+                    address: row.get(0)?,
+                    first_drip: row.get(1)?,
+                    nonce_at_drip: row.get(2)?,
+                    scanned_nonce: row.get(3)?,
+                    transactions: row.get(4)?,
+                    contracts: row.get(5)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        admin_audit: conn
+            .prepare(
+                "SELECT id, timestamp, actor, action, payload, ip FROM admin_audit ORDER BY id",
+            )?
+            .query_map([], |row| {
+                Ok(AuditRow {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    actor: row.get(2)?,
+                    action: row.get(3)?,
+                    payload: row.get(4)?,
+                    ip: row.get(5)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        address_links: conn
+            .prepare(
+                "SELECT address, linked, kind, created FROM address_links
+                 ORDER BY created, address, linked",
+            )?
+            .query_map([], |row| {
+                Ok(LinkRow {
+                    address: row.get(0)?,
+                    linked: row.get(1)?,
+                    kind: row.get(2)?,
+                    created: row.get(3)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        maintenance: conn
+            .prepare("SELECT id, message, eta, since FROM maintenance ORDER BY id")?
+            .query_map([], |row| {
+                Ok(MaintenanceRow {
+                    id: row.get(0)?,
+                    message: row.get(1)?,
+                    eta: row.get(2)?,
+                    since: row.get(3)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        feature_flags: conn
+            .prepare(
+                "SELECT name, percent, partners, updated_by, updated FROM feature_flags
+                 ORDER BY name",
+            )?
+            .query_map([], |row| {
+                Ok(FlagRow {
+                    name: row.get(0)?,
+                    percent: row.get(1)?,
+                    partners: row.get(2)?,
+                    updated_by: row.get(3)?,
+                    updated: row.get(4)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        badges: conn
+            .prepare(
+                "SELECT address, status, attempts, tx_hash, error, queued, updated FROM badges
+                 ORDER BY queued, address",
+            )?
+            .query_map([], |row| {
+                Ok(BadgeRow {
+                    address: row.get(0)?,
+                    status: row.get(1)?,
+                    attempts: row.get(2)?,
+                    tx_hash: row.get(3)?,
+                    error: row.get(4)?,
+                    queued: row.get(5)?,
+                    updated: row.get(6)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        identities: conn
+            .prepare(
+                "SELECT address, provider, subject, verified FROM identities
+                 ORDER BY address",
+            )?
+            .query_map([], |row| {
+                Ok(IdentityRow {
+                    address: row.get(0)?,
+                    provider: row.get(1)?,
+                    subject: row.get(2)?,
+                    verified: row.get(3)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+        address_labels: conn
+            .prepare(
+                "SELECT address, label, note, added_by, added FROM address_labels
+                 ORDER BY address, label",
+            )?
+            .query_map([], |row| {
+                Ok(LabelRow {
+                    address: row.get(0)?,
+                    label: row.get(1)?,
+                    note: row.get(2)?,
+                    added_by: row.get(3)?,
+                    added: row.get(4)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?,
+    };
+
+    let contents = SnapshotFile {
+        checksum: snapshot.checksum()?,
+        snapshot,
+    };
+    let tmp = file.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&contents)?)?;
+    std::fs::rename(&tmp, file)?;
+
+    println!(
+        "Backed up {} transfers, {} requests and {} receipts to {} (checksum {})",
+        contents.snapshot.transfers.len(),
+        contents.snapshot.requests.len(),
+        contents.snapshot.receipts.len(),
+        file.display(),
+        contents.checksum
+    );
+    Ok(())
+}
+
+/// Load the snapshot in `file` into the database at `db_path`
+///
+/// Refuses to overwrite existing records unless `force` is set.
+pub fn restore(db_path: &str, file: &Path, force: bool) -> anyhow::Result<()> {
+    let contents: SnapshotFile = serde_json::from_slice(
+        &std::fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?,
+    )
+    .context("Invalid snapshot file")?;
+    let snapshot = contents.snapshot;
+
+    if snapshot.format != FORMAT_VERSION {
+        bail!("Unsupported snapshot format {}", snapshot.format);
+    }
+    if snapshot.checksum()? != contents.checksum {
+        bail!("Snapshot checksum mismatch, the file is corrupt or was modified");
+    }
+
+    let mut conn = crate::db::open(db_path)?;
+    crate::db::migrate(&mut conn)?;
+    let schema_version = crate::db::schema_version(&mut conn)?;
+    if snapshot.schema_version > schema_version {
+        bail!(
+            "Snapshot schema version {:?} is newer than this binary ({:?})",
+            snapshot.schema_version,
+            schema_version
+        );
+    }
+
+    let tx = conn.transaction()?;
+    let existing: i64 = tx.query_row(
+        "SELECT (SELECT COUNT(*) FROM transfers) + (SELECT COUNT(*) FROM requests)
+              + (SELECT COUNT(*) FROM receipts) + (SELECT COUNT(*) FROM recipient_activity)
+              + (SELECT COUNT(*) FROM address_links) + (SELECT COUNT(*) FROM maintenance)
+              + (SELECT COUNT(*) FROM feature_flags) + (SELECT COUNT(*) FROM badges)
+              + (SELECT COUNT(*) FROM identities) + (SELECT COUNT(*) FROM address_labels)",
+        [],
+        |row| row.get(0),
+    )?;
+    if existing > 0 {
+        if !force {
+            bail!(
+                "{} already contains {} records; pass --force to replace them",
+                db_path,
+                existing
+            );
+        }
+        tx.execute_batch(
+            "DELETE FROM transfers; DELETE FROM requests; DELETE FROM receipts;
+             DELETE FROM recipient_activity; DELETE FROM address_links;
+             DELETE FROM maintenance; DELETE FROM feature_flags; DELETE FROM badges;
+             DELETE FROM identities; DELETE FROM address_labels;",
+        )?;
+    }
+
+    // Rebuilt from the restored transfers on the next read
+    tx.execute("DELETE FROM transfer_totals", [])?;
+    for t in &snapshot.transfers {
+        tx.execute(
+            "INSERT INTO transfers (id, kind, address, amount, tx_hash, timestamp, partner)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                t.id,
+                t.kind,
+                t.address,
+                t.amount,
+                t.tx_hash,
+                t.timestamp,
+                t.partner
+            ],
+        )?;
+    }
+    for r in &snapshot.requests {
+        tx.execute(
+            "INSERT INTO requests
+             (id, address, ip, granted, reason, timestamp, country, asn, as_org, partner)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                r.id,
+                r.address,
+                r.ip,
+                r.granted,
+                r.reason,
+                r.timestamp,
+                r.country,
+                r.asn,
+                r.as_org,
+                r.partner
+            ],
+        )?;
+    }
+    for r in &snapshot.receipts {
+        tx.execute(
+            "INSERT INTO receipts (claim_id, address, amount, tx_hash, timestamp, chain_id, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                r.claim_id,
+                r.address,
+                r.amount,
+                r.tx_hash,
+                r.timestamp,
+                r.chain_id,
+                r.signature
+            ],
+        )?;
+    }
+    for a in &snapshot.recipient_activity {
+        tx.execute(
+            "INSERT INTO recipient_activity
+             (address, first_drip, nonce_at_drip, scanned_nonce, transactions, contracts)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                a.address,
+                a.first_drip,
+                a.nonce_at_drip,
+                a.scanned_nonce,
+                a.transactions,
+                a.contracts
+            ],
+        )?;
+    }
+    for l in &snapshot.address_links {
+        tx.execute(
+            "INSERT INTO address_links (address, linked, kind, created) VALUES (?1, ?2, ?3, ?4)",
+            params![l.address, l.linked, l.kind, l.created],
+        )?;
+    }
+    for m in &snapshot.maintenance {
+        tx.execute(
+            "INSERT INTO maintenance (id, message, eta, since) VALUES (?1, ?2, ?3, ?4)",
+            params![m.id, m.message, m.eta, m.since],
+        )?;
+    }
+    for f in &snapshot.feature_flags {
+        tx.execute(
+            "INSERT INTO feature_flags (name, percent, partners, updated_by, updated)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![f.name, f.percent, f.partners, f.updated_by, f.updated],
+        )?;
+    }
+    for b in &snapshot.badges {
+        tx.execute(
+            "INSERT INTO badges (address, status, attempts, tx_hash, error, queued, updated)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![b.address, b.status, b.attempts, b.tx_hash, b.error, b.queued, b.updated],
+        )?;
+    }
+    for i in &snapshot.identities {
+        tx.execute(
+            "INSERT INTO identities (address, provider, subject, verified) VALUES (?1, ?2, ?3, ?4)",
+            params![i.address, i.provider, i.subject, i.verified],
+        )?;
+    }
+    for l in &snapshot.address_labels {
+        tx.execute(
+            "INSERT INTO address_labels (address, label, note, added_by, added)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![l.address, l.label, l.note, l.added_by, l.added],
+        )?;
+    }
+    // Entries already in the database are kept as they are
+    for a in &snapshot.admin_audit {
+        tx.execute(
+            "INSERT OR IGNORE INTO admin_audit (id, timestamp, actor, action, payload, ip)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![a.id, a.timestamp, a.actor, a.action, a.payload, a.ip],
+        )?;
+    }
+    tx.commit()?;
+
+    println!(
+        "Restored {} transfers, {} requests and {} receipts into {}",
+        snapshot.transfers.len(),
+        snapshot.requests.len(),
+        snapshot.receipts.len(),
+        db_path
+    );
+    Ok(())
+}
@@ -0,0 +1,175 @@
+//! "Testnet builder" badges for first-time recipients
+//!
+//! With `BADGE_CONTRACT` set, an address's first drip queues a badge in the
+//! `badges` table. The `mint_badges` job calls `BADGE_MINT_FUNCTION` (default
+//! `mint(address)`) on the contract from the faucet signer with the recipient
+//! as its only argument, so the contract must let the faucet mint. Mints hold
+//! the grant lock, so they never race a drip for a nonce. A mint that can't be
+//! sent is retried on the next run and given up after `BADGE_MAX_ATTEMPTS`
+//! (default 5) tries. Addresses first dripped before badges were enabled
+//! don't get one.
+//!
+//! `GET /badges/:address` reports the badge of an address.
+
+use axionax_tx::{Transaction, U256};
+use axum::extract::{Json, Path, State};
+use serde_json::json;
+use sha3::{Digest, Keccak256};
+use tracing::{info, warn};
+
+use crate::ledger::{Badge, BadgeStatus};
+use crate::rpc::parse_quantity;
+use crate::{wallet, FaucetError, FaucetState};
+use axionax_faucet::validation;
+
+/// Mint function used when `BADGE_MINT_FUNCTION` is unset
+const DEFAULT_MINT_FUNCTION: &str = "mint(address)";
+
+/// Send attempts per badge when `BADGE_MAX_ATTEMPTS` is unset
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Most badges minted by one run of the job
+const MINTS_PER_RUN: usize = 10;
+
+/// Badge contract and how to mint from it
+#[derive(Debug)]
+pub struct Badges {
+    contract: String,
+    selector: [u8; 4],
+    max_attempts: u32,
+}
+
+impl Badges {
+    /// Load from `BADGE_CONTRACT`; `None` when badges are disabled
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(contract) = std::env::var("BADGE_CONTRACT") else {
+            return Ok(None);
+        };
+        let contract = validation::parse_address(&contract)
+            .map_err(|_| anyhow::anyhow!("Invalid BADGE_CONTRACT"))?;
+        let function = std::env::var("BADGE_MINT_FUNCTION")
+            .unwrap_or_else(|_| DEFAULT_MINT_FUNCTION.to_string());
+        let function: String = function.chars().filter(|c| !c.is_whitespace()).collect();
+        if !function.ends_with("(address)") || function.len() == "(address)".len() {
+            anyhow::bail!(
+                "BADGE_MINT_FUNCTION must take a single address, e.g. {}",
+                DEFAULT_MINT_FUNCTION
+            );
+        }
+        let max_attempts = match std::env::var("BADGE_MAX_ATTEMPTS") {
+            Ok(n) => n
+                .parse()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| anyhow::anyhow!("Invalid BADGE_MAX_ATTEMPTS"))?,
+            Err(_) => DEFAULT_MAX_ATTEMPTS,
+        };
+
+        let mut selector = [0u8; 4];
+        selector.copy_from_slice(&Keccak256::digest(function.as_bytes())[..4]);
+        Ok(Some(Self {
+            contract,
+            selector,
+            max_attempts,
+        }))
+    }
+
+    /// Calldata minting a badge to `recipient`
+    fn calldata(&self, recipient: &str) -> Result<Vec<u8>, FaucetError> {
+        let address =
+            axionax_tx::parse_address(recipient).map_err(|_| FaucetError::InvalidAddress)?;
+        let mut data = self.selector.to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(&address);
+        Ok(data)
+    }
+}
+
+/// Queue a badge after a drip to `address`, if it was the address's first
+pub async fn queue(state: &FaucetState, address: &str) {
+    if state.badges.is_none() {
+        return;
+    }
+    match state.ledger.lock().await.queue_badge(address) {
+        Ok(true) => info!("Queued a badge for {}", address),
+        Ok(false) => {}
+        Err(e) => warn!("Failed to queue a badge for {}: {:?}", address, e),
+    }
+}
+
+/// Mint the oldest queued badges
+pub async fn mint_queued(state: &FaucetState) -> Result<(), FaucetError> {
+    let Some(badges) = state.badges.as_ref() else {
+        return Ok(());
+    };
+    let queued = state.ledger.lock().await.queued_badges(MINTS_PER_RUN)?;
+    for address in queued {
+        let sent = {
+            let _grant = state.grant_lock.lock().await;
+            send_mint(state, badges, &address).await
+        };
+        let status = {
+            let ledger = state.ledger.lock().await;
+            match &sent {
+                Ok(tx_hash) => {
+                    ledger.record_badge_mint(&address, Ok(tx_hash), badges.max_attempts)?
+                }
+                Err(e) => {
+                    ledger.record_badge_mint(&address, Err(&e.message()), badges.max_attempts)?
+                }
+            }
+        };
+        match (sent, status) {
+            (Ok(tx_hash), _) => info!("✓ Minted a badge to {} (tx: {})", address, tx_hash),
+            (Err(e), BadgeStatus::Failed) => {
+                warn!("Gave up minting a badge to {}: {:?}", address, e)
+            }
+            (Err(e), _) => warn!("Failed to mint a badge to {}, retrying: {:?}", address, e),
+        }
+    }
+    Ok(())
+}
+
+/// Sign and send the mint call for `recipient`
+async fn send_mint(
+    state: &FaucetState,
+    badges: &Badges,
+    recipient: &str,
+) -> Result<String, FaucetError> {
+    if let Some(chain) = state.rpc.mock_chain() {
+        return chain.transfer(&state.address, &badges.contract, U256::zero());
+    }
+
+    let signing = |e: &dyn std::fmt::Display| axionax_errors::Error::signing(e.to_string());
+    let key = wallet::signing_key(&state.private_key).map_err(|e| signing(&e))?;
+    let contract = axionax_tx::parse_address(&badges.contract).map_err(|e| signing(&e))?;
+    let data = badges.calldata(recipient)?;
+    let call = json!([{
+        "from": state.address,
+        "to": badges.contract,
+        "data": format!("0x{}", hex::encode(&data)),
+    }]);
+    let (nonce, gas_price, gas) = tokio::try_join!(
+        state.rpc.get_pending_nonce(&state.address),
+        state.rpc.get_gas_price(),
+        state.rpc.call::<String>("eth_estimateGas", call),
+    )?;
+    let signed = Transaction::transfer(state.chain_id, contract, U256::zero())
+        .with_nonce(nonce)
+        .with_gas_limit(parse_quantity(&gas)? as u64)
+        .with_gas_price(gas_price)
+        .with_input(data)
+        .sign(&key)
+        .map_err(|e| signing(&e))?;
+    state.rpc.send_raw_transaction(&signed.raw_hex()).await
+}
+
+/// Get the badge of an address
+pub async fn badge(
+    State(state): State<FaucetState>,
+    Path(address): Path<String>,
+) -> Result<Json<Badge>, FaucetError> {
+    let address = validation::parse_address(&address).map_err(|_| FaucetError::InvalidAddress)?;
+    let badge = state.ledger.lock().await.badge(&address)?;
+    badge.map(Json).ok_or(FaucetError::NotFound)
+}
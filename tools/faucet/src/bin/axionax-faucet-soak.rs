@@ -0,0 +1,420 @@
+//! Soak-tests a running faucet with a realistic mix of traffic
+//!
+//! Sends fresh requests, repeats of addresses already sent, invalid
+//! addresses and bursts from a single client at a steady rate, then reports
+//! how many were granted and rejected, why, and how fast the faucet answered.
+//! Run it against a staging faucet with the production rate limits to see
+//! how they hold up before an event.
+//!
+//! Clients are simulated with `X-Forwarded-For`, which the faucet only reads
+//! on its unix socket; over TCP every request comes from this host's IP.
+
+use clap::Parser;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Command-line options
+#[derive(Debug, Parser)]
+#[command(version, about = "Soak-test a running faucet with mixed traffic")]
+struct Cli {
+    /// Faucet base URL
+    #[arg(long, env = "FAUCET_URL", default_value = "http://localhost:3000")]
+    url: String,
+
+    /// Send requests over the faucet's unix socket, from simulated client IPs
+    #[arg(long)]
+    unix_socket: Option<PathBuf>,
+
+    /// Seconds to send traffic for
+    #[arg(long, default_value_t = 60)]
+    duration: u64,
+
+    /// Traffic events per second; a burst is one event
+    #[arg(long, default_value_t = 10.0)]
+    rate: f64,
+
+    /// Requests in flight at once
+    #[arg(long, default_value_t = 32)]
+    concurrency: usize,
+
+    /// Distinct simulated clients
+    #[arg(long, default_value_t = 100)]
+    clients: u32,
+
+    /// Relative weight of requests for fresh addresses
+    #[arg(long, default_value_t = 60)]
+    valid: u32,
+
+    /// Relative weight of repeats of an address already sent
+    #[arg(long, default_value_t = 20)]
+    duplicate: u32,
+
+    /// Relative weight of requests with an invalid address
+    #[arg(long, default_value_t = 10)]
+    invalid: u32,
+
+    /// Relative weight of bursts from a single client
+    #[arg(long, default_value_t = 10)]
+    burst: u32,
+
+    /// Requests sent at once in a burst
+    #[arg(long, default_value_t = 20)]
+    burst_size: usize,
+
+    /// Per-request timeout in seconds; a timeout counts as a failure
+    #[arg(long, default_value_t = 30)]
+    timeout: u64,
+
+    /// Also write the results as JSON to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+/// Kind of traffic a request simulates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Valid,
+    Duplicate,
+    Invalid,
+    Burst,
+}
+
+impl Kind {
+    const ALL: [Kind; 4] = [Kind::Valid, Kind::Duplicate, Kind::Invalid, Kind::Burst];
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Valid => "valid",
+            Kind::Duplicate => "duplicate",
+            Kind::Invalid => "invalid",
+            Kind::Burst => "burst",
+        }
+    }
+}
+
+/// How the faucet answered a request
+enum Outcome {
+    Granted,
+    /// Turned down with a 4xx, by error code
+    Rejected(String),
+    /// A 5xx or no answer at all
+    Failed(String),
+}
+
+/// One answered (or failed) request
+struct Sample {
+    kind: Kind,
+    latency: Duration,
+    outcome: Outcome,
+}
+
+/// Where requests are sent
+enum Target {
+    Tcp { http: reqwest::Client, url: String },
+    Unix(PathBuf),
+}
+
+impl Target {
+    /// `POST /v1/request` as client `ip`, returning the status and JSON body
+    async fn request(&self, ip: Ipv4Addr, body: &Value) -> anyhow::Result<(u16, Value)> {
+        match self {
+            Target::Tcp { http, url } => {
+                let response = http
+                    .post(format!("{}/v1/request", url))
+                    .json(body)
+                    .send()
+                    .await?;
+                let status = response.status().as_u16();
+                Ok((status, response.json().await.unwrap_or(Value::Null)))
+            }
+            Target::Unix(path) => unix_request(path, ip, body).await,
+        }
+    }
+}
+
+/// Send one request over a unix socket, on a connection of its own
+#[cfg(unix)]
+async fn unix_request(path: &Path, ip: Ipv4Addr, body: &Value) -> anyhow::Result<(u16, Value)> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let body = body.to_string();
+    let mut stream = tokio::net::UnixStream::connect(path).await?;
+    let request = format!(
+        "POST /v1/request HTTP/1.1\r\nHost: faucet\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nX-Forwarded-For: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        ip,
+        body
+    );
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let status = head
+        .split(' ')
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("malformed response"))?;
+    Ok((status, serde_json::from_str(body).unwrap_or(Value::Null)))
+}
+
+#[cfg(not(unix))]
+async fn unix_request(_: &Path, _: Ipv4Addr, _: &Value) -> anyhow::Result<(u16, Value)> {
+    anyhow::bail!("Unix sockets are not supported on this platform")
+}
+
+/// Latencies in milliseconds
+#[derive(Debug, Default, Serialize)]
+struct Latency {
+    p50: f64,
+    p90: f64,
+    p99: f64,
+    max: f64,
+}
+
+/// Results of one kind of traffic, or of all of it
+#[derive(Debug, Default, Serialize)]
+struct KindReport {
+    kind: &'static str,
+    sent: usize,
+    granted: usize,
+    rejected: usize,
+    failed: usize,
+    /// Rejections and failures by error code
+    reasons: BTreeMap<String, usize>,
+    latency: Latency,
+}
+
+/// JSON report of a run
+#[derive(Serialize)]
+struct Report {
+    timestamp: u64,
+    url: String,
+    duration_secs: f64,
+    /// Share of all requests that were granted
+    grant_ratio: f64,
+    kinds: Vec<KindReport>,
+    total: KindReport,
+}
+
+/// Nearest-rank percentile of sorted latencies; zero when there are none
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Tally `samples` under the name `kind`
+fn summarize<'a>(kind: &'static str, samples: impl Iterator<Item = &'a Sample>) -> KindReport {
+    let mut report = KindReport {
+        kind,
+        ..KindReport::default()
+    };
+    let mut latencies = Vec::new();
+    for sample in samples {
+        report.sent += 1;
+        latencies.push(sample.latency);
+        match &sample.outcome {
+            Outcome::Granted => report.granted += 1,
+            Outcome::Rejected(reason) => {
+                report.rejected += 1;
+                *report.reasons.entry(reason.clone()).or_default() += 1;
+            }
+            Outcome::Failed(reason) => {
+                report.failed += 1;
+                *report.reasons.entry(reason.clone()).or_default() += 1;
+            }
+        }
+    }
+    latencies.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    report.latency = Latency {
+        p50: ms(percentile(&latencies, 50.0)),
+        p90: ms(percentile(&latencies, 90.0)),
+        p99: ms(percentile(&latencies, 99.0)),
+        max: ms(latencies.last().copied().unwrap_or_default()),
+    };
+    report
+}
+
+/// A random, well-formed address
+fn fresh_address() -> String {
+    format!("0x{}", hex::encode(rand::thread_rng().gen::<[u8; 20]>()))
+}
+
+/// One of the malformed addresses clients send
+fn invalid_address() -> String {
+    let address = fresh_address();
+    match rand::thread_rng().gen_range(0..4) {
+        0 => address[..20].to_string(),
+        1 => address.replacen("0x", "0x zz", 1),
+        2 => address.trim_start_matches("0x").to_string() + "00",
+        _ => "not-an-address".to_string(),
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let mix = WeightedIndex::new([cli.valid, cli.duplicate, cli.invalid, cli.burst])
+        .map_err(|_| anyhow::anyhow!("At least one traffic kind needs a weight"))?;
+    if cli.rate <= 0.0 {
+        anyhow::bail!("--rate must be positive");
+    }
+    let target = Arc::new(match &cli.unix_socket {
+        Some(path) => Target::Unix(path.clone()),
+        None => {
+            eprintln!("Over TCP all requests come from this host, see --unix-socket");
+            Target::Tcp {
+                http: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(cli.timeout))
+                    .build()?,
+                url: cli.url.trim_end_matches('/').to_string(),
+            }
+        }
+    });
+    let clients: Vec<Ipv4Addr> = (0..cli.clients.max(1))
+        .map(|i| Ipv4Addr::from(u32::from(Ipv4Addr::new(10, 0, 0, 1)) + i))
+        .collect();
+
+    let sent_addresses = Arc::new(Mutex::new(Vec::<String>::new()));
+    let permits = Arc::new(Semaphore::new(cli.concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    let mut ticks = tokio::time::interval(Duration::from_secs_f64(1.0 / cli.rate));
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let started = Instant::now();
+    let until = started + Duration::from_secs(cli.duration);
+    println!(
+        "Soaking {} for {}s at {} events/s",
+        cli.unix_socket
+            .as_ref()
+            .map_or(cli.url.clone(), |path| format!("unix:{}", path.display())),
+        cli.duration,
+        cli.rate
+    );
+    while Instant::now() < until {
+        ticks.tick().await;
+        let (kind, ip, addresses) = {
+            let mut rng = rand::thread_rng();
+            let kind = Kind::ALL[mix.sample(&mut rng)];
+            let ip = *clients.choose(&mut rng).unwrap_or(&Ipv4Addr::LOCALHOST);
+            let addresses = match kind {
+                Kind::Valid => vec![fresh_address()],
+                Kind::Duplicate => {
+                    let sent = sent_addresses.lock().unwrap();
+                    vec![sent.choose(&mut rng).cloned().unwrap_or_else(fresh_address)]
+                }
+                Kind::Invalid => vec![invalid_address()],
+                Kind::Burst => (0..cli.burst_size.max(1))
+                    .map(|_| fresh_address())
+                    .collect(),
+            };
+            (kind, ip, addresses)
+        };
+
+        for address in addresses {
+            if kind != Kind::Invalid {
+                sent_addresses.lock().unwrap().push(address.clone());
+            }
+            let permit = permits.clone().acquire_owned().await?;
+            let target = target.clone();
+            tasks.spawn(async move {
+                let sent = Instant::now();
+                let result = target.request(ip, &json!({ "address": address })).await;
+                drop(permit);
+                let outcome = match result {
+                    Ok((200, _)) => Outcome::Granted,
+                    Ok((status, body)) => {
+                        let reason = body["code"]
+                            .as_str()
+                            .map_or_else(|| format!("http_{}", status), str::to_string);
+                        if status >= 500 {
+                            Outcome::Failed(reason)
+                        } else {
+                            Outcome::Rejected(reason)
+                        }
+                    }
+                    Err(_) => Outcome::Failed("unreachable".to_string()),
+                };
+                Sample {
+                    kind,
+                    latency: sent.elapsed(),
+                    outcome,
+                }
+            });
+        }
+    }
+    let mut samples = Vec::new();
+    while let Some(sample) = tasks.join_next().await {
+        samples.push(sample?);
+    }
+    let elapsed = started.elapsed();
+
+    let kinds: Vec<KindReport> = Kind::ALL
+        .into_iter()
+        .map(|kind| summarize(kind.name(), samples.iter().filter(|s| s.kind == kind)))
+        .collect();
+    let total = summarize("total", samples.iter());
+    println!(
+        "{:<10} {:>7} {:>8} {:>9} {:>7} {:>9} {:>9} {:>9} {:>9}",
+        "kind", "sent", "granted", "rejected", "failed", "p50 ms", "p90 ms", "p99 ms", "max ms"
+    );
+    for report in kinds.iter().chain([&total]) {
+        println!(
+            "{:<10} {:>7} {:>8} {:>9} {:>7} {:>9.1} {:>9.1} {:>9.1} {:>9.1}",
+            report.kind,
+            report.sent,
+            report.granted,
+            report.rejected,
+            report.failed,
+            report.latency.p50,
+            report.latency.p90,
+            report.latency.p99,
+            report.latency.max
+        );
+    }
+    let grant_ratio = match total.sent {
+        0 => 0.0,
+        sent => total.granted as f64 / sent as f64,
+    };
+    println!();
+    println!(
+        "Granted {:.1}% of {} requests",
+        grant_ratio * 100.0,
+        total.sent
+    );
+    for (reason, count) in &total.reasons {
+        println!("  {:<28} {:>7}", reason, count);
+    }
+
+    if let Some(path) = &cli.json {
+        let report = Report {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            url: cli.url.clone(),
+            duration_secs: elapsed.as_secs_f64(),
+            grant_ratio,
+            kinds,
+            total,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Per-deployment branding
+//!
+//! Community operators running the faucet for their own axionax-based network
+//! rename it without forking. `NETWORK_NAME` (default `axionax Testnet`) and
+//! `TOKEN_SYMBOL` (default `AXX`) appear in `/info`, every formatted amount
+//! and error messages. `EXPLORER_TX_URL` is a link template with a `{tx}`
+//! placeholder, e.g. `https://explorer.example.org/tx/{tx}`. `SUPPORT_URL` is
+//! returned with every error, and `LOGO_URL` is passed through `/info` for the
+//! frontend.
+
+use std::sync::OnceLock;
+
+/// Network name used when `NETWORK_NAME` is unset
+const DEFAULT_NETWORK: &str = "axionax Testnet";
+
+/// Token symbol used when `TOKEN_SYMBOL` is unset
+const DEFAULT_SYMBOL: &str = "AXX";
+
+/// Placeholder replaced by the transaction hash in `EXPLORER_TX_URL`
+const TX_PLACEHOLDER: &str = "{tx}";
+
+static BRANDING: OnceLock<Branding> = OnceLock::new();
+
+/// Names and links shown to users of this deployment
+#[derive(Debug, Clone)]
+pub struct Branding {
+    pub network: String,
+    pub symbol: String,
+    pub explorer_tx_url: Option<String>,
+    pub support_url: Option<String>,
+    pub logo_url: Option<String>,
+}
+
+impl Branding {
+    fn from_env() -> anyhow::Result<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|v: &String| !v.trim().is_empty())
+        };
+        let explorer_tx_url = var("EXPLORER_TX_URL");
+        if let Some(template) = &explorer_tx_url {
+            if !template.contains(TX_PLACEHOLDER) {
+                anyhow::bail!(
+                    "EXPLORER_TX_URL must contain {}, e.g. https://explorer.example.org/tx/{}",
+                    TX_PLACEHOLDER,
+                    TX_PLACEHOLDER
+                );
+            }
+        }
+        Ok(Self {
+            network: var("NETWORK_NAME").unwrap_or_else(|| DEFAULT_NETWORK.to_string()),
+            symbol: var("TOKEN_SYMBOL").unwrap_or_else(|| DEFAULT_SYMBOL.to_string()),
+            explorer_tx_url,
+            support_url: var("SUPPORT_URL"),
+            logo_url: var("LOGO_URL"),
+        })
+    }
+
+    fn defaults() -> Self {
+        Self {
+            network: DEFAULT_NETWORK.to_string(),
+            symbol: DEFAULT_SYMBOL.to_string(),
+            explorer_tx_url: None,
+            support_url: None,
+            logo_url: None,
+        }
+    }
+}
+
+/// Explorer link of a transaction, when `EXPLORER_TX_URL` is set
+pub fn tx_url(tx_hash: &str) -> Option<String> {
+    get()
+        .explorer_tx_url
+        .as_ref()
+        .map(|template| template.replace(TX_PLACEHOLDER, tx_hash))
+}
+
+/// Load the branding from the environment; call once at startup
+pub fn init() -> anyhow::Result<()> {
+    let _ = BRANDING.set(Branding::from_env()?);
+    Ok(())
+}
+
+/// Branding in effect; the defaults for subcommands that don't call [`init`]
+pub fn get() -> &'static Branding {
+    BRANDING.get_or_init(Branding::defaults)
+}
@@ -0,0 +1,385 @@
+//! Signed configuration bundles for fleets of faucets
+//!
+//! With `CONFIG_BUNDLE_URL`, the faucet fetches a bundle at startup and then
+//! every 5 minutes, and applies its drip amount, IP limit, blocklist and
+//! feature flags once the ed25519 signature checks out against
+//! `CONFIG_BUNDLE_KEY`. Publishing one bundle changes the policy of every
+//! faucet that follows the URL. A bundle is a JSON document
+//!
+//! ```json
+//! { "payload": "{\"version\":3,\"amount\":\"50\"}", "signature": "<hex>" }
+//! ```
+//!
+//! whose signature covers the bytes of the `payload` string. A bundle only
+//! applies when its `version` is newer than the applied one, and is rejected
+//! after its `expires_at` or when its `chain_id` is another network. Settings
+//! a bundle leaves out fall back to the local configuration; a rejected
+//! bundle changes nothing.
+
+use anyhow::{anyhow, bail};
+use axionax_faucet::validation;
+use axionax_notify::{Event, Severity};
+use axionax_tx::U256;
+use clap::Subcommand;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::flags::{self, Rollout};
+use crate::rate_limit::Gcra;
+use crate::scheduler::unix_now;
+use crate::{alerts, axx, faucet_amount, FaucetError, FaucetState};
+
+/// Bundle as served from `CONFIG_BUNDLE_URL`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedBundle {
+    /// JSON-encoded [`Bundle`], exactly as signed
+    payload: String,
+    /// Hex ed25519 signature of the payload bytes
+    signature: String,
+}
+
+/// Settings carried by a bundle
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Bundle {
+    /// Increases with every published bundle
+    version: u64,
+    /// Network the bundle is meant for
+    #[serde(default)]
+    chain_id: Option<u64>,
+    /// Unix time after which the bundle is rejected
+    #[serde(default)]
+    expires_at: Option<u64>,
+    /// Drip amount, e.g. `"50"` AXX
+    #[serde(default)]
+    amount: Option<String>,
+    /// Grants per IP, in `IP_LIMIT` syntax
+    #[serde(default)]
+    ip_limit: Option<String>,
+    /// Addresses and IPs refused a drip, on top of `BLOCKLIST_FILE`
+    #[serde(default)]
+    blocklist: Vec<String>,
+    /// Feature flag rollouts, replacing `FEATURE_FLAGS`
+    #[serde(default)]
+    flags: Option<BTreeMap<String, Rollout>>,
+}
+
+/// Settings in effect
+#[derive(Debug, Clone)]
+struct Settings {
+    amount: U256,
+    ip_limit: Gcra,
+    blocked_addresses: HashSet<String>,
+    blocked_ips: HashSet<IpAddr>,
+    flags: BTreeMap<String, Rollout>,
+    flags_source: flags::Source,
+    /// Version of the applied bundle, `None` on local configuration
+    version: Option<u64>,
+}
+
+/// Rollout of a feature flag and where it comes from
+#[derive(Debug, Clone, Serialize)]
+pub struct FlagStatus {
+    name: String,
+    #[serde(flatten)]
+    rollout: Rollout,
+    source: flags::Source,
+}
+
+/// Where bundles are fetched from
+struct Source {
+    url: String,
+    key: VerifyingKey,
+    http: reqwest::Client,
+}
+
+/// Settings that a bundle can replace at runtime
+pub struct RuntimeConfig {
+    settings: RwLock<Settings>,
+    /// Local configuration, which omitted bundle fields fall back to
+    local: Settings,
+    source: Option<Source>,
+    /// Flag rollouts set through the admin API, ahead of env and bundle
+    overrides: RwLock<BTreeMap<String, Rollout>>,
+}
+
+impl RuntimeConfig {
+    /// Local settings, and the bundle source from `CONFIG_BUNDLE_URL` and `CONFIG_BUNDLE_KEY`
+    pub fn from_env(ip_limit: Gcra) -> anyhow::Result<Self> {
+        let local = Settings {
+            amount: faucet_amount(),
+            ip_limit,
+            blocked_addresses: HashSet::new(),
+            blocked_ips: HashSet::new(),
+            flags: flags::from_env()?,
+            flags_source: flags::Source::Env,
+            version: None,
+        };
+        let source = match std::env::var("CONFIG_BUNDLE_URL") {
+            Ok(url) => {
+                let key = std::env::var("CONFIG_BUNDLE_KEY").map_err(|_| {
+                    anyhow!("CONFIG_BUNDLE_URL is set but CONFIG_BUNDLE_KEY is not")
+                })?;
+                Some(Source {
+                    url,
+                    key: parse_verifying_key(&key)?,
+                    http: reqwest::Client::builder()
+                        .timeout(Duration::from_secs(10))
+                        .build()?,
+                })
+            }
+            Err(_) => None,
+        };
+        Ok(Self {
+            settings: RwLock::new(local.clone()),
+            local,
+            source,
+            overrides: RwLock::new(BTreeMap::new()),
+        })
+    }
+
+    /// Whether bundles are fetched at all
+    pub fn is_remote(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Drip size in wei
+    pub fn amount(&self) -> U256 {
+        self.settings.read().unwrap().amount
+    }
+
+    /// Grant limit per IP
+    pub fn ip_limit(&self) -> Gcra {
+        self.settings.read().unwrap().ip_limit
+    }
+
+    /// Version of the applied bundle
+    pub fn version(&self) -> Option<u64> {
+        self.settings.read().unwrap().version
+    }
+
+    /// Whether the bundle blocklists the address or IP
+    pub fn is_blocked(&self, address: &str, ip: IpAddr) -> bool {
+        let settings = self.settings.read().unwrap();
+        settings.blocked_addresses.contains(address) || settings.blocked_ips.contains(&ip)
+    }
+
+    /// Whether a feature flag is on for a client
+    pub fn flag_enabled(&self, name: &str, key: &str, partner: Option<&str>) -> bool {
+        let overrides = self.overrides.read().unwrap();
+        let settings = self.settings.read().unwrap();
+        overrides
+            .get(name)
+            .or_else(|| settings.flags.get(name))
+            .is_some_and(|rollout| rollout.includes(name, key, partner))
+    }
+
+    /// Every flag with its effective rollout
+    pub fn flags(&self) -> Vec<FlagStatus> {
+        let overrides = self.overrides.read().unwrap();
+        let settings = self.settings.read().unwrap();
+        let mut flags: BTreeMap<&String, FlagStatus> = BTreeMap::new();
+        for (name, rollout) in &settings.flags {
+            let status = FlagStatus {
+                name: name.clone(),
+                rollout: rollout.clone(),
+                source: settings.flags_source,
+            };
+            flags.insert(name, status);
+        }
+        for (name, rollout) in overrides.iter() {
+            let status = FlagStatus {
+                name: name.clone(),
+                rollout: rollout.clone(),
+                source: flags::Source::Admin,
+            };
+            flags.insert(name, status);
+        }
+        flags.into_values().collect()
+    }
+
+    /// Replace the admin overrides, e.g. with those stored by another replica
+    pub fn set_overrides(&self, overrides: BTreeMap<String, Rollout>) {
+        *self.overrides.write().unwrap() = overrides;
+    }
+
+    /// Fetch, verify and apply the bundle; returns whether the settings changed
+    pub async fn refresh(&self, state: &FaucetState) -> Result<bool, FaucetError> {
+        let Some(source) = &self.source else {
+            return Ok(false);
+        };
+        let signed: SignedBundle = source
+            .http
+            .get(&source.url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| axionax_errors::Error::internal(format!("config bundle: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| axionax_errors::Error::internal(format!("config bundle: {}", e)))?;
+
+        let result = verify(&signed, &source.key)
+            .and_then(|bundle| self.apply(bundle, state.chain_id, unix_now()));
+        match result {
+            Ok(changed) => Ok(changed),
+            Err(e) => {
+                warn!("Rejected config bundle from {}: {}", source.url, e);
+                alerts::send(
+                    state,
+                    Event::new(
+                        alerts::CONFIG_REJECTED,
+                        Severity::Warning,
+                        "Config bundle rejected",
+                    )
+                    .with_field("url", source.url.clone())
+                    .with_field("error", e.to_string()),
+                );
+                Err(
+                    axionax_errors::Error::internal(format!("config bundle: {}", e))
+                        .with_code("config_rejected")
+                        .into(),
+                )
+            }
+        }
+    }
+
+    /// Apply a verified bundle unless it is stale or meant for another chain
+    fn apply(&self, bundle: Bundle, chain_id: u64, now: u64) -> anyhow::Result<bool> {
+        let current = self.version();
+        if current.is_some_and(|v| bundle.version <= v) {
+            if current != Some(bundle.version) {
+                warn!(
+                    "Ignoring config bundle version {}, version {} is applied",
+                    bundle.version,
+                    current.unwrap_or_default()
+                );
+            }
+            return Ok(false);
+        }
+        if let Some(id) = bundle.chain_id.filter(|id| *id != chain_id) {
+            bail!("bundle is for chain {}, not {}", id, chain_id);
+        }
+        if let Some(expires_at) = bundle.expires_at.filter(|at| *at <= now) {
+            bail!("bundle expired at {}", expires_at);
+        }
+
+        let mut settings = Settings {
+            version: Some(bundle.version),
+            ..self.local.clone()
+        };
+        if let Some(amount) = &bundle.amount {
+            settings.amount = validation::parse_amount(amount)
+                .ok()
+                .filter(|a| !a.is_zero())
+                .ok_or_else(|| anyhow!("invalid amount {:?}", amount))?;
+        }
+        if let Some(spec) = &bundle.ip_limit {
+            settings.ip_limit =
+                Gcra::parse(spec).ok_or_else(|| anyhow!("invalid ip_limit {:?}", spec))?;
+        }
+        if let Some(rollouts) = bundle.flags {
+            for (name, rollout) in &rollouts {
+                flags::valid_name(name).ok_or_else(|| anyhow!("invalid flag name {:?}", name))?;
+                rollout
+                    .validate()
+                    .map_err(|e| anyhow!("invalid flag {}: {}", name, e))?;
+            }
+            settings.flags = rollouts;
+            settings.flags_source = flags::Source::Bundle;
+        }
+        for entry in &bundle.blocklist {
+            match entry.parse::<IpAddr>() {
+                Ok(ip) => settings.blocked_ips.insert(ip),
+                Err(_) => settings.blocked_addresses.insert(
+                    validation::parse_address(entry)
+                        .map_err(|e| anyhow!("invalid blocklist entry {:?}: {}", entry, e))?,
+                ),
+            };
+        }
+
+        info!(
+            "Applied config bundle version {}: amount {}, {} blocklist entries",
+            bundle.version,
+            axx(settings.amount),
+            bundle.blocklist.len()
+        );
+        *self.settings.write().unwrap() = settings;
+        Ok(true)
+    }
+}
+
+/// Check the signature of a bundle and decode its payload
+fn verify(signed: &SignedBundle, key: &VerifyingKey) -> anyhow::Result<Bundle> {
+    let signature: [u8; 64] = hex::decode(signed.signature.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("signature is not 64 hex bytes"))?;
+    key.verify_strict(
+        signed.payload.as_bytes(),
+        &Signature::from_bytes(&signature),
+    )
+    .map_err(|_| anyhow!("invalid signature"))?;
+    serde_json::from_str(&signed.payload).map_err(|e| anyhow!("invalid payload: {}", e))
+}
+
+fn parse_verifying_key(hex_key: &str) -> anyhow::Result<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(hex_key.trim().trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("CONFIG_BUNDLE_KEY must be a 32-byte hex ed25519 public key"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| anyhow!("Invalid CONFIG_BUNDLE_KEY: {}", e))
+}
+
+/// Bundle signing commands, run by whoever publishes the fleet's bundles
+#[derive(Debug, Subcommand)]
+pub enum BundleCommand {
+    /// Create a signing key and print it with its public key
+    Keygen,
+    /// Sign a payload file and print the bundle to serve at CONFIG_BUNDLE_URL
+    Sign {
+        /// JSON payload, e.g. `{ "version": 3, "amount": "50" }`
+        payload: PathBuf,
+        /// Hex ed25519 signing key from `bundle keygen`
+        #[arg(long, env = "CONFIG_BUNDLE_SIGNING_KEY", hide_env_values = true)]
+        key: String,
+    },
+}
+
+/// Run a `bundle` subcommand
+pub fn run(command: &BundleCommand) -> anyhow::Result<()> {
+    match command {
+        BundleCommand::Keygen => {
+            let key = SigningKey::from_bytes(&rand::random());
+            println!("CONFIG_BUNDLE_SIGNING_KEY={}", hex::encode(key.to_bytes()));
+            println!(
+                "CONFIG_BUNDLE_KEY={}",
+                hex::encode(key.verifying_key().to_bytes())
+            );
+        }
+        BundleCommand::Sign { payload, key } => {
+            let payload = std::fs::read_to_string(payload)?;
+            // Catch typos before the bundle reaches the fleet
+            let bundle: Bundle =
+                serde_json::from_str(&payload).map_err(|e| anyhow!("Invalid payload: {}", e))?;
+            let key: [u8; 32] = hex::decode(key.trim().trim_start_matches("0x"))
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| anyhow!("The signing key must be 32 hex bytes"))?;
+            let key = SigningKey::from_bytes(&key);
+            let signed = SignedBundle {
+                signature: hex::encode(key.sign(payload.as_bytes()).to_bytes()),
+                payload,
+            };
+            println!("{}", serde_json::to_string_pretty(&signed)?);
+            eprintln!("Signed config bundle version {}", bundle.version);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//! Captcha and proof-of-work challenges for escalated clients
+
+use axionax_errors::Kind;
+use axum::extract::{Json, State};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::{FaucetError, FaucetState};
+
+const DEFAULT_CAPTCHA_VERIFY_URL: &str = "https://hcaptcha.com/siteverify";
+const DEFAULT_POW_DIFFICULTY: u32 = 20;
+const POW_CHALLENGE_TTL: Duration = Duration::from_secs(600);
+/// Captchas are verified while a request is evaluated, so the call is bounded
+const CAPTCHA_VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Challenge a suspicious client must pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeKind {
+    Captcha,
+    Pow,
+}
+
+impl ChallengeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChallengeKind::Captcha => "captcha",
+            ChallengeKind::Pow => "pow",
+        }
+    }
+}
+
+/// Proof-of-work solution submitted with a request
+#[derive(Debug, Clone, Deserialize)]
+pub struct PowSolution {
+    pub challenge: String,
+    pub nonce: u64,
+}
+
+/// Proof-of-work challenge issued by `/challenge`
+#[derive(Debug, Serialize)]
+pub struct PowChallenge {
+    challenge: String,
+    difficulty: u32,
+    expires_in_secs: u64,
+}
+
+/// Captcha verification response (hCaptcha/reCAPTCHA/Turnstile)
+#[derive(Debug, Deserialize)]
+struct CaptchaVerification {
+    success: bool,
+}
+
+/// Challenge configuration and issued proof-of-work challenges
+pub struct Challenges {
+    /// Challenge required from escalated clients
+    pub escalation: ChallengeKind,
+    captcha_secret: Option<String>,
+    captcha_verify_url: String,
+    pow_difficulty: u32,
+    /// Outstanding proof-of-work challenges and their issue time
+    issued: Mutex<HashMap<String, SystemTime>>,
+    http: reqwest::Client,
+}
+
+impl Challenges {
+    /// Load challenge settings from the environment
+    pub fn from_env() -> anyhow::Result<Self> {
+        let escalation = match std::env::var("REPUTATION_CHALLENGE")
+            .unwrap_or_else(|_| "pow".to_string())
+            .as_str()
+        {
+            "captcha" => ChallengeKind::Captcha,
+            "pow" => ChallengeKind::Pow,
+            other => anyhow::bail!("Invalid REPUTATION_CHALLENGE: {:?}", other),
+        };
+        let pow_difficulty = match std::env::var("POW_DIFFICULTY") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid POW_DIFFICULTY: {:?}", v))?,
+            Err(_) => DEFAULT_POW_DIFFICULTY,
+        };
+
+        Ok(Self {
+            escalation,
+            captcha_secret: std::env::var("CAPTCHA_SECRET").ok(),
+            captcha_verify_url: std::env::var("CAPTCHA_VERIFY_URL")
+                .unwrap_or_else(|_| DEFAULT_CAPTCHA_VERIFY_URL.to_string()),
+            pow_difficulty,
+            issued: Mutex::new(HashMap::new()),
+            http: reqwest::Client::builder()
+                .timeout(CAPTCHA_VERIFY_TIMEOUT)
+                .build()?,
+        })
+    }
+
+    /// Issue a new proof-of-work challenge
+    pub fn issue_pow(&self) -> PowChallenge {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let challenge = format!("0x{}", hex::encode(bytes));
+
+        let now = SystemTime::now();
+        let mut issued = self.issued.lock().unwrap();
+        issued.retain(|_, issued_at| {
+            now.duration_since(*issued_at).unwrap_or(Duration::ZERO) < POW_CHALLENGE_TTL
+        });
+        issued.insert(challenge.clone(), now);
+
+        PowChallenge {
+            challenge,
+            difficulty: self.pow_difficulty,
+            expires_in_secs: POW_CHALLENGE_TTL.as_secs(),
+        }
+    }
+
+    /// Check and consume a proof-of-work solution
+    pub fn verify_pow(&self, solution: &PowSolution) -> Result<(), FaucetError> {
+        let issued_at = self
+            .issued
+            .lock()
+            .unwrap()
+            .remove(&solution.challenge)
+            .ok_or(FaucetError::ChallengeFailed)?;
+        if SystemTime::now()
+            .duration_since(issued_at)
+            .unwrap_or(Duration::ZERO)
+            >= POW_CHALLENGE_TTL
+        {
+            return Err(FaucetError::ChallengeFailed);
+        }
+
+        let challenge = hex::decode(solution.challenge.trim_start_matches("0x"))
+            .map_err(|_| FaucetError::ChallengeFailed)?;
+        if leading_zero_bits(&pow_hash(&challenge, solution.nonce)) >= self.pow_difficulty {
+            Ok(())
+        } else {
+            Err(FaucetError::ChallengeFailed)
+        }
+    }
+
+    /// Verify a captcha response token with the captcha provider
+    pub async fn verify_captcha(&self, token: &str) -> Result<(), FaucetError> {
+        let secret = self.captcha_secret.as_deref().ok_or_else(|| {
+            axionax_errors::Error::internal("CAPTCHA_SECRET is not configured")
+                .with_code("captcha_not_configured")
+        })?;
+        // Fails closed, but the provider being down or timing out is worth a
+        // retry, unlike a failed captcha
+        let unavailable = |e: reqwest::Error| {
+            axionax_errors::Error::new(
+                Kind::Unavailable,
+                "captcha_unavailable",
+                format!("captcha: {}", e),
+            )
+        };
+
+        let verification: CaptchaVerification = self
+            .http
+            .post(&self.captcha_verify_url)
+            .form(&[("secret", secret), ("response", token)])
+            .send()
+            .await
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)?;
+
+        if verification.success {
+            Ok(())
+        } else {
+            Err(FaucetError::ChallengeFailed)
+        }
+    }
+}
+
+/// Find a nonce solving a proof-of-work challenge of `difficulty` bits
+pub fn solve_pow(challenge: &str, difficulty: u32) -> Option<u64> {
+    let challenge = hex::decode(challenge.trim_start_matches("0x")).ok()?;
+    (0..=u64::MAX).find(|nonce| leading_zero_bits(&pow_hash(&challenge, *nonce)) >= difficulty)
+}
+
+/// `keccak256(challenge || nonce_be_u64)`
+fn pow_hash(challenge: &[u8], nonce: u64) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(challenge)
+        .chain_update(nonce.to_be_bytes())
+        .finalize()
+        .into()
+}
+
+/// Number of leading zero bits in a hash
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        if *byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
+
+/// Issue a proof-of-work challenge
+pub async fn pow_challenge(State(state): State<FaucetState>) -> Json<PowChallenge> {
+    Json(state.challenges.issue_pow())
+}
@@ -0,0 +1,130 @@
+//! Fault injection for rehearsing incidents
+//!
+//! `--chaos` (`CHAOS`), hidden from `--help`, makes the faucet misbehave on
+//! purpose so operators can check that alerts fire, clients retry and RPC
+//! failover kicks in before a real outage does it for them. Each fault
+//! happens independently with its own probability, from `0` to `1`:
+//!
+//! - `CHAOS_RPC_ERROR_RATE` (default `0.1`): an RPC call fails as if the
+//!   endpoint were unreachable
+//! - `CHAOS_RPC_SLOW_RATE` (default `0.1`): an RPC call is held for
+//!   `CHAOS_RPC_DELAY_MS` (default `2000`) before it is sent
+//! - `CHAOS_STORAGE_ERROR_RATE` (default `0.05`): a ledger write is rolled
+//!   back and fails
+//!
+//! Never enable it on a faucet users depend on.
+
+use async_trait::async_trait;
+use axionax_rpc::Transport;
+use rand::Rng;
+use rusqlite::Connection;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Injected fault probabilities
+#[derive(Debug, Clone)]
+pub struct Chaos {
+    rpc_error_rate: f64,
+    rpc_slow_rate: f64,
+    rpc_delay: Duration,
+    storage_error_rate: f64,
+}
+
+impl Chaos {
+    /// Load the `CHAOS_*` probabilities
+    pub fn from_env() -> anyhow::Result<Self> {
+        let chaos = Self {
+            rpc_error_rate: rate("CHAOS_RPC_ERROR_RATE", 0.1)?,
+            rpc_slow_rate: rate("CHAOS_RPC_SLOW_RATE", 0.1)?,
+            rpc_delay: Duration::from_millis(match std::env::var("CHAOS_RPC_DELAY_MS") {
+                Ok(ms) => ms
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid CHAOS_RPC_DELAY_MS: {:?}", ms))?,
+                Err(_) => 2000,
+            }),
+            storage_error_rate: rate("CHAOS_STORAGE_ERROR_RATE", 0.05)?,
+        };
+        warn!(
+            "   CHAOS MODE: failing {:.0}% of RPC calls, delaying {:.0}% by {:?}, \
+             failing {:.0}% of ledger writes",
+            chaos.rpc_error_rate * 100.0,
+            chaos.rpc_slow_rate * 100.0,
+            chaos.rpc_delay,
+            chaos.storage_error_rate * 100.0,
+        );
+        Ok(chaos)
+    }
+
+    /// Wrap `transport` so its calls fail and stall at random
+    pub fn transport(&self, inner: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        Arc::new(ChaosTransport {
+            inner,
+            chaos: self.clone(),
+        })
+    }
+
+    /// Roll back writes on `conn` at random
+    pub fn storage(&self, conn: &Connection) {
+        let rate = self.storage_error_rate;
+        if rate == 0.0 {
+            return;
+        }
+        // Returning true turns the commit into a rollback
+        conn.commit_hook(Some(move || {
+            let fail = rand::thread_rng().gen_bool(rate);
+            if fail {
+                debug!("Chaos: failing a ledger write");
+            }
+            fail
+        }));
+    }
+}
+
+/// Probability in `var`, or `default` when unset
+fn rate(var: &str, default: f64) -> anyhow::Result<f64> {
+    let Ok(value) = std::env::var(var) else {
+        return Ok(default);
+    };
+    value
+        .parse()
+        .ok()
+        .filter(|rate| (0.0..=1.0).contains(rate))
+        .ok_or_else(|| anyhow::anyhow!("Invalid {}: {:?}, expected 0 to 1", var, value))
+}
+
+/// Transport injecting failures and delays into another
+struct ChaosTransport {
+    inner: Arc<dyn Transport>,
+    chaos: Chaos,
+}
+
+#[async_trait]
+impl Transport for ChaosTransport {
+    fn endpoint(&self) -> &str {
+        self.inner.endpoint()
+    }
+
+    async fn send(&self, method: &str, params: &Value) -> Result<Value, axionax_rpc::Error> {
+        let (fail, slow) = {
+            let mut rng = rand::thread_rng();
+            (
+                rng.gen_bool(self.chaos.rpc_error_rate),
+                rng.gen_bool(self.chaos.rpc_slow_rate),
+            )
+        };
+        if slow {
+            debug!("Chaos: delaying {} by {:?}", method, self.chaos.rpc_delay);
+            tokio::time::sleep(self.chaos.rpc_delay).await;
+        }
+        if fail {
+            debug!("Chaos: failing {}", method);
+            return Err(axionax_rpc::Error::Transport(format!(
+                "chaos: injected failure of {}",
+                method
+            )));
+        }
+        self.inner.send(method, params).await
+    }
+}
@@ -0,0 +1,228 @@
+//! Confirmation events for claims, streamed over WebSocket
+//!
+//! The `confirm_claims` job follows the transactions of recent claims and
+//! records in the ledger whether they confirmed or failed. Instead of polling
+//! `/receipt/:claim_id`, clients open a WebSocket on `/ws` and subscribe to
+//! their claim IDs with JSON-RPC, much like `eth_subscribe`:
+//!
+//! ```text
+//! -> {"jsonrpc":"2.0","id":1,"method":"faucet_subscribe","params":["0x5f3e…"]}
+//! <- {"jsonrpc":"2.0","id":1,"result":"0x1"}
+//! <- {"jsonrpc":"2.0","method":"faucet_subscription",
+//!     "params":{"subscription":"0x1","result":{"claim_id":"0x5f3e…","status":"confirmed",…}}}
+//! ```
+//!
+//! A subscription ends after its confirmation or failure event, and claims
+//! that already resolved are answered straight away. The leader publishes
+//! events as it records them; connections also re-read the ledger, so
+//! clients of follower replicas hear about claims confirmed by the leader.
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::branding;
+use crate::ledger::{ClaimEvent, ClaimStatus};
+use crate::rpc::parse_quantity;
+use crate::{FaucetError, FaucetState};
+
+/// Claims older than this are no longer followed
+const WATCH_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Transactions unknown to the node this long after the claim count as dropped
+const DROP_AFTER: Duration = Duration::from_secs(600);
+
+/// How often a connection re-reads the ledger for its subscriptions
+const RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most open subscriptions per connection
+const MAX_SUBSCRIPTIONS: usize = 16;
+
+/// Events buffered per connection before slow clients fall back to the ledger
+pub const EVENT_BUFFER: usize = 256;
+
+/// Record the outcome of recent claims' transactions and publish it
+pub async fn confirm(state: &FaucetState) -> Result<(), FaucetError> {
+    let since = SystemTime::now() - WATCH_WINDOW;
+    let pending = state.ledger.lock().await.pending_claims(since)?;
+
+    for (claim_id, tx_hash, issued) in pending {
+        let receipt = state.rpc.get_transaction_receipt(&tx_hash).await?;
+        let (status, block_number) = match receipt {
+            Some(receipt) => {
+                let block = receipt["blockNumber"]
+                    .as_str()
+                    .map(parse_quantity)
+                    .transpose()?
+                    .map(|b| b as u64);
+                match receipt["status"].as_str() {
+                    Some("0x0") => (ClaimStatus::Failed, block),
+                    _ => (ClaimStatus::Confirmed, block),
+                }
+            }
+            None if issued.elapsed().unwrap_or_default() >= DROP_AFTER => {
+                if state.rpc.get_transaction_by_hash(&tx_hash).await?.is_some() {
+                    continue;
+                }
+                (ClaimStatus::Failed, None)
+            }
+            None => continue,
+        };
+
+        state
+            .ledger
+            .lock()
+            .await
+            .set_claim_status(&claim_id, status, block_number)?;
+        match status {
+            ClaimStatus::Failed => warn!("Claim {} failed (tx: {})", claim_id, tx_hash),
+            _ => info!("Claim {} confirmed (tx: {})", claim_id, tx_hash),
+        }
+
+        // Nobody listening is fine
+        let _ = state.claim_events.send(ClaimEvent {
+            claim_id,
+            explorer_url: branding::tx_url(&tx_hash),
+            tx_hash,
+            status,
+            block_number,
+        });
+    }
+    Ok(())
+}
+
+/// Upgrade to a WebSocket serving claim subscriptions
+pub async fn subscribe(State(state): State<FaucetState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| serve(state, socket))
+}
+
+/// Open subscriptions of a connection
+#[derive(Default)]
+struct Subscriptions {
+    /// Subscription ID per claim ID
+    claims: HashMap<String, String>,
+    next_id: u64,
+}
+
+async fn serve(state: FaucetState, mut socket: WebSocket) {
+    let mut events = state.claim_events.subscribe();
+    let mut subscriptions = Subscriptions::default();
+    let mut recheck = tokio::time::interval(RECHECK_INTERVAL);
+
+    loop {
+        let mut resolved = Vec::new();
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                };
+                let (reply, event) = handle(&state, &text, &mut subscriptions).await;
+                if socket.send(Message::Text(reply.to_string())).await.is_err() {
+                    return;
+                }
+                resolved.extend(event);
+            }
+            event = events.recv() => match event {
+                Ok(event) => resolved.push(event),
+                // Missed events are picked up from the ledger
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return,
+            },
+            _ = recheck.tick(), if !subscriptions.claims.is_empty() => {
+                let ledger = state.ledger.lock().await;
+                for claim_id in subscriptions.claims.keys() {
+                    match ledger.claim_event(claim_id) {
+                        Ok(Some(event)) if event.status != ClaimStatus::Pending => {
+                            resolved.push(event)
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Failed to read claim {}: {:?}", claim_id, e),
+                    }
+                }
+            }
+        }
+
+        for event in resolved {
+            let Some(subscription) = subscriptions.claims.remove(&event.claim_id) else {
+                continue;
+            };
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "faucet_subscription",
+                "params": { "subscription": subscription, "result": event },
+            });
+            if socket
+                .send(Message::Text(notification.to_string()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Answer a JSON-RPC message, returning the reply and the claim's event if
+/// it already resolved
+async fn handle(
+    state: &FaucetState,
+    text: &str,
+    subscriptions: &mut Subscriptions,
+) -> (Value, Option<ClaimEvent>) {
+    let Ok(request) = serde_json::from_str::<Value>(text) else {
+        return (rpc_error(Value::Null, -32700, "parse error"), None);
+    };
+    let id = request["id"].clone();
+    let param = request["params"][0].as_str().map(str::to_lowercase);
+
+    match request["method"].as_str() {
+        Some("faucet_subscribe") => {
+            let Some(claim_id) = param else {
+                return (rpc_error(id, -32602, "expected a claim ID"), None);
+            };
+            if subscriptions.claims.len() >= MAX_SUBSCRIPTIONS {
+                return (rpc_error(id, -32005, "too many subscriptions"), None);
+            }
+            let event = match state.ledger.lock().await.claim_event(&claim_id) {
+                Ok(Some(event)) => event,
+                Ok(None) => return (rpc_error(id, -32602, "unknown claim"), None),
+                Err(e) => {
+                    warn!("Failed to read claim {}: {:?}", claim_id, e);
+                    return (rpc_error(id, -32603, "internal error"), None);
+                }
+            };
+
+            subscriptions.next_id += 1;
+            let subscription = format!("0x{:x}", subscriptions.next_id);
+            subscriptions.claims.insert(claim_id, subscription.clone());
+            let resolved = (event.status != ClaimStatus::Pending).then_some(event);
+            (
+                json!({ "jsonrpc": "2.0", "id": id, "result": subscription }),
+                resolved,
+            )
+        }
+        Some("faucet_unsubscribe") => {
+            let before = subscriptions.claims.len();
+            subscriptions
+                .claims
+                .retain(|_, subscription| Some(subscription.as_str()) != param.as_deref());
+            let removed = subscriptions.claims.len() < before;
+            (
+                json!({ "jsonrpc": "2.0", "id": id, "result": removed }),
+                None,
+            )
+        }
+        _ => (rpc_error(id, -32601, "method not found"), None),
+    }
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
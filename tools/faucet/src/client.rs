@@ -0,0 +1,155 @@
+//! `axionax-faucet request`: ask a running faucet for tokens
+//!
+//! Meant for onboarding scripts and CI. Proof-of-work challenges are solved
+//! locally; captcha challenges need a token solved in a browser and passed
+//! with `--captcha-token`. With `--wait` the command only returns once the
+//! recipient's balance has increased on-chain.
+
+use anyhow::{anyhow, bail};
+use axionax_faucet::validation;
+use axionax_tx::amount::{self, Unit};
+use clap::Args;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+use crate::challenge::solve_pow;
+use crate::rpc::RpcClient;
+
+/// How often the balance is checked while waiting
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Options of the `request` subcommand
+#[derive(Debug, Args)]
+pub struct RequestArgs {
+    /// Recipient address
+    address: String,
+
+    /// Faucet base URL
+    #[arg(
+        long,
+        env = "FAUCET_URL",
+        default_value = "https://testnet-faucet.axionax.org"
+    )]
+    url: String,
+
+    /// Wait until the recipient's balance increases on-chain
+    #[arg(long)]
+    wait: bool,
+
+    /// JSON-RPC endpoint used to watch the balance
+    #[arg(
+        long,
+        env = "RPC_URL",
+        default_value = "https://testnet-rpc.axionax.org"
+    )]
+    rpc_url: String,
+
+    /// Seconds to wait for the funds before giving up
+    #[arg(long, default_value_t = 300)]
+    timeout: u64,
+
+    /// Captcha response token, for faucets that challenge with a captcha
+    #[arg(long)]
+    captcha_token: Option<String>,
+}
+
+/// Request tokens, solving a proof-of-work challenge if one is required
+pub async fn run(args: &RequestArgs) -> anyhow::Result<()> {
+    let address = validation::parse_address(&args.address)
+        .map_err(|e| anyhow!("Invalid address {}: {}", args.address, e))?;
+    let rpc = RpcClient::new(&args.rpc_url);
+    let before = if args.wait {
+        let balance = rpc
+            .get_balance(&address)
+            .await
+            .map_err(|e| anyhow!("Failed to read the balance of {}: {:?}", address, e))?;
+        Some(balance)
+    } else {
+        None
+    };
+
+    let http = reqwest::Client::new();
+    let url = args.url.trim_end_matches('/');
+    let mut body = json!({ "address": address, "captcha_token": args.captcha_token });
+    let mut response = post(&http, url, &body).await?;
+    match response["challenge"].as_str() {
+        Some("pow") => {
+            let challenge: Value = http
+                .get(format!("{}/v1/challenge", url))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let (Some(id), Some(difficulty)) = (
+                challenge["challenge"].as_str(),
+                challenge["difficulty"].as_u64(),
+            ) else {
+                bail!("Unexpected challenge from the faucet: {}", challenge);
+            };
+            println!("Solving a {}-bit proof-of-work challenge...", difficulty);
+            let nonce = solve_pow(id, difficulty as u32)
+                .ok_or_else(|| anyhow!("Invalid proof-of-work challenge {}", id))?;
+            body["pow"] = json!({ "challenge": id, "nonce": nonce });
+            response = post(&http, url, &body).await?;
+        }
+        Some("captcha") if args.captcha_token.is_none() => {
+            bail!("The faucet requires a captcha; solve it in a browser and pass --captcha-token")
+        }
+        _ => {}
+    }
+
+    if response["success"] != true {
+        bail!(
+            "Faucet request failed: {}",
+            response["error"].as_str().unwrap_or("unknown error")
+        );
+    }
+    println!(
+        "Sent {} to {} (tx: {})",
+        response["amount"].as_str().unwrap_or("tokens"),
+        address,
+        response["tx_hash"].as_str().unwrap_or("unknown")
+    );
+    if let Some(claim_id) = response["receipt"]["claim_id"].as_str() {
+        println!("Claim: {}", claim_id);
+    }
+
+    let Some(before) = before else {
+        return Ok(());
+    };
+    println!("Waiting for the balance of {} to increase...", address);
+    let deadline = Instant::now() + Duration::from_secs(args.timeout);
+    loop {
+        match rpc.get_balance(&address).await {
+            Ok(balance) if balance > before => {
+                println!("Balance: {}", amount::format(balance, Unit::Axx));
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to read the balance, retrying: {:?}", e),
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "Timed out after {}s waiting for the funds to arrive",
+                args.timeout
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// `POST /v1/request`; error responses carry the same JSON body
+async fn post(http: &reqwest::Client, url: &str, body: &Value) -> anyhow::Result<Value> {
+    let response = http
+        .post(format!("{}/v1/request", url))
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| anyhow!("Failed to reach the faucet at {}: {}", url, e))?;
+    let status = response.status();
+    response
+        .json()
+        .await
+        .map_err(|e| anyhow!("Unexpected {} response from the faucet: {}", status, e))
+}
@@ -0,0 +1,45 @@
+//! Client IP normalization for rate limiting
+
+use std::net::{IpAddr, Ipv6Addr};
+
+/// Default IPv6 prefix length used to group clients
+pub const DEFAULT_IPV6_PREFIX_LEN: u8 = 64;
+
+/// IPv6 prefix length from `IPV6_PREFIX_LEN`, between 1 and 128
+pub fn ipv6_prefix_len_from_env() -> anyhow::Result<u8> {
+    let value = match std::env::var("IPV6_PREFIX_LEN") {
+        Ok(value) => value,
+        Err(_) => return Ok(DEFAULT_IPV6_PREFIX_LEN),
+    };
+    match value.parse() {
+        Ok(len @ 1..=128) => Ok(len),
+        _ => anyhow::bail!("Invalid IPV6_PREFIX_LEN: {:?} (expected 1-128)", value),
+    }
+}
+
+/// Canonical form of a client IP, unwrapping IPv4-mapped IPv6 addresses
+pub fn normalize(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(v6),
+        },
+        v4 => v4,
+    }
+}
+
+/// Key under which a client is rate limited
+///
+/// IPv4 clients are limited per address. IPv6 clients are grouped by their
+/// `ipv6_prefix_len` network (1-128), since a single user usually controls a
+/// whole /64.
+pub fn rate_limit_key(ip: IpAddr, ipv6_prefix_len: u8) -> String {
+    match normalize(ip) {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => {
+            let mask = u128::MAX << (128 - u32::from(ipv6_prefix_len));
+            let network = Ipv6Addr::from(u128::from(v6) & mask);
+            format!("{}/{}", network, ipv6_prefix_len)
+        }
+    }
+}
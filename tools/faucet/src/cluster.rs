@@ -0,0 +1,169 @@
+//! Leader election between faucet replicas sharing a ledger
+//!
+//! Replicas started with a `REPLICA_ID` and the same `DATABASE_PATH` compete
+//! for a lease row in the ledger. The ledger is a SQLite file, so replicas
+//! must run on one host (or share one local volume); this covers process
+//! and container restarts, not the loss of the host. Only the lease holder sends drips and runs
+//! jobs that move funds or write shared state; followers keep serving reads
+//! and answer `/request` with `not_leader`. A leader that stops renewing
+//! stops sending once its lease runs out, and another replica takes over on
+//! its next renewal.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json};
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{info, warn};
+
+use crate::shedding::SheddingStatus;
+use crate::{FaucetError, FaucetState};
+
+/// Role of this replica
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Clustering is disabled; the replica always sends
+    Standalone,
+    Leader,
+    Follower,
+}
+
+/// Lease state of a clustered replica
+pub struct Cluster {
+    pub replica_id: String,
+    /// How long an acquired lease lasts
+    pub ttl: Duration,
+    /// When the lease held by this replica runs out
+    lease_until: Mutex<Option<Instant>>,
+}
+
+impl Cluster {
+    /// Enabled when `REPLICA_ID` is set
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(replica_id) = std::env::var("REPLICA_ID") else {
+            return Ok(None);
+        };
+        let ttl = std::env::var("LEADER_LEASE_SECS").unwrap_or_else(|_| "15".to_string());
+        let ttl = match ttl.parse::<u64>() {
+            Ok(secs) if secs >= 3 => Duration::from_secs(secs),
+            _ => anyhow::bail!("Invalid LEADER_LEASE_SECS: {:?} (at least 3)", ttl),
+        };
+        Ok(Some(Self {
+            replica_id,
+            ttl,
+            lease_until: Mutex::new(None),
+        }))
+    }
+
+    /// Whether this replica holds an unexpired lease
+    pub fn is_leader(&self) -> bool {
+        self.lease_until
+            .lock()
+            .unwrap()
+            .is_some_and(|until| Instant::now() < until)
+    }
+}
+
+/// Role of the replica behind `state`
+pub fn role(state: &FaucetState) -> Role {
+    match state.cluster.as_deref() {
+        None => Role::Standalone,
+        Some(cluster) if cluster.is_leader() => Role::Leader,
+        Some(_) => Role::Follower,
+    }
+}
+
+/// Whether this replica may send transactions
+pub fn is_sender(state: &FaucetState) -> bool {
+    role(state) != Role::Follower
+}
+
+/// Take or renew the lease, reloading cooldowns when this replica is promoted
+pub async fn renew(state: &FaucetState) -> Result<(), FaucetError> {
+    let Some(cluster) = state.cluster.as_deref() else {
+        return Ok(());
+    };
+    let was_leader = cluster.is_leader();
+    let started = Instant::now();
+    let acquired = state
+        .ledger
+        .lock()
+        .await
+        .acquire_lease(&cluster.replica_id, SystemTime::now() + cluster.ttl)?;
+
+    if !acquired {
+        *cluster.lease_until.lock().unwrap() = None;
+        if was_leader {
+            warn!("Replica {} lost the sender lease", cluster.replica_id);
+        }
+        return Ok(());
+    }
+
+    if !was_leader {
+        // Grants made by the previous leader aren't in this replica's memory
+        let (address_requests, ip_requests) = crate::recent_grants(
+            &*state.ledger.lock().await,
+            state.ipv6_prefix_len,
+            &state.config.ip_limit(),
+        )?;
+        *state.address_requests.write().await = address_requests;
+        *state.ip_requests.write().await = ip_requests;
+        info!("Replica {} is now the leader", cluster.replica_id);
+    }
+    *cluster.lease_until.lock().unwrap() = Some(started + cluster.ttl);
+    Ok(())
+}
+
+/// Give up the lease so another replica can take over immediately
+pub async fn release(state: &FaucetState) {
+    let Some(cluster) = state.cluster.as_deref() else {
+        return;
+    };
+    *cluster.lease_until.lock().unwrap() = None;
+    if let Err(e) = state.ledger.lock().await.release_lease(&cluster.replica_id) {
+        warn!("Failed to release the sender lease: {:?}", e);
+    }
+}
+
+/// Readiness response
+#[derive(Debug, Serialize)]
+pub struct ReadyResponse {
+    ready: bool,
+    role: Role,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replica_id: Option<String>,
+    /// Replica currently holding the sender lease
+    #[serde(skip_serializing_if = "Option::is_none")]
+    leader: Option<String>,
+    /// Load shedding state; a shedding replica stays ready, since every
+    /// replica shares the slow RPC endpoint
+    load_shedding: SheddingStatus,
+}
+
+/// Readiness and replica role; not ready while the chain ID is unverified or
+/// the replica is still warming up
+pub async fn readyz(State(state): State<FaucetState>) -> impl IntoResponse {
+    let ready = state.chain_verified.load(Ordering::Relaxed) && state.warm.load(Ordering::Relaxed);
+    let leader = match state.cluster.as_ref() {
+        Some(_) => state.ledger.lock().await.lease_holder().ok().flatten(),
+        None => None,
+    };
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(ReadyResponse {
+            ready,
+            role: role(&state),
+            replica_id: state.cluster.as_ref().map(|c| c.replica_id.clone()),
+            leader,
+            load_shedding: state.shedder.status(),
+        }),
+    )
+}
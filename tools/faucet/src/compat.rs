@@ -0,0 +1,160 @@
+//! Response shapes of other faucets, for clients written against them
+//!
+//! Partners moving over from another faucet keep their client code by asking
+//! for its response schema, per request with `?compat=<mode>` or for every
+//! request carrying an `X-Api-Key` listed in `COMPAT_API_KEYS`, a
+//! comma-separated list of `key:mode` pairs. The query flag wins over the
+//! key. Only `/request` and `/info` are shaped; status codes and headers such
+//! as `Retry-After` are left as they are.
+//!
+//! - `eth-faucet` ([chainflag/eth-faucet](https://github.com/chainflag/eth-faucet)):
+//!   `/request` answers `{ "message": "Txhash: 0x..." }` on success and
+//!   `{ "message": "<error>" }` otherwise, and `/info` answers
+//!   `{ account, network, payout, symbol, hcaptcha_sitekey }`.
+//!
+//! New modes are added to [`Mode`].
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Query, Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::{api, branding, FaucetError, FaucetState};
+
+/// Header carrying a partner's API key
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Largest response body that is reshaped
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Response schema of another faucet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    EthFaucet,
+}
+
+impl Mode {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "eth-faucet" => Some(Mode::EthFaucet),
+            _ => None,
+        }
+    }
+
+    /// Map a `/request` or `/info` body onto this schema
+    fn shape(self, route: &str, body: Value) -> Value {
+        match (self, route) {
+            (Mode::EthFaucet, "/request") => match body["tx_hash"].as_str() {
+                Some(tx_hash) => json!({ "message": format!("Txhash: {}", tx_hash) }),
+                None => json!({ "message": body["error"] }),
+            },
+            (Mode::EthFaucet, _) => {
+                let symbol = &branding::get().symbol;
+                let amount = body["amount"].as_str().unwrap_or_default();
+                json!({
+                    "account": body["receipts"]["signer"],
+                    "network": body["network"],
+                    "payout": amount.strip_suffix(symbol.as_str()).unwrap_or(amount).trim(),
+                    "symbol": symbol,
+                    "hcaptcha_sitekey": "",
+                })
+            }
+        }
+    }
+}
+
+/// Compatibility modes assigned to API keys
+pub struct Compat {
+    keys: HashMap<String, Mode>,
+}
+
+impl Compat {
+    /// Load `COMPAT_API_KEYS`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut keys = HashMap::new();
+        let list = std::env::var("COMPAT_API_KEYS").unwrap_or_default();
+        for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry
+                .split_once(':')
+                .and_then(|(key, mode)| Some((key, Mode::parse(mode)?)))
+            {
+                Some((key, mode)) if !key.is_empty() => {
+                    keys.insert(key.to_string(), mode);
+                }
+                _ => anyhow::bail!(
+                    "Invalid COMPAT_API_KEYS entry, expected key:mode with mode eth-faucet"
+                ),
+            }
+        }
+        if !keys.is_empty() {
+            info!("   Compatibility API keys: {}", keys.len());
+        }
+        Ok(Self { keys })
+    }
+
+    /// Mode asked for by `request`, if any
+    fn mode(&self, request: &Request) -> Result<Option<Mode>, FaucetError> {
+        if let Ok(Query(query)) = Query::<CompatQuery>::try_from_uri(request.uri()) {
+            if let Some(name) = query.compat {
+                return Mode::parse(&name).map(Some).ok_or_else(|| {
+                    FaucetError::InvalidPayload(format!("unknown compat mode {:?}", name))
+                });
+            }
+        }
+        Ok(request
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|key| key.to_str().ok())
+            .and_then(|key| self.keys.get(key))
+            .copied())
+    }
+}
+
+/// Query parameters selecting a compatibility mode
+#[derive(Debug, Deserialize)]
+struct CompatQuery {
+    compat: Option<String>,
+}
+
+/// Reshape `/request` and `/info` responses for clients asking for a mode
+pub async fn shape(State(state): State<FaucetState>, request: Request, next: Next) -> Response {
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(route) => api::unversioned(route.as_str()).to_string(),
+        None => return next.run(request).await,
+    };
+    if route != "/request" && route != "/info" {
+        return next.run(request).await;
+    }
+    let mode = match state.compat.mode(&request) {
+        Ok(Some(mode)) => mode,
+        Ok(None) => return next.run(request).await,
+        Err(e) => return e.into_response(),
+    };
+
+    let (mut parts, body) = next.run(request).await.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to read the {} response to reshape: {}", route, e);
+            return FaucetError::from(axionax_errors::Error::internal(e.to_string()))
+                .into_response();
+        }
+    };
+    let Ok(body) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let shaped = serde_json::to_vec(&mode.shape(&route, body)).unwrap_or_default();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    Response::from_parts(parts, Body::from(shaped))
+}
@@ -0,0 +1,164 @@
+//! Versioned database schema and the `db` subcommands
+//!
+//! Migrations live in `migrations/` as `V<version>__<name>.sql` and are
+//! embedded into the binary. They are applied automatically when the ledger is
+//! opened; `axionax-faucet db migrate` and `db status` manage them by hand.
+//!
+//! A new database file is created with `DATABASE_MODE` permissions (octal,
+//! default `600`) whatever the umask, and SQLite gives its `-wal` and `-shm`
+//! files the same. An existing file is only changed when `DATABASE_MODE` is
+//! set.
+
+use clap::Subcommand;
+use rusqlite::Connection;
+
+mod embedded {
+    refinery::embed_migrations!("migrations");
+}
+
+/// Permissions of a new database file when `DATABASE_MODE` is unset
+#[cfg(unix)]
+const DEFAULT_FILE_MODE: u32 = 0o600;
+
+/// Table holding applied migration versions
+const HISTORY_TABLE: &str = "refinery_schema_history";
+
+/// Columns added to tables before schema versioning was introduced
+const UNVERSIONED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("requests", "country", "TEXT"),
+    ("requests", "asn", "INTEGER"),
+    ("requests", "as_org", "TEXT"),
+];
+
+/// Database maintenance commands
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Apply pending migrations
+    Migrate,
+    /// Show applied and pending migrations
+    Status,
+}
+
+/// Open the database at `path`, creating it with `DATABASE_MODE` permissions
+pub fn open(path: &str) -> anyhow::Result<Connection> {
+    #[cfg(unix)]
+    set_file_mode(path)?;
+    Ok(Connection::open(path)?)
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &str) -> anyhow::Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let configured = match std::env::var("DATABASE_MODE") {
+        Ok(mode) => Some(
+            crate::listen::parse_mode(&mode)
+                .map_err(|e| anyhow::anyhow!("Invalid DATABASE_MODE: {}", e))?,
+        ),
+        Err(_) => None,
+    };
+    if path == ":memory:" {
+        return Ok(());
+    }
+    let created = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(configured.unwrap_or(DEFAULT_FILE_MODE))
+        .open(path);
+    let mode = match created {
+        // The umask may have masked the mode given at creation
+        Ok(_) => configured.unwrap_or(DEFAULT_FILE_MODE),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => match configured {
+            Some(mode) => mode,
+            None => return Ok(()),
+        },
+        Err(e) => return Err(anyhow::anyhow!("Failed to create {}: {}", path, e)),
+    };
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+/// Run a `db` subcommand against the database at `path`
+pub fn run(command: &DbCommand, path: &str) -> anyhow::Result<()> {
+    let mut conn = open(path)?;
+    match command {
+        DbCommand::Migrate => {
+            let applied = migrate(&mut conn)?;
+            if applied.is_empty() {
+                println!("{}: schema is up to date", path);
+            }
+            for migration in applied {
+                println!("Applied {}", migration);
+            }
+        }
+        DbCommand::Status => {
+            let applied = applied(&mut conn)?;
+            let mut migrations = embedded::migrations::runner().get_migrations().clone();
+            migrations.sort_by_key(|m| m.version());
+            for migration in &migrations {
+                match applied.iter().find(|m| m.version() == migration.version()) {
+                    Some(m) => match m.applied_on() {
+                        Some(on) => println!("{}  applied {}", migration, on),
+                        None => println!("{}  applied", migration),
+                    },
+                    None => println!("{}  pending", migration),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bring the schema up to date, returning the migrations applied
+pub fn migrate(conn: &mut Connection) -> anyhow::Result<Vec<String>> {
+    if !table_exists(conn, HISTORY_TABLE)? && table_exists(conn, "requests")? {
+        upgrade_unversioned(conn)?;
+    }
+
+    let report = embedded::migrations::runner().run(conn)?;
+    Ok(report
+        .applied_migrations()
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Latest applied schema version, if any
+pub fn schema_version(conn: &mut Connection) -> anyhow::Result<Option<u32>> {
+    Ok(applied(conn)?.iter().map(|m| m.version()).max())
+}
+
+/// Migrations recorded in the history table
+fn applied(conn: &mut Connection) -> anyhow::Result<Vec<refinery::Migration>> {
+    if !table_exists(conn, HISTORY_TABLE)? {
+        return Ok(Vec::new());
+    }
+    Ok(embedded::migrations::runner().get_applied_migrations(conn)?)
+}
+
+/// Align a database created before schema versioning with `V1`
+fn upgrade_unversioned(conn: &Connection) -> rusqlite::Result<()> {
+    for (table, column, decl) in UNVERSIONED_COLUMNS {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .any(|name| name == column);
+        if !exists {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, decl
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+fn table_exists(conn: &Connection, table: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [table],
+        |row| row.get(0),
+    )
+}
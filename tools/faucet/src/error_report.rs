@@ -0,0 +1,147 @@
+//! Error reporting to an external webhook
+//!
+//! Built with the `error-report` feature. Panics, RPC failures and 5xx
+//! responses are posted as JSON to `ERROR_REPORT_URL`. Without the feature,
+//! or when the URL is unset, every function here is a no-op.
+
+use axum::{extract::Request, middleware::Next, response::Response};
+
+/// Error message attached to 5xx responses for the report
+#[cfg_attr(not(feature = "error-report"), allow(dead_code))]
+#[derive(Debug, Clone)]
+pub struct ErrorDetail(pub String);
+
+/// Install the reporter and panic hook
+pub fn init() {
+    #[cfg(feature = "error-report")]
+    imp::init();
+}
+
+/// Report a failed JSON-RPC call
+pub fn rpc_failure(_method: &str, _error: &str) {
+    #[cfg(feature = "error-report")]
+    imp::send(imp::Report {
+        kind: "rpc_error",
+        message: format!("{}: {}", _method, _error),
+        ..Default::default()
+    });
+}
+
+/// Report 5xx responses with their request context
+pub async fn report_server_errors(request: Request, next: Next) -> Response {
+    #[cfg(feature = "error-report")]
+    let context = (
+        request
+            .headers()
+            .get(axionax_http::REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        request.method().to_string(),
+        request.uri().path().to_string(),
+    );
+
+    let response = next.run(request).await;
+
+    #[cfg(feature = "error-report")]
+    if response.status().is_server_error() {
+        let (request_id, method, path) = context;
+        let message = response
+            .extensions()
+            .get::<ErrorDetail>()
+            .map(|d| d.0.clone())
+            .unwrap_or_else(|| response.status().to_string());
+        imp::send(imp::Report {
+            kind: "server_error",
+            message,
+            request_id,
+            method: Some(method),
+            path: Some(path),
+            status: Some(response.status().as_u16()),
+            ..Default::default()
+        });
+    }
+
+    response
+}
+
+#[cfg(feature = "error-report")]
+mod imp {
+    use serde::Serialize;
+    use std::sync::OnceLock;
+    use std::time::SystemTime;
+    use tokio::sync::mpsc;
+    use tracing::{info, warn};
+
+    static REPORTER: OnceLock<mpsc::UnboundedSender<Report>> = OnceLock::new();
+
+    /// Payload posted to the webhook
+    #[derive(Debug, Default, Serialize)]
+    pub struct Report {
+        pub kind: &'static str,
+        pub message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub request_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub method: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub path: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub status: Option<u16>,
+        pub service: &'static str,
+        pub version: &'static str,
+        pub environment: Option<String>,
+        pub timestamp: u64,
+    }
+
+    pub fn init() {
+        let Ok(url) = std::env::var("ERROR_REPORT_URL") else {
+            return;
+        };
+        let environment = std::env::var("ERROR_REPORT_ENV").ok();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Report>();
+        if REPORTER.set(tx).is_err() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let http = reqwest::Client::new();
+            while let Some(mut report) = rx.recv().await {
+                report.environment = environment.clone();
+                let result = http
+                    .post(&url)
+                    .json(&report)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status());
+                if let Err(e) = result {
+                    warn!("Failed to send error report: {}", e);
+                }
+            }
+        });
+
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic| {
+            send(Report {
+                kind: "panic",
+                message: panic.to_string(),
+                ..Default::default()
+            });
+            previous(panic);
+        }));
+
+        info!("   Error reports: enabled");
+    }
+
+    pub fn send(mut report: Report) {
+        let Some(tx) = REPORTER.get() else {
+            return;
+        };
+        report.service = env!("CARGO_PKG_NAME");
+        report.version = env!("CARGO_PKG_VERSION");
+        report.timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let _ = tx.send(report);
+    }
+}
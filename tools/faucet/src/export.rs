@@ -0,0 +1,109 @@
+//! Allocations for re-funding active builders after a testnet reset
+//!
+//! `axionax-faucet export-recipients <file>` lists drip recipients that sent
+//! at least `--min-activity` transactions after their first drip, in the
+//! allocation format read by `tools/create_genesis.py`. Activity comes from
+//! the counters kept for the leaderboard, which stop being refreshed after
+//! `LEADERBOARD_DAYS`; with `--rpc-url`, each recipient's current nonce is
+//! read from the node instead.
+//!
+//! Each allocation lists the recipient's operator labels, which
+//! `create_genesis.py` ignores; `--exclude-label suspected_bot` leaves out
+//! recipients carrying that label.
+
+use anyhow::anyhow;
+use clap::Args;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::ledger::{self, Ledger};
+use crate::rpc::RpcClient;
+use crate::{axx_to_wei, FAUCET_AMOUNT_AXX};
+
+/// Options of the `export-recipients` subcommand
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Allocation file to write
+    file: PathBuf,
+
+    /// Transactions a recipient must have sent since its first drip
+    #[arg(long, default_value_t = 1)]
+    min_activity: u64,
+
+    /// AXX allocated to each exported recipient
+    #[arg(long, default_value_t = FAUCET_AMOUNT_AXX)]
+    amount: u64,
+
+    /// Count transactions from current nonces on this node
+    #[arg(long)]
+    rpc_url: Option<String>,
+
+    /// Leave out recipients carrying this label; repeatable
+    #[arg(long = "exclude-label")]
+    exclude_labels: Vec<String>,
+}
+
+/// Genesis allocation, as read by `create_genesis.py`
+#[derive(Debug, Serialize)]
+struct Allocation {
+    address: String,
+    /// Wei, in decimal
+    balance: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
+}
+
+/// Write the allocation file for recipients of the ledger at `db_path`
+pub async fn run(args: &ExportArgs, db_path: &str) -> anyhow::Result<()> {
+    let ledger = Ledger::open(db_path)?;
+    let recipients = ledger
+        .recipient_activity(ledger::from_unix(0))
+        .map_err(|e| anyhow!("Failed to read recipients: {:?}", e))?;
+    let balance = axx_to_wei(args.amount).to_string();
+    let rpc = args.rpc_url.as_deref().map(RpcClient::new);
+
+    let mut allocations = Vec::new();
+    for recipient in &recipients {
+        let transactions = match rpc.as_ref() {
+            Some(rpc) => rpc
+                .get_transaction_count(&recipient.address)
+                .await
+                .map_err(|e| anyhow!("Failed to read the nonce of {}: {:?}", recipient.address, e))?
+                .saturating_sub(recipient.nonce_at_drip),
+            None => recipient.transactions,
+        };
+        if transactions < args.min_activity {
+            continue;
+        }
+        let labels: Vec<String> = ledger
+            .labels(&recipient.address)
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to read the labels of {}: {:?}",
+                    recipient.address,
+                    e
+                )
+            })?
+            .into_iter()
+            .map(|l| l.label)
+            .collect();
+        if labels.iter().any(|l| args.exclude_labels.contains(l)) {
+            continue;
+        }
+        allocations.push(Allocation {
+            address: recipient.address.clone(),
+            balance: balance.clone(),
+            labels,
+        });
+    }
+    allocations.sort_by(|a, b| a.address.cmp(&b.address));
+
+    std::fs::write(&args.file, serde_json::to_string_pretty(&allocations)?)?;
+    println!(
+        "Exported {} of {} recipients to {}",
+        allocations.len(),
+        recipients.len(),
+        args.file.display()
+    );
+    Ok(())
+}
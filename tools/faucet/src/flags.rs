@@ -0,0 +1,115 @@
+//! Feature flags for rolling out faucet behaviors gradually
+//!
+//! A flag is on for a percentage of clients and for listed partners, so a
+//! risky behavior can be tried on part of the traffic during a live event.
+//! `FEATURE_FLAGS` sets the rollouts, e.g. `pow_rollout=10,new_scoring=0@acme`
+//! (`name=percent`, then `@` and `|`-separated partner IDs). A config bundle
+//! with `flags` replaces them fleet-wide, and `PUT /admin/flags/:name`
+//! overrides a single flag on every replica until it is deleted again.
+//!
+//! Clients are bucketed by a hash of the flag name and their rate-limit key,
+//! so a client gets the same behavior on every request and each flag samples
+//! different clients. Unknown flags are off.
+//!
+//! Policy stages are gated with `stage@flag` in `POLICY_STAGES`: with
+//! `pow@pow_rollout`, only clients in the `pow_rollout` rollout are asked for
+//! proof of work.
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Who a flag is on for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rollout {
+    /// Share of clients, 0-100
+    pub percent: u8,
+    /// Partners the flag is always on for
+    #[serde(default)]
+    pub partners: BTreeSet<String>,
+}
+
+impl Rollout {
+    /// Reject percentages above 100
+    pub fn validate(&self) -> Result<(), String> {
+        if self.percent > 100 {
+            return Err(format!("percent must be 0-100, got {}", self.percent));
+        }
+        Ok(())
+    }
+
+    /// Whether the flag is on for a client, keyed by its rate-limit key
+    pub fn includes(&self, flag: &str, key: &str, partner: Option<&str>) -> bool {
+        partner.is_some_and(|p| self.partners.contains(p)) || bucket(flag, key) < self.percent
+    }
+}
+
+/// Where the rollout of a flag comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Env,
+    Bundle,
+    Admin,
+}
+
+/// Parse `FEATURE_FLAGS`
+pub fn from_env() -> anyhow::Result<BTreeMap<String, Rollout>> {
+    match std::env::var("FEATURE_FLAGS") {
+        Ok(spec) => parse(&spec),
+        Err(_) => Ok(BTreeMap::new()),
+    }
+}
+
+/// Parse `name=percent[@partner|partner...]` entries separated by commas
+pub fn parse(spec: &str) -> anyhow::Result<BTreeMap<String, Rollout>> {
+    let mut flags = BTreeMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once('=').and_then(|(name, rest)| {
+            let (percent, partners) = match rest.split_once('@') {
+                Some((percent, partners)) => (percent, partners),
+                None => (rest, ""),
+            };
+            let rollout = Rollout {
+                percent: percent.trim().parse().ok()?,
+                partners: partners
+                    .split('|')
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            };
+            rollout.validate().ok()?;
+            Some((valid_name(name.trim())?, rollout))
+        });
+        match parsed {
+            Some((name, rollout)) => flags.insert(name.to_string(), rollout),
+            None => anyhow::bail!(
+                "Invalid feature flag {:?}, expected name=percent[@partner|...]",
+                entry
+            ),
+        };
+    }
+    Ok(flags)
+}
+
+/// Flag names are lowercase `[a-z0-9_]`
+pub fn valid_name(name: &str) -> Option<&str> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    valid.then_some(name)
+}
+
+/// Stable bucket 0-99 of a client for a flag
+fn bucket(flag: &str, key: &str) -> u8 {
+    let hash = Keccak256::new()
+        .chain_update(flag.as_bytes())
+        .chain_update(b":")
+        .chain_update(key.as_bytes())
+        .finalize();
+    (u16::from_be_bytes([hash[0], hash[1]]) % 100) as u8
+}
@@ -0,0 +1,121 @@
+//! GeoIP enrichment and country/ASN policy
+
+use maxminddb::{geoip2, Reader};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// Country and network of a client IP
+#[derive(Debug, Clone, Default)]
+pub struct GeoInfo {
+    /// ISO 3166-1 alpha-2 country code
+    pub country: Option<String>,
+    /// Autonomous system number
+    pub asn: Option<u32>,
+    /// Autonomous system organization
+    pub as_org: Option<String>,
+}
+
+/// Limit applying to a client's country or ASN
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeoLimit {
+    Country(String, u32),
+    Asn(u32, u32),
+}
+
+/// MaxMind-compatible GeoIP lookup with optional per-country/ASN limits
+pub struct GeoIp {
+    country_db: Option<Reader<Vec<u8>>>,
+    asn_db: Option<Reader<Vec<u8>>>,
+    /// Max grants per country per cooldown window
+    country_limits: HashMap<String, u32>,
+    /// Max grants per ASN per cooldown window
+    asn_limits: HashMap<u32, u32>,
+}
+
+impl GeoIp {
+    /// Load GeoIP databases and limits from the environment
+    ///
+    /// Returns `None` when neither `GEOIP_COUNTRY_DB` nor `GEOIP_ASN_DB` is set.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let country_db = std::env::var("GEOIP_COUNTRY_DB")
+            .ok()
+            .map(Reader::open_readfile)
+            .transpose()?;
+        let asn_db = std::env::var("GEOIP_ASN_DB")
+            .ok()
+            .map(Reader::open_readfile)
+            .transpose()?;
+
+        if country_db.is_none() && asn_db.is_none() {
+            return Ok(None);
+        }
+
+        let country_limits =
+            parse_limits(&std::env::var("GEOIP_COUNTRY_LIMITS").unwrap_or_default())?
+                .into_iter()
+                .map(|(country, limit)| (country.to_uppercase(), limit))
+                .collect();
+        let asn_limits = parse_limits(&std::env::var("GEOIP_ASN_LIMITS").unwrap_or_default())?
+            .into_iter()
+            .map(|(asn, limit)| Ok((asn.trim_start_matches("AS").parse()?, limit)))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Some(Self {
+            country_db,
+            asn_db,
+            country_limits,
+            asn_limits,
+        }))
+    }
+
+    /// Look up the country and ASN of an IP
+    pub fn lookup(&self, ip: IpAddr) -> GeoInfo {
+        let mut info = GeoInfo::default();
+
+        if let Some(db) = &self.country_db {
+            if let Ok(record) = db.lookup::<geoip2::Country>(ip) {
+                info.country = record.country.and_then(|c| c.iso_code).map(str::to_string);
+            }
+        }
+
+        if let Some(db) = &self.asn_db {
+            if let Ok(record) = db.lookup::<geoip2::Asn>(ip) {
+                info.asn = record.autonomous_system_number;
+                info.as_org = record.autonomous_system_organization.map(str::to_string);
+            }
+        }
+
+        info
+    }
+
+    /// Limits configured for the client's country and ASN
+    pub fn limits_for(&self, info: &GeoInfo) -> Vec<GeoLimit> {
+        let mut limits = Vec::new();
+        if let Some(country) = &info.country {
+            if let Some(&limit) = self.country_limits.get(country) {
+                limits.push(GeoLimit::Country(country.clone(), limit));
+            }
+        }
+        if let Some(asn) = info.asn {
+            if let Some(&limit) = self.asn_limits.get(&asn) {
+                limits.push(GeoLimit::Asn(asn, limit));
+            }
+        }
+        limits
+    }
+}
+
+/// Parse `KEY:LIMIT,KEY:LIMIT` lists
+fn parse_limits(value: &str) -> anyhow::Result<Vec<(String, u32)>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (key, limit) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("invalid limit entry: {}", entry))?;
+            Ok((key.trim().to_string(), limit.trim().parse()?))
+        })
+        .collect()
+}
@@ -0,0 +1,336 @@
+//! Proof of personhood for larger drips and shorter cooldowns
+//!
+//! The `identity` policy stage checks the `identity` object of a `/request`
+//! body, `{ "provider": "...", "proof": ... }`, with that provider's
+//! [`IdentityVerifier`]. A verified address is granted `IDENTITY_AMOUNT_AXX`
+//! per drip and waits `IDENTITY_COOLDOWN_HOURS` between drips instead of the
+//! defaults; requests without a proof are granted as usual. Verifications are
+//! stored in the `identities` table, so later requests from the address don't
+//! need the proof again, and each person verifies a single address.
+//!
+//! - `brightid` (with `BRIGHTID_CONTEXT`): the address is a context ID linked
+//!   to a unique BrightID in that app context, as reported by
+//!   `BRIGHTID_NODE_URL`. No `proof` is needed.
+//! - `worldid` (with `WORLDID_APP_ID`): `proof` is the IDKit result
+//!   (`merkle_root`, `nullifier_hash`, `proof`, `verification_level`) for the
+//!   `WORLDID_ACTION` action (default `faucet`), with the lowercase address as
+//!   signal, checked with the Developer Portal at `WORLDID_VERIFY_URL`.
+//!
+//! New providers implement [`IdentityVerifier`] and are registered in
+//! [`Identity::from_env`].
+
+use async_trait::async_trait;
+use axionax_errors::Kind;
+use axionax_faucet::validation::parse_amount;
+use axionax_tx::U256;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::time::Duration;
+use tracing::info;
+
+use crate::{FaucetError, FaucetState, COOLDOWN_HOURS};
+
+/// BrightID node used when `BRIGHTID_NODE_URL` is unset
+const DEFAULT_BRIGHTID_NODE: &str = "https://app.brightid.org/node/v6";
+
+/// World ID verification endpoint used when `WORLDID_VERIFY_URL` is unset
+const DEFAULT_WORLDID_VERIFY_URL: &str = "https://developer.worldcoin.org/api/v2/verify";
+
+/// World ID action used when `WORLDID_ACTION` is unset
+const DEFAULT_WORLDID_ACTION: &str = "faucet";
+
+/// Time a provider has to answer a verification
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Identity proof sent with a request
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdentityProof {
+    pub provider: String,
+    #[serde(default)]
+    pub proof: Value,
+}
+
+/// A unique person, as identified by a provider
+#[derive(Debug)]
+pub struct Person {
+    /// Provider-scoped ID, the same whichever address the person verifies
+    pub subject: String,
+}
+
+/// A proof-of-personhood provider
+#[async_trait]
+pub trait IdentityVerifier: Send + Sync {
+    /// Provider name as given in `identity.provider`
+    fn provider(&self) -> &'static str;
+
+    /// Check that `proof` shows a unique person controls `address`
+    async fn verify(&self, address: &str, proof: &Value) -> Result<Person, FaucetError>;
+}
+
+/// Configured providers and what verified addresses are granted
+pub struct Identity {
+    verifiers: Vec<Box<dyn IdentityVerifier>>,
+    /// Drip amount of verified addresses
+    pub amount: Option<U256>,
+    /// Cooldown of verified addresses
+    pub cooldown: Option<Duration>,
+}
+
+impl Identity {
+    /// Load the providers; `None` when none is configured
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let http = reqwest::Client::builder().timeout(VERIFY_TIMEOUT).build()?;
+        let mut verifiers: Vec<Box<dyn IdentityVerifier>> = Vec::new();
+        if let Some(verifier) = BrightId::from_env(&http) {
+            verifiers.push(Box::new(verifier));
+        }
+        if let Some(verifier) = WorldId::from_env(&http) {
+            verifiers.push(Box::new(verifier));
+        }
+        if verifiers.is_empty() {
+            return Ok(None);
+        }
+
+        let amount = match std::env::var("IDENTITY_AMOUNT_AXX") {
+            Ok(axx) => Some(
+                parse_amount(&axx).map_err(|_| anyhow::anyhow!("Invalid IDENTITY_AMOUNT_AXX"))?,
+            ),
+            Err(_) => None,
+        };
+        let cooldown = match std::env::var("IDENTITY_COOLDOWN_HOURS") {
+            Ok(hours) => {
+                let hours: u64 = hours
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid IDENTITY_COOLDOWN_HOURS"))?;
+                if hours > COOLDOWN_HOURS {
+                    anyhow::bail!(
+                        "IDENTITY_COOLDOWN_HOURS can't be longer than the {} hour cooldown",
+                        COOLDOWN_HOURS
+                    );
+                }
+                Some(Duration::from_secs(hours * 3600))
+            }
+            Err(_) => None,
+        };
+        let providers: Vec<_> = verifiers.iter().map(|v| v.provider()).collect();
+        info!("   Identity providers: {}", providers.join(", "));
+        Ok(Some(Self {
+            verifiers,
+            amount,
+            cooldown,
+        }))
+    }
+
+    /// Whether `address` is verified, checking `proof` if it wasn't before
+    pub async fn verified(
+        &self,
+        state: &FaucetState,
+        address: &str,
+        proof: Option<&IdentityProof>,
+    ) -> Result<bool, FaucetError> {
+        if state.ledger.lock().await.identity(address)?.is_some() {
+            return Ok(true);
+        }
+        let Some(proof) = proof else {
+            return Ok(false);
+        };
+        let verifier = self
+            .verifiers
+            .iter()
+            .find(|v| v.provider() == proof.provider)
+            .ok_or_else(|| {
+                FaucetError::IdentityRejected(format!("unknown provider {}", proof.provider))
+            })?;
+
+        let person = verifier.verify(address, &proof.proof).await?;
+        let bound = state.ledger.lock().await.record_identity(
+            address,
+            verifier.provider(),
+            &person.subject,
+        )?;
+        if bound != address {
+            return Err(FaucetError::IdentityRejected(
+                "this identity already verified another address".to_string(),
+            ));
+        }
+        info!("Verified {} with {}", address, verifier.provider());
+        Ok(true)
+    }
+}
+
+/// The provider being down is worth a retry, unlike a rejected proof
+fn unavailable(provider: &str, e: reqwest::Error) -> FaucetError {
+    axionax_errors::Error::new(
+        Kind::Unavailable,
+        "identity_unavailable",
+        format!("{}: {}", provider, e),
+    )
+    .into()
+}
+
+/// BrightID app context the faucet's addresses are linked in
+struct BrightId {
+    http: reqwest::Client,
+    node_url: String,
+    context: String,
+}
+
+impl BrightId {
+    fn from_env(http: &reqwest::Client) -> Option<Self> {
+        let context = std::env::var("BRIGHTID_CONTEXT").ok()?;
+        let node_url = std::env::var("BRIGHTID_NODE_URL")
+            .unwrap_or_else(|_| DEFAULT_BRIGHTID_NODE.to_string());
+        Some(Self {
+            http: http.clone(),
+            node_url: node_url.trim_end_matches('/').to_string(),
+            context,
+        })
+    }
+}
+
+/// Body of a BrightID node verification response
+#[derive(Debug, Deserialize)]
+struct BrightIdResponse {
+    data: Option<BrightIdVerification>,
+    #[serde(rename = "errorMessage")]
+    error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BrightIdVerification {
+    unique: bool,
+    /// Context IDs linked to the BrightID, most recent first
+    #[serde(rename = "contextIds", default)]
+    context_ids: Vec<String>,
+}
+
+#[async_trait]
+impl IdentityVerifier for BrightId {
+    fn provider(&self) -> &'static str {
+        "brightid"
+    }
+
+    async fn verify(&self, address: &str, _proof: &Value) -> Result<Person, FaucetError> {
+        let url = format!(
+            "{}/verifications/{}/{}",
+            self.node_url, self.context, address
+        );
+        let response: BrightIdResponse = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| unavailable("brightid", e))?
+            .json()
+            .await
+            .map_err(|e| unavailable("brightid", e))?;
+        match response.data {
+            Some(verification) if verification.unique => Ok(Person {
+                // The oldest linked context ID stays the same as more are linked
+                subject: verification
+                    .context_ids
+                    .last()
+                    .map(|id| id.to_lowercase())
+                    .unwrap_or_else(|| address.to_string()),
+            }),
+            Some(_) => Err(FaucetError::IdentityRejected(
+                "the BrightID is not verified as unique".to_string(),
+            )),
+            None => Err(FaucetError::IdentityRejected(
+                response
+                    .error_message
+                    .unwrap_or_else(|| "the address is not linked to a BrightID".to_string()),
+            )),
+        }
+    }
+}
+
+/// World ID app the faucet verifies proofs for
+struct WorldId {
+    http: reqwest::Client,
+    verify_url: String,
+    action: String,
+}
+
+impl WorldId {
+    fn from_env(http: &reqwest::Client) -> Option<Self> {
+        let app_id = std::env::var("WORLDID_APP_ID").ok()?;
+        let verify_url = std::env::var("WORLDID_VERIFY_URL")
+            .unwrap_or_else(|_| DEFAULT_WORLDID_VERIFY_URL.to_string());
+        Some(Self {
+            http: http.clone(),
+            verify_url: format!("{}/{}", verify_url.trim_end_matches('/'), app_id),
+            action: std::env::var("WORLDID_ACTION")
+                .unwrap_or_else(|_| DEFAULT_WORLDID_ACTION.to_string()),
+        })
+    }
+}
+
+/// IDKit proof of a World ID
+#[derive(Debug, Deserialize, Serialize)]
+struct WorldIdProof {
+    merkle_root: String,
+    nullifier_hash: String,
+    proof: String,
+    verification_level: String,
+}
+
+/// Error body of the World ID verification endpoint
+#[derive(Debug, Deserialize)]
+struct WorldIdError {
+    detail: Option<String>,
+}
+
+#[async_trait]
+impl IdentityVerifier for WorldId {
+    fn provider(&self) -> &'static str {
+        "worldid"
+    }
+
+    async fn verify(&self, address: &str, proof: &Value) -> Result<Person, FaucetError> {
+        let proof: WorldIdProof = serde_json::from_value(proof.clone())
+            .map_err(|e| FaucetError::IdentityRejected(format!("invalid World ID proof: {}", e)))?;
+        let body = json!({
+            "merkle_root": proof.merkle_root,
+            "nullifier_hash": proof.nullifier_hash,
+            "proof": proof.proof,
+            "verification_level": proof.verification_level,
+            "action": self.action,
+            "signal_hash": signal_hash(address),
+        });
+        let response = self
+            .http
+            .post(&self.verify_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| unavailable("worldid", e))?;
+        if response.status().is_server_error() {
+            return Err(unavailable(
+                "worldid",
+                response.error_for_status().unwrap_err(),
+            ));
+        }
+        if !response.status().is_success() {
+            let error: WorldIdError = response
+                .json()
+                .await
+                .map_err(|e| unavailable("worldid", e))?;
+            return Err(FaucetError::IdentityRejected(
+                error
+                    .detail
+                    .unwrap_or_else(|| "the World ID proof is invalid".to_string()),
+            ));
+        }
+        Ok(Person {
+            subject: proof.nullifier_hash.to_lowercase(),
+        })
+    }
+}
+
+/// IDKit's `hashToField` of a string signal: keccak256 shifted right by a byte
+fn signal_hash(signal: &str) -> String {
+    let hash = Keccak256::digest(signal.as_bytes());
+    format!("0x00{}", hex::encode(&hash[..31]))
+}
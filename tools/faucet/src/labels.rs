@@ -0,0 +1,64 @@
+//! Operator labels on addresses
+//!
+//! Admins attach labels such as `team`, `partner`, `exchange` or
+//! `suspected_bot` to addresses with `PUT /admin/labels/:address/:label`,
+//! optionally with a note. Labels are kept in the `address_labels` table and
+//! shown in admin `/why` answers, in `export-recipients` allocations and to
+//! the policy script as `req.labels`.
+//!
+//! The `labels` policy stage rejects addresses carrying any label listed in
+//! `LABEL_DENY` (default `suspected_bot`) as blocked; an empty `LABEL_DENY`
+//! disables the stage.
+
+use std::collections::BTreeSet;
+use tracing::{info, warn};
+
+use crate::{FaucetError, FaucetState};
+
+/// Labels denied when `LABEL_DENY` is unset
+const DEFAULT_DENY: &str = "suspected_bot";
+
+/// Longest note kept with a label
+pub const MAX_NOTE_LEN: usize = 500;
+
+/// `name` if it is a valid label: 1-64 lowercase letters, digits or `_`
+pub fn valid_label(name: &str) -> Option<&str> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    valid.then_some(name)
+}
+
+/// Labels that keep an address from being granted
+pub struct DeniedLabels(BTreeSet<String>);
+
+impl DeniedLabels {
+    /// Parse `LABEL_DENY`; `None` when it lists no label
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let list = std::env::var("LABEL_DENY").unwrap_or_else(|_| DEFAULT_DENY.to_string());
+        let mut labels = BTreeSet::new();
+        for label in list.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+            let label = valid_label(label)
+                .ok_or_else(|| anyhow::anyhow!("Invalid label in LABEL_DENY: {:?}", label))?;
+            labels.insert(label.to_string());
+        }
+        if labels.is_empty() {
+            return Ok(None);
+        }
+        let names: Vec<_> = labels.iter().map(String::as_str).collect();
+        info!("   Denied labels: {}", names.join(", "));
+        Ok(Some(Self(labels)))
+    }
+
+    /// Reject `address` if it carries a denied label
+    pub async fn check(&self, state: &FaucetState, address: &str) -> Result<(), FaucetError> {
+        let labels = state.ledger.lock().await.labels(address)?;
+        if let Some(label) = labels.iter().find(|l| self.0.contains(&l.label)) {
+            warn!("Denied {}, labeled {}", address, label.label);
+            return Err(FaucetError::Blocked);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Leaderboard of the most active drip recipients
+//!
+//! After each drip the recipient's nonce is recorded; the `refresh_leaderboard`
+//! job then counts the transactions it has sent and the contracts it has
+//! deployed since, straight from the RPC endpoint.
+
+use axum::extract::{Json, Query, State};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+use crate::ledger::{Ranking, RecipientActivity};
+use crate::{FaucetError, FaucetState};
+
+const MAX_ENTRIES: u32 = 100;
+
+/// Query parameters for `/leaderboard`
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    by: Ranking,
+    /// Only rank recipients first dripped within this many days
+    #[serde(default = "default_days")]
+    days: u64,
+    #[serde(default = "default_limit")]
+    limit: u32,
+}
+
+fn default_days() -> u64 {
+    30
+}
+
+fn default_limit() -> u32 {
+    25
+}
+
+/// A ranked recipient
+#[derive(Debug, Serialize)]
+pub struct LeaderboardEntry {
+    rank: usize,
+    #[serde(flatten)]
+    activity: RecipientActivity,
+}
+
+/// Leaderboard response
+#[derive(Debug, Serialize)]
+pub struct LeaderboardResponse {
+    by: Ranking,
+    days: u64,
+    entries: Vec<LeaderboardEntry>,
+}
+
+/// Get the top builders by contracts deployed or transactions sent after their first drip
+pub async fn leaderboard(
+    State(state): State<FaucetState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardResponse>, FaucetError> {
+    let since = SystemTime::now() - Duration::from_secs(query.days * 86400);
+    let activity = state.ledger.lock().await.leaderboard(
+        query.by,
+        since,
+        query.limit.clamp(1, MAX_ENTRIES),
+    )?;
+
+    Ok(Json(LeaderboardResponse {
+        by: query.by,
+        days: query.days,
+        entries: activity
+            .into_iter()
+            .enumerate()
+            .map(|(i, activity)| LeaderboardEntry {
+                rank: i + 1,
+                activity,
+            })
+            .collect(),
+    }))
+}
+
+/// Start tracking a recipient after a drip
+pub async fn track(state: &FaucetState, address: &str) {
+    let result = match state.rpc.get_transaction_count(address).await {
+        Ok(nonce) => state.ledger.lock().await.track_recipient(address, nonce),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!("Failed to track recipient {}: {:?}", address, e);
+    }
+}
@@ -0,0 +1,1361 @@
+//! Persistent ledger of faucet requests, drips and treasury top-ups
+
+use axionax_faucet::receipt::{Receipt, SignedReceipt};
+use axionax_tx::U256;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::branding;
+use crate::chaos::Chaos;
+use crate::flags::Rollout;
+use crate::geoip::GeoInfo;
+use crate::FaucetError;
+
+/// Kind of ledger entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Tokens sent from the faucet to a user
+    Drip,
+    /// Tokens sent from the treasury to the faucet
+    TopUp,
+}
+
+impl EntryKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EntryKind::Drip => "drip",
+            EntryKind::TopUp => "top_up",
+        }
+    }
+}
+
+/// A single recorded transfer
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct LedgerEntry {
+    pub kind: EntryKind,
+    pub address: String,
+    pub amount: U256,
+    pub tx_hash: String,
+    pub timestamp: SystemTime,
+}
+
+/// Request and grant counts of one time bucket
+#[derive(Debug, Clone)]
+pub struct RequestBucket {
+    /// Bucket start (unix seconds)
+    pub start: i64,
+    pub requests: u64,
+    pub grants: u64,
+}
+
+/// Request and grant counts for a country or ASN
+#[derive(Debug, Clone, Serialize)]
+pub struct GeoAggregate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_org: Option<String>,
+    pub requests: u64,
+    pub grants: u64,
+}
+
+/// On-chain activity of a drip recipient since their first drip
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipientActivity {
+    pub address: String,
+    /// First drip (unix seconds)
+    pub first_drip: i64,
+    #[serde(skip)]
+    pub nonce_at_drip: u64,
+    /// Nonces below this have been checked for contract deployments
+    #[serde(skip)]
+    pub scanned_nonce: u64,
+    /// Transactions sent since the first drip
+    pub transactions: u64,
+    /// Contracts deployed since the first drip
+    pub contracts: u64,
+}
+
+/// Leaderboard ranking
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ranking {
+    #[default]
+    Contracts,
+    Transactions,
+}
+
+/// Request, grant and drip totals for a referring partner
+#[derive(Debug, Clone)]
+pub struct PartnerAggregate {
+    pub partner: String,
+    pub requests: u64,
+    pub grants: u64,
+    /// Amount dripped (wei)
+    pub distributed: U256,
+}
+
+/// A privileged action taken through the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// When the action was taken (unix seconds)
+    pub timestamp: i64,
+    pub actor: String,
+    pub action: String,
+    pub payload: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+}
+
+/// Maintenance window during which drips are paused
+#[derive(Debug, Clone, Serialize)]
+pub struct Maintenance {
+    /// Message shown to requesters
+    pub message: String,
+    /// Expected end (unix seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta: Option<i64>,
+    /// When maintenance started (unix seconds)
+    pub since: i64,
+}
+
+/// On-chain outcome of a drip's transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimStatus {
+    /// Not yet mined
+    Pending,
+    Confirmed,
+    /// Reverted, or dropped from the mempool
+    Failed,
+}
+
+impl ClaimStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ClaimStatus::Pending => "pending",
+            ClaimStatus::Confirmed => "confirmed",
+            ClaimStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(status: Option<String>) -> Self {
+        match status.as_deref() {
+            Some("confirmed") => ClaimStatus::Confirmed,
+            Some("failed") => ClaimStatus::Failed,
+            _ => ClaimStatus::Pending,
+        }
+    }
+}
+
+/// Confirmation state of a claim
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimEvent {
+    pub claim_id: String,
+    pub tx_hash: String,
+    /// Explorer link of `tx_hash`, when `EXPLORER_TX_URL` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    pub status: ClaimStatus,
+    /// Block the transaction was mined in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+}
+
+/// Progress of a first-drip badge mint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BadgeStatus {
+    /// Waiting for the `mint_badges` job
+    Queued,
+    /// Mint transaction sent
+    Minted,
+    /// Given up after `BADGE_MAX_ATTEMPTS` failed sends
+    Failed,
+}
+
+impl BadgeStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            BadgeStatus::Queued => "queued",
+            BadgeStatus::Minted => "minted",
+            BadgeStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(status: &str) -> Self {
+        match status {
+            "minted" => BadgeStatus::Minted,
+            "failed" => BadgeStatus::Failed,
+            _ => BadgeStatus::Queued,
+        }
+    }
+}
+
+/// Badge of an address
+#[derive(Debug, Clone, Serialize)]
+pub struct Badge {
+    pub address: String,
+    pub status: BadgeStatus,
+    /// Mint transaction, once sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// Explorer link of `tx_hash`, when `EXPLORER_TX_URL` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    /// Unix time the badge was queued
+    pub queued: i64,
+}
+
+/// Label an operator attached to an address
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressLabel {
+    pub address: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub added_by: String,
+    /// When the label was attached (unix seconds)
+    pub added: i64,
+}
+
+/// SQLite-backed record of transfers made by or to the faucet
+pub struct Ledger {
+    conn: Connection,
+}
+
+impl From<rusqlite::Error> for FaucetError {
+    fn from(err: rusqlite::Error) -> Self {
+        FaucetError::Service(axionax_errors::Error::storage(err.to_string()))
+    }
+}
+
+/// SQLite connection settings, from `SQLITE_*` variables
+#[derive(Debug, Clone)]
+struct StorageOptions {
+    /// How long a write waits for another connection's lock
+    busy_timeout: Duration,
+    journal_mode: String,
+    synchronous: String,
+    /// Page cache size, when overridden
+    cache_mib: Option<u32>,
+}
+
+impl StorageOptions {
+    fn from_env() -> anyhow::Result<Self> {
+        let busy_timeout = match std::env::var("SQLITE_BUSY_TIMEOUT_MS") {
+            Ok(ms) => Duration::from_millis(
+                ms.parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid SQLITE_BUSY_TIMEOUT_MS"))?,
+            ),
+            Err(_) => Duration::from_secs(5),
+        };
+        let choice = |var: &str, default: &str, allowed: &[&str]| -> anyhow::Result<String> {
+            let value = std::env::var(var)
+                .unwrap_or_else(|_| default.to_string())
+                .to_lowercase();
+            if !allowed.contains(&value.as_str()) {
+                anyhow::bail!("{} must be one of {}", var, allowed.join(", "));
+            }
+            Ok(value)
+        };
+        let cache_mib = match std::env::var("SQLITE_CACHE_MB") {
+            Ok(mib) => Some(
+                mib.parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid SQLITE_CACHE_MB"))?,
+            ),
+            Err(_) => None,
+        };
+        Ok(Self {
+            busy_timeout,
+            journal_mode: choice("SQLITE_JOURNAL_MODE", "wal", &["wal", "delete", "truncate"])?,
+            synchronous: choice("SQLITE_SYNCHRONOUS", "normal", &["off", "normal", "full"])?,
+            cache_mib,
+        })
+    }
+
+    fn apply(&self, conn: &Connection) -> anyhow::Result<()> {
+        conn.busy_timeout(self.busy_timeout)?;
+        let mode: String =
+            conn.pragma_update_and_check(None, "journal_mode", &self.journal_mode, |row| {
+                row.get(0)
+            })?;
+        if mode != self.journal_mode {
+            warn!(
+                "SQLite kept journal mode {} instead of {}",
+                mode, self.journal_mode
+            );
+        }
+        conn.pragma_update(None, "synchronous", &self.synchronous)?;
+        if let Some(mib) = self.cache_mib {
+            // Negative sizes are in KiB rather than pages
+            conn.pragma_update(None, "cache_size", -(i64::from(mib) * 1024))?;
+        }
+        Ok(())
+    }
+}
+
+impl Ledger {
+    /// Open (or create) the ledger database at `path`
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let mut conn = crate::db::open(path)?;
+        StorageOptions::from_env()?.apply(&conn)?;
+        for migration in crate::db::migrate(&mut conn)? {
+            info!("   Applied migration {}", migration);
+        }
+        Ok(Self { conn })
+    }
+
+    /// Fail writes at the rate `chaos` asks for
+    pub fn inject_faults(&self, chaos: &Chaos) {
+        chaos.storage(&self.conn);
+    }
+
+    /// Record a transfer, attributed to the referring partner if any
+    pub fn record(
+        &self,
+        kind: EntryKind,
+        address: &str,
+        amount: U256,
+        tx_hash: &str,
+        partner: Option<&str>,
+    ) -> Result<(), FaucetError> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO transfers (kind, address, amount, tx_hash, timestamp, partner)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                kind.as_str(),
+                address,
+                amount.to_string(),
+                tx_hash,
+                to_unix(SystemTime::now()),
+                partner
+            ],
+        )?;
+        // A total that isn't kept yet is summed on its first read instead
+        let total: Option<String> = tx
+            .query_row(
+                "SELECT amount FROM transfer_totals WHERE kind = ?1",
+                params![kind.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(total) = total {
+            let total = U256::from_dec_str(&total)
+                .unwrap_or_default()
+                .saturating_add(amount);
+            tx.execute(
+                "UPDATE transfer_totals SET amount = ?2 WHERE kind = ?1",
+                params![kind.as_str(), total.to_string()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record the outcome of a `/request` call
+    pub fn record_request(
+        &self,
+        address: &str,
+        ip: &str,
+        geo: &GeoInfo,
+        partner: Option<&str>,
+        rejection: Option<&str>,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT INTO requests
+             (address, ip, granted, reason, timestamp, country, asn, as_org, partner)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                address,
+                ip,
+                rejection.is_none(),
+                rejection,
+                to_unix(SystemTime::now()),
+                geo.country,
+                geo.asn,
+                geo.as_org,
+                partner
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Store a signed drip receipt
+    pub fn record_receipt(&self, signed: &SignedReceipt) -> Result<(), FaucetError> {
+        let receipt = &signed.receipt;
+        self.conn.execute(
+            "INSERT INTO receipts (claim_id, address, amount, tx_hash, timestamp, chain_id, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                receipt.claim_id,
+                receipt.recipient,
+                receipt.amount,
+                receipt.tx_hash,
+                receipt.timestamp,
+                signed.chain_id,
+                signed.signature
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Look up a drip receipt by claim id
+    pub fn receipt(&self, claim_id: &str) -> Result<Option<SignedReceipt>, FaucetError> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT claim_id, address, amount, tx_hash, timestamp, chain_id, signature
+                 FROM receipts WHERE claim_id = ?1",
+                params![claim_id],
+                |row| {
+                    let receipt = Receipt {
+                        claim_id: row.get(0)?,
+                        recipient: row.get(1)?,
+                        amount: row.get(2)?,
+                        tx_hash: row.get(3)?,
+                        timestamp: row.get(4)?,
+                    };
+                    Ok((receipt, row.get::<_, u64>(5)?, row.get::<_, String>(6)?))
+                },
+            )
+            .optional()?;
+
+        row.map(|(receipt, chain_id, signature)| {
+            let signer = receipt
+                .recover_signer(chain_id, &signature)
+                .map_err(|e| axionax_errors::Error::signing(e.to_string()))?;
+            Ok(SignedReceipt {
+                receipt,
+                chain_id,
+                signer,
+                signature,
+            })
+        })
+        .transpose()
+    }
+
+    /// Confirmation state of a claim
+    pub fn claim_event(&self, claim_id: &str) -> Result<Option<ClaimEvent>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT r.claim_id, r.tx_hash, s.status, s.block
+                 FROM receipts r LEFT JOIN claim_status s ON s.claim_id = r.claim_id
+                 WHERE r.claim_id = ?1",
+                params![claim_id],
+                |row| {
+                    let tx_hash: String = row.get(1)?;
+                    Ok(ClaimEvent {
+                        claim_id: row.get(0)?,
+                        explorer_url: branding::tx_url(&tx_hash),
+                        tx_hash,
+                        status: ClaimStatus::parse(row.get(2)?),
+                        block_number: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Claims issued at or after `since` whose transaction hasn't been resolved,
+    /// as `(claim_id, tx_hash, timestamp)`
+    pub fn pending_claims(
+        &self,
+        since: SystemTime,
+    ) -> Result<Vec<(String, String, SystemTime)>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.claim_id, r.tx_hash, r.timestamp
+             FROM receipts r LEFT JOIN claim_status s ON s.claim_id = r.claim_id
+             WHERE s.claim_id IS NULL AND r.timestamp >= ?1 ORDER BY r.timestamp",
+        )?;
+        let claims = stmt
+            .query_map(params![to_unix(since)], |row| {
+                Ok((row.get(0)?, row.get(1)?, from_unix(row.get(2)?)))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(claims)
+    }
+
+    /// Record the outcome of a claim's transaction
+    pub fn set_claim_status(
+        &self,
+        claim_id: &str,
+        status: ClaimStatus,
+        block: Option<u64>,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO claim_status (claim_id, status, block, updated)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![claim_id, status.as_str(), block, to_unix(SystemTime::now())],
+        )?;
+        Ok(())
+    }
+
+    /// Start tracking a recipient's activity at its current nonce, unless already tracked
+    pub fn track_recipient(&self, address: &str, nonce: u64) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO recipient_activity
+             (address, first_drip, nonce_at_drip, scanned_nonce) VALUES (?1, ?2, ?3, ?3)",
+            params![address, to_unix(SystemTime::now()), nonce],
+        )?;
+        Ok(())
+    }
+
+    /// Recipients first dripped at or after `since`
+    pub fn recipient_activity(
+        &self,
+        since: SystemTime,
+    ) -> Result<Vec<RecipientActivity>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT address, first_drip, nonce_at_drip, scanned_nonce, transactions, contracts
+             FROM recipient_activity WHERE first_drip >= ?1",
+        )?;
+        let activity = stmt
+            .query_map(params![to_unix(since)], row_to_activity)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(activity)
+    }
+
+    /// Store refreshed activity counters for a recipient
+    pub fn update_recipient_activity(
+        &self,
+        activity: &RecipientActivity,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE recipient_activity SET scanned_nonce = ?2, transactions = ?3, contracts = ?4
+             WHERE address = ?1",
+            params![
+                activity.address,
+                activity.scanned_nonce,
+                activity.transactions,
+                activity.contracts
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Most active recipients first dripped at or after `since`
+    pub fn leaderboard(
+        &self,
+        ranking: Ranking,
+        since: SystemTime,
+        limit: u32,
+    ) -> Result<Vec<RecipientActivity>, FaucetError> {
+        let order = match ranking {
+            Ranking::Contracts => "contracts DESC, transactions DESC",
+            Ranking::Transactions => "transactions DESC, contracts DESC",
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT address, first_drip, nonce_at_drip, scanned_nonce, transactions, contracts
+             FROM recipient_activity
+             WHERE first_drip >= ?1 AND (transactions > 0 OR contracts > 0)
+             ORDER BY {}, first_drip LIMIT ?2",
+            order
+        ))?;
+        let activity = stmt
+            .query_map(params![to_unix(since), limit], row_to_activity)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(activity)
+    }
+
+    /// Number of granted requests from a country since `since`
+    pub fn country_grants_since(
+        &self,
+        country: &str,
+        since: SystemTime,
+    ) -> Result<u64, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM requests WHERE granted = 1 AND country = ?1 AND timestamp >= ?2",
+            params![country, to_unix(since)],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Number of granted requests from an ASN since `since`
+    pub fn asn_grants_since(&self, asn: u32, since: SystemTime) -> Result<u64, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM requests WHERE granted = 1 AND asn = ?1 AND timestamp >= ?2",
+            params![asn, to_unix(since)],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Requests and grants per country since `since`, busiest first
+    pub fn country_aggregates(&self, since: SystemTime) -> Result<Vec<GeoAggregate>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT country, COUNT(*), SUM(granted) FROM requests
+             WHERE country IS NOT NULL AND timestamp >= ?1
+             GROUP BY country ORDER BY COUNT(*) DESC",
+        )?;
+        let aggregates = stmt
+            .query_map(params![to_unix(since)], |row| {
+                Ok(GeoAggregate {
+                    country: row.get(0)?,
+                    asn: None,
+                    as_org: None,
+                    requests: row.get(1)?,
+                    grants: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(aggregates)
+    }
+
+    /// Requests and grants per ASN since `since`, busiest first
+    pub fn asn_aggregates(&self, since: SystemTime) -> Result<Vec<GeoAggregate>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT asn, MAX(as_org), COUNT(*), SUM(granted) FROM requests
+             WHERE asn IS NOT NULL AND timestamp >= ?1
+             GROUP BY asn ORDER BY COUNT(*) DESC",
+        )?;
+        let aggregates = stmt
+            .query_map(params![to_unix(since)], |row| {
+                Ok(GeoAggregate {
+                    country: None,
+                    asn: row.get(0)?,
+                    as_org: row.get(1)?,
+                    requests: row.get(2)?,
+                    grants: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(aggregates)
+    }
+
+    /// Amount (wei) dripped on behalf of a partner since `since`
+    pub fn partner_spent_since(
+        &self,
+        partner: &str,
+        since: SystemTime,
+    ) -> Result<U256, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT amount FROM transfers WHERE kind = ?1 AND partner = ?2 AND timestamp >= ?3",
+        )?;
+        let amounts = stmt
+            .query_map(
+                params![EntryKind::Drip.as_str(), partner, to_unix(since)],
+                |row| row.get::<_, String>(0),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(amounts
+            .iter()
+            .map(|a| U256::from_dec_str(a).unwrap_or_default())
+            .fold(U256::zero(), U256::saturating_add))
+    }
+
+    /// Requests, grants and amount dripped per partner since `since`, busiest first
+    pub fn partner_aggregates(
+        &self,
+        since: SystemTime,
+    ) -> Result<Vec<PartnerAggregate>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT partner, COUNT(*), SUM(granted) FROM requests
+             WHERE partner IS NOT NULL AND timestamp >= ?1
+             GROUP BY partner ORDER BY COUNT(*) DESC",
+        )?;
+        let counts = stmt
+            .query_map(params![to_unix(since)], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        counts
+            .into_iter()
+            .map(|(partner, requests, grants)| {
+                Ok(PartnerAggregate {
+                    distributed: self.partner_spent_since(&partner, since)?,
+                    partner,
+                    requests,
+                    grants,
+                })
+            })
+            .collect()
+    }
+
+    /// Number of entries of the given kind
+    pub fn count(&self, kind: EntryKind) -> Result<usize, FaucetError> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM transfers WHERE kind = ?1",
+            params![kind.as_str()],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Total amount (wei) of entries of the given kind
+    ///
+    /// Kept as a running total by [`Ledger::record`]; the transfers are only
+    /// summed on the first read after a migration or restore.
+    pub fn total(&self, kind: EntryKind) -> Result<U256, FaucetError> {
+        let total: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT amount FROM transfer_totals WHERE kind = ?1",
+                params![kind.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(total) = total {
+            return Ok(U256::from_dec_str(&total).unwrap_or_default());
+        }
+
+        let total = self
+            .conn
+            .prepare("SELECT amount FROM transfers WHERE kind = ?1")?
+            .query_map(params![kind.as_str()], |row| row.get::<_, String>(0))?
+            .try_fold(U256::zero(), |sum, amount| {
+                Ok::<_, rusqlite::Error>(
+                    sum.saturating_add(U256::from_dec_str(&amount?).unwrap_or_default()),
+                )
+            })?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO transfer_totals (kind, amount) VALUES (?1, ?2)",
+            params![kind.as_str(), total.to_string()],
+        )?;
+        Ok(total)
+    }
+
+    /// Most recent entry of the given kind
+    pub fn last(&self, kind: EntryKind) -> Result<Option<LedgerEntry>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT kind, address, amount, tx_hash, timestamp FROM transfers
+                 WHERE kind = ?1 ORDER BY id DESC LIMIT 1",
+                params![kind.as_str()],
+                |row| row_to_entry(kind, row),
+            )
+            .optional()?)
+    }
+
+    /// Drips previously sent to an address
+    pub fn drips_to(&self, address: &str) -> Result<Vec<LedgerEntry>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, address, amount, tx_hash, timestamp FROM transfers
+             WHERE kind = ?1 AND address = ?2 ORDER BY id",
+        )?;
+        let entries = stmt
+            .query_map(params![EntryKind::Drip.as_str(), address], |row| {
+                row_to_entry(EntryKind::Drip, row)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Request and grant counts per `width`-second bucket from `first` (unix
+    /// seconds) on; buckets without requests are left out
+    pub fn request_buckets(
+        &self,
+        first: i64,
+        width: i64,
+    ) -> Result<Vec<RequestBucket>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT (timestamp - ?1) / ?2, COUNT(*), SUM(granted) FROM requests
+             WHERE timestamp >= ?1 GROUP BY 1 ORDER BY 1",
+        )?;
+        let buckets = stmt
+            .query_map(params![first, width], |row| {
+                Ok(RequestBucket {
+                    start: first + row.get::<_, i64>(0)? * width,
+                    requests: row.get(1)?,
+                    grants: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(buckets)
+    }
+
+    /// Total amount (wei) of entries of the given kind per `width`-second
+    /// bucket from `first` (unix seconds) on, keyed by bucket start
+    ///
+    /// Amounts are too wide for SQLite integers, so rows are grouped by
+    /// bucket and amount, and only the few distinct amounts are added here.
+    pub fn transfer_buckets(
+        &self,
+        kind: EntryKind,
+        first: i64,
+        width: i64,
+    ) -> Result<BTreeMap<i64, U256>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT (timestamp - ?1) / ?2, amount, COUNT(*) FROM transfers
+             WHERE kind = ?3 AND timestamp >= ?1 GROUP BY 1, 2",
+        )?;
+        let mut buckets = BTreeMap::new();
+        let rows = stmt.query_map(params![first, width, kind.as_str()], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u64>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (index, amount, count) = row?;
+            let amount = U256::from_dec_str(&amount)
+                .unwrap_or_default()
+                .saturating_mul(count.into());
+            let total: &mut U256 = buckets.entry(first + index * width).or_default();
+            *total = total.saturating_add(amount);
+        }
+        Ok(buckets)
+    }
+
+    /// Granted requests recorded at or after `since`, as `(address, ip, timestamp)`
+    pub fn grants_since(
+        &self,
+        since: SystemTime,
+    ) -> Result<Vec<(String, String, SystemTime)>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT address, ip, timestamp FROM requests
+             WHERE granted = 1 AND timestamp >= ?1 ORDER BY id",
+        )?;
+        let grants = stmt
+            .query_map(params![to_unix(since)], |row| {
+                Ok((row.get(0)?, row.get(1)?, from_unix(row.get(2)?)))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(grants)
+    }
+
+    /// Delete request outcomes recorded before `before`; transfers are kept
+    pub fn prune_requests(&self, before: SystemTime) -> Result<usize, FaucetError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM requests WHERE timestamp < ?1",
+            params![to_unix(before)],
+        )?;
+        Ok(deleted)
+    }
+
+    /// Reclaim free pages and refresh query planner statistics
+    pub fn compact(&self) -> Result<(), FaucetError> {
+        self.conn.execute_batch("VACUUM; PRAGMA optimize;")?;
+        Ok(())
+    }
+
+    /// Refresh query planner statistics of tables that changed, and fold the
+    /// write-ahead log back into the database
+    pub fn optimize(&self) -> Result<(), FaucetError> {
+        self.conn
+            .execute_batch("PRAGMA optimize; PRAGMA wal_checkpoint(TRUNCATE);")?;
+        Ok(())
+    }
+
+    /// Take or renew the sender lease for `holder` until `expires`, returning
+    /// whether `holder` now holds it
+    pub fn acquire_lease(&self, holder: &str, expires: SystemTime) -> Result<bool, FaucetError> {
+        let changed = self.conn.execute(
+            "INSERT INTO leader_lease (id, holder, expires) VALUES (1, ?1, ?2)
+             ON CONFLICT (id) DO UPDATE SET holder = excluded.holder, expires = excluded.expires
+             WHERE leader_lease.holder = excluded.holder OR leader_lease.expires < ?3",
+            params![holder, to_unix(expires), to_unix(SystemTime::now())],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Give up the sender lease if `holder` has it
+    pub fn release_lease(&self, holder: &str) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "DELETE FROM leader_lease WHERE holder = ?1",
+            params![holder],
+        )?;
+        Ok(())
+    }
+
+    /// Current holder of an unexpired sender lease
+    pub fn lease_holder(&self) -> Result<Option<String>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT holder FROM leader_lease WHERE expires >= ?1",
+                params![to_unix(SystemTime::now())],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Link `address` to another address or a session node as the same user
+    pub fn link_address(&self, address: &str, linked: &str, kind: &str) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO address_links (address, linked, kind, created)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![address, linked, kind, to_unix(SystemTime::now())],
+        )?;
+        Ok(())
+    }
+
+    /// Nodes transitively linked to `address`, excluding `address` itself
+    ///
+    /// Nodes linked to more than `max_fanout` addresses, such as exchange
+    /// wallets or a shared session, are included but not expanded.
+    pub fn linked_nodes(
+        &self,
+        address: &str,
+        max_fanout: u32,
+        limit: u32,
+    ) -> Result<Vec<String>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE
+               hubs(node) AS (
+                 SELECT linked FROM address_links GROUP BY linked HAVING COUNT(*) > ?2
+               ),
+               cluster(node) AS (
+                 SELECT ?1
+                 UNION
+                 SELECT l.linked FROM address_links l JOIN cluster c ON l.address = c.node
+                 UNION
+                 SELECT l.address FROM address_links l JOIN cluster c ON l.linked = c.node
+                  WHERE c.node NOT IN hubs
+               )
+             SELECT node FROM cluster WHERE node != ?1 LIMIT ?3",
+        )?;
+        let nodes = stmt
+            .query_map(params![address, max_fanout, limit], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(nodes)
+    }
+
+    /// Whether `address` has ever requested tokens or been dripped
+    pub fn is_known_address(&self, address: &str) -> Result<bool, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM requests WHERE address = ?1)
+                 OR EXISTS (SELECT 1 FROM transfers WHERE kind = 'drip' AND address = ?1)",
+            params![address],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Last block scanned for funding links
+    pub fn alias_scan_block(&self) -> Result<Option<u64>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row("SELECT block FROM alias_scan WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    /// Record the last block scanned for funding links
+    pub fn set_alias_scan_block(&self, block: u64) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT INTO alias_scan (id, block) VALUES (1, ?1)
+             ON CONFLICT (id) DO UPDATE SET block = excluded.block",
+            params![block],
+        )?;
+        Ok(())
+    }
+
+    /// Current maintenance window, if drips are paused
+    pub fn maintenance(&self) -> Result<Option<Maintenance>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT message, eta, since FROM maintenance WHERE id = 1",
+                [],
+                |row| {
+                    Ok(Maintenance {
+                        message: row.get(0)?,
+                        eta: row.get(1)?,
+                        since: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Start or update a maintenance window, or end it with `None`
+    pub fn set_maintenance(&self, maintenance: Option<&Maintenance>) -> Result<(), FaucetError> {
+        match maintenance {
+            Some(m) => self.conn.execute(
+                "INSERT INTO maintenance (id, message, eta, since) VALUES (1, ?1, ?2, ?3)
+                 ON CONFLICT (id) DO UPDATE SET message = excluded.message, eta = excluded.eta",
+                params![m.message, m.eta, m.since],
+            )?,
+            None => self.conn.execute("DELETE FROM maintenance", [])?,
+        };
+        Ok(())
+    }
+
+    /// Feature flag rollouts set through the admin API
+    pub fn flag_overrides(&self) -> Result<BTreeMap<String, Rollout>, FaucetError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name, percent, partners FROM feature_flags")?;
+        let rows = stmt.query_map([], |row| {
+            let partners: String = row.get(2)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                Rollout {
+                    percent: row.get(1)?,
+                    partners: partners
+                        .split(',')
+                        .filter(|p| !p.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Override a flag's rollout, or remove the override with `None`
+    pub fn set_flag_override(
+        &self,
+        name: &str,
+        rollout: Option<&Rollout>,
+        actor: &str,
+    ) -> Result<(), FaucetError> {
+        match rollout {
+            Some(r) => self.conn.execute(
+                "INSERT INTO feature_flags (name, percent, partners, updated_by, updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (name) DO UPDATE SET percent = excluded.percent,
+                   partners = excluded.partners, updated_by = excluded.updated_by,
+                   updated = excluded.updated",
+                params![
+                    name,
+                    r.percent,
+                    r.partners.iter().cloned().collect::<Vec<_>>().join(","),
+                    actor,
+                    to_unix(SystemTime::now())
+                ],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM feature_flags WHERE name = ?1", params![name])?,
+        };
+        Ok(())
+    }
+
+    /// Queue a badge for `address` if it has exactly one recorded drip
+    ///
+    /// Returns whether a badge was queued.
+    pub fn queue_badge(&self, address: &str) -> Result<bool, FaucetError> {
+        let now = to_unix(SystemTime::now());
+        let queued = self.conn.execute(
+            "INSERT OR IGNORE INTO badges (address, status, queued, updated)
+             SELECT ?1, ?2, ?3, ?3
+             WHERE (SELECT COUNT(*) FROM transfers WHERE kind = 'drip' AND address = ?1) = 1",
+            params![address, BadgeStatus::Queued.as_str(), now],
+        )?;
+        Ok(queued > 0)
+    }
+
+    /// Oldest queued badges, at most `limit`
+    pub fn queued_badges(&self, limit: usize) -> Result<Vec<String>, FaucetError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT address FROM badges WHERE status = ?1 ORDER BY queued LIMIT ?2")?;
+        let addresses = stmt
+            .query_map(params![BadgeStatus::Queued.as_str(), limit as i64], |row| {
+                row.get(0)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(addresses)
+    }
+
+    /// Record a mint attempt; the badge fails once `max_attempts` sends failed
+    pub fn record_badge_mint(
+        &self,
+        address: &str,
+        outcome: Result<&str, &str>,
+        max_attempts: u32,
+    ) -> Result<BadgeStatus, FaucetError> {
+        let now = to_unix(SystemTime::now());
+        match outcome {
+            Ok(tx_hash) => {
+                self.conn.execute(
+                    "UPDATE badges SET status = ?2, attempts = attempts + 1,
+                       tx_hash = ?3, error = NULL, updated = ?4 WHERE address = ?1",
+                    params![address, BadgeStatus::Minted.as_str(), tx_hash, now],
+                )?;
+            }
+            Err(error) => {
+                self.conn.execute(
+                    "UPDATE badges SET attempts = attempts + 1, error = ?2, updated = ?3,
+                       status = CASE WHEN attempts + 1 >= ?4 THEN ?5 ELSE status END
+                     WHERE address = ?1",
+                    params![
+                        address,
+                        error,
+                        now,
+                        max_attempts,
+                        BadgeStatus::Failed.as_str()
+                    ],
+                )?;
+            }
+        }
+        let status: String = self.conn.query_row(
+            "SELECT status FROM badges WHERE address = ?1",
+            params![address],
+            |row| row.get(0),
+        )?;
+        Ok(BadgeStatus::parse(&status))
+    }
+
+    /// Badge of `address`, if one was queued
+    pub fn badge(&self, address: &str) -> Result<Option<Badge>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT address, status, tx_hash, queued FROM badges WHERE address = ?1",
+                params![address],
+                |row| {
+                    let status: String = row.get(1)?;
+                    let tx_hash: Option<String> = row.get(2)?;
+                    Ok(Badge {
+                        address: row.get(0)?,
+                        status: BadgeStatus::parse(&status),
+                        explorer_url: tx_hash.as_deref().and_then(branding::tx_url),
+                        tx_hash,
+                        queued: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Provider that verified `address` as a unique person, if any
+    pub fn identity(&self, address: &str) -> Result<Option<String>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT provider FROM identities WHERE address = ?1",
+                params![address],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Record that `subject` of `provider` verified `address`, unless it
+    /// verified another address before
+    ///
+    /// Returns the address the subject is bound to.
+    pub fn record_identity(
+        &self,
+        address: &str,
+        provider: &str,
+        subject: &str,
+    ) -> Result<String, FaucetError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO identities (address, provider, subject, verified)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![address, provider, subject, to_unix(SystemTime::now())],
+        )?;
+        Ok(self.conn.query_row(
+            "SELECT address FROM identities WHERE provider = ?1 AND subject = ?2",
+            params![provider, subject],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Labels of `address`, by name
+    pub fn labels(&self, address: &str) -> Result<Vec<AddressLabel>, FaucetError> {
+        self.query_labels("WHERE address = ?1 ORDER BY label", address)
+    }
+
+    /// Addresses carrying `label`, most recently labeled first
+    pub fn labeled(&self, label: &str) -> Result<Vec<AddressLabel>, FaucetError> {
+        self.query_labels("WHERE label = ?1 ORDER BY added DESC, address", label)
+    }
+
+    fn query_labels(&self, filter: &str, param: &str) -> Result<Vec<AddressLabel>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT address, label, note, added_by, added FROM address_labels {}",
+            filter
+        ))?;
+        let labels = stmt
+            .query_map(params![param], |row| {
+                Ok(AddressLabel {
+                    address: row.get(0)?,
+                    label: row.get(1)?,
+                    note: row.get(2)?,
+                    added_by: row.get(3)?,
+                    added: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(labels)
+    }
+
+    /// Attach `label` to `address`, replacing its note if already attached
+    pub fn set_label(
+        &self,
+        address: &str,
+        label: &str,
+        note: Option<&str>,
+        actor: &str,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT INTO address_labels (address, label, note, added_by, added)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (address, label) DO UPDATE SET note = excluded.note,
+               added_by = excluded.added_by, added = excluded.added",
+            params![address, label, note, actor, to_unix(SystemTime::now())],
+        )?;
+        Ok(())
+    }
+
+    /// Detach `label` from `address`; returns whether it was attached
+    pub fn remove_label(&self, address: &str, label: &str) -> Result<bool, FaucetError> {
+        let removed = self.conn.execute(
+            "DELETE FROM address_labels WHERE address = ?1 AND label = ?2",
+            params![address, label],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Append an admin action to the audit log
+    pub fn record_audit(
+        &self,
+        actor: &str,
+        action: &str,
+        payload: &serde_json::Value,
+        ip: Option<&str>,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT INTO admin_audit (timestamp, actor, action, payload, ip)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                to_unix(SystemTime::now()),
+                actor,
+                action,
+                payload.to_string(),
+                ip
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Audit log entries matching the filters, newest first
+    pub fn audit_log(
+        &self,
+        actor: Option<&str>,
+        action: Option<&str>,
+        since: Option<SystemTime>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, actor, action, payload, ip FROM admin_audit
+             WHERE (?1 IS NULL OR actor = ?1) AND (?2 IS NULL OR action = ?2)
+               AND timestamp >= ?3
+             ORDER BY id DESC LIMIT ?4",
+        )?;
+        let entries = stmt
+            .query_map(
+                params![actor, action, since.map_or(0, to_unix), limit as i64],
+                |row| {
+                    let payload: String = row.get(4)?;
+                    Ok(AuditEntry {
+                        id: row.get(0)?,
+                        timestamp: row.get(1)?,
+                        actor: row.get(2)?,
+                        action: row.get(3)?,
+                        payload: serde_json::from_str(&payload)
+                            .unwrap_or(serde_json::Value::String(payload)),
+                        ip: row.get(5)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+}
+
+fn row_to_entry(kind: EntryKind, row: &rusqlite::Row) -> rusqlite::Result<LedgerEntry> {
+    let amount: String = row.get(2)?;
+    Ok(LedgerEntry {
+        kind,
+        address: row.get(1)?,
+        amount: U256::from_dec_str(&amount).unwrap_or_default(),
+        tx_hash: row.get(3)?,
+        timestamp: from_unix(row.get(4)?),
+    })
+}
+
+fn row_to_activity(row: &rusqlite::Row) -> rusqlite::Result<RecipientActivity> {
+    Ok(RecipientActivity {
+        address: row.get(0)?,
+        first_drip: row.get(1)?,
+        nonce_at_drip: row.get(2)?,
+        scanned_nonce: row.get(3)?,
+        transactions: row.get(4)?,
+        contracts: row.get(5)?,
+    })
+}
+
+/// Seconds since the unix epoch
+pub fn to_unix(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs() as i64
+}
+
+/// Convert seconds since the unix epoch back into a `SystemTime`
+pub fn from_unix(secs: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A throwaway database file, removed with its WAL files on drop
+    struct TempDb(String);
+
+    impl TempDb {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("ledger-{}-{}.db", name, std::process::id()));
+            Self(path.to_string_lossy().into_owned())
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", self.0, suffix));
+            }
+        }
+    }
+
+    fn pragma<T: rusqlite::types::FromSql>(conn: &Connection, name: &str) -> T {
+        conn.pragma_query_value(None, name, |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn ledger_opens_with_the_default_pragmas() {
+        let db = TempDb::new("defaults");
+        let ledger = Ledger::open(&db.0).unwrap();
+        assert_eq!(pragma::<String>(&ledger.conn, "journal_mode"), "wal");
+        // 1 is NORMAL
+        assert_eq!(pragma::<i64>(&ledger.conn, "synchronous"), 1);
+        assert_eq!(pragma::<i64>(&ledger.conn, "busy_timeout"), 5000);
+    }
+
+    #[test]
+    fn storage_options_are_applied() {
+        let db = TempDb::new("options");
+        let conn = Connection::open(&db.0).unwrap();
+        let options = StorageOptions {
+            busy_timeout: Duration::from_millis(250),
+            journal_mode: "truncate".to_string(),
+            synchronous: "full".to_string(),
+            cache_mib: Some(8),
+        };
+        options.apply(&conn).unwrap();
+        assert_eq!(pragma::<String>(&conn, "journal_mode"), "truncate");
+        // 2 is FULL
+        assert_eq!(pragma::<i64>(&conn, "synchronous"), 2);
+        assert_eq!(pragma::<i64>(&conn, "busy_timeout"), 250);
+        assert_eq!(pragma::<i64>(&conn, "cache_size"), -8 * 1024);
+    }
+}
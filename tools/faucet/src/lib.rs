@@ -0,0 +1,4 @@
+//! Library surface of the faucet, shared with tests, fuzz targets and receipt verifiers
+
+pub mod receipt;
+pub mod validation;
@@ -0,0 +1,249 @@
+//! Where the HTTP server accepts connections
+//!
+//! The server listens on `--listen` (`LISTEN_ADDR`, default `0.0.0.0:3000`);
+//! `--port` (`PORT`) replaces just the port, as set by most container
+//! platforms. `--unix-socket` (`LISTEN_UNIX_SOCKET`) listens on a unix domain
+//! socket instead, for a reverse proxy on the same host, with
+//! `--socket-mode` (`LISTEN_SOCKET_MODE`, default `660`) permissions. Under
+//! systemd socket activation (`LISTEN_FDS`), the single socket systemd passes
+//! is used, TCP or unix, and the other options are ignored.
+//!
+//! Connections over a unix socket carry no client IP, so the client is the
+//! last `X-Forwarded-For` entry, i.e. the address the proxy saw. Clients
+//! connecting over TCP can't set their IP this way.
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    middleware::Next,
+    response::Response,
+    Router,
+};
+use clap::Args;
+use std::fmt;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tracing::{debug, warn};
+
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+/// Header a reverse proxy appends the client address to
+const FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Pause after a failed accept, e.g. when out of file descriptors
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Options choosing where the server listens
+#[derive(Debug, Args)]
+pub struct ListenArgs {
+    /// Address the HTTP server listens on
+    #[arg(long, env = "LISTEN_ADDR", default_value = "0.0.0.0:3000")]
+    listen: SocketAddr,
+
+    /// Port to listen on, replacing the port of --listen
+    #[arg(long, env = "PORT")]
+    port: Option<u16>,
+
+    /// Listen on this unix domain socket instead of TCP
+    #[arg(long, env = "LISTEN_UNIX_SOCKET")]
+    unix_socket: Option<PathBuf>,
+
+    /// Permissions of the unix socket, in octal
+    #[arg(long, env = "LISTEN_SOCKET_MODE", default_value = "660", value_parser = parse_mode)]
+    socket_mode: u32,
+}
+
+/// Parse octal permissions such as `660` or `0o600`
+pub fn parse_mode(mode: &str) -> Result<u32, String> {
+    u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+        .ok_or_else(|| format!("invalid permissions {:?}, expected octal like 660", mode))
+}
+
+/// A bound server socket
+pub enum Listener {
+    Tcp(TcpListener),
+    /// With the socket file to remove on shutdown, unless systemd owns it
+    #[cfg(unix)]
+    Unix(UnixListener, Option<PathBuf>),
+}
+
+impl ListenArgs {
+    /// Bind the configured socket, or take over the one systemd passed
+    pub async fn bind(&self) -> anyhow::Result<Listener> {
+        if let Some(listener) = activated()? {
+            return Ok(listener);
+        }
+        match &self.unix_socket {
+            #[cfg(unix)]
+            Some(path) => {
+                use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+                // A socket left behind by an unclean exit would fail the bind
+                if std::fs::metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+                    std::fs::remove_file(path)?;
+                }
+                let listener = UnixListener::bind(path)?;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(self.socket_mode))?;
+                Ok(Listener::Unix(listener, Some(path.clone())))
+            }
+            #[cfg(not(unix))]
+            Some(_) => anyhow::bail!("Unix sockets are not supported on this platform"),
+            None => {
+                let mut addr = self.listen;
+                if let Some(port) = self.port {
+                    addr.set_port(port);
+                }
+                Ok(Listener::Tcp(TcpListener::bind(addr).await?))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Listener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => write!(f, "{}", addr),
+                Err(_) => write!(f, "a TCP socket"),
+            },
+            #[cfg(unix)]
+            Listener::Unix(_, Some(path)) => write!(f, "unix:{}", path.display()),
+            #[cfg(unix)]
+            Listener::Unix(_, None) => write!(f, "a unix socket from systemd"),
+        }
+    }
+}
+
+impl Listener {
+    /// Serve `app` until `shutdown` resolves and open connections finish
+    pub async fn serve(
+        self,
+        app: Router,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> anyhow::Result<()> {
+        match self {
+            Listener::Tcp(listener) => {
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(shutdown)
+                .await?
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, path) => {
+                let app = app.layer(axum::middleware::from_fn(forwarded_peer));
+                serve_unix(listener, app, shutdown).await;
+                if let Some(path) = path {
+                    let _ = std::fs::remove_file(path);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accept HTTP/1 connections on a unix socket
+#[cfg(unix)]
+async fn serve_unix(
+    listener: UnixListener,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto::Builder;
+    use hyper_util::server::graceful::GracefulShutdown;
+    use hyper_util::service::TowerToHyperService;
+
+    let builder = Builder::new(TokioExecutor::new()).http1_only();
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Failed to accept a connection: {}", e);
+                    tokio::time::sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        // With upgrades, so WebSockets work through the socket too
+        let connection = builder.serve_connection_with_upgrades(
+            TokioIo::new(stream),
+            TowerToHyperService::new(app.clone()),
+        );
+        let connection = graceful.watch(connection.into_owned());
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                debug!("Connection closed with an error: {}", e);
+            }
+        });
+    }
+    graceful.shutdown().await;
+}
+
+/// Use the proxy's `X-Forwarded-For` as the peer of a unix socket request
+#[cfg(unix)]
+async fn forwarded_peer(mut request: Request, next: Next) -> Response {
+    let ip = request
+        .headers()
+        .get(FORWARDED_FOR)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::new(ip, 0)));
+    next.run(request).await
+}
+
+/// The socket passed by systemd socket activation, if any
+#[cfg(unix)]
+fn activated() -> anyhow::Result<Option<Listener>> {
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    /// First descriptor systemd passes sockets from
+    const LISTEN_FDS_START: i32 = 3;
+
+    let Ok(fds) = std::env::var("LISTEN_FDS") else {
+        return Ok(None);
+    };
+    let pid = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|p| p.parse().ok());
+    if pid != Some(std::process::id()) {
+        return Ok(None);
+    }
+    if fds != "1" {
+        anyhow::bail!("Socket activation passed {} sockets, expected 1", fds);
+    }
+    // The variables are left set: changing the environment isn't safe once
+    // the runtime's threads run, and child processes ignore them anyway since
+    // `LISTEN_PID` names this process
+
+    // SAFETY: systemd hands the process this descriptor, and nothing else owns it
+    let fd = unsafe { OwnedFd::from_raw_fd(LISTEN_FDS_START) };
+    let tcp = std::net::TcpListener::from(fd);
+    // A unix socket has no IP address
+    if tcp.local_addr().is_ok() {
+        tcp.set_nonblocking(true)?;
+        return Ok(Some(Listener::Tcp(TcpListener::from_std(tcp)?)));
+    }
+    let unix = std::os::unix::net::UnixListener::from(OwnedFd::from(tcp));
+    unix.set_nonblocking(true)?;
+    Ok(Some(Listener::Unix(UnixListener::from_std(unix)?, None)))
+}
+
+#[cfg(not(unix))]
+fn activated() -> anyhow::Result<Option<Listener>> {
+    Ok(None)
+}
@@ -0,0 +1,109 @@
+//! Log output format and file rotation
+
+use clap::{Args, ValueEnum};
+use file_rotate::{
+    compression::Compression,
+    suffix::{AppendCount, AppendTimestamp, FileLimit},
+    ContentLimit, FileRotate, TimeFrequency,
+};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::EnvFilter;
+
+/// Log filter used when `RUST_LOG` is unset
+const DEFAULT_FILTER: &str = "info,refinery_core=warn";
+
+/// Log line format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable, colored output
+    Pretty,
+    /// One JSON object per line
+    Json,
+}
+
+/// When to rotate the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogRotation {
+    Never,
+    Hourly,
+    Daily,
+    /// Rotate once the file reaches `--log-max-size-mb`
+    Size,
+}
+
+/// Logging options
+#[derive(Debug, Clone, Args)]
+pub struct LogArgs {
+    /// Log line format
+    #[arg(long, env = "LOG_FORMAT", value_enum, default_value_t = LogFormat::Pretty)]
+    pub log_format: LogFormat,
+
+    /// Write logs to this file instead of stdout
+    #[arg(long, env = "LOG_FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// Log file rotation policy
+    #[arg(long, env = "LOG_ROTATION", value_enum, default_value_t = LogRotation::Daily)]
+    pub log_rotation: LogRotation,
+
+    /// Maximum log file size before rotating (with `--log-rotation size`)
+    #[arg(long, env = "LOG_MAX_SIZE_MB", default_value_t = 100)]
+    pub log_max_size_mb: usize,
+
+    /// Number of rotated log files to keep
+    #[arg(long, env = "LOG_MAX_FILES", default_value_t = 7)]
+    pub log_max_files: usize,
+}
+
+/// Install the global tracing subscriber
+///
+/// The returned guard flushes buffered file output and must be kept alive.
+pub fn init(args: &LogArgs) -> Option<WorkerGuard> {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let Some(path) = args.log_file.as_ref() else {
+        match args.log_format {
+            LogFormat::Pretty => builder.init(),
+            LogFormat::Json => builder.json().with_current_span(true).init(),
+        }
+        return None;
+    };
+
+    let writer: Box<dyn Write + Send> = match args.log_rotation {
+        LogRotation::Size => Box::new(FileRotate::new(
+            path,
+            AppendCount::new(args.log_max_files),
+            ContentLimit::BytesSurpassed(args.log_max_size_mb * 1024 * 1024),
+            Compression::None,
+            #[cfg(unix)]
+            None,
+        )),
+        rotation => {
+            let limit = match rotation {
+                LogRotation::Hourly => ContentLimit::Time(TimeFrequency::Hourly),
+                LogRotation::Daily => ContentLimit::Time(TimeFrequency::Daily),
+                _ => ContentLimit::None,
+            };
+            Box::new(FileRotate::new(
+                path,
+                AppendTimestamp::default(FileLimit::MaxFiles(args.log_max_files)),
+                limit,
+                Compression::None,
+                #[cfg(unix)]
+                None,
+            ))
+        }
+    };
+    let (writer, guard) = tracing_appender::non_blocking(writer);
+    let builder = builder.with_writer(writer).with_ansi(false);
+
+    match args.log_format {
+        LogFormat::Pretty => builder.init(),
+        LogFormat::Json => builder.json().with_current_span(true).init(),
+    }
+    Some(guard)
+}
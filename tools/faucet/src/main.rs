@@ -1,37 +1,203 @@
+mod admin;
+mod alerts;
+mod aliases;
+mod analytics;
+mod api;
+mod backup;
+mod badges;
+mod branding;
+mod bundle;
+mod challenge;
+mod chaos;
+mod claims;
+mod client;
+mod client_ip;
+mod cluster;
+mod compat;
+mod db;
+mod error_report;
+mod export;
+mod flags;
+mod geoip;
+mod identity;
+mod labels;
+mod leaderboard;
+mod ledger;
+mod listen;
+mod logging;
+mod metrics;
+mod mock_chain;
+mod names;
+mod policy;
+mod rate_limit;
+mod referral;
+mod reputation;
+mod rotate;
+mod rpc;
+mod scheduler;
+mod script;
+mod shedding;
+mod tasks;
+mod treasury;
+mod wallet;
+mod warmup;
+mod why;
+
+use axionax_notify::{Event, Notifications, Severity};
+use axionax_tx::amount::{self, Unit};
+use axionax_tx::{Transaction, U256};
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::{get, post},
+    body::Bytes,
+    extract::{ConnectInfo, Json, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{self, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
     Router,
 };
+use clap::{Parser, Subcommand};
+use futures_util::stream::{self, Stream, StreamExt};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
-use tokio::sync::RwLock;
-use tower_http::cors::{Any, CorsLayer};
+use tokio::sync::{broadcast, watch, Mutex, RwLock};
 use tracing::{error, info, warn};
 
+use aliases::Aliases;
+use axionax_errors::Kind;
+use axionax_faucet::receipt::{self, Receipt, SignedReceipt};
+use axionax_faucet::validation;
+use badges::Badges;
+use bundle::RuntimeConfig;
+use challenge::{ChallengeKind, Challenges, PowSolution};
+use cluster::Cluster;
+use geoip::{GeoInfo, GeoIp};
+use identity::IdentityProof;
+use ledger::{ClaimEvent, EntryKind, Ledger};
+use metrics::Metrics;
+use mock_chain::MockChain;
+use names::NameResolver;
+use policy::Policy;
+use rate_limit::{Gcra, RouteLimits};
+use referral::Partners;
+use rpc::RpcClient;
+use shedding::LoadShedder;
+use tasks::{TaskStatus, Tasks};
+use treasury::TreasuryConfig;
+
 // Configuration
-const FAUCET_AMOUNT: u64 = 100_000_000_000_000_000_000; // 100 AXX (in wei)
+const FAUCET_AMOUNT_AXX: u64 = 100; // per drip
 const COOLDOWN_HOURS: u64 = 24;
-const MAX_REQUESTS_PER_IP: usize = 3;
+
+/// Well-known development key used by `--dry-run` when FAUCET_PRIVATE_KEY is unset
+const DRY_RUN_PRIVATE_KEY: &str =
+    "0x0101010101010101010101010101010101010101010101010101010101010101";
+
+/// Whole AXX in wei
+fn axx_to_wei(axx: u64) -> U256 {
+    U256::from(axx) * U256::exp10(Unit::Axx.decimals())
+}
+
+/// Drip size in wei, unless a config bundle sets another
+fn faucet_amount() -> U256 {
+    axx_to_wei(FAUCET_AMOUNT_AXX)
+}
+
+/// Display wei as AXX, e.g. `1.5 AXX`, under the deployment's token symbol
+fn axx(wei: U256) -> String {
+    let formatted = amount::format(wei, Unit::Axx);
+    let number = formatted
+        .strip_suffix(Unit::Axx.symbol())
+        .unwrap_or(&formatted);
+    format!("{}{}", number, branding::get().symbol)
+}
+
+/// Display wei as gwei, for gas prices
+fn gwei(wei: U256) -> String {
+    amount::format(wei, Unit::Gwei)
+}
+
+/// Last grant time per address
+type AddressRequests = HashMap<String, SystemTime>;
+
+/// Theoretical arrival time of the next grant per IP (or IPv6 prefix), see [`Gcra`]
+type IpRequests = HashMap<String, SystemTime>;
 
 /// Faucet state
 #[derive(Clone)]
 struct FaucetState {
     /// Map of address -> last request time
-    address_requests: Arc<RwLock<HashMap<String, SystemTime>>>,
-    /// Map of IP -> request count
-    ip_requests: Arc<RwLock<HashMap<String, Vec<SystemTime>>>>,
+    address_requests: Arc<RwLock<AddressRequests>>,
+    /// Map of IP (or IPv6 prefix) -> grant limiter state
+    ip_requests: Arc<RwLock<IpRequests>>,
+    /// Drip amount, IP limit and blocklist, replaceable by config bundles
+    config: Arc<RuntimeConfig>,
+    /// Request limits per route
+    route_limits: Arc<RouteLimits>,
+    /// Response shapes of other faucets, per API key
+    compat: Arc<compat::Compat>,
+    /// Prefix length used to group IPv6 clients for rate limiting
+    ipv6_prefix_len: u8,
+    /// Persistent ledger of requests, drips and treasury top-ups
+    ledger: Arc<Mutex<Ledger>>,
+    /// GeoIP lookup and country/ASN limits
+    geoip: Option<Arc<GeoIp>>,
+    /// Resolver for names like `alice.axx`
+    names: Option<Arc<NameResolver>>,
+    /// Partner dApps allowed to refer requests
+    partners: Option<Arc<Partners>>,
+    /// On-chain tasks required before a grant
+    tasks: Option<Arc<Tasks>>,
+    /// Badge minted to first-time recipients
+    badges: Option<Arc<Badges>>,
+    /// Address alias clustering settings
+    aliases: Arc<Aliases>,
+    /// Eligibility stages run for every request
+    policy: Arc<Policy>,
+    /// Serializes sends, and the checks of earlier grants right before them, so
+    /// concurrent requests can't race past the limits
+    grant_lock: Arc<Mutex<()>>,
+    /// Captcha and proof-of-work challenges
+    challenges: Arc<Challenges>,
+    /// Bearer tokens for the admin API (admin routes are disabled when empty)
+    admin_tokens: Arc<Vec<admin::AdminToken>>,
     /// Faucet wallet private key
     private_key: String,
-    /// RPC endpoint
-    rpc_url: String,
+    /// Faucet wallet address
+    address: String,
+    /// RPC client
+    rpc: RpcClient,
+    /// Faucet balance (wei) as last refreshed by the scheduler
+    balance: Arc<RwLock<Option<U256>>>,
     /// Chain ID
     chain_id: u64,
+    /// Whether the RPC endpoint last reported `chain_id`; drips pause while it doesn't
+    chain_verified: Arc<AtomicBool>,
+    /// Whether startup warmup has finished; `/readyz` waits for it
+    warm: Arc<AtomicBool>,
+    /// Per-route request metrics and SLOs
+    metrics: Arc<Metrics>,
+    /// Sender lease shared with other replicas, when clustered
+    cluster: Option<Arc<Cluster>>,
+    /// Confirmation events of claims, for WebSocket subscribers
+    claim_events: broadcast::Sender<ClaimEvent>,
+    /// Operator notification channels
+    notifications: Arc<Notifications>,
+    /// Unix time the claim confirmation loop last completed a cycle
+    worker_cycle: Arc<AtomicU64>,
+    /// Whether `/why/:address` is served without an admin token
+    why_public: bool,
+    /// Latest stats, for `/stats/stream` subscribers
+    stats_updates: watch::Sender<Option<StatsResponse>>,
+    /// RPC latency and `/request` load shedding
+    shedder: Arc<LoadShedder>,
 }
 
 /// Request model
@@ -40,6 +206,46 @@ struct FaucetRequest {
     address: String,
     #[serde(default)]
     captcha_token: Option<String>,
+    #[serde(default)]
+    pow: Option<PowSolution>,
+    /// Signed referral token from a partner dApp
+    #[serde(default)]
+    referral: Option<String>,
+    /// Send to the address even if it is a contract
+    #[serde(default)]
+    allow_contract: bool,
+    /// Client session fingerprint; addresses requested from one session share a cooldown
+    #[serde(default)]
+    fingerprint: Option<String>,
+    /// Proof of personhood for the `identity` stage
+    #[serde(default)]
+    identity: Option<IdentityProof>,
+}
+
+/// Per-request data shared by the grant checks
+struct RequestContext {
+    /// Normalized recipient address
+    address: String,
+    /// Name the address was resolved from, if any
+    name: Option<String>,
+    /// Normalized client IP
+    ip: IpAddr,
+    /// Key the client is rate limited under
+    rate_limit_key: String,
+    /// Country and ASN of the client
+    geo: GeoInfo,
+    /// Captcha response token
+    captcha_token: Option<String>,
+    /// Proof-of-work solution
+    pow: Option<PowSolution>,
+    /// Partner that referred the request, once its token is verified
+    partner: Option<String>,
+    /// Requester confirmed that a contract recipient is intended
+    allow_contract: bool,
+    /// Link graph node of the client session, if a fingerprint was sent
+    session: Option<String>,
+    /// Proof of personhood, if one was sent
+    identity: Option<IdentityProof>,
 }
 
 /// Response model
@@ -48,273 +254,1050 @@ struct FaucetResponse {
     success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     tx_hash: Option<String>,
+    /// Explorer link of `tx_hash`, when `EXPLORER_TX_URL` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explorer_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     amount: Option<String>,
+    /// Address a requested name resolved to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolved_address: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    /// Error class, which determines the HTTP status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<Kind>,
+    /// Machine-readable error code, e.g. `too_soon`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    challenge: Option<ChallengeKind>,
+    /// Signed receipt attesting a successful drip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    receipt: Option<SignedReceipt>,
+    /// Expected end of maintenance (unix seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eta: Option<i64>,
+    /// Every invalid field of a rejected request body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<Vec<validation::FieldError>>,
+    /// Where users can get help, on errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    support_url: Option<String>,
 }
 
 /// Stats response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct StatsResponse {
     total_requests: usize,
     total_distributed: String,
     faucet_balance: String,
     cooldown_hours: u64,
     amount_per_request: String,
+    total_top_ups: usize,
+    total_topped_up: String,
 }
 
 /// Error type
 #[derive(Debug)]
+#[allow(dead_code)]
 enum FaucetError {
     InvalidAddress,
+    InvalidPayload(String),
+    /// Well-formed JSON with missing, mistyped or invalid fields
+    InvalidFields(Vec<validation::FieldError>),
+    UnresolvedName(String),
+    InvalidReferral,
+    PartnerBudgetExhausted,
+    TaskIncomplete(String),
     TooSoon(Duration),
-    RateLimited,
+    RateLimited(Duration),
+    GeoLimited,
+    Blocked,
+    AlreadyFunded,
+    ContractRecipient,
+    Denied(String),
+    ChallengeRequired(ChallengeKind),
+    ChallengeFailed,
+    /// An identity proof was rejected by its provider
+    IdentityRejected(String),
+    Unauthorized,
+    NotFound,
     InsufficientFunds,
-    RpcError(String),
-    InternalError(String),
+    InsufficientGas,
+    ChainMismatch,
+    NotLeader,
+    Maintenance(ledger::Maintenance),
+    /// Shed while the RPC endpoint is slow; retry after the given time
+    Overloaded(Duration),
+    /// Failure of the faucet itself or a service it depends on
+    Service(axionax_errors::Error),
 }
 
-impl IntoResponse for FaucetError {
-    fn into_response(self) -> Response {
-        let (status, message) = match self {
-            FaucetError::InvalidAddress => {
-                (StatusCode::BAD_REQUEST, "Invalid Ethereum address".to_string())
+impl From<axionax_errors::Error> for FaucetError {
+    fn from(err: axionax_errors::Error) -> Self {
+        FaucetError::Service(err)
+    }
+}
+
+impl FaucetError {
+    /// Short machine-readable reason, recorded for rejected requests
+    fn reason(&self) -> &'static str {
+        match self {
+            FaucetError::InvalidAddress => "invalid_address",
+            FaucetError::InvalidPayload(_) => "invalid_payload",
+            FaucetError::InvalidFields(_) => "invalid_request",
+            FaucetError::UnresolvedName(_) => "unresolved_name",
+            FaucetError::InvalidReferral => "invalid_referral",
+            FaucetError::PartnerBudgetExhausted => "partner_budget_exhausted",
+            FaucetError::TaskIncomplete(_) => "task_incomplete",
+            FaucetError::TooSoon(_) => "too_soon",
+            FaucetError::RateLimited(_) => "rate_limited",
+            FaucetError::GeoLimited => "geo_limited",
+            FaucetError::Blocked => "blocked",
+            FaucetError::AlreadyFunded => "already_funded",
+            FaucetError::ContractRecipient => "contract_recipient",
+            FaucetError::Denied(_) => "denied",
+            FaucetError::ChallengeRequired(_) => "challenge_required",
+            FaucetError::ChallengeFailed => "challenge_failed",
+            FaucetError::IdentityRejected(_) => "identity_rejected",
+            FaucetError::Unauthorized => "unauthorized",
+            FaucetError::NotFound => "not_found",
+            FaucetError::InsufficientFunds => "insufficient_funds",
+            FaucetError::InsufficientGas => "insufficient_gas",
+            FaucetError::ChainMismatch => "chain_mismatch",
+            FaucetError::NotLeader => "not_leader",
+            FaucetError::Maintenance(_) => "maintenance",
+            FaucetError::Overloaded(_) => "overloaded",
+            FaucetError::Service(err) => err.code,
+        }
+    }
+
+    /// Error class, which determines the HTTP status
+    fn kind(&self) -> Kind {
+        match self {
+            FaucetError::InvalidAddress
+            | FaucetError::InvalidPayload(_)
+            | FaucetError::InvalidFields(_)
+            | FaucetError::UnresolvedName(_)
+            | FaucetError::AlreadyFunded
+            | FaucetError::ContractRecipient => Kind::Validation,
+            FaucetError::Unauthorized => Kind::Unauthorized,
+            FaucetError::InvalidReferral
+            | FaucetError::TaskIncomplete(_)
+            | FaucetError::Blocked
+            | FaucetError::Denied(_)
+            | FaucetError::ChallengeRequired(_)
+            | FaucetError::ChallengeFailed
+            | FaucetError::IdentityRejected(_) => Kind::Forbidden,
+            FaucetError::NotFound => Kind::NotFound,
+            FaucetError::PartnerBudgetExhausted
+            | FaucetError::TooSoon(_)
+            | FaucetError::RateLimited(_)
+            | FaucetError::GeoLimited => Kind::RateLimit,
+            FaucetError::InsufficientFunds
+            | FaucetError::InsufficientGas
+            | FaucetError::ChainMismatch
+            | FaucetError::NotLeader
+            | FaucetError::Maintenance(_)
+            | FaucetError::Overloaded(_) => Kind::Unavailable,
+            FaucetError::Service(err) => err.kind,
+        }
+    }
+
+    /// Message shown to the client
+    fn message(&self) -> String {
+        match self {
+            FaucetError::InvalidAddress => "Invalid Ethereum address".to_string(),
+            FaucetError::InvalidPayload(err) => format!("Invalid request: {}", err),
+            FaucetError::InvalidFields(errors) => format!(
+                "Invalid request: {}",
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            FaucetError::UnresolvedName(name) => format!("Could not resolve name {}", name),
+            FaucetError::InvalidReferral => "Invalid or expired referral".to_string(),
+            FaucetError::PartnerBudgetExhausted => {
+                "This partner's faucet budget is used up. Try again later.".to_string()
             }
-            FaucetError::TooSoon(remaining) => {
-                let hours = remaining.as_secs() / 3600;
-                (
-                    StatusCode::TOO_MANY_REQUESTS,
-                    format!("Please wait {} hours before requesting again", hours),
-                )
+            FaucetError::TaskIncomplete(task) => {
+                format!("Complete this task before requesting tokens: {}", task)
             }
-            FaucetError::RateLimited => (
-                StatusCode::TOO_MANY_REQUESTS,
-                "Too many requests from this IP. Try again later.".to_string(),
+            FaucetError::TooSoon(remaining) => format!(
+                "Please wait {} hours before requesting again",
+                remaining.as_secs() / 3600
             ),
-            FaucetError::InsufficientFunds => (
-                StatusCode::SERVICE_UNAVAILABLE,
-                "Faucet is currently out of funds. Please try again later.".to_string(),
+            FaucetError::RateLimited(wait) => format!(
+                "Too many requests from this IP. Try again in {} minutes.",
+                wait.as_secs().div_ceil(60)
             ),
-            FaucetError::RpcError(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("RPC error: {}", err),
+            FaucetError::GeoLimited => {
+                "Too many requests from your region or network. Try again later.".to_string()
+            }
+            FaucetError::Blocked => {
+                "This address or IP is not eligible for faucet funds.".to_string()
+            }
+            FaucetError::AlreadyFunded => {
+                format!(
+                    "This address already has enough {}.",
+                    branding::get().symbol
+                )
+            }
+            FaucetError::ContractRecipient => {
+                "This address is a contract. Set allow_contract to send to it anyway.".to_string()
+            }
+            FaucetError::Denied(reason) => format!("Request denied: {}", reason),
+            FaucetError::ChallengeRequired(kind) => match kind {
+                ChallengeKind::Captcha => {
+                    "Additional verification required: please complete the captcha".to_string()
+                }
+                ChallengeKind::Pow => {
+                    "Additional verification required: solve the challenge from /challenge"
+                        .to_string()
+                }
+            },
+            FaucetError::ChallengeFailed => "Verification failed. Please try again.".to_string(),
+            FaucetError::IdentityRejected(reason) => {
+                format!("Identity verification failed: {}", reason)
+            }
+            FaucetError::Unauthorized => "Unauthorized".to_string(),
+            FaucetError::NotFound => "Not found".to_string(),
+            FaucetError::InsufficientFunds => {
+                "Faucet is currently out of funds. Please try again later.".to_string()
+            }
+            FaucetError::InsufficientGas => {
+                "Faucet cannot cover network fees right now. Please try again later.".to_string()
+            }
+            FaucetError::ChainMismatch => format!(
+                "Faucet is paused: its RPC endpoint is not on {}.",
+                branding::get().network
             ),
-            FaucetError::InternalError(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Internal error: {}", err),
+            FaucetError::NotLeader => {
+                "This faucet replica is not sending drips. Please try again.".to_string()
+            }
+            FaucetError::Maintenance(maintenance) => maintenance.message.clone(),
+            FaucetError::Overloaded(wait) => format!(
+                "The faucet is busy while the network is slow. Try again in {} seconds.",
+                wait.as_secs()
             ),
+            FaucetError::Service(err) => match err.kind {
+                Kind::Rpc => format!("RPC error: {}", err),
+                Kind::Signing => format!("Signing error: {}", err),
+                Kind::Storage => format!("Storage error: {}", err),
+                _ => format!("Internal error: {}", err),
+            },
+        }
+    }
+}
+
+impl IntoResponse for FaucetError {
+    fn into_response(self) -> Response {
+        let challenge = match self {
+            FaucetError::ChallengeRequired(kind) => Some(kind),
+            _ => None,
+        };
+        let eta = match &self {
+            FaucetError::Maintenance(maintenance) => maintenance.eta,
+            _ => None,
+        };
+        let retry_after = match self {
+            FaucetError::RateLimited(wait) | FaucetError::Overloaded(wait) => {
+                Some(wait.as_secs().max(1))
+            }
+            FaucetError::Maintenance(_) => eta
+                .map(|eta| eta - ledger::to_unix(SystemTime::now()))
+                .filter(|secs| *secs > 0)
+                .map(|secs| secs as u64),
+            _ => None,
+        };
+        let (kind, code) = (self.kind(), self.reason());
+        let message = self.message();
+        let errors = match self {
+            FaucetError::InvalidFields(errors) => Some(errors),
+            _ => None,
+        };
+        // Field errors are well-formed but unprocessable, unlike broken JSON
+        let status = match errors {
+            Some(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            None => kind.status(),
         };
 
+        let detail = status.is_server_error().then(|| message.clone());
         let body = FaucetResponse {
             success: false,
             tx_hash: None,
+            explorer_url: None,
             amount: None,
+            resolved_address: None,
             message: None,
             error: Some(message),
+            kind: Some(kind),
+            code: Some(code),
+            challenge,
+            receipt: None,
+            eta,
+            errors,
+            support_url: branding::get().support_url.clone(),
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let Some(detail) = detail {
+            response
+                .extensions_mut()
+                .insert(error_report::ErrorDetail(detail));
+        }
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, secs.into());
+        }
+        response
     }
 }
 
-/// Health check endpoint
-async fn health() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "ok",
-        "service": "axionax-faucet",
-        "version": "1.0.0"
-    }))
-}
-
 /// Get faucet info
 async fn info(State(state): State<FaucetState>) -> impl IntoResponse {
+    let maintenance = state.ledger.lock().await.maintenance().ok().flatten();
+    let branding = branding::get();
     Json(serde_json::json!({
         "chain_id": state.chain_id,
-        "amount": format!("{} AXX", FAUCET_AMOUNT / 1_000_000_000_000_000_000),
+        "amount": axx(state.config.amount()),
         "cooldown_hours": COOLDOWN_HOURS,
-        "network": "axionax Testnet"
+        "network": branding.network,
+        "symbol": branding.symbol,
+        "explorer_tx_url": branding.explorer_tx_url,
+        "support_url": branding.support_url,
+        "logo_url": branding.logo_url,
+        "maintenance": maintenance,
+        "config_version": state.config.version(),
+        "receipts": {
+            "signer": state.address,
+            "domain": {
+                "name": receipt::DOMAIN_NAME,
+                "version": receipt::DOMAIN_VERSION,
+                "chainId": state.chain_id
+            },
+            "type": receipt::RECEIPT_TYPE
+        }
     }))
 }
 
 /// Request tokens
 async fn request_tokens(
     State(state): State<FaucetState>,
-    client_ip: Option<String>,
-    Json(payload): Json<FaucetRequest>,
-) -> Result<impl IntoResponse, FaucetError> {
-    let address = payload.address.trim().to_lowercase();
-    
-    // Validate address format
-    if !is_valid_address(&address) {
-        warn!("Invalid address format: {}", address);
-        return Err(FaucetError::InvalidAddress);
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<FaucetResponse>, FaucetError> {
+    let body: serde_json::Value = validation::parse_json(&body).map_err(|e| {
+        warn!("Invalid request payload: {}", e);
+        FaucetError::InvalidPayload(e.to_string())
+    })?;
+    let rules = validation::RequestRules {
+        chain_id: state.chain_id,
+        names: state.names.is_some(),
+        captcha: state.policy.requires("captcha"),
+    };
+    let errors = validation::check_request(&body, &rules);
+    if !errors.is_empty() {
+        warn!("Invalid request: {} invalid fields", errors.len());
+        return Err(FaucetError::InvalidFields(errors));
     }
+    let payload: FaucetRequest =
+        serde_json::from_value(body).map_err(|e| FaucetError::InvalidPayload(e.to_string()))?;
+    let (address, name, mut rejection) = match recipient(&state, &payload.address).await {
+        Ok((address, name)) => (address, name, None),
+        Err(e) => (validation::excerpt(&payload.address), None, Some(e)),
+    };
 
-    // Check IP rate limiting
-    if let Some(ip) = client_ip.as_ref() {
-        let mut ip_requests = state.ip_requests.write().await;
-        let now = SystemTime::now();
-        
-        // Clean old requests (older than 24 hours)
-        let cutoff = now - Duration::from_secs(COOLDOWN_HOURS * 3600);
-        
-        let requests = ip_requests.entry(ip.clone()).or_insert_with(Vec::new);
-        requests.retain(|&time| time > cutoff);
-        
-        if requests.len() >= MAX_REQUESTS_PER_IP {
-            warn!("Rate limited IP: {}", ip);
-            return Err(FaucetError::RateLimited);
+    // Verify partner referrals against the resolved recipient
+    let mut partner = None;
+    if let (Some(token), None) = (payload.referral.as_deref(), rejection.as_ref()) {
+        let origin = headers.get(header::ORIGIN).and_then(|o| o.to_str().ok());
+        match state.partners.as_ref() {
+            Some(partners) => match partners.verify(token, &address, origin) {
+                Ok(id) => partner = Some(id),
+                Err(e) => rejection = Some(e),
+            },
+            None => rejection = Some(FaucetError::InvalidReferral),
         }
     }
 
-    // Check address cooldown
-    let mut address_requests = state.address_requests.write().await;
-    let now = SystemTime::now();
-    
-    if let Some(&last_request) = address_requests.get(&address) {
-        let elapsed = now.duration_since(last_request).unwrap_or(Duration::ZERO);
-        let cooldown = Duration::from_secs(COOLDOWN_HOURS * 3600);
-        
-        if elapsed < cooldown {
-            let remaining = cooldown - elapsed;
-            warn!("Address {} requested too soon", address);
-            return Err(FaucetError::TooSoon(remaining));
-        }
+    // Partner traffic is never shed
+    if let Some(wait) = state.shedder.shed(partner.is_some()) {
+        warn!("Shedding request for {} while the RPC is slow", address);
+        return Err(FaucetError::Overloaded(wait));
+    }
+
+    let ip = client_ip::normalize(peer.ip());
+    let ctx = RequestContext {
+        address,
+        name,
+        ip,
+        rate_limit_key: client_ip::rate_limit_key(ip, state.ipv6_prefix_len),
+        geo: state
+            .geoip
+            .as_ref()
+            .map(|geoip| geoip.lookup(ip))
+            .unwrap_or_default(),
+        captcha_token: payload.captcha_token,
+        pow: payload.pow,
+        partner,
+        allow_contract: payload.allow_contract,
+        session: payload.fingerprint.as_deref().map(aliases::session_node),
+        identity: payload.identity,
+    };
+
+    let result = match rejection {
+        Some(e) => Err(e),
+        None => process_request(&state, &ctx).await,
+    };
+
+    // Record the outcome for analytics
+    let rejection = result.as_ref().err().map(FaucetError::reason);
+    if let Err(e) = state.ledger.lock().await.record_request(
+        &ctx.address,
+        &ctx.ip.to_string(),
+        &ctx.geo,
+        ctx.partner.as_deref(),
+        rejection,
+    ) {
+        error!("Failed to record request: {:?}", e);
+    }
+
+    result
+}
+
+/// Recipient address for a request, resolving names when a resolver is configured
+async fn recipient(
+    state: &FaucetState,
+    input: &str,
+) -> Result<(String, Option<String>), FaucetError> {
+    if let Some(names) = state
+        .names
+        .as_ref()
+        .filter(|_| validation::looks_like_name(input))
+    {
+        let name = validation::parse_name(input).map_err(|e| {
+            warn!("Invalid name {}: {}", validation::excerpt(input), e);
+            FaucetError::UnresolvedName(validation::excerpt(input))
+        })?;
+        let address = names.resolve(&state.rpc, &name).await?;
+        info!("Resolved {} to {}", name, address);
+        return Ok((address, Some(name)));
     }
 
+    validation::parse_address(input)
+        .map(|address| (address, None))
+        .map_err(|e| {
+            warn!("Invalid address {}: {}", validation::excerpt(input), e);
+            FaucetError::InvalidAddress
+        })
+}
+
+/// Run the grant checks for a request and send the tokens
+async fn process_request(
+    state: &FaucetState,
+    ctx: &RequestContext,
+) -> Result<Json<FaucetResponse>, FaucetError> {
+    let address = &ctx.address;
+    if let Some(maintenance) = state.ledger.lock().await.maintenance()? {
+        return Err(FaucetError::Maintenance(maintenance));
+    }
+    if !state.chain_verified.load(Ordering::Relaxed) {
+        return Err(FaucetError::ChainMismatch);
+    }
+    if !cluster::is_sender(state) {
+        return Err(FaucetError::NotLeader);
+    }
+
+    // Run the eligibility pipeline. Its stages call out to other services, so
+    // they run alongside other requests
+    let mut grant = state.policy.evaluate(state, ctx).await?;
+
+    // Sends go one at a time, each after checking what earlier grants changed
+    let _grant = state.grant_lock.lock().await;
+    state.policy.recheck(state, ctx, &mut grant).await?;
+
+    // Make sure the drip and its fees are covered before sending
+    preflight(state, address, grant.amount).await?;
+
     // Send transaction
-    info!("Sending {} AXX to {}", FAUCET_AMOUNT / 1_000_000_000_000_000_000, address);
-    
-    match send_transaction(&state, &address, FAUCET_AMOUNT).await {
+    info!("Sending {} to {}", axx(grant.amount), address);
+
+    let sent = send_transaction(
+        &state.rpc,
+        state.chain_id,
+        &state.private_key,
+        address,
+        grant.amount,
+    );
+    match sent.await {
         Ok(tx_hash) => {
             // Update request tracking
-            address_requests.insert(address.clone(), now);
-            
-            if let Some(ip) = client_ip {
-                let mut ip_requests = state.ip_requests.write().await;
-                ip_requests.entry(ip).or_insert_with(Vec::new).push(now);
+            state.policy.record(state, ctx).await;
+            // The tokens are gone either way, so the user still gets the hash
+            let recorded = state.ledger.lock().await.record(
+                EntryKind::Drip,
+                address,
+                grant.amount,
+                &tx_hash,
+                ctx.partner.as_deref(),
+            );
+            if let Err(e) = recorded {
+                error!("Failed to record drip {} to {}: {:?}", tx_hash, address, e);
+                let event = Event::new(
+                    alerts::RECORD_FAILED,
+                    Severity::Critical,
+                    "Drip sent but not recorded",
+                )
+                .with_field("recipient", address)
+                .with_field("amount", axx(grant.amount))
+                .with_field("tx_hash", &tx_hash)
+                .with_field("error", format!("{:?}", e));
+                alerts::send(state, event);
             }
 
-            info!("✓ Sent {} AXX to {} (tx: {})", 
-                  FAUCET_AMOUNT / 1_000_000_000_000_000_000, 
-                  address, 
-                  tx_hash);
+            info!(
+                "✓ Sent {} to {} (tx: {})",
+                axx(grant.amount),
+                address,
+                tx_hash
+            );
+
+            // Baseline for the leaderboard
+            let (tracking_state, recipient) = (state.clone(), address.clone());
+            tokio::spawn(async move { leaderboard::track(&tracking_state, &recipient).await });
+            badges::queue(state, address).await;
+
+            let receipt = match issue_receipt(state, address, grant.amount, &tx_hash).await {
+                Ok(receipt) => Some(receipt),
+                Err(e) => {
+                    error!("Failed to issue receipt for {}: {:?}", tx_hash, e);
+                    None
+                }
+            };
 
             Ok(Json(FaucetResponse {
                 success: true,
+                explorer_url: branding::tx_url(&tx_hash),
                 tx_hash: Some(tx_hash),
-                amount: Some(format!("{} AXX", FAUCET_AMOUNT / 1_000_000_000_000_000_000)),
+                amount: Some(axx(grant.amount)),
+                resolved_address: ctx.name.as_ref().map(|_| address.clone()),
                 message: Some("Tokens sent successfully!".to_string()),
                 error: None,
+                kind: None,
+                code: None,
+                challenge: None,
+                receipt,
+                eta: None,
+                errors: None,
+                support_url: None,
             }))
         }
         Err(e) => {
             error!("Failed to send transaction: {:?}", e);
+            let event = Event::new(alerts::SEND_FAILED, Severity::Critical, "Drip failed")
+                .with_field("recipient", address)
+                .with_field("amount", axx(grant.amount))
+                .with_field("error", format!("{:?}", e));
+            alerts::send(state, event);
             Err(e)
         }
     }
 }
 
+/// Get an address's progress on the tasks required before a grant
+async fn task_status(
+    State(state): State<FaucetState>,
+    Path(address): Path<String>,
+) -> Result<Json<Vec<TaskStatus>>, FaucetError> {
+    let address = validation::parse_address(&address).map_err(|_| FaucetError::InvalidAddress)?;
+    match state.tasks.as_ref() {
+        Some(tasks) => Ok(Json(tasks.status(&state.rpc, &address).await?)),
+        None => Ok(Json(Vec::new())),
+    }
+}
+
+/// Sign and store a receipt for a drip
+async fn issue_receipt(
+    state: &FaucetState,
+    address: &str,
+    amount: U256,
+    tx_hash: &str,
+) -> Result<SignedReceipt, FaucetError> {
+    let mut claim_id = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut claim_id);
+    let key = wallet::signing_key(&state.private_key)
+        .map_err(|e| axionax_errors::Error::signing(e.to_string()))?;
+
+    let receipt = Receipt {
+        claim_id: format!("0x{}", hex::encode(claim_id)),
+        recipient: address.to_string(),
+        amount: amount.to_string(),
+        timestamp: ledger::to_unix(SystemTime::now()) as u64,
+        tx_hash: tx_hash.to_string(),
+    }
+    .sign(state.chain_id, &key)
+    .map_err(|e| axionax_errors::Error::signing(e.to_string()))?;
+
+    state.ledger.lock().await.record_receipt(&receipt)?;
+    Ok(receipt)
+}
+
+/// Get the signed receipt of a drip
+async fn get_receipt(
+    State(state): State<FaucetState>,
+    Path(claim_id): Path<String>,
+) -> Result<Json<SignedReceipt>, FaucetError> {
+    let receipt = state
+        .ledger
+        .lock()
+        .await
+        .receipt(&claim_id.to_lowercase())?;
+    receipt.map(Json).ok_or(FaucetError::NotFound)
+}
+
 /// Get stats
-async fn stats(State(state): State<FaucetState>) -> impl IntoResponse {
-    let address_requests = state.address_requests.read().await;
-    let total_requests = address_requests.len();
-    let total_distributed = total_requests as u64 * FAUCET_AMOUNT;
+async fn stats(State(state): State<FaucetState>) -> Result<Json<StatsResponse>, FaucetError> {
+    current_stats(&state).await.map(Json)
+}
+
+/// Stream stats as server-sent `stats` events: the current stats, then every change
+async fn stats_stream(
+    State(state): State<FaucetState>,
+) -> Result<Sse<impl Stream<Item = Result<sse::Event, axum::Error>>>, FaucetError> {
+    let current = current_stats(&state).await?;
+    publish_stats(&state, current.clone());
+    let updates = state.stats_updates.subscribe();
+
+    let event = |stats: StatsResponse| sse::Event::default().event("stats").json_data(stats);
+    let first = stream::once(async move { event(current) });
+    let changes = stream::unfold(updates, move |mut updates| async move {
+        updates.changed().await.ok()?;
+        let stats = updates.borrow_and_update().clone()?;
+        Some((event(stats), updates))
+    });
+    Ok(Sse::new(first.chain(changes)).keep_alive(KeepAlive::default()))
+}
 
-    // Get faucet balance (mock for now)
-    let faucet_balance = "1000 AXX"; // Would call RPC in production
+/// Stats as served by `/stats`
+async fn current_stats(state: &FaucetState) -> Result<StatsResponse, FaucetError> {
+    let ledger = state.ledger.lock().await;
+    let total_requests = ledger.count(EntryKind::Drip)?;
+    let total_distributed = ledger.total(EntryKind::Drip)?;
 
-    Json(StatsResponse {
+    let faucet_balance = match *state.balance.read().await {
+        Some(balance) => axx(balance),
+        None => "unknown".to_string(),
+    };
+
+    Ok(StatsResponse {
         total_requests,
-        total_distributed: format!("{} AXX", total_distributed / 1_000_000_000_000_000_000),
-        faucet_balance: faucet_balance.to_string(),
+        total_distributed: axx(total_distributed),
+        faucet_balance,
         cooldown_hours: COOLDOWN_HOURS,
-        amount_per_request: format!("{} AXX", FAUCET_AMOUNT / 1_000_000_000_000_000_000),
+        amount_per_request: axx(state.config.amount()),
+        total_top_ups: ledger.count(EntryKind::TopUp)?,
+        total_topped_up: axx(ledger.total(EntryKind::TopUp)?),
     })
 }
 
-/// Validate Ethereum address format
-fn is_valid_address(address: &str) -> bool {
-    address.starts_with("0x") && address.len() == 42 && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+/// Hand `stats` to `/stats/stream` subscribers if they changed
+fn publish_stats(state: &FaucetState, stats: StatsResponse) {
+    state.stats_updates.send_if_modified(|current| {
+        if current.as_ref() == Some(&stats) {
+            return false;
+        }
+        *current = Some(stats);
+        true
+    });
 }
 
-/// Send transaction via RPC
+/// Check that the faucet balance covers `amount` plus fees at current gas prices
+async fn preflight(state: &FaucetState, to: &str, amount: U256) -> Result<(), FaucetError> {
+    let (balance, gas_price, gas) = tokio::try_join!(
+        state.rpc.get_balance(&state.address),
+        state.rpc.get_gas_price(),
+        state.rpc.estimate_gas(&state.address, to, amount),
+    )?;
+    *state.balance.write().await = Some(balance);
+
+    let fee = gas_price.saturating_mul(gas.into());
+    if balance < amount {
+        warn!(
+            "Faucet balance {} is below the drip amount {}",
+            axx(balance),
+            axx(amount)
+        );
+        return Err(FaucetError::InsufficientFunds);
+    }
+    if balance < amount.saturating_add(fee) {
+        warn!(
+            "Faucet balance {} can't cover {} + {} fees ({} gas at {})",
+            axx(balance),
+            axx(amount),
+            axx(fee),
+            gas,
+            gwei(gas_price)
+        );
+        return Err(FaucetError::InsufficientGas);
+    }
+    Ok(())
+}
+
+/// Sign a transfer of `amount` wei with `private_key` and submit it
 async fn send_transaction(
-    state: &FaucetState,
+    rpc: &RpcClient,
+    chain_id: u64,
+    private_key: &str,
     to_address: &str,
-    amount: u64,
+    amount: U256,
 ) -> Result<String, FaucetError> {
-    // This is a simplified version
-    // In production, you would:
-    // 1. Get nonce from RPC
-    // 2. Create and sign transaction
-    // 3. Send via eth_sendRawTransaction
-    
-    // For now, return mock transaction hash
-    let mock_tx_hash = format!(
-        "0x{:x}",
-        std::collections::hash_map::DefaultHasher::new()
-    );
-    
-    // TODO: Implement actual transaction signing and sending
-    // See: https://docs.rs/ethers/latest/ethers/
-    
-    Ok(mock_tx_hash)
+    let signing = |e: &dyn std::fmt::Display| axionax_errors::Error::signing(e.to_string());
+    let from = wallet::address_from_private_key(private_key).map_err(|e| signing(&e))?;
+    if let Some(chain) = rpc.mock_chain() {
+        return chain.transfer(&from, to_address, amount);
+    }
+
+    let key = wallet::signing_key(private_key).map_err(|e| signing(&e))?;
+    let to = axionax_tx::parse_address(to_address).map_err(|e| signing(&e))?;
+    let (nonce, gas_price, gas) = tokio::try_join!(
+        rpc.get_pending_nonce(&from),
+        rpc.get_gas_price(),
+        rpc.estimate_gas(&from, to_address, amount),
+    )?;
+    let signed = Transaction::transfer(chain_id, to, amount)
+        .with_nonce(nonce)
+        .with_gas_limit(gas)
+        .with_gas_price(gas_price)
+        .sign(&key)
+        .map_err(|e| signing(&e))?;
+    rpc.send_raw_transaction(&signed.raw_hex()).await
+}
+
+/// Cooldowns and IP limiter state replayed from recent grants
+///
+/// Grants older than the limiter's horizon are not replayed, so a client
+/// that kept its bucket nearly empty for longer may get a slightly early grant.
+fn recent_grants(
+    ledger: &Ledger,
+    ipv6_prefix_len: u8,
+    ip_limit: &Gcra,
+) -> Result<(AddressRequests, IpRequests), FaucetError> {
+    let mut address_requests = AddressRequests::new();
+    let mut ip_requests = IpRequests::new();
+    let now = SystemTime::now();
+    let cooldown_since = now - Duration::from_secs(COOLDOWN_HOURS * 3600);
+    let since = cooldown_since.min(now - ip_limit.horizon());
+    for (grant_address, ip, time) in ledger.grants_since(since)? {
+        if time >= cooldown_since {
+            address_requests.insert(grant_address, time);
+        }
+        if let Ok(ip) = ip.parse() {
+            let key = client_ip::rate_limit_key(ip, ipv6_prefix_len);
+            let tat = ip_limit.advance(ip_requests.get(&key).copied(), time);
+            ip_requests.insert(key, tat);
+        }
+    }
+    Ok((address_requests, ip_requests))
+}
+
+/// Command-line options
+#[derive(Debug, Parser)]
+#[command(version, about = "Axionax testnet faucet")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Run every policy check against an in-process mock chain instead of RPC_URL
+    #[arg(long, env = "DRY_RUN")]
+    dry_run: bool,
+
+    /// Inject RPC and storage faults at the `CHAOS_*` rates
+    #[arg(long, env = "CHAOS", hide = true)]
+    chaos: bool,
+
+    /// Serve `/why/:address` without an admin token
+    #[arg(long, env = "WHY_PUBLIC")]
+    why_public: bool,
+
+    #[command(flatten)]
+    listen: listen::ListenArgs,
+
+    /// SQLite ledger path
+    #[arg(
+        long,
+        env = "DATABASE_PATH",
+        default_value = "faucet.db",
+        global = true
+    )]
+    database_path: String,
+
+    #[command(flatten)]
+    log: logging::LogArgs,
+}
+
+/// Subcommands; without one the faucet server runs
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Manage the database schema
+    Db {
+        #[command(subcommand)]
+        command: db::DbCommand,
+    },
+    /// Write a snapshot of the ledger to a file
+    Backup { file: PathBuf },
+    /// Load a ledger snapshot into the database
+    Restore {
+        file: PathBuf,
+        /// Replace existing records
+        #[arg(long)]
+        force: bool,
+    },
+    /// Request tokens from a running faucet
+    Request(client::RequestArgs),
+    /// Write a genesis allocation file of recipients that went on to transact
+    ExportRecipients(export::ExportArgs),
+    /// Create keys for and sign configuration bundles
+    Bundle {
+        #[command(subcommand)]
+        command: bundle::BundleCommand,
+    },
+    /// Move the faucet's funds to a new signer and replace its key file
+    RotateKey(rotate::RotateArgs),
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
     // Initialize tracing
-    tracing_subscriber::fmt::init();
+    let _log_guard = logging::init(&cli.log);
+    error_report::init();
+    branding::init()?;
+
+    match &cli.command {
+        Some(Command::Db { command }) => return db::run(command, &cli.database_path),
+        Some(Command::Backup { file }) => return backup::backup(&cli.database_path, file),
+        Some(Command::Restore { file, force }) => {
+            return backup::restore(&cli.database_path, file, *force)
+        }
+        Some(Command::Request(args)) => return client::run(args).await,
+        Some(Command::ExportRecipients(args)) => {
+            return export::run(args, &cli.database_path).await
+        }
+        Some(Command::Bundle { command }) => return bundle::run(command),
+        Some(Command::RotateKey(args)) => return rotate::run(args, &cli.database_path).await,
+        None => {}
+    }
 
     // Load configuration from environment
-    let private_key = std::env::var("FAUCET_PRIVATE_KEY")
-        .expect("FAUCET_PRIVATE_KEY must be set");
-    let rpc_url = std::env::var("RPC_URL")
-        .unwrap_or_else(|_| "http://localhost:8545".to_string());
+    let key_file = std::env::var("FAUCET_PRIVATE_KEY_FILE").ok();
+    let private_key = match (std::env::var("FAUCET_PRIVATE_KEY"), key_file) {
+        (Ok(key), _) => key,
+        (Err(_), Some(path)) => wallet::read_key_file(path.as_ref())?,
+        (Err(_), None) if cli.dry_run => DRY_RUN_PRIVATE_KEY.to_string(),
+        (Err(_), None) => panic!("FAUCET_PRIVATE_KEY or FAUCET_PRIVATE_KEY_FILE must be set"),
+    };
+    let endpoints = rpc::Endpoints::from_env()?;
     let chain_id = std::env::var("CHAIN_ID")
         .unwrap_or_else(|_| "86137".to_string())
         .parse()
         .expect("Invalid CHAIN_ID");
+    let ipv6_prefix_len = client_ip::ipv6_prefix_len_from_env()?;
+    let address =
+        wallet::address_from_private_key(&private_key).expect("Invalid FAUCET_PRIVATE_KEY");
+    let database_path = cli.database_path;
+    let ledger = Ledger::open(&database_path)?;
+    let chaos = cli.chaos.then(chaos::Chaos::from_env).transpose()?;
+    if let Some(chaos) = &chaos {
+        ledger.inject_faults(chaos);
+    }
+
+    // Rebuild cooldowns and IP limits from recent grants, so they survive
+    // restarts and restores
+    let ip_limit = Gcra::ip_limit_from_env()?;
+    let (address_requests, ip_requests) = recent_grants(&ledger, ipv6_prefix_len, &ip_limit)
+        .map_err(|e| anyhow::anyhow!("Failed to load recent grants: {:?}", e))?;
+
+    let geoip = GeoIp::from_env()?.map(Arc::new);
+    let policy = Policy::from_env()?;
+    let partners = Partners::from_env()?.map(Arc::new);
+    if partners.is_some() && !policy.stage_names().contains(&"referral") {
+        anyhow::bail!("PARTNERS_FILE is set but POLICY_STAGES has no referral stage");
+    }
+    let treasury = TreasuryConfig::from_env()?;
+    let tasks = Tasks::from_env()?.map(Arc::new);
+    if tasks.is_some() && !policy.stage_names().contains(&"task") {
+        anyhow::bail!("TASKS_FILE is set but POLICY_STAGES has no task stage");
+    }
+    let admin_tokens = admin::tokens_from_env()?;
+    let shedder = Arc::new(LoadShedder::from_env()?);
+    let observer = Some(shedder.clone() as Arc<dyn axionax_rpc::Middleware>);
+    let mock_latency = match std::env::var("DRY_RUN_RPC_LATENCY_MS") {
+        Ok(value) => Duration::from_millis(
+            value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid DRY_RUN_RPC_LATENCY_MS: {:?}", value))?,
+        ),
+        Err(_) => Duration::ZERO,
+    };
 
     // Create state
     let state = FaucetState {
-        address_requests: Arc::new(RwLock::new(HashMap::new())),
-        ip_requests: Arc::new(RwLock::new(HashMap::new())),
+        address_requests: Arc::new(RwLock::new(address_requests)),
+        ip_requests: Arc::new(RwLock::new(ip_requests)),
+        config: Arc::new(RuntimeConfig::from_env(ip_limit)?),
+        route_limits: Arc::new(RouteLimits::from_env()?),
+        compat: Arc::new(compat::Compat::from_env()?),
+        ipv6_prefix_len,
+        ledger: Arc::new(Mutex::new(ledger)),
+        geoip,
+        names: NameResolver::from_env()?.map(Arc::new),
+        partners,
+        tasks,
+        badges: Badges::from_env()?.map(Arc::new),
+        aliases: Arc::new(Aliases::from_env()?),
+        policy: Arc::new(policy),
+        grant_lock: Arc::new(Mutex::new(())),
+        challenges: Arc::new(Challenges::from_env()?),
+        admin_tokens: Arc::new(admin_tokens),
         private_key,
-        rpc_url: rpc_url.clone(),
+        address: address.clone(),
+        rpc: if cli.dry_run {
+            // A configured treasury can top up the faucet on the mock chain too
+            let treasury = treasury.as_ref().map(TreasuryConfig::address).transpose()?;
+            let funded: Vec<&str> = std::iter::once(address.as_str())
+                .chain(treasury.as_deref())
+                .collect();
+            let chain = MockChain::new(chain_id, &funded);
+            RpcClient::mock(
+                chain,
+                mock_latency,
+                endpoints.retry,
+                observer,
+                chaos.as_ref(),
+            )
+        } else {
+            RpcClient::observed(&endpoints, observer, chaos.as_ref())
+        },
+        balance: Arc::new(RwLock::new(None)),
         chain_id,
+        chain_verified: Arc::new(AtomicBool::new(false)),
+        warm: Arc::new(AtomicBool::new(false)),
+        metrics: Arc::new(Metrics::from_env()?),
+        cluster: Cluster::from_env()?.map(Arc::new),
+        claim_events: broadcast::channel(claims::EVENT_BUFFER).0,
+        notifications: Arc::new(alerts::from_env()?),
+        worker_cycle: Arc::new(AtomicU64::new(scheduler::unix_now())),
+        why_public: cli.why_public,
+        stats_updates: watch::channel(None).0,
+        shedder,
     };
 
-    // CORS configuration
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    // Flag overrides set through the admin API outlive restarts
+    let overrides = state.ledger.lock().await.flag_overrides();
+    state.config.set_overrides(
+        overrides.map_err(|e| anyhow::anyhow!("Failed to load flag overrides: {:?}", e))?,
+    );
+
+    // Refuse to drip on the wrong network
+    match state.rpc.get_chain_id().await {
+        Ok(id) if id == chain_id => state.chain_verified.store(true, Ordering::Relaxed),
+        Ok(id) => anyhow::bail!(
+            "CHAIN_ID is {} but the RPC endpoint reports chain {}",
+            chain_id,
+            id
+        ),
+        Err(e) => warn!(
+            "Could not verify chain ID, drips paused until the RPC responds: {:?}",
+            e
+        ),
+    }
+
+    // Apply the fleet configuration before serving; local settings until it loads
+    if let Err(e) = state.config.refresh(&state).await {
+        warn!(
+            "Running on local configuration, config bundle not applied: {:?}",
+            e
+        );
+    }
 
     // Build router
     let app = Router::new()
-        .route("/health", get(health))
-        .route("/info", get(info))
-        .route("/request", post(request_tokens))
-        .route("/stats", get(stats))
-        .layer(cors)
-        .with_state(state);
+        .route(
+            "/health",
+            axionax_http::health("axionax-faucet", env!("CARGO_PKG_VERSION")),
+        )
+        .route("/readyz", get(cluster::readyz))
+        .route("/metrics", get(metrics::metrics))
+        .nest(api::V1, api::v1())
+        .merge(api::legacy(state.metrics.clone())?)
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::limit,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            compat::shape,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            metrics::track,
+        ))
+        .layer(axum::middleware::from_fn(
+            error_report::report_server_errors,
+        ));
+    let app = axionax_http::stack(app).with_state(state.clone());
 
     // Start server
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    info!("🚰 Faucet server starting on {}", addr);
+    let listener = cli.listen.bind().await?;
+    info!("🚰 Faucet server starting on {}", listener);
     info!("   Chain ID: {}", chain_id);
-    info!("   RPC: {}", rpc_url);
-    info!("   Amount: {} AXX", FAUCET_AMOUNT / 1_000_000_000_000_000_000);
+    if cli.dry_run {
+        warn!("   RPC: mock chain (dry run, no real transactions are sent)");
+    } else {
+        info!("   RPC: {}", endpoints.urls.join(", "));
+    }
+    info!("   Database: {}", database_path);
+    info!("   Address: {}", address);
+    info!("   Amount: {}", axx(state.config.amount()));
     info!("   Cooldown: {} hours", COOLDOWN_HOURS);
+    if state.geoip.is_some() {
+        info!("   GeoIP: enabled");
+    }
+    if !state.notifications.is_empty() {
+        info!("   Notifications: enabled");
+    }
+    info!("   Policy: {}", state.policy.stage_names().join(" → "));
+    if let Some(cluster) = state.cluster.as_ref() {
+        info!(
+            "   Replica: {} (lease {}s)",
+            cluster.replica_id,
+            cluster.ttl.as_secs()
+        );
+        cluster::renew(&state)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to acquire the sender lease: {:?}", e))?;
+        info!("   Role: {:?}", cluster::role(&state));
+    }
+
+    warmup::spawn(state.clone())?;
+    scheduler::spawn(state.clone())?;
+    if let Some(treasury) = treasury {
+        treasury::spawn(state.clone(), treasury);
+    }
+
+    listener.serve(app, axionax_http::shutdown_signal()).await?;
 
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await?;
+    // Hand over to another replica without waiting for the lease to expire
+    cluster::release(&state).await;
 
     Ok(())
 }
@@ -0,0 +1,398 @@
+//! Per-route request metrics and latency SLOs
+//!
+//! Every request is counted under its matched route, without the API version,
+//! with its status class and latency. `/metrics` exposes the counters and latency histograms in the
+//! Prometheus text format; `/slo` reports rolling compliance with the
+//! objectives in `SLO_OBJECTIVES`, e.g. `/request=2000@99` for "99% of
+//! `/request` calls succeed within 2000 ms".
+
+use anyhow::Context;
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Json, Response};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::shedding::SheddingStatus;
+use crate::{api, FaucetState};
+
+/// Histogram bucket upper bounds, in seconds
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Route label for requests that matched no route
+const UNMATCHED: &str = "unmatched";
+
+/// Objectives used when `SLO_OBJECTIVES` is unset
+const DEFAULT_OBJECTIVES: &str = "/request=2000@99";
+
+/// Latency objective for a route
+#[derive(Debug, Clone)]
+struct Objective {
+    route: String,
+    threshold: Duration,
+    /// Required share of good requests, in percent
+    target: f64,
+}
+
+/// Good and total requests in one minute of the SLO window
+#[derive(Debug, Default, Clone, Copy)]
+struct Minute {
+    minute: u64,
+    good: u64,
+    total: u64,
+}
+
+/// Counters and latency histogram of a route
+#[derive(Debug, Default)]
+struct RouteStats {
+    /// Requests by status class (`2xx`, `4xx`, ...)
+    responses: BTreeMap<&'static str, u64>,
+    /// Cumulative counts per bucket in `BUCKETS`
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+    /// Recent minutes, oldest first
+    window: VecDeque<Minute>,
+}
+
+/// Request metrics shared by all routes
+pub struct Metrics {
+    started: Instant,
+    window_minutes: u64,
+    objectives: Vec<Objective>,
+    routes: Mutex<BTreeMap<String, RouteStats>>,
+    /// Requests to deprecated unversioned paths, by route
+    deprecated: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Rolling compliance with one objective
+#[derive(Debug, Serialize)]
+pub struct SloStatus {
+    route: String,
+    threshold_ms: u64,
+    target: f64,
+    requests: u64,
+    good: u64,
+    /// Share of good requests in percent, `null` without traffic
+    compliance: Option<f64>,
+    met: bool,
+}
+
+/// `/slo` response
+#[derive(Debug, Serialize)]
+pub struct SloResponse {
+    window_minutes: u64,
+    objectives: Vec<SloStatus>,
+}
+
+impl Metrics {
+    /// Load objectives from `SLO_OBJECTIVES` and the window from `SLO_WINDOW_MINUTES`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let window = std::env::var("SLO_WINDOW_MINUTES").unwrap_or_else(|_| "60".to_string());
+        let window_minutes: u64 = window
+            .parse()
+            .ok()
+            .filter(|&minutes| minutes > 0)
+            .with_context(|| format!("Invalid SLO_WINDOW_MINUTES: {:?}", window))?;
+        let spec =
+            std::env::var("SLO_OBJECTIVES").unwrap_or_else(|_| DEFAULT_OBJECTIVES.to_string());
+
+        let mut objectives = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(route, rest)| {
+                let (threshold, target) = rest.split_once('@')?;
+                Some(Objective {
+                    route: route.to_string(),
+                    threshold: Duration::from_millis(threshold.parse().ok()?),
+                    target: target.parse().ok().filter(|t| (0.0..=100.0).contains(t))?,
+                })
+            });
+            match parsed {
+                Some(objective) => objectives.push(objective),
+                None => anyhow::bail!(
+                    "Invalid SLO objective {:?}, expected route=ms@percent",
+                    entry
+                ),
+            }
+        }
+
+        Ok(Self {
+            started: Instant::now(),
+            window_minutes,
+            objectives,
+            routes: Mutex::new(BTreeMap::new()),
+            deprecated: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    fn record(&self, route: &str, status: u16, latency: Duration) {
+        let minute = self.started.elapsed().as_secs() / 60;
+        let seconds = latency.as_secs_f64();
+        let mut routes = self.routes.lock().unwrap();
+        let stats = routes.entry(route.to_string()).or_default();
+
+        *stats.responses.entry(status_class(status)).or_default() += 1;
+        for (count, bound) in stats.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+        stats.count += 1;
+        stats.sum += seconds;
+
+        if let Some(objective) = self.objectives.iter().find(|o| o.route == route) {
+            if stats.window.back().map(|m| m.minute) != Some(minute) {
+                stats.window.push_back(Minute {
+                    minute,
+                    ..Default::default()
+                });
+            }
+            let current = stats.window.back_mut().unwrap();
+            current.total += 1;
+            if status < 500 && latency <= objective.threshold {
+                current.good += 1;
+            }
+            while stats
+                .window
+                .front()
+                .is_some_and(|m| m.minute + self.window_minutes <= minute)
+            {
+                stats.window.pop_front();
+            }
+        }
+    }
+
+    /// Count a request to a deprecated path
+    pub fn record_deprecated(&self, route: &str) {
+        *self
+            .deprecated
+            .lock()
+            .unwrap()
+            .entry(route.to_string())
+            .or_default() += 1;
+    }
+
+    /// Compliance with every objective over the window
+    pub fn slo(&self) -> SloResponse {
+        let minute = self.started.elapsed().as_secs() / 60;
+        let routes = self.routes.lock().unwrap();
+        let objectives = self
+            .objectives
+            .iter()
+            .map(|objective| {
+                let (good, requests) = routes
+                    .get(&objective.route)
+                    .map(|stats| {
+                        stats
+                            .window
+                            .iter()
+                            .filter(|m| m.minute + self.window_minutes > minute)
+                            .fold((0, 0), |(good, total), m| (good + m.good, total + m.total))
+                    })
+                    .unwrap_or_default();
+                let compliance = (requests > 0).then(|| good as f64 * 100.0 / requests as f64);
+                SloStatus {
+                    route: objective.route.clone(),
+                    threshold_ms: objective.threshold.as_millis() as u64,
+                    target: objective.target,
+                    requests,
+                    good,
+                    compliance,
+                    met: compliance.is_none_or(|c| c >= objective.target),
+                }
+            })
+            .collect();
+
+        SloResponse {
+            window_minutes: self.window_minutes,
+            objectives,
+        }
+    }
+
+    /// Render every metric in the Prometheus text format
+    fn render(&self) -> String {
+        let mut out = String::new();
+        {
+            let routes = self.routes.lock().unwrap();
+
+            describe(
+                &mut out,
+                "faucet_http_requests_total",
+                "counter",
+                "HTTP requests by route and status class",
+            );
+            for (route, stats) in routes.iter() {
+                for (class, count) in &stats.responses {
+                    let _ = writeln!(
+                        out,
+                        "faucet_http_requests_total{{route=\"{}\",status=\"{}\"}} {}",
+                        route, class, count
+                    );
+                }
+            }
+
+            describe(
+                &mut out,
+                "faucet_http_request_duration_seconds",
+                "histogram",
+                "HTTP request latency by route",
+            );
+            for (route, stats) in routes.iter() {
+                for (count, bound) in stats.buckets.iter().zip(BUCKETS) {
+                    let _ = writeln!(
+                        out,
+                        "faucet_http_request_duration_seconds_bucket{{route=\"{}\",le=\"{}\"}} {}",
+                        route, bound, count
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    "faucet_http_request_duration_seconds_bucket{{route=\"{}\",le=\"+Inf\"}} {}",
+                    route, stats.count
+                );
+                let _ = writeln!(
+                    out,
+                    "faucet_http_request_duration_seconds_sum{{route=\"{}\"}} {}",
+                    route, stats.sum
+                );
+                let _ = writeln!(
+                    out,
+                    "faucet_http_request_duration_seconds_count{{route=\"{}\"}} {}",
+                    route, stats.count
+                );
+            }
+        }
+
+        describe(
+            &mut out,
+            "faucet_http_deprecated_requests_total",
+            "counter",
+            "HTTP requests to deprecated unversioned paths by route",
+        );
+        for (route, count) in self.deprecated.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "faucet_http_deprecated_requests_total{{route=\"{}\"}} {}",
+                route, count
+            );
+        }
+
+        let slo = self.slo();
+        describe(
+            &mut out,
+            "faucet_slo_compliance_ratio",
+            "gauge",
+            "Share of good requests over the SLO window",
+        );
+        for status in &slo.objectives {
+            if let Some(compliance) = status.compliance {
+                let _ = writeln!(
+                    out,
+                    "faucet_slo_compliance_ratio{{route=\"{}\"}} {}",
+                    status.route,
+                    compliance / 100.0
+                );
+            }
+        }
+        describe(
+            &mut out,
+            "faucet_slo_target_ratio",
+            "gauge",
+            "Required share of good requests",
+        );
+        for status in &slo.objectives {
+            let _ = writeln!(
+                out,
+                "faucet_slo_target_ratio{{route=\"{}\"}} {}",
+                status.route,
+                status.target / 100.0
+            );
+        }
+        out
+    }
+}
+
+/// Write the `HELP` and `TYPE` lines of a metric
+fn describe(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn status_class(status: u16) -> &'static str {
+    match status {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+/// Record the route, status and latency of every request
+pub async fn track(State(state): State<FaucetState>, request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| api::unversioned(path.as_str()).to_string())
+        .unwrap_or_else(|| UNMATCHED.to_string());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    state
+        .metrics
+        .record(&route, response.status().as_u16(), started.elapsed());
+    response
+}
+
+/// Prometheus metrics
+pub async fn metrics(State(state): State<FaucetState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render() + render_shedding(&state.shedder.status()).as_str(),
+    )
+}
+
+/// Render the load shedding state in the Prometheus text format
+fn render_shedding(status: &SheddingStatus) -> String {
+    let mut out = String::new();
+    describe(
+        &mut out,
+        "faucet_load_shedding",
+        "gauge",
+        "Whether /request traffic is being shed for slow RPC",
+    );
+    let _ = writeln!(out, "faucet_load_shedding {}", status.shedding as u8);
+    describe(
+        &mut out,
+        "faucet_requests_shed_total",
+        "counter",
+        "Requests shed since startup",
+    );
+    let _ = writeln!(out, "faucet_requests_shed_total {}", status.shed_total);
+    if let Some(p95) = status.rpc_p95_ms {
+        describe(
+            &mut out,
+            "faucet_rpc_latency_p95_seconds",
+            "gauge",
+            "p95 RPC call latency over the shedding window",
+        );
+        let _ = writeln!(
+            out,
+            "faucet_rpc_latency_p95_seconds {}",
+            p95 as f64 / 1000.0
+        );
+    }
+    out
+}
+
+/// Rolling SLO compliance
+pub async fn slo(State(state): State<FaucetState>) -> Json<SloResponse> {
+    Json(state.metrics.slo())
+}
@@ -0,0 +1,319 @@
+//! In-process mock chain for `--dry-run`
+//!
+//! Answers the JSON-RPC methods the faucet uses and applies transfers to an
+//! in-memory balance sheet. Transaction hashes are deterministic in
+//! `(from, to, amount, nonce)`, and a transaction is confirmed once the next
+//! simulated block is produced.
+
+use axionax_tx::U256;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::rpc::parse_quantity;
+use crate::{axx_to_wei, FaucetError};
+
+/// Simulated block time
+const BLOCK_TIME: Duration = Duration::from_secs(2);
+
+/// Balance of each funded account at startup (AXX)
+const INITIAL_BALANCE_AXX: u64 = 1_000_000;
+
+/// A mined mock transaction
+struct MockTransaction {
+    from: String,
+    to: String,
+    amount: U256,
+    nonce: u64,
+    block: u64,
+}
+
+#[derive(Default)]
+struct Ledger {
+    balances: HashMap<String, U256>,
+    nonces: HashMap<String, u64>,
+    transactions: HashMap<String, MockTransaction>,
+}
+
+/// Deterministic in-memory chain
+pub struct MockChain {
+    chain_id: u64,
+    started: Instant,
+    ledger: Mutex<Ledger>,
+}
+
+impl MockChain {
+    /// Create a chain where every `funded` account starts with 1,000,000 AXX
+    pub fn new(chain_id: u64, funded: &[&str]) -> Self {
+        let mut ledger = Ledger::default();
+        for address in funded {
+            ledger
+                .balances
+                .insert(address.to_lowercase(), axx_to_wei(INITIAL_BALANCE_AXX));
+        }
+        Self {
+            chain_id,
+            started: Instant::now(),
+            ledger: Mutex::new(ledger),
+        }
+    }
+
+    fn block_number(&self) -> u64 {
+        (self.started.elapsed().as_millis() / BLOCK_TIME.as_millis()) as u64
+    }
+
+    /// Move `amount` from `from` to `to`, returning the transaction hash
+    pub fn transfer(&self, from: &str, to: &str, amount: U256) -> Result<String, FaucetError> {
+        let (from, to) = (from.to_lowercase(), to.to_lowercase());
+        let block = self.block_number() + 1;
+        let mut ledger = self.ledger.lock().unwrap();
+
+        let balance = ledger.balances.get(&from).copied().unwrap_or_default();
+        if balance < amount {
+            return Err(FaucetError::InsufficientFunds);
+        }
+        let nonce = ledger.nonces.get(&from).copied().unwrap_or_default();
+
+        let mut value = [0u8; 32];
+        amount.to_big_endian(&mut value);
+        let hash = Keccak256::new()
+            .chain_update(from.as_bytes())
+            .chain_update(to.as_bytes())
+            .chain_update(value)
+            .chain_update(nonce.to_be_bytes())
+            .finalize();
+        let hash = format!("0x{}", hex::encode(hash));
+
+        ledger.balances.insert(from.clone(), balance - amount);
+        *ledger.balances.entry(to.clone()).or_default() += amount;
+        ledger.nonces.insert(from.clone(), nonce + 1);
+        ledger.transactions.insert(
+            hash.clone(),
+            MockTransaction {
+                from,
+                to,
+                amount,
+                nonce,
+                block,
+            },
+        );
+        Ok(hash)
+    }
+
+    /// Answer a JSON-RPC call
+    pub fn call(&self, method: &str, params: &Value) -> Result<Value, axionax_rpc::Error> {
+        let param = |i: usize| {
+            params
+                .get(i)
+                .and_then(Value::as_str)
+                .map(str::to_lowercase)
+                .ok_or_else(|| axionax_rpc::Error::Rpc {
+                    code: -32602,
+                    message: format!("missing param {} for {}", i, method),
+                })
+        };
+        let ledger = self.ledger.lock().unwrap();
+        let block = self.block_number();
+
+        let result = match method {
+            "eth_chainId" => json!(format!("0x{:x}", self.chain_id)),
+            "eth_blockNumber" => json!(format!("0x{:x}", block)),
+            "eth_gasPrice" => json!("0x3b9aca00"),
+            "eth_estimateGas" => json!("0x5208"),
+            "eth_getBalance" => {
+                let balance = ledger.balances.get(&param(0)?).copied().unwrap_or_default();
+                json!(format!("0x{:x}", balance))
+            }
+            "eth_getTransactionCount" => {
+                let nonce = ledger.nonces.get(&param(0)?).copied().unwrap_or_default();
+                json!(format!("0x{:x}", nonce))
+            }
+            "eth_getCode" => json!("0x"),
+            // No contracts are deployed, so every call returns a zero word
+            "eth_call" => json!(format!("0x{}", "0".repeat(64))),
+            "eth_getLogs" => json!([]),
+            "eth_getBlockByNumber" => {
+                let number = parse_quantity(&param(0)?)? as u64;
+                if number > block {
+                    Value::Null
+                } else {
+                    let transactions: Vec<Value> = ledger
+                        .transactions
+                        .iter()
+                        .filter(|(_, tx)| tx.block == number)
+                        .map(|(hash, tx)| {
+                            json!({
+                                "hash": hash,
+                                "from": tx.from,
+                                "to": tx.to,
+                                "value": format!("0x{:x}", tx.amount),
+                                "nonce": format!("0x{:x}", tx.nonce),
+                            })
+                        })
+                        .collect();
+                    json!({
+                        "number": format!("0x{:x}", number),
+                        "transactions": transactions,
+                    })
+                }
+            }
+            "eth_getTransactionByHash" => match ledger.transactions.get(&param(0)?) {
+                Some(tx) => json!({
+                    "hash": param(0)?,
+                    "from": tx.from,
+                    "to": tx.to,
+                    "value": format!("0x{:x}", tx.amount),
+                    "nonce": format!("0x{:x}", tx.nonce),
+                    "blockNumber": (tx.block <= block).then(|| format!("0x{:x}", tx.block)),
+                }),
+                None => Value::Null,
+            },
+            "eth_getTransactionReceipt" => match ledger.transactions.get(&param(0)?) {
+                Some(tx) if tx.block <= block => json!({
+                    "transactionHash": param(0)?,
+                    "from": tx.from,
+                    "to": tx.to,
+                    "blockNumber": format!("0x{:x}", tx.block),
+                    "status": "0x1",
+                }),
+                _ => Value::Null,
+            },
+            other => {
+                return Err(axionax_rpc::Error::Rpc {
+                    code: axionax_rpc::METHOD_NOT_FOUND,
+                    message: format!("method {} not supported by the mock chain", other),
+                })
+            }
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAUCET: &str = "0xF39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const RECIPIENT: &str = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+
+    /// Produce the next simulated block
+    fn mine(chain: &mut MockChain) {
+        chain.started -= BLOCK_TIME;
+    }
+
+    fn quantity(chain: &MockChain, method: &str, address: &str) -> U256 {
+        let result = chain.call(method, &json!([address, "latest"])).unwrap();
+        U256::from_str_radix(result.as_str().unwrap(), 16).unwrap()
+    }
+
+    #[test]
+    fn transfers_move_balances_and_nonces() {
+        let chain = MockChain::new(86137, &[FAUCET]);
+        assert_eq!(chain.call("eth_chainId", &json!([])).unwrap(), "0x15079");
+        assert_eq!(
+            quantity(&chain, "eth_getBalance", FAUCET),
+            axx_to_wei(INITIAL_BALANCE_AXX)
+        );
+        assert_eq!(quantity(&chain, "eth_getBalance", RECIPIENT), U256::zero());
+
+        chain.transfer(FAUCET, RECIPIENT, axx_to_wei(100)).unwrap();
+        chain.transfer(FAUCET, RECIPIENT, axx_to_wei(50)).unwrap();
+        assert_eq!(
+            quantity(&chain, "eth_getBalance", FAUCET),
+            axx_to_wei(INITIAL_BALANCE_AXX - 150)
+        );
+        assert_eq!(
+            quantity(&chain, "eth_getBalance", RECIPIENT),
+            axx_to_wei(150)
+        );
+        assert_eq!(
+            quantity(&chain, "eth_getTransactionCount", FAUCET),
+            U256::from(2)
+        );
+        assert_eq!(
+            quantity(&chain, "eth_getTransactionCount", RECIPIENT),
+            U256::zero()
+        );
+    }
+
+    #[test]
+    fn overdrafts_are_rejected() {
+        let chain = MockChain::new(86137, &[FAUCET]);
+        let result = chain.transfer(RECIPIENT, FAUCET, axx_to_wei(1));
+        assert!(matches!(result, Err(FaucetError::InsufficientFunds)));
+        assert_eq!(
+            quantity(&chain, "eth_getTransactionCount", RECIPIENT),
+            U256::zero()
+        );
+        assert_eq!(
+            quantity(&chain, "eth_getBalance", FAUCET),
+            axx_to_wei(INITIAL_BALANCE_AXX)
+        );
+    }
+
+    #[test]
+    fn hashes_depend_on_the_transfer_and_nonce() {
+        let (a, b) = (MockChain::new(1, &[FAUCET]), MockChain::new(2, &[FAUCET]));
+        let first = a.transfer(FAUCET, RECIPIENT, axx_to_wei(100)).unwrap();
+        assert_eq!(
+            b.transfer(FAUCET, RECIPIENT, axx_to_wei(100)).unwrap(),
+            first
+        );
+        assert_ne!(
+            a.transfer(FAUCET, RECIPIENT, axx_to_wei(100)).unwrap(),
+            first
+        );
+        assert_ne!(
+            b.transfer(FAUCET, RECIPIENT, axx_to_wei(99)).unwrap(),
+            first
+        );
+    }
+
+    #[test]
+    fn transactions_confirm_in_the_next_block() {
+        let mut chain = MockChain::new(86137, &[FAUCET]);
+        let hash = chain.transfer(FAUCET, RECIPIENT, axx_to_wei(100)).unwrap();
+        let lookup = |chain: &MockChain, method: &str| chain.call(method, &json!([hash])).unwrap();
+
+        assert!(lookup(&chain, "eth_getTransactionReceipt").is_null());
+        let pending = lookup(&chain, "eth_getTransactionByHash");
+        assert_eq!(pending["to"], RECIPIENT);
+        assert!(pending["blockNumber"].is_null());
+        assert!(chain
+            .call("eth_getBlockByNumber", &json!(["0x1", false]))
+            .unwrap()
+            .is_null());
+
+        mine(&mut chain);
+        assert_eq!(chain.call("eth_blockNumber", &json!([])).unwrap(), "0x1");
+        let receipt = lookup(&chain, "eth_getTransactionReceipt");
+        assert_eq!(receipt["transactionHash"], hash);
+        assert_eq!(receipt["status"], "0x1");
+        assert_eq!(receipt["blockNumber"], "0x1");
+        assert_eq!(
+            lookup(&chain, "eth_getTransactionByHash")["blockNumber"],
+            "0x1"
+        );
+        let block = chain
+            .call("eth_getBlockByNumber", &json!(["0x1", false]))
+            .unwrap();
+        assert_eq!(block["transactions"][0]["hash"], hash);
+        assert_eq!(block["transactions"][0]["nonce"], "0x0");
+    }
+
+    #[test]
+    fn unsupported_calls_are_rpc_errors() {
+        let chain = MockChain::new(86137, &[]);
+        let code = |method: &str, params: Value| match chain.call(method, &params) {
+            Err(axionax_rpc::Error::Rpc { code, .. }) => code,
+            other => panic!("{}: {:?}", method, other),
+        };
+        assert_eq!(
+            code("eth_sendRawTransaction", json!(["0x00"])),
+            axionax_rpc::METHOD_NOT_FOUND
+        );
+        assert_eq!(code("eth_getBalance", json!([])), -32602);
+    }
+}
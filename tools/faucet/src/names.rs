@@ -0,0 +1,113 @@
+//! ENS-style name resolution for drip targets
+//!
+//! Names such as `alice.axx` are hashed with the ENS `namehash` algorithm and
+//! resolved by calling `addr(bytes32)` on the resolver contract at
+//! `NAME_RESOLVER`. Resolutions are cached for `NAME_CACHE_SECS`.
+
+use serde_json::json;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::rpc::RpcClient;
+use crate::FaucetError;
+use axionax_faucet::validation;
+
+/// `addr(bytes32)` function selector
+const ADDR_SELECTOR: &str = "3b3b57de";
+
+/// Upper bound on cached names, so the cache can't grow without limit
+const MAX_CACHE_ENTRIES: usize = 10_000;
+
+/// Resolves names through a resolver contract
+pub struct NameResolver {
+    /// Resolver contract address
+    resolver: String,
+    /// Accepted top-level suffixes, e.g. `.axx`
+    suffixes: Vec<String>,
+    ttl: Duration,
+    cache: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl NameResolver {
+    /// Enabled when `NAME_RESOLVER` is set
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(resolver) = std::env::var("NAME_RESOLVER") else {
+            return Ok(None);
+        };
+        let resolver = validation::parse_address(&resolver)
+            .map_err(|e| anyhow::anyhow!("Invalid NAME_RESOLVER: {}", e))?;
+        let suffixes = std::env::var("NAME_SUFFIXES")
+            .unwrap_or_else(|_| "axx".to_string())
+            .split(',')
+            .map(|s| format!(".{}", s.trim().trim_start_matches('.').to_lowercase()))
+            .collect();
+        let ttl = std::env::var("NAME_CACHE_SECS").unwrap_or_else(|_| "300".to_string());
+        let ttl: u64 = ttl
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid NAME_CACHE_SECS: {:?}", ttl))?;
+
+        Ok(Some(Self {
+            resolver,
+            suffixes,
+            ttl: Duration::from_secs(ttl),
+            cache: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Resolve `name` to a lowercase address
+    pub async fn resolve(&self, rpc: &RpcClient, name: &str) -> Result<String, FaucetError> {
+        let name = validation::parse_name(name)
+            .map_err(|_| FaucetError::UnresolvedName(validation::excerpt(name)))?;
+        if !self
+            .suffixes
+            .iter()
+            .any(|suffix| name.ends_with(suffix.as_str()))
+        {
+            return Err(FaucetError::UnresolvedName(name));
+        }
+
+        if let Some((address, resolved_at)) = self.cache.lock().unwrap().get(&name) {
+            if resolved_at.elapsed() < self.ttl {
+                return Ok(address.clone());
+            }
+        }
+
+        let data = format!("0x{}{}", ADDR_SELECTOR, hex::encode(namehash(&name)));
+        let result: String = rpc
+            .call(
+                "eth_call",
+                json!([{ "to": self.resolver, "data": data }, "latest"]),
+            )
+            .await?;
+        let word = result.trim_start_matches("0x");
+        if word.len() != 64 || word[24..].bytes().all(|b| b == b'0') {
+            return Err(FaucetError::UnresolvedName(name));
+        }
+        let address = validation::parse_address(&format!("0x{}", &word[24..]))
+            .map_err(|_| FaucetError::UnresolvedName(name.clone()))?;
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            let ttl = self.ttl;
+            cache.retain(|_, (_, resolved_at)| resolved_at.elapsed() < ttl);
+        }
+        cache.insert(name, (address.clone(), Instant::now()));
+        Ok(address)
+    }
+}
+
+/// ENS namehash of a normalized name
+pub fn namehash(name: &str) -> [u8; 32] {
+    let mut node = [0u8; 32];
+    for label in name.rsplit('.').filter(|l| !l.is_empty()) {
+        let label_hash = Keccak256::digest(label.as_bytes());
+        node = Keccak256::new()
+            .chain_update(node)
+            .chain_update(label_hash)
+            .finalize()
+            .into();
+    }
+    node
+}
@@ -0,0 +1,762 @@
+//! Eligibility policy pipeline for grant decisions
+//!
+//! Each stage implements [`EligibilityCheck`]. Stages run in the order given
+//! by `POLICY_STAGES`; the first rejection wins. Stages whose backing
+//! configuration is missing are skipped. A stage written `stage@flag` only
+//! runs for clients the feature flag is on for, see [`crate::flags`].
+
+use async_trait::async_trait;
+use axionax_faucet::validation::{parse_address, parse_amount};
+use axionax_tx::U256;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+
+use crate::aliases;
+use crate::challenge::ChallengeKind;
+use crate::flags;
+use crate::geoip::GeoLimit;
+use crate::identity::Identity;
+use crate::labels::DeniedLabels;
+use crate::reputation::Reputation;
+use crate::script::ScriptStage;
+use crate::{FaucetError, FaucetState, RequestContext, COOLDOWN_HOURS};
+
+/// Stage order used when `POLICY_STAGES` is unset
+const DEFAULT_STAGES: &str =
+    "blocklist,labels,reputation,ip_limit,geo_limit,contract,onchain,task,script,\
+     referral,identity,alias,cooldown";
+
+/// A single stage of the grant decision
+#[async_trait]
+pub trait EligibilityCheck: Send + Sync {
+    /// Stage name as used in `POLICY_STAGES`
+    fn name(&self) -> &'static str;
+
+    /// Reject the request if it is not eligible
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError>;
+
+    /// Update stage bookkeeping after a successful grant
+    async fn record(&self, _state: &FaucetState, _ctx: &RequestContext) {}
+
+    /// Whether the stage only runs for part of the traffic
+    fn gated(&self) -> bool {
+        false
+    }
+
+    /// Whether the stage depends on earlier grants, so it is checked again
+    /// under the grant lock right before sending
+    fn rechecked(&self) -> bool {
+        false
+    }
+}
+
+/// What a request is granted if every stage passes
+#[derive(Debug, Clone)]
+pub struct Grant {
+    /// Amount to send (wei)
+    pub amount: U256,
+    /// Time the address must wait after this grant
+    pub cooldown: Duration,
+}
+
+impl Grant {
+    fn new(state: &FaucetState) -> Self {
+        Self {
+            amount: state.config.amount(),
+            cooldown: Duration::from_secs(COOLDOWN_HOURS * 3600),
+        }
+    }
+}
+
+/// Ordered list of eligibility stages
+pub struct Policy {
+    stages: Vec<Box<dyn EligibilityCheck>>,
+}
+
+impl Policy {
+    /// Build the pipeline from `POLICY_STAGES`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let names = std::env::var("POLICY_STAGES").unwrap_or_else(|_| DEFAULT_STAGES.to_string());
+        let mut stages: Vec<Box<dyn EligibilityCheck>> = Vec::new();
+
+        for entry in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let (name, flag) = match entry.split_once('@') {
+                Some((name, flag)) => {
+                    let flag = flags::valid_name(flag.trim())
+                        .ok_or_else(|| anyhow::anyhow!("Invalid flag in policy stage {}", entry))?;
+                    (name.trim(), Some(flag))
+                }
+                None => (entry, None),
+            };
+            let stage: Option<Box<dyn EligibilityCheck>> = match name {
+                "blocklist" => Blocklist::from_env()?.map(|s| Box::new(s) as _),
+                "labels" => DeniedLabels::from_env()?.map(|l| Box::new(LabelStage(l)) as _),
+                "reputation" => Reputation::from_env()?.map(|r| Box::new(ReputationStage(r)) as _),
+                "captcha" => Some(Box::new(ChallengeStage(ChallengeKind::Captcha))),
+                "pow" => Some(Box::new(ChallengeStage(ChallengeKind::Pow))),
+                "ip_limit" => Some(Box::new(IpLimit)),
+                "geo_limit" => Some(Box::new(GeoLimitStage)),
+                "contract" => Some(Box::new(ContractGuard::from_env()?)),
+                "onchain" => OnChainBalance::from_env()?.map(|s| Box::new(s) as _),
+                "task" => std::env::var("TASKS_FILE")
+                    .ok()
+                    .map(|_| Box::new(TaskGate) as _),
+                "script" => ScriptStage::from_env()?.map(|s| Box::new(s) as _),
+                "referral" => std::env::var("PARTNERS_FILE")
+                    .ok()
+                    .map(|_| Box::new(PartnerBudget) as _),
+                "identity" => Identity::from_env()?.map(|i| Box::new(IdentityStage(i)) as _),
+                "alias" => Some(Box::new(AliasCooldown)),
+                "cooldown" => Some(Box::new(Cooldown)),
+                other => anyhow::bail!("Unknown policy stage: {}", other),
+            };
+            let stage = match flag {
+                Some(flag) => stage.map(|inner| {
+                    Box::new(Flagged {
+                        flag: flag.to_string(),
+                        inner,
+                    }) as _
+                }),
+                None => stage,
+            };
+            match stage {
+                Some(stage) => stages.push(stage),
+                None => info!("   Policy stage '{}' not configured, skipping", name),
+            }
+        }
+
+        Ok(Self { stages })
+    }
+
+    /// Names of the active stages, in order
+    pub fn stage_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// Whether the named stage runs for every request
+    pub fn requires(&self, name: &str) -> bool {
+        self.stages.iter().any(|s| s.name() == name && !s.gated())
+    }
+
+    /// Run every stage against the request and return the resulting grant
+    pub async fn evaluate(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+    ) -> Result<Grant, FaucetError> {
+        let mut grant = Grant::new(state);
+        for stage in &self.stages {
+            stage.check(state, ctx, &mut grant).await?;
+        }
+        Ok(grant)
+    }
+
+    /// Check the stages that depend on earlier grants again, for a request
+    /// that passed [`Policy::evaluate`] while other grants may have gone out
+    pub async fn recheck(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        for stage in self.stages.iter().filter(|s| s.rechecked()) {
+            stage.check(state, ctx, grant).await?;
+        }
+        Ok(())
+    }
+
+    /// Run every stage, without stopping at the first rejection, and return
+    /// the grant with each stage's outcome
+    pub async fn explain(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+    ) -> (Grant, Vec<(&'static str, Result<(), FaucetError>)>) {
+        let mut grant = Grant::new(state);
+        let mut outcomes = Vec::with_capacity(self.stages.len());
+        for stage in &self.stages {
+            let outcome = stage.check(state, ctx, &mut grant).await;
+            outcomes.push((stage.name(), outcome));
+        }
+        (grant, outcomes)
+    }
+
+    /// Let every stage record a successful grant
+    pub async fn record(&self, state: &FaucetState, ctx: &RequestContext) {
+        for stage in &self.stages {
+            stage.record(state, ctx).await;
+        }
+    }
+}
+
+/// Stage that only runs for clients a feature flag is on for
+struct Flagged {
+    flag: String,
+    inner: Box<dyn EligibilityCheck>,
+}
+
+impl Flagged {
+    fn enabled(&self, state: &FaucetState, ctx: &RequestContext) -> bool {
+        state
+            .config
+            .flag_enabled(&self.flag, &ctx.rate_limit_key, ctx.partner.as_deref())
+    }
+}
+
+#[async_trait]
+impl EligibilityCheck for Flagged {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        if !self.enabled(state, ctx) {
+            return Ok(());
+        }
+        self.inner.check(state, ctx, grant).await
+    }
+
+    async fn record(&self, state: &FaucetState, ctx: &RequestContext) {
+        if self.enabled(state, ctx) {
+            self.inner.record(state, ctx).await;
+        }
+    }
+
+    fn gated(&self) -> bool {
+        true
+    }
+
+    fn rechecked(&self) -> bool {
+        self.inner.rechecked()
+    }
+}
+
+/// Require the given challenge from a client
+async fn verify_challenge(
+    state: &FaucetState,
+    ctx: &RequestContext,
+    kind: ChallengeKind,
+) -> Result<(), FaucetError> {
+    let challenges = &state.challenges;
+    if kind == ChallengeKind::Captcha && ctx.partner.is_some() {
+        // Partner referrals stand in for the captcha
+        return Ok(());
+    }
+    match kind {
+        ChallengeKind::Captcha => match ctx.captcha_token.as_deref() {
+            Some(token) => challenges.verify_captcha(token).await,
+            None => Err(FaucetError::ChallengeRequired(ChallengeKind::Captcha)),
+        },
+        ChallengeKind::Pow => match ctx.pow.as_ref() {
+            Some(solution) => challenges.verify_pow(solution),
+            None => Err(FaucetError::ChallengeRequired(ChallengeKind::Pow)),
+        },
+    }
+}
+
+/// Reject blocklisted addresses and IPs
+struct Blocklist {
+    addresses: HashSet<String>,
+    ips: HashSet<IpAddr>,
+}
+
+impl Blocklist {
+    /// Load `BLOCKLIST_FILE`: one address or IP per line, `#` comments
+    ///
+    /// The stage also runs without the file when `CONFIG_BUNDLE_URL` is set,
+    /// since bundles can carry a blocklist.
+    fn from_env() -> anyhow::Result<Option<Self>> {
+        let mut blocklist = Self {
+            addresses: HashSet::new(),
+            ips: HashSet::new(),
+        };
+        let Ok(path) = std::env::var("BLOCKLIST_FILE") else {
+            let bundles = std::env::var("CONFIG_BUNDLE_URL").is_ok();
+            return Ok(bundles.then_some(blocklist));
+        };
+        for line in std::fs::read_to_string(path)?.lines() {
+            let entry = line.split('#').next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            match entry.parse::<IpAddr>() {
+                Ok(ip) => blocklist.ips.insert(ip),
+                Err(_) => blocklist.addresses.insert(entry.to_lowercase()),
+            };
+        }
+
+        Ok(Some(blocklist))
+    }
+}
+
+#[async_trait]
+impl EligibilityCheck for Blocklist {
+    fn name(&self) -> &'static str {
+        "blocklist"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        if self.addresses.contains(&ctx.address)
+            || self.ips.contains(&ctx.ip)
+            || state.config.is_blocked(&ctx.address, ctx.ip)
+        {
+            warn!("Blocked request from {} for {}", ctx.ip, ctx.address);
+            return Err(FaucetError::Blocked);
+        }
+        Ok(())
+    }
+}
+
+/// Escalate suspicious traffic to the configured challenge
+struct ReputationStage(Reputation);
+
+#[async_trait]
+impl EligibilityCheck for ReputationStage {
+    fn name(&self) -> &'static str {
+        "reputation"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        if let Some(reason) = self.0.check(ctx.ip, &ctx.geo).await {
+            info!("Challenging {} ({})", ctx.ip, reason);
+            verify_challenge(state, ctx, state.challenges.escalation).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Require a captcha or proof-of-work from every client
+struct ChallengeStage(ChallengeKind);
+
+#[async_trait]
+impl EligibilityCheck for ChallengeStage {
+    fn name(&self) -> &'static str {
+        self.0.as_str()
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        verify_challenge(state, ctx, self.0).await
+    }
+}
+
+/// Limit grants per IP (or IPv6 prefix) to the `IP_LIMIT` rate and burst
+struct IpLimit;
+
+#[async_trait]
+impl EligibilityCheck for IpLimit {
+    fn name(&self) -> &'static str {
+        "ip_limit"
+    }
+
+    fn rechecked(&self) -> bool {
+        true
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let ip = &ctx.rate_limit_key;
+        let tat = state.ip_requests.read().await.get(ip).copied();
+        state
+            .config
+            .ip_limit()
+            .check(tat, SystemTime::now())
+            .map_err(|wait| {
+                warn!("Rate limited IP: {}", ip);
+                FaucetError::RateLimited(wait)
+            })
+    }
+
+    async fn record(&self, state: &FaucetState, ctx: &RequestContext) {
+        let mut ip_requests = state.ip_requests.write().await;
+        let tat = ip_requests.get(&ctx.rate_limit_key).copied();
+        ip_requests.insert(
+            ctx.rate_limit_key.clone(),
+            state.config.ip_limit().advance(tat, SystemTime::now()),
+        );
+    }
+}
+
+/// Limit grants per country/ASN per cooldown window
+struct GeoLimitStage;
+
+#[async_trait]
+impl EligibilityCheck for GeoLimitStage {
+    fn name(&self) -> &'static str {
+        "geo_limit"
+    }
+
+    fn rechecked(&self) -> bool {
+        true
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let Some(geoip) = state.geoip.as_ref() else {
+            return Ok(());
+        };
+
+        let since = SystemTime::now() - Duration::from_secs(COOLDOWN_HOURS * 3600);
+        let ledger = state.ledger.lock().await;
+        for limit in geoip.limits_for(&ctx.geo) {
+            let (granted, max) = match &limit {
+                GeoLimit::Country(country, max) => {
+                    (ledger.country_grants_since(country, since)?, *max)
+                }
+                GeoLimit::Asn(asn, max) => (ledger.asn_grants_since(*asn, since)?, *max),
+            };
+            if granted >= u64::from(max) {
+                warn!("Geo limited {:?} for {}", limit, ctx.address);
+                return Err(FaucetError::GeoLimited);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reject contract recipients unless allowlisted or explicitly allowed
+struct ContractGuard {
+    allowlist: HashSet<String>,
+}
+
+impl ContractGuard {
+    /// Load `CONTRACT_ALLOWLIST_FILE`: one address per line, `#` comments
+    fn from_env() -> anyhow::Result<Self> {
+        let mut allowlist = HashSet::new();
+        if let Ok(path) = std::env::var("CONTRACT_ALLOWLIST_FILE") {
+            for line in std::fs::read_to_string(path)?.lines() {
+                let entry = line.split('#').next().unwrap_or_default().trim();
+                if entry.is_empty() {
+                    continue;
+                }
+                let address = parse_address(entry).map_err(|e| {
+                    anyhow::anyhow!("Invalid allowlisted contract {}: {}", entry, e)
+                })?;
+                allowlist.insert(address);
+            }
+        }
+        Ok(Self { allowlist })
+    }
+}
+
+#[async_trait]
+impl EligibilityCheck for ContractGuard {
+    fn name(&self) -> &'static str {
+        "contract"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        if ctx.allow_contract || self.allowlist.contains(&ctx.address) {
+            return Ok(());
+        }
+        if state.rpc.is_contract(&ctx.address).await? {
+            warn!("Rejected drip to contract {}", ctx.address);
+            return Err(FaucetError::ContractRecipient);
+        }
+        Ok(())
+    }
+}
+
+/// Reject recipients that already hold enough tokens
+struct OnChainBalance {
+    /// Maximum recipient balance (wei) still eligible for a drip
+    max_balance: U256,
+}
+
+impl OnChainBalance {
+    /// Enabled when `MAX_RECIPIENT_BALANCE_AXX` is set
+    fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(value) = std::env::var("MAX_RECIPIENT_BALANCE_AXX") else {
+            return Ok(None);
+        };
+        let max_balance = parse_amount(&value)
+            .map_err(|e| anyhow::anyhow!("Invalid MAX_RECIPIENT_BALANCE_AXX {:?}: {}", value, e))?;
+        Ok(Some(Self { max_balance }))
+    }
+}
+
+#[async_trait]
+impl EligibilityCheck for OnChainBalance {
+    fn name(&self) -> &'static str {
+        "onchain"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let balance = state.rpc.get_balance(&ctx.address).await?;
+        if balance > self.max_balance {
+            warn!("Address {} already funded", ctx.address);
+            return Err(FaucetError::AlreadyFunded);
+        }
+        Ok(())
+    }
+}
+
+/// Require the on-chain tasks in `TASKS_FILE`
+struct TaskGate;
+
+#[async_trait]
+impl EligibilityCheck for TaskGate {
+    fn name(&self) -> &'static str {
+        "task"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let Some(tasks) = state.tasks.as_ref() else {
+            return Ok(());
+        };
+        let statuses = tasks.status(&state.rpc, &ctx.address).await?;
+        if let Some(task) = statuses.into_iter().find(|t| !t.completed) {
+            info!(
+                "Address {} has not completed task '{}'",
+                ctx.address, task.id
+            );
+            return Err(FaucetError::TaskIncomplete(task.description));
+        }
+        Ok(())
+    }
+}
+
+/// Cap what each partner's referrals can draw per cooldown window
+struct PartnerBudget;
+
+#[async_trait]
+impl EligibilityCheck for PartnerBudget {
+    fn name(&self) -> &'static str {
+        "referral"
+    }
+
+    fn rechecked(&self) -> bool {
+        true
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let (Some(id), Some(partners)) = (ctx.partner.as_deref(), state.partners.as_ref()) else {
+            return Ok(());
+        };
+        let partner = partners.get(id).ok_or(FaucetError::InvalidReferral)?;
+
+        let since = SystemTime::now() - Duration::from_secs(COOLDOWN_HOURS * 3600);
+        let spent = state.ledger.lock().await.partner_spent_since(id, since)?;
+        if spent.saturating_add(grant.amount) > partner.budget {
+            warn!("Partner {} budget exhausted", id);
+            return Err(FaucetError::PartnerBudgetExhausted);
+        }
+        Ok(())
+    }
+}
+
+/// Reject addresses carrying a label listed in `LABEL_DENY`
+struct LabelStage(DeniedLabels);
+
+#[async_trait]
+impl EligibilityCheck for LabelStage {
+    fn name(&self) -> &'static str {
+        "labels"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        _grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        self.0.check(state, &ctx.address).await
+    }
+}
+
+/// Grant verified humans the identity drip amount and cooldown
+struct IdentityStage(Identity);
+
+#[async_trait]
+impl EligibilityCheck for IdentityStage {
+    fn name(&self) -> &'static str {
+        "identity"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        if !self
+            .0
+            .verified(state, &ctx.address, ctx.identity.as_ref())
+            .await?
+        {
+            return Ok(());
+        }
+        if let Some(amount) = self.0.amount {
+            grant.amount = amount;
+        }
+        if let Some(cooldown) = self.0.cooldown {
+            grant.cooldown = cooldown;
+        }
+        Ok(())
+    }
+}
+
+/// Per-address cooldown between drips
+struct Cooldown;
+
+#[async_trait]
+impl EligibilityCheck for Cooldown {
+    fn name(&self) -> &'static str {
+        "cooldown"
+    }
+
+    fn rechecked(&self) -> bool {
+        true
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let address_requests = state.address_requests.read().await;
+
+        if let Some(&last_request) = address_requests.get(&ctx.address) {
+            let elapsed = SystemTime::now()
+                .duration_since(last_request)
+                .unwrap_or(Duration::ZERO);
+            let cooldown = grant.cooldown;
+
+            if elapsed < cooldown {
+                let remaining = cooldown - elapsed;
+                warn!("Address {} requested too soon", ctx.address);
+                return Err(FaucetError::TooSoon(remaining));
+            }
+        }
+        Ok(())
+    }
+
+    async fn record(&self, state: &FaucetState, ctx: &RequestContext) {
+        let mut address_requests = state.address_requests.write().await;
+        address_requests.insert(ctx.address.clone(), SystemTime::now());
+    }
+}
+
+/// Apply the cooldown to every address linked to the recipient
+///
+/// Addresses requested from the client's session are linked in too, so they
+/// cluster even before any funding between them is seen. The recipient is
+/// only linked to the session once the grant is recorded.
+struct AliasCooldown;
+
+#[async_trait]
+impl EligibilityCheck for AliasCooldown {
+    fn name(&self) -> &'static str {
+        "alias"
+    }
+
+    fn rechecked(&self) -> bool {
+        true
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let linked = {
+            let ledger = state.ledger.lock().await;
+            let (max_fanout, limit) = (state.aliases.max_fanout, aliases::MAX_CLUSTER_NODES);
+            let mut linked = ledger.linked_nodes(&ctx.address, max_fanout, limit)?;
+            // The session is only linked in once granted, so a rejected
+            // request can't tie an address into someone else's cluster
+            if let Some(session) = ctx.session.as_deref() {
+                linked.extend(ledger.linked_nodes(session, max_fanout, limit)?);
+            }
+            linked
+        };
+
+        let address_requests = state.address_requests.read().await;
+        let latest = linked
+            .iter()
+            .filter(|node| !aliases::is_session(node) && **node != ctx.address)
+            .filter_map(|alias| Some((alias, *address_requests.get(alias)?)))
+            .max_by_key(|(_, time)| *time);
+        if let Some((alias, last_request)) = latest {
+            let elapsed = SystemTime::now()
+                .duration_since(last_request)
+                .unwrap_or(Duration::ZERO);
+            let cooldown = grant.cooldown;
+            if elapsed < cooldown {
+                warn!("Address {} shares a cooldown with {}", ctx.address, alias);
+                return Err(FaucetError::TooSoon(cooldown - elapsed));
+            }
+        }
+        Ok(())
+    }
+
+    async fn record(&self, state: &FaucetState, ctx: &RequestContext) {
+        let Some(session) = ctx.session.as_deref() else {
+            return;
+        };
+        let linked = state
+            .ledger
+            .lock()
+            .await
+            .link_address(&ctx.address, session, "session");
+        if let Err(e) = linked {
+            error!("Failed to link {} to its session: {:?}", ctx.address, e);
+        }
+    }
+}
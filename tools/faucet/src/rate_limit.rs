@@ -0,0 +1,175 @@
+//! Smooth rate limiting with the generic cell rate algorithm (GCRA)
+//!
+//! A limit such as `3/24h@5` sustains 3 requests per 24 hours (one every 8
+//! hours) and admits bursts of up to 5 requests, so a NAT shared by a few
+//! clients isn't locked out after its first requests while a farm issuing
+//! many is held to the sustained rate. Each client key only needs its
+//! theoretical arrival time (TAT): the time at which its bucket is full again.
+//!
+//! `IP_LIMIT` limits grants per IP (or IPv6 prefix) in the `ip_limit` policy
+//! stage. `RATE_LIMITS` limits every request to the listed routes, e.g.
+//! `/request=30/1m@10,/challenge=60/1m@20`.
+
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::{api, client_ip, FaucetError, FaucetState};
+
+/// Grant limit used when `IP_LIMIT` is unset
+const DEFAULT_IP_LIMIT: &str = "3/24h@3";
+
+/// Sustained rate and burst of a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gcra {
+    /// Time between requests at the sustained rate
+    interval: Duration,
+    /// Requests admitted at once by a full bucket
+    burst: u32,
+}
+
+impl Gcra {
+    /// Parse `count/period@burst`, where the period is a number with an
+    /// `s`, `m`, `h` or `d` suffix; the burst defaults to `count`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (rate, burst) = match spec.split_once('@') {
+            Some((rate, burst)) => (rate, Some(burst.trim().parse().ok()?)),
+            None => (spec, None),
+        };
+        let (count, period) = rate.split_once('/')?;
+        let count: u32 = count.trim().parse().ok().filter(|c| *c > 0)?;
+        let period = period.trim();
+        let unit = match period.chars().last()? {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            _ => return None,
+        };
+        let amount: u64 = period[..period.len() - 1].parse().ok().filter(|a| *a > 0)?;
+        Some(Self {
+            interval: Duration::from_secs(amount * unit) / count,
+            burst: burst.unwrap_or(count).max(1),
+        })
+    }
+
+    /// Load the grant limit from `IP_LIMIT`
+    pub fn ip_limit_from_env() -> anyhow::Result<Self> {
+        let spec = std::env::var("IP_LIMIT").unwrap_or_else(|_| DEFAULT_IP_LIMIT.to_string());
+        Self::parse(&spec).ok_or_else(|| {
+            anyhow::anyhow!("Invalid IP_LIMIT {:?}, expected count/period@burst", spec)
+        })
+    }
+
+    /// How long a full burst takes to drain; older requests no longer matter
+    pub fn horizon(&self) -> Duration {
+        self.interval * self.burst
+    }
+
+    /// Admit a request at `now` given the key's TAT, or return how long to wait
+    pub fn check(&self, tat: Option<SystemTime>, now: SystemTime) -> Result<(), Duration> {
+        let tolerance = self.interval * (self.burst - 1);
+        let ahead = tat
+            .and_then(|tat| tat.duration_since(now).ok())
+            .unwrap_or_default();
+        if ahead > tolerance {
+            Err(ahead - tolerance)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Requests admitted at once by a full bucket
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Requests the key could make at `now` before being limited
+    pub fn available(&self, tat: Option<SystemTime>, now: SystemTime) -> u32 {
+        let ahead = tat
+            .and_then(|tat| tat.duration_since(now).ok())
+            .unwrap_or_default();
+        let used = ahead.as_nanos().div_ceil(self.interval.as_nanos().max(1));
+        self.burst
+            .saturating_sub(used.min(u128::from(u32::MAX)) as u32)
+    }
+
+    /// TAT after a request at `now`
+    pub fn advance(&self, tat: Option<SystemTime>, now: SystemTime) -> SystemTime {
+        tat.map_or(now, |tat| tat.max(now)) + self.interval
+    }
+}
+
+/// Request limits for individual routes, keyed by client IP (or IPv6 prefix)
+pub struct RouteLimits {
+    routes: HashMap<String, Gcra>,
+    /// TAT per route and client
+    buckets: Mutex<HashMap<(String, String), SystemTime>>,
+}
+
+impl RouteLimits {
+    /// Load limits from `RATE_LIMITS`, a comma-separated list of `route=count/period@burst`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let spec = std::env::var("RATE_LIMITS").unwrap_or_default();
+        let mut routes = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry
+                .split_once('=')
+                .and_then(|(route, limit)| Some((route.trim(), Gcra::parse(limit)?)))
+            {
+                Some((route, limit)) => {
+                    routes.insert(route.to_string(), limit);
+                }
+                None => anyhow::bail!(
+                    "Invalid rate limit {:?}, expected route=count/period@burst",
+                    entry
+                ),
+            }
+        }
+        Ok(Self {
+            routes,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn admit(&self, route: &str, key: String) -> Result<(), Duration> {
+        let Some(limit) = self.routes.get(route) else {
+            return Ok(());
+        };
+        let now = SystemTime::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let tat = buckets.entry((route.to_string(), key)).or_insert(now);
+        limit.check(Some(*tat), now)?;
+        *tat = limit.advance(Some(*tat), now);
+        Ok(())
+    }
+
+    /// Forget clients whose buckets are full again
+    pub fn prune(&self) {
+        let now = SystemTime::now();
+        self.buckets.lock().unwrap().retain(|_, tat| *tat > now);
+    }
+}
+
+/// Reject requests to limited routes from clients over their limit
+pub async fn limit(
+    State(state): State<FaucetState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(route) = request.extensions().get::<MatchedPath>() {
+        let ip = client_ip::normalize(peer.ip());
+        let key = client_ip::rate_limit_key(ip, state.ipv6_prefix_len);
+        // Limits are shared by every version of a route
+        let route = api::unversioned(route.as_str());
+        if let Err(wait) = state.route_limits.admit(route, key) {
+            return FaucetError::RateLimited(wait).into_response();
+        }
+    }
+    next.run(request).await
+}
@@ -0,0 +1,181 @@
+//! EIP-712 signed drip receipts
+//!
+//! Every successful drip gets a receipt in which the faucet signer attests the
+//! recipient, amount, time and transaction hash. Receipts are typed data under
+//! the domain `EIP712Domain(string name,string version,uint256 chainId)` with
+//! `name = "axionax Faucet"` and `version = "1"`, so they can be verified
+//! on-chain with `ecrecover` or off-chain with any EIP-712 library. Each
+//! receipt carries a random `claimId`; consumers prevent replays by accepting
+//! a claim id only once.
+
+use axionax_tx::U256;
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// EIP-712 domain name
+pub const DOMAIN_NAME: &str = "axionax Faucet";
+
+/// EIP-712 domain version
+pub const DOMAIN_VERSION: &str = "1";
+
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId)";
+
+/// EIP-712 type of a receipt
+pub const RECEIPT_TYPE: &str =
+    "Receipt(bytes32 claimId,address recipient,uint256 amount,uint64 timestamp,bytes32 txHash)";
+
+/// Why a receipt couldn't be signed or verified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptError {
+    /// A field isn't valid for its EIP-712 type
+    InvalidField(&'static str),
+    InvalidSignature,
+}
+
+impl fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptError::InvalidField(field) => write!(f, "invalid receipt field {}", field),
+            ReceiptError::InvalidSignature => write!(f, "invalid receipt signature"),
+        }
+    }
+}
+
+impl std::error::Error for ReceiptError {}
+
+/// Attested facts about a drip
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    /// Random 32-byte claim id, `0x`-prefixed
+    pub claim_id: String,
+    pub recipient: String,
+    /// Amount in wei, as a decimal string
+    pub amount: String,
+    /// Unix timestamp of the drip
+    pub timestamp: u64,
+    pub tx_hash: String,
+}
+
+/// A receipt with its signature and the domain it was signed under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    #[serde(flatten)]
+    pub receipt: Receipt,
+    pub chain_id: u64,
+    /// Address of the faucet signer
+    pub signer: String,
+    /// 65-byte `r ‖ s ‖ v` signature, `0x`-prefixed
+    pub signature: String,
+}
+
+impl Receipt {
+    /// EIP-712 digest of the receipt on `chain_id`
+    pub fn digest(&self, chain_id: u64) -> Result<[u8; 32], ReceiptError> {
+        let amount = match self.amount.as_str() {
+            "" => None,
+            digits => U256::from_dec_str(digits).ok(),
+        }
+        .ok_or(ReceiptError::InvalidField("amount"))?;
+
+        let mut encoded = Keccak256::digest(RECEIPT_TYPE).to_vec();
+        encoded.extend(bytes32(&self.claim_id).ok_or(ReceiptError::InvalidField("claim_id"))?);
+        encoded
+            .extend(address_word(&self.recipient).ok_or(ReceiptError::InvalidField("recipient"))?);
+        encoded.extend(uint_word(amount));
+        encoded.extend(uint_word(self.timestamp.into()));
+        encoded.extend(bytes32(&self.tx_hash).ok_or(ReceiptError::InvalidField("tx_hash"))?);
+        let struct_hash = Keccak256::digest(encoded);
+
+        Ok(Keccak256::new()
+            .chain_update([0x19, 0x01])
+            .chain_update(domain_separator(chain_id))
+            .chain_update(struct_hash)
+            .finalize()
+            .into())
+    }
+
+    /// Sign the receipt with `key`
+    pub fn sign(self, chain_id: u64, key: &SigningKey) -> Result<SignedReceipt, ReceiptError> {
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(&self.digest(chain_id)?)
+            .map_err(|_| ReceiptError::InvalidSignature)?;
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+
+        Ok(SignedReceipt {
+            receipt: self,
+            chain_id,
+            signer: address_of(key.verifying_key()),
+            signature: format!("0x{}", hex::encode(bytes)),
+        })
+    }
+
+    /// Address that produced `signature` over this receipt
+    pub fn recover_signer(&self, chain_id: u64, signature: &str) -> Result<String, ReceiptError> {
+        let bytes = hex::decode(signature.trim_start_matches("0x"))
+            .map_err(|_| ReceiptError::InvalidSignature)?;
+        if bytes.len() != 65 {
+            return Err(ReceiptError::InvalidSignature);
+        }
+        let signature =
+            Signature::from_slice(&bytes[..64]).map_err(|_| ReceiptError::InvalidSignature)?;
+        let recovery_id = RecoveryId::from_byte(bytes[64].wrapping_sub(27))
+            .ok_or(ReceiptError::InvalidSignature)?;
+        let key =
+            VerifyingKey::recover_from_prehash(&self.digest(chain_id)?, &signature, recovery_id)
+                .map_err(|_| ReceiptError::InvalidSignature)?;
+        Ok(address_of(&key))
+    }
+}
+
+impl SignedReceipt {
+    /// Check that the signature was made by `signer`
+    pub fn verify(&self) -> Result<(), ReceiptError> {
+        let signer = self
+            .receipt
+            .recover_signer(self.chain_id, &self.signature)?;
+        if !signer.eq_ignore_ascii_case(&self.signer) {
+            return Err(ReceiptError::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+
+fn domain_separator(chain_id: u64) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(Keccak256::digest(DOMAIN_TYPE))
+        .chain_update(Keccak256::digest(DOMAIN_NAME))
+        .chain_update(Keccak256::digest(DOMAIN_VERSION))
+        .chain_update(uint_word(chain_id.into()))
+        .finalize()
+        .into()
+}
+
+/// Lowercase `0x` address of a public key
+fn address_of(key: &VerifyingKey) -> String {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+fn bytes32(value: &str) -> Option<[u8; 32]> {
+    hex::decode(value.strip_prefix("0x")?).ok()?.try_into().ok()
+}
+
+fn address_word(value: &str) -> Option<[u8; 32]> {
+    let bytes: [u8; 20] = hex::decode(value.strip_prefix("0x")?)
+        .ok()?
+        .try_into()
+        .ok()?;
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&bytes);
+    Some(word)
+}
+
+fn uint_word(value: U256) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    word
+}
@@ -0,0 +1,130 @@
+//! Signed referral tokens for partner dApps
+//!
+//! Partners listed in `PARTNERS_FILE` can embed the faucet. Their backend
+//! issues tokens of the form `<partner>.<expires>.<signature>`, where
+//! `expires` is a unix timestamp and `signature` is the hex HMAC-SHA3-256 of
+//! `"<partner>.<address>.<expires>"` keyed with the partner's secret. Requests carrying a valid
+//! token from one of the partner's origins skip the captcha and draw from the
+//! partner's daily budget.
+
+use axionax_faucet::validation::parse_amount;
+use axionax_tx::U256;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha3::Sha3_256;
+use std::collections::HashMap;
+use std::time::SystemTime;
+use tracing::warn;
+
+use crate::ledger::to_unix;
+use crate::FaucetError;
+
+/// Partner entry in `PARTNERS_FILE`
+#[derive(Debug, Deserialize)]
+struct PartnerConfig {
+    id: String,
+    /// Origins the faucet may be embedded from
+    origins: Vec<String>,
+    /// Shared secret used to sign referral tokens
+    secret: String,
+    /// AXX the partner may hand out per cooldown window
+    daily_budget_axx: String,
+}
+
+/// A partner dApp
+#[derive(Debug)]
+pub struct Partner {
+    origins: Vec<String>,
+    secret: String,
+    /// Budget (wei) per cooldown window
+    pub budget: U256,
+}
+
+/// Configured partners by id
+pub struct Partners {
+    partners: HashMap<String, Partner>,
+}
+
+impl Partners {
+    /// Load `PARTNERS_FILE`, a JSON array of partners
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(path) = std::env::var("PARTNERS_FILE") else {
+            return Ok(None);
+        };
+
+        let configs: Vec<PartnerConfig> = serde_json::from_slice(&std::fs::read(path)?)?;
+        let mut partners = HashMap::new();
+        for config in configs {
+            if config.id.is_empty() || config.id.contains('.') {
+                anyhow::bail!("Invalid partner id: {:?}", config.id);
+            }
+            let budget = parse_amount(&config.daily_budget_axx)
+                .map_err(|e| anyhow::anyhow!("Invalid budget for {}: {}", config.id, e))?;
+            let partner = Partner {
+                origins: config.origins,
+                secret: config.secret,
+                budget,
+            };
+            if partners.insert(config.id.clone(), partner).is_some() {
+                anyhow::bail!("Duplicate partner id: {}", config.id);
+            }
+        }
+
+        Ok(Some(Self { partners }))
+    }
+
+    /// Look up a partner by id
+    pub fn get(&self, id: &str) -> Option<&Partner> {
+        self.partners.get(id)
+    }
+
+    /// Ids and budgets of all partners
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Partner)> {
+        self.partners
+            .iter()
+            .map(|(id, partner)| (id.as_str(), partner))
+    }
+
+    /// Verify a referral token for `address` sent from `origin`, returning the partner id
+    pub fn verify(
+        &self,
+        token: &str,
+        address: &str,
+        origin: Option<&str>,
+    ) -> Result<String, FaucetError> {
+        let mut parts = token.splitn(3, '.');
+        let (Some(id), Some(expires), Some(signature)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(FaucetError::InvalidReferral);
+        };
+        let partner = self.partners.get(id).ok_or(FaucetError::InvalidReferral)?;
+
+        if !origin.is_some_and(|origin| partner.origins.iter().any(|o| o == origin)) {
+            warn!("Referral for {} from unexpected origin {:?}", id, origin);
+            return Err(FaucetError::InvalidReferral);
+        }
+        let expires: i64 = expires.parse().map_err(|_| FaucetError::InvalidReferral)?;
+        if expires < to_unix(SystemTime::now()) {
+            return Err(FaucetError::InvalidReferral);
+        }
+        let valid = hex::decode(signature).is_ok_and(|signature| {
+            mac(&partner.secret, id, address, expires)
+                .verify_slice(&signature)
+                .is_ok()
+        });
+        if !valid {
+            warn!("Bad referral signature for {}", id);
+            return Err(FaucetError::InvalidReferral);
+        }
+
+        Ok(id.to_string())
+    }
+}
+
+/// HMAC over a referral for `address`; `verify_slice` compares in constant time
+fn mac(secret: &str, partner: &str, address: &str, expires: i64) -> Hmac<Sha3_256> {
+    let mut mac = Hmac::<Sha3_256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}.{}", partner, address, expires).as_bytes());
+    mac
+}
@@ -0,0 +1,103 @@
+//! IP reputation checks for proxy, VPN, Tor and data-center traffic
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::geoip::GeoInfo;
+
+/// Response fields that mark an IP as suspicious in the reputation API
+const FLAG_FIELDS: &[&str] = &["proxy", "vpn", "tor", "hosting"];
+
+/// Reputation API calls run while a request is evaluated, so they are bounded
+const API_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Static ASN lists plus an optional external reputation API
+pub struct Reputation {
+    /// Known data-center/VPN ASNs
+    asns: HashSet<u32>,
+    /// Reputation API URL, with `{ip}` replaced by the client IP
+    api_url: Option<String>,
+    /// Challenge the client when the API fails, instead of letting it through
+    challenge_on_error: bool,
+    http: reqwest::Client,
+}
+
+impl Reputation {
+    /// Load reputation sources from the environment
+    ///
+    /// Returns `None` when no ASN list or reputation API is configured.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let mut asns = parse_asns(&std::env::var("REPUTATION_ASNS").unwrap_or_default())?;
+        if let Ok(path) = std::env::var("REPUTATION_ASN_FILE") {
+            asns.extend(parse_asns(&std::fs::read_to_string(path)?)?);
+        }
+        let api_url = std::env::var("REPUTATION_API_URL").ok();
+        let challenge_on_error = match std::env::var("REPUTATION_ON_ERROR").as_deref() {
+            Err(_) | Ok("allow") => false,
+            Ok("challenge") => true,
+            Ok(other) => anyhow::bail!(
+                "Invalid REPUTATION_ON_ERROR: {:?}, expected allow or challenge",
+                other
+            ),
+        };
+
+        if asns.is_empty() && api_url.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            asns,
+            api_url,
+            challenge_on_error,
+            http: reqwest::Client::builder().timeout(API_TIMEOUT).build()?,
+        }))
+    }
+
+    /// Reason the client looks like proxy or data-center traffic, if any
+    pub async fn check(&self, ip: IpAddr, geo: &GeoInfo) -> Option<String> {
+        if let Some(asn) = geo.asn.filter(|asn| self.asns.contains(asn)) {
+            return Some(format!("listed ASN AS{}", asn));
+        }
+
+        let url = self.api_url.as_ref()?.replace("{ip}", &ip.to_string());
+        let response: Value = match self.http.get(&url).send().await {
+            Ok(response) => match response.json().await {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Invalid reputation API response: {}", e);
+                    return self.unavailable();
+                }
+            },
+            Err(e) => {
+                warn!("Reputation API request failed: {}", e);
+                return self.unavailable();
+            }
+        };
+
+        FLAG_FIELDS
+            .iter()
+            .find(|field| response.get(**field).and_then(Value::as_bool) == Some(true))
+            .map(|field| format!("reputation API flagged {}", field))
+    }
+
+    /// Reason to challenge a client whose reputation couldn't be looked up
+    fn unavailable(&self) -> Option<String> {
+        self.challenge_on_error
+            .then(|| "reputation API unavailable".to_string())
+    }
+}
+
+/// Parse ASNs separated by commas or newlines, ignoring `#` comments
+fn parse_asns(value: &str) -> anyhow::Result<HashSet<u32>> {
+    value
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|asn| !asn.is_empty())
+        .map(|asn| Ok(asn.trim_start_matches("AS").parse()?))
+        .collect()
+}
@@ -0,0 +1,256 @@
+//! `axionax-faucet rotate-key`: replace the faucet signer
+//!
+//! The signer's key lives in `FAUCET_PRIVATE_KEY_FILE`. Rotation generates a
+//! new key, sweeps the old signer's balance to it minus the fee of the sweep,
+//! waits for the sweep to confirm, swaps the key file with a rename and
+//! appends `rotate_key` to the admin audit log. The new key is written to
+//! `<file>.next` before anything is sent, and a rotation that was interrupted
+//! picks that key up again instead of generating another one, so funds are
+//! never swept to a key that isn't on disk. Likewise the sweep's hash is kept
+//! in `<file>.next.tx` until it confirms, and a resumed rotation waits for
+//! that sweep instead of sending another.
+//!
+//! Stop the faucet (or pause it for maintenance) before rotating, and restart
+//! it afterwards to pick up the new signer.
+
+use anyhow::{anyhow, bail};
+use axionax_tx::{Transaction, U256};
+use clap::Args;
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::ledger::Ledger;
+use crate::rpc::RpcClient;
+use crate::{axx, wallet, FaucetError};
+
+/// How often the sweep receipt is polled for
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Options of the `rotate-key` subcommand
+#[derive(Debug, Args)]
+pub struct RotateArgs {
+    /// File holding the hex private key of the current signer
+    #[arg(long, env = "FAUCET_PRIVATE_KEY_FILE")]
+    key_file: PathBuf,
+
+    /// JSON-RPC endpoint the sweep is sent to
+    #[arg(long, env = "RPC_URL", default_value = "http://localhost:8545")]
+    rpc_url: String,
+
+    /// Chain the sweep is signed for
+    #[arg(long, env = "CHAIN_ID", default_value_t = 86137)]
+    chain_id: u64,
+
+    /// Who rotated the key, as recorded in the audit log
+    #[arg(long, default_value = "cli")]
+    actor: String,
+
+    /// Seconds to wait for the sweep to confirm before giving up
+    #[arg(long, default_value_t = 120)]
+    timeout: u64,
+}
+
+/// Rotate the signer and record the rotation in the ledger at `db_path`
+pub async fn run(args: &RotateArgs, db_path: &str) -> anyhow::Result<()> {
+    let old_key = wallet::read_key_file(&args.key_file)?;
+    let old_address = wallet::address_from_private_key(&old_key)
+        .map_err(|e| anyhow!("Invalid key in {}: {}", args.key_file.display(), e))?;
+    let ledger = Ledger::open(db_path)?;
+    let rpc = RpcClient::new(&args.rpc_url);
+
+    let chain_id = rpc
+        .get_chain_id()
+        .await
+        .map_err(|e| anyhow!("Failed to reach {}: {:?}", args.rpc_url, e))?;
+    if chain_id != args.chain_id {
+        bail!(
+            "CHAIN_ID is {} but the RPC endpoint reports chain {}",
+            args.chain_id,
+            chain_id
+        );
+    }
+
+    let next_file = sibling(&args.key_file, "next");
+    let sweep_file = sibling(&next_file, "tx");
+    let resuming = next_file.exists();
+    let new_key = match resuming {
+        true => {
+            println!(
+                "Resuming the rotation to the key in {}",
+                next_file.display()
+            );
+            wallet::read_key_file(&next_file)?
+        }
+        false => {
+            let key = wallet::generate_key();
+            write_private(&next_file, &key)?;
+            key
+        }
+    };
+    let new_address = wallet::address_from_private_key(&new_key)?;
+    if new_address == old_address {
+        bail!("{} already holds the new key", args.key_file.display());
+    }
+    println!(
+        "Rotating the faucet signer {} → {}",
+        old_address, new_address
+    );
+
+    let sent = match resuming {
+        true => read_sweep(&sweep_file)?,
+        false => None,
+    };
+    let sweep = match sent {
+        Some(sweep) => {
+            println!("Resuming the sweep {}", sweep.0);
+            Some(sweep)
+        }
+        None => {
+            let sweep = sweep(&rpc, args, &old_key, &old_address, &new_address).await?;
+            if let Some((tx_hash, amount)) = &sweep {
+                write_private(&sweep_file, &format!("{} {}", tx_hash, amount))?;
+            }
+            sweep
+        }
+    };
+    if let Some((tx_hash, amount)) = &sweep {
+        println!(
+            "Swept {} (tx: {}), waiting for confirmation...",
+            axx(*amount),
+            tx_hash
+        );
+        wait_for_receipt(&rpc, tx_hash, Duration::from_secs(args.timeout)).await?;
+    }
+
+    // Removed first: a rerun after an interrupted rename has nothing left to
+    // sweep, while a stale hash would be waited for forever
+    if sweep_file.exists() {
+        std::fs::remove_file(&sweep_file)?;
+    }
+    std::fs::rename(&next_file, &args.key_file)?;
+    ledger
+        .record_audit(
+            &args.actor,
+            "rotate_key",
+            &json!({
+                "old_address": old_address,
+                "new_address": new_address,
+                "tx_hash": sweep.as_ref().map(|(hash, _)| hash),
+                "amount": sweep.as_ref().map(|(_, amount)| amount.to_string()),
+            }),
+            None,
+        )
+        .map_err(|e| anyhow!("Rotated the key but failed to audit it: {:?}", e))?;
+
+    println!(
+        "Rotated: {} now holds the key of {}; restart the faucet to use it",
+        args.key_file.display(),
+        new_address
+    );
+    Ok(())
+}
+
+/// Send the old signer's balance minus the sweep fee to the new signer
+///
+/// Returns `None` when the balance doesn't cover the fee.
+async fn sweep(
+    rpc: &RpcClient,
+    args: &RotateArgs,
+    old_key: &str,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Option<(String, U256)>> {
+    let failed = |e: FaucetError| anyhow!("Failed to prepare the sweep: {:?}", e);
+    let (balance, gas_price, gas, nonce, pending) = tokio::try_join!(
+        rpc.get_balance(from),
+        rpc.get_gas_price(),
+        rpc.estimate_gas(from, to, U256::zero()),
+        rpc.get_transaction_count(from),
+        rpc.get_pending_nonce(from),
+    )
+    .map_err(failed)?;
+    if pending != nonce {
+        bail!(
+            "{} has {} pending transactions; stop the faucet and let them confirm first",
+            from,
+            pending - nonce
+        );
+    }
+
+    let fee = gas_price.saturating_mul(gas.into());
+    if balance <= fee {
+        println!("Nothing to sweep: {} holds {}", from, axx(balance));
+        return Ok(None);
+    }
+    let amount = balance - fee;
+    let key = wallet::signing_key(old_key)?;
+    let signed = Transaction::transfer(args.chain_id, axionax_tx::parse_address(to)?, amount)
+        .with_nonce(nonce)
+        .with_gas_limit(gas)
+        .with_gas_price(gas_price)
+        .sign(&key)?;
+    let tx_hash = rpc
+        .send_raw_transaction(&signed.raw_hex())
+        .await
+        .map_err(|e| anyhow!("Failed to send the sweep: {:?}", e))?;
+    Ok(Some((tx_hash, amount)))
+}
+
+/// Poll until the transaction is mined, failing if it reverted
+async fn wait_for_receipt(rpc: &RpcClient, tx_hash: &str, timeout: Duration) -> anyhow::Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match rpc.get_transaction_receipt(tx_hash).await {
+            Ok(Some(receipt)) if receipt["status"] == "0x1" => return Ok(()),
+            Ok(Some(receipt)) => bail!("The sweep {} failed: {}", tx_hash, receipt),
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to read the sweep receipt, retrying: {:?}", e),
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "Timed out after {}s waiting for the sweep {}; rerun to resume",
+                timeout.as_secs(),
+                tx_hash
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// `<file>.<extension>`, next to `file`
+fn sibling(file: &Path, extension: &str) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// Hash and amount of the sweep recorded in `file`, if one was sent
+fn read_sweep(file: &Path) -> anyhow::Result<Option<(String, U256)>> {
+    if !file.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(file)?;
+    let (tx_hash, amount) = contents
+        .trim()
+        .split_once(' ')
+        .and_then(|(hash, amount)| Some((hash, U256::from_dec_str(amount).ok()?)))
+        .ok_or_else(|| anyhow!("Invalid sweep in {}: {:?}", file.display(), contents))?;
+    Ok(Some((tx_hash.to_string(), amount)))
+}
+
+/// Write `contents` readable by the owner only, replacing `file` in one rename
+fn write_private(file: &Path, contents: &str) -> anyhow::Result<()> {
+    let tmp = sibling(file, "tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut out = options.open(&tmp)?;
+    writeln!(out, "{}", contents)?;
+    out.sync_all()?;
+    std::fs::rename(&tmp, file)?;
+    Ok(())
+}
@@ -0,0 +1,278 @@
+//! JSON-RPC client for the faucet, on top of the shared `axionax-rpc` client
+
+use async_trait::async_trait;
+use axionax_rpc::middleware::{Request, Tracing};
+use axionax_rpc::{Client, Http, Middleware, Retry, Transport, U256};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::chaos::Chaos;
+use crate::mock_chain::MockChain;
+use crate::FaucetError;
+
+pub use axionax_rpc::parse_quantity;
+
+/// Default endpoint when neither `RPC_URLS` nor `RPC_URL` is set
+const DEFAULT_RPC_URL: &str = "http://localhost:8545";
+
+/// JSON-RPC client with failover between endpoints
+#[derive(Clone)]
+pub struct RpcClient {
+    client: Client,
+    /// In-process chain answering calls instead of the endpoint (dry-run mode)
+    mock: Option<Arc<MockChain>>,
+}
+
+/// Client over `transports` with the faucet's middleware, so the mock chain
+/// is traced and reported like a real node
+fn client(
+    transports: Vec<Arc<dyn Transport>>,
+    retry: Retry,
+    observer: Option<Arc<dyn Middleware>>,
+    chaos: Option<&Chaos>,
+) -> Client {
+    let mut builder = Client::builder()
+        .retry(retry)
+        .middleware(Arc::new(Tracing))
+        .middleware(Arc::new(ReportFailures));
+    for transport in transports {
+        builder = builder.transport(match chaos {
+            Some(chaos) => chaos.transport(transport),
+            None => transport,
+        });
+    }
+    if let Some(observer) = observer {
+        builder = builder.middleware(observer);
+    }
+    builder.build()
+}
+
+impl From<axionax_rpc::Error> for FaucetError {
+    fn from(err: axionax_rpc::Error) -> Self {
+        FaucetError::Service(err.into())
+    }
+}
+
+/// RPC endpoints, tried in order, and how failed calls are retried
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    pub urls: Vec<String>,
+    pub retry: Retry,
+}
+
+impl Endpoints {
+    /// A single endpoint, without retries
+    pub fn single(url: &str) -> Self {
+        Self {
+            urls: vec![url.to_string()],
+            retry: Retry::NONE,
+        }
+    }
+
+    /// Load the comma-separated `RPC_URLS` (or a single `RPC_URL`) and the
+    /// `RPC_RETRIES` / `RPC_BACKOFF_MS` retry policy
+    pub fn from_env() -> anyhow::Result<Self> {
+        let urls: Vec<String> = match std::env::var("RPC_URLS") {
+            Ok(urls) => urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(_) => {
+                vec![std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string())]
+            }
+        };
+        if urls.is_empty() {
+            anyhow::bail!("RPC_URLS is set but lists no endpoint");
+        }
+        let var = |name: &str, default: u64| -> anyhow::Result<u64> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid {}: {:?}", name, value)),
+                Err(_) => Ok(default),
+            }
+        };
+        let backoff = Duration::from_millis(var("RPC_BACKOFF_MS", 250)?);
+        Ok(Self {
+            urls,
+            retry: Retry {
+                retries: var("RPC_RETRIES", 2)? as usize,
+                backoff,
+                max_backoff: backoff * 8,
+            },
+        })
+    }
+}
+
+/// Posts failed calls to the error report webhook
+struct ReportFailures;
+
+impl Middleware for ReportFailures {
+    fn on_response(
+        &self,
+        request: &Request,
+        _elapsed: Duration,
+        result: Result<&Value, &axionax_rpc::Error>,
+    ) {
+        if let Err(e) = result {
+            crate::error_report::rpc_failure(request.method, &e.to_string());
+        }
+    }
+}
+
+/// Serves calls from the mock chain
+struct MockTransport {
+    chain: Arc<MockChain>,
+    /// Simulated round trip of every call
+    latency: Duration,
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    fn endpoint(&self) -> &str {
+        "mock"
+    }
+
+    async fn send(&self, method: &str, params: &Value) -> Result<Value, axionax_rpc::Error> {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        self.chain.call(method, params)
+    }
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self::observed(&Endpoints::single(url), None, None)
+    }
+
+    /// Client whose call latencies are also reported to `observer`, with
+    /// faults injected by `chaos`
+    pub fn observed(
+        endpoints: &Endpoints,
+        observer: Option<Arc<dyn Middleware>>,
+        chaos: Option<&Chaos>,
+    ) -> Self {
+        let http = reqwest::Client::new();
+        let transports = endpoints
+            .urls
+            .iter()
+            .map(|url| Arc::new(Http::new(url, http.clone(), None)) as Arc<dyn Transport>)
+            .collect();
+        Self {
+            client: client(transports, endpoints.retry, observer, chaos),
+            mock: None,
+        }
+    }
+
+    /// Client answered by an in-process mock chain, after `latency` per call
+    pub fn mock(
+        chain: MockChain,
+        latency: Duration,
+        retry: Retry,
+        observer: Option<Arc<dyn Middleware>>,
+        chaos: Option<&Chaos>,
+    ) -> Self {
+        let chain = Arc::new(chain);
+        let transport = Arc::new(MockTransport {
+            chain: chain.clone(),
+            latency,
+        });
+        Self {
+            client: client(vec![transport], retry, observer, chaos),
+            mock: Some(chain),
+        }
+    }
+
+    /// The mock chain, when running in dry-run mode
+    pub fn mock_chain(&self) -> Option<&MockChain> {
+        self.mock.as_deref()
+    }
+
+    /// Call a JSON-RPC method and decode its result
+    pub async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, FaucetError> {
+        Ok(self.client.call(method, params).await?)
+    }
+
+    /// Get the chain ID reported by the endpoint
+    pub async fn get_chain_id(&self) -> Result<u64, FaucetError> {
+        Ok(self.client.get_chain_id().await?)
+    }
+
+    /// Get the number of the latest block
+    pub async fn get_block_number(&self) -> Result<u64, FaucetError> {
+        Ok(self.client.get_block_number().await?)
+    }
+
+    /// Get the balance of an address in wei
+    pub async fn get_balance(&self, address: &str) -> Result<U256, FaucetError> {
+        Ok(self.client.get_balance(address).await?)
+    }
+
+    /// Get the number of transactions sent from an address
+    pub async fn get_transaction_count(&self, address: &str) -> Result<u64, FaucetError> {
+        Ok(self.client.get_transaction_count(address).await?)
+    }
+
+    /// Next nonce of an address, counting its transactions still in the pool
+    pub async fn get_pending_nonce(&self, address: &str) -> Result<u64, FaucetError> {
+        Ok(self.client.get_pending_nonce(address).await?)
+    }
+
+    /// Get the current gas price in wei
+    pub async fn get_gas_price(&self) -> Result<U256, FaucetError> {
+        Ok(self.client.get_gas_price().await?)
+    }
+
+    /// Estimate the gas used by a plain value transfer
+    pub async fn estimate_gas(
+        &self,
+        from: &str,
+        to: &str,
+        value: U256,
+    ) -> Result<u64, FaucetError> {
+        Ok(self.client.estimate_gas(from, to, value).await?)
+    }
+
+    /// Whether an address has contract code deployed
+    pub async fn is_contract(&self, address: &str) -> Result<bool, FaucetError> {
+        Ok(self.client.is_contract(address).await?)
+    }
+
+    /// Submit a signed transaction and return its hash
+    pub async fn send_raw_transaction(&self, raw: &str) -> Result<String, FaucetError> {
+        Ok(self.client.send_raw_transaction(raw).await?)
+    }
+
+    /// Transaction by hash; `None` when the node doesn't know it
+    pub async fn get_transaction_by_hash(&self, hash: &str) -> Result<Option<Value>, FaucetError> {
+        Ok(self.client.get_transaction_by_hash(hash).await?)
+    }
+
+    /// Receipt of a transaction; `None` until it is mined
+    pub async fn get_transaction_receipt(&self, hash: &str) -> Result<Option<Value>, FaucetError> {
+        Ok(self.client.get_transaction_receipt(hash).await?)
+    }
+
+    /// Block by number, with full transactions or only their hashes
+    pub async fn get_block_by_number(
+        &self,
+        number: u64,
+        full: bool,
+    ) -> Result<Option<Value>, FaucetError> {
+        Ok(self.client.get_block_by_number(number, full).await?)
+    }
+
+    /// Logs matching an `eth_getLogs` filter object
+    pub async fn get_logs(&self, filter: &Value) -> Result<Vec<Value>, FaucetError> {
+        Ok(self.client.get_logs(filter).await?)
+    }
+}
@@ -0,0 +1,782 @@
+//! Embedded scheduler for periodic maintenance jobs
+//!
+//! Each job runs on its own interval, overridable with
+//! `JOB_<NAME>_SECS` (e.g. `JOB_COMPACT_LEDGER_SECS`); `0` disables a job.
+
+use async_trait::async_trait;
+use axionax_faucet::validation::parse_amount;
+use axionax_notify::{Event, Severity};
+use axionax_tx::U256;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+use tracing::{debug, error, info, warn};
+
+use crate::alerts;
+use crate::aliases;
+use crate::analytics;
+use crate::badges;
+use crate::claims;
+use crate::cluster;
+use crate::ledger::{to_unix, EntryKind};
+use crate::tasks::create_address;
+use crate::{
+    axx, axx_to_wei, current_stats, publish_stats, FaucetError, FaucetState, COOLDOWN_HOURS,
+};
+
+const HOUR: u64 = 3600;
+const DAY: u64 = 24 * HOUR;
+
+/// A periodic maintenance job
+#[async_trait]
+pub trait Job: Send + Sync + 'static {
+    /// Job name, used in logs and the `JOB_<NAME>_SECS` override
+    fn name(&self) -> &'static str;
+
+    /// Interval used when no override is set
+    fn default_interval(&self) -> Duration;
+
+    /// Whether the first run happens at startup rather than one interval later
+    fn run_at_startup(&self) -> bool {
+        false
+    }
+
+    /// Whether only the cluster leader runs the job
+    fn leader_only(&self) -> bool {
+        false
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError>;
+}
+
+/// Spawn every configured maintenance job
+pub fn spawn(state: FaucetState) -> anyhow::Result<()> {
+    let mut jobs: Vec<Box<dyn Job>> = vec![
+        Box::new(PruneRateLimits),
+        Box::new(CompactLedger::from_env()?),
+        Box::new(OptimizeLedger),
+        Box::new(RefreshBalance::from_env()?),
+        Box::new(VerifyChainId),
+        Box::new(RefreshLeaderboard::from_env()?),
+        Box::new(LinkAliases),
+        Box::new(ConfirmClaims),
+        Box::new(PublishStats),
+    ];
+    if let Some(job) = DailySummary::from_env() {
+        jobs.push(Box::new(job));
+    }
+    if let Some(job) = Heartbeat::from_env()? {
+        jobs.push(Box::new(job));
+    }
+    if let Some(job) = CheckRunway::from_env()? {
+        jobs.push(Box::new(job));
+    }
+    if state.badges.is_some() {
+        jobs.push(Box::new(MintBadges));
+    }
+    if state.config.is_remote() {
+        jobs.push(Box::new(FetchConfigBundle));
+    }
+    if let Some(cluster) = state.cluster.as_ref() {
+        jobs.push(Box::new(RenewLease {
+            interval: cluster.ttl / 3,
+        }));
+        jobs.push(Box::new(SyncFlagOverrides));
+    }
+
+    for job in jobs {
+        let var = format!("JOB_{}_SECS", job.name().to_uppercase());
+        let interval = match std::env::var(&var) {
+            Ok(secs) => Duration::from_secs(
+                secs.parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid {}", var))?,
+            ),
+            Err(_) => job.default_interval(),
+        };
+        if interval.is_zero() {
+            info!("   Job '{}' disabled", job.name());
+            continue;
+        }
+
+        let state = state.clone();
+        tokio::spawn(async move {
+            let start = if job.run_at_startup() {
+                Instant::now()
+            } else {
+                Instant::now() + interval
+            };
+            let mut ticker = interval_at(start, interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if job.leader_only() && !cluster::is_sender(&state) {
+                    continue;
+                }
+                debug!("Running job '{}'", job.name());
+                if let Err(e) = job.run(&state).await {
+                    warn!("Job '{}' failed: {:?}", job.name(), e);
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Take or renew the sender lease shared with other replicas
+struct RenewLease {
+    interval: Duration,
+}
+
+#[async_trait]
+impl Job for RenewLease {
+    fn name(&self) -> &'static str {
+        "renew_lease"
+    }
+
+    fn default_interval(&self) -> Duration {
+        self.interval
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        cluster::renew(state).await
+    }
+}
+
+/// Drop in-memory rate-limit entries older than the cooldown window
+struct PruneRateLimits;
+
+#[async_trait]
+impl Job for PruneRateLimits {
+    fn name(&self) -> &'static str {
+        "prune_rate_limits"
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(600)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let cutoff = SystemTime::now() - Duration::from_secs(COOLDOWN_HOURS * HOUR);
+
+        let mut address_requests = state.address_requests.write().await;
+        address_requests.retain(|_, last| *last > cutoff);
+        drop(address_requests);
+
+        // Limiter state is only needed until the client's bucket is full again
+        let now = SystemTime::now();
+        state.ip_requests.write().await.retain(|_, tat| *tat > now);
+        state.route_limits.prune();
+        Ok(())
+    }
+}
+
+/// Re-check the RPC chain ID, pausing drips while it doesn't match `CHAIN_ID`
+///
+/// Catches endpoints swapped to another network behind a load balancer.
+struct VerifyChainId;
+
+#[async_trait]
+impl Job for VerifyChainId {
+    fn name(&self) -> &'static str {
+        "verify_chain_id"
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let chain_id = state.rpc.get_chain_id().await?;
+        let matches = chain_id == state.chain_id;
+        let was_verified = state.chain_verified.swap(matches, Ordering::Relaxed);
+
+        if !matches {
+            let message = format!(
+                "chain ID mismatch: expected {}, RPC reports {}",
+                state.chain_id, chain_id
+            );
+            error!("{}, drips paused", message);
+            crate::error_report::rpc_failure("eth_chainId", &message);
+            if was_verified {
+                let event = Event::new(
+                    alerts::CHAIN_MISMATCH,
+                    Severity::Critical,
+                    "RPC endpoint is on the wrong chain, drips paused",
+                )
+                .with_field("expected", state.chain_id)
+                .with_field("reported", chain_id);
+                alerts::send(state, event);
+            }
+        } else if !was_verified {
+            info!("Chain ID {} verified, drips resumed", chain_id);
+        }
+        Ok(())
+    }
+}
+
+/// Prune old request outcomes and compact the ledger database
+struct CompactLedger {
+    /// How long request outcomes are kept
+    retention: Duration,
+}
+
+impl CompactLedger {
+    /// Retention from `LEDGER_RETENTION_DAYS` (default 90)
+    fn from_env() -> anyhow::Result<Self> {
+        let days = std::env::var("LEDGER_RETENTION_DAYS").unwrap_or_else(|_| "90".to_string());
+        let days: u64 = days
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid LEDGER_RETENTION_DAYS: {:?}", days))?;
+        Ok(Self {
+            retention: Duration::from_secs(days * DAY),
+        })
+    }
+}
+
+#[async_trait]
+impl Job for CompactLedger {
+    fn name(&self) -> &'static str {
+        "compact_ledger"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(DAY)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let ledger = state.ledger.lock().await;
+        let pruned = ledger.prune_requests(SystemTime::now() - self.retention)?;
+        ledger.compact()?;
+        info!("Compacted ledger, pruned {} request records", pruned);
+        Ok(())
+    }
+}
+
+/// Keep query plans current and the write-ahead log short between compactions
+struct OptimizeLedger;
+
+#[async_trait]
+impl Job for OptimizeLedger {
+    fn name(&self) -> &'static str {
+        "optimize_ledger"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(HOUR)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        state.ledger.lock().await.optimize()
+    }
+}
+
+/// Refresh the cached faucet balance shown in `/stats`, and notify when it
+/// drops below `LOW_BALANCE_AXX`
+struct RefreshBalance {
+    threshold: Option<U256>,
+    /// Whether the last refresh was below the threshold, so each drop
+    /// notifies once
+    low: AtomicBool,
+}
+
+impl RefreshBalance {
+    fn from_env() -> anyhow::Result<Self> {
+        let threshold = match std::env::var("LOW_BALANCE_AXX") {
+            Ok(value) => Some(
+                parse_amount(&value)
+                    .map_err(|e| anyhow::anyhow!("Invalid LOW_BALANCE_AXX {:?}: {}", value, e))?,
+            ),
+            Err(_) => None,
+        };
+        Ok(Self {
+            threshold,
+            low: AtomicBool::new(false),
+        })
+    }
+}
+
+#[async_trait]
+impl Job for RefreshBalance {
+    fn name(&self) -> &'static str {
+        "refresh_balance"
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn run_at_startup(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let balance = state.rpc.get_balance(&state.address).await?;
+        *state.balance.write().await = Some(balance);
+
+        let Some(threshold) = self.threshold else {
+            return Ok(());
+        };
+        let low = balance < threshold;
+        let was_low = self.low.swap(low, Ordering::Relaxed);
+        if low && !was_low && cluster::is_sender(state) {
+            warn!(
+                "Faucet balance {} is below {}",
+                axx(balance),
+                axx(threshold)
+            );
+            let event = Event::new(alerts::LOW_BALANCE, Severity::Warning, "Faucet balance low")
+                .with_field("balance", axx(balance))
+                .with_field("threshold", axx(threshold))
+                .with_field("address", &state.address);
+            alerts::send(state, event);
+        }
+        Ok(())
+    }
+}
+
+/// Notify when the projected runway of the balance drops below a threshold
+struct CheckRunway {
+    threshold_days: f64,
+    /// Whether the last check was below the threshold, so each drop notifies once
+    low: AtomicBool,
+}
+
+impl CheckRunway {
+    fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(days) = std::env::var("RUNWAY_ALERT_DAYS") else {
+            return Ok(None);
+        };
+        let threshold_days = days
+            .parse()
+            .ok()
+            .filter(|days: &f64| *days > 0.0)
+            .ok_or_else(|| anyhow::anyhow!("Invalid RUNWAY_ALERT_DAYS"))?;
+        Ok(Some(Self {
+            threshold_days,
+            low: AtomicBool::new(false),
+        }))
+    }
+}
+
+#[async_trait]
+impl Job for CheckRunway {
+    fn name(&self) -> &'static str {
+        "check_runway"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(HOUR)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let runway = analytics::forecast(state, analytics::DEFAULT_RUNWAY_WINDOW_DAYS).await?;
+        let Some(days) = runway.runway_days else {
+            self.low.store(false, Ordering::Relaxed);
+            return Ok(());
+        };
+        let low = days < self.threshold_days;
+        let was_low = self.low.swap(low, Ordering::Relaxed);
+        if low && !was_low {
+            warn!(
+                "Faucet runway {:.1} days is below {} days",
+                days, self.threshold_days
+            );
+            let event = Event::new(alerts::LOW_RUNWAY, Severity::Warning, "Faucet runway low")
+                .with_field("runway_days", format!("{:.1}", days))
+                .with_field("threshold_days", self.threshold_days)
+                .with_field("balance", &runway.balance)
+                .with_field("burn_per_day", &runway.burn_per_day);
+            alerts::send(state, event);
+        }
+        Ok(())
+    }
+}
+
+/// Count transactions and contract deployments of recent drip recipients
+struct RefreshLeaderboard {
+    /// Recipients first dripped longer ago than this are no longer refreshed
+    window: Duration,
+}
+
+impl RefreshLeaderboard {
+    /// Most nonces checked for contract deployments per recipient and run
+    const MAX_SCAN: u64 = 64;
+
+    /// Window from `LEADERBOARD_DAYS` (default 30)
+    fn from_env() -> anyhow::Result<Self> {
+        let days = std::env::var("LEADERBOARD_DAYS").unwrap_or_else(|_| "30".to_string());
+        let days: u64 = days
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid LEADERBOARD_DAYS: {:?}", days))?;
+        Ok(Self {
+            window: Duration::from_secs(days * DAY),
+        })
+    }
+}
+
+#[async_trait]
+impl Job for RefreshLeaderboard {
+    fn name(&self) -> &'static str {
+        "refresh_leaderboard"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(600)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let since = SystemTime::now() - self.window;
+        let recipients = state.ledger.lock().await.recipient_activity(since)?;
+
+        for mut activity in recipients {
+            let nonce = state.rpc.get_transaction_count(&activity.address).await?;
+            let scan_to = nonce.min(activity.scanned_nonce + Self::MAX_SCAN);
+            let mut deployed = 0;
+            for n in activity.scanned_nonce..scan_to {
+                let address = create_address(&activity.address, n);
+                if state.rpc.is_contract(&address).await? {
+                    deployed += 1;
+                }
+            }
+
+            let transactions = nonce.saturating_sub(activity.nonce_at_drip);
+            if transactions == activity.transactions && scan_to == activity.scanned_nonce {
+                continue;
+            }
+            activity.transactions = transactions;
+            activity.contracts += deployed;
+            activity.scanned_nonce = scan_to;
+            state
+                .ledger
+                .lock()
+                .await
+                .update_recipient_activity(&activity)?;
+        }
+        Ok(())
+    }
+}
+
+/// Link faucet addresses to the wallets funding them or funded by them
+struct LinkAliases;
+
+#[async_trait]
+impl Job for LinkAliases {
+    fn name(&self) -> &'static str {
+        "link_aliases"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn run_at_startup(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        aliases::link_funding(state).await
+    }
+}
+
+/// Record whether recent claims' transactions confirmed or failed
+struct ConfirmClaims;
+
+#[async_trait]
+impl Job for ConfirmClaims {
+    fn name(&self) -> &'static str {
+        "confirm_claims"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(2)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let result = claims::confirm(state).await;
+        // A failed cycle still shows the loop is alive; only a hang is a stall
+        state.worker_cycle.store(unix_now(), Ordering::Relaxed);
+        result
+    }
+}
+
+/// Mint the badges queued by first drips
+struct MintBadges;
+
+#[async_trait]
+impl Job for MintBadges {
+    fn name(&self) -> &'static str {
+        "mint_badges"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(30)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        badges::mint_queued(state).await
+    }
+}
+
+/// Push changed stats to `/stats/stream` subscribers
+struct PublishStats;
+
+#[async_trait]
+impl Job for PublishStats {
+    fn name(&self) -> &'static str {
+        "publish_stats"
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(2)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        if state.stats_updates.receiver_count() == 0 {
+            return Ok(());
+        }
+        publish_stats(state, current_stats(state).await?);
+        Ok(())
+    }
+}
+
+/// Post a daily activity summary to `SUMMARY_WEBHOOK_URL`
+struct DailySummary {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl DailySummary {
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            url: std::env::var("SUMMARY_WEBHOOK_URL").ok()?,
+            http: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Job for DailySummary {
+    fn name(&self) -> &'static str {
+        "daily_summary"
+    }
+
+    fn leader_only(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(DAY)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let window = self.default_interval().as_secs() as i64;
+        let since = to_unix(SystemTime::now()) - window;
+        // The whole day as a single bucket
+        let (requests, distributed) = {
+            let ledger = state.ledger.lock().await;
+            let requests = ledger.request_buckets(since, window)?;
+            let distributed = ledger
+                .transfer_buckets(EntryKind::Drip, since, window)?
+                .into_values()
+                .fold(U256::zero(), U256::saturating_add);
+            (requests, distributed)
+        };
+        let grants: u64 = requests.iter().map(|b| b.grants).sum();
+        let requests: u64 = requests.iter().map(|b| b.requests).sum();
+        let rejects = requests - grants;
+        let balance = *state.balance.read().await;
+
+        let text = format!(
+            "Faucet daily summary: {} requests, {} grants, {} rejects, {} distributed, balance {}",
+            requests,
+            grants,
+            rejects,
+            axx(distributed),
+            balance.map_or_else(|| "unknown".to_string(), axx),
+        );
+        let body = json!({
+            "text": text,
+            "requests": requests,
+            "grants": grants,
+            "rejects": rejects,
+            "distributed_axx": (distributed / axx_to_wei(1)).to_string(),
+            "balance_axx": balance.map(|b| (b / axx_to_wei(1)).to_string()),
+        });
+
+        self.http
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| axionax_errors::Error::internal(format!("summary webhook: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Ping `HEARTBEAT_URL` while the claim confirmation loop keeps cycling
+///
+/// Healthchecks.io-style: the URL is hit on every run, so a dead process goes
+/// quiet, and `<url>/fail` is hit instead when the loop hasn't completed a
+/// cycle within `HEARTBEAT_MAX_STALL_SECS`.
+struct Heartbeat {
+    url: String,
+    max_stall: Duration,
+    http: reqwest::Client,
+    /// Whether the last run found the loop stalled, so each stall notifies once
+    stalled: AtomicBool,
+}
+
+impl Heartbeat {
+    fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(url) = std::env::var("HEARTBEAT_URL") else {
+            return Ok(None);
+        };
+        let max_stall = match std::env::var("HEARTBEAT_MAX_STALL_SECS") {
+            Ok(secs) => Duration::from_secs(
+                secs.parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid HEARTBEAT_MAX_STALL_SECS"))?,
+            ),
+            Err(_) => Duration::from_secs(120),
+        };
+        Ok(Some(Self {
+            url: url.trim_end_matches('/').to_string(),
+            max_stall,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()?,
+            stalled: AtomicBool::new(false),
+        }))
+    }
+}
+
+#[async_trait]
+impl Job for Heartbeat {
+    fn name(&self) -> &'static str {
+        "heartbeat"
+    }
+
+    fn run_at_startup(&self) -> bool {
+        true
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        // Followers don't run the loop, so only the sender can be stalled
+        let idle = unix_now().saturating_sub(state.worker_cycle.load(Ordering::Relaxed));
+        let stalled = cluster::is_sender(state) && idle > self.max_stall.as_secs();
+
+        let was_stalled = self.stalled.swap(stalled, Ordering::Relaxed);
+        if stalled && !was_stalled {
+            error!(
+                "Claim confirmation loop has not completed a cycle in {}s",
+                idle
+            );
+            alerts::send(
+                state,
+                Event::new(
+                    alerts::WORKER_STALLED,
+                    Severity::Critical,
+                    "Faucet worker stalled",
+                )
+                .with_field("last_cycle_secs_ago", idle.to_string())
+                .with_field("address", state.address.clone()),
+            );
+        } else if !stalled && was_stalled {
+            info!("Claim confirmation loop is cycling again");
+        }
+
+        let request = if stalled {
+            self.http
+                .post(format!("{}/fail", self.url))
+                .body(format!("claim confirmation loop idle for {}s", idle))
+        } else {
+            self.http.get(&self.url)
+        };
+        request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| axionax_errors::Error::internal(format!("heartbeat: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Fetch and apply the config bundle from `CONFIG_BUNDLE_URL`
+struct FetchConfigBundle;
+
+#[async_trait]
+impl Job for FetchConfigBundle {
+    fn name(&self) -> &'static str {
+        "fetch_config_bundle"
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(5 * 60)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        state.config.refresh(state).await?;
+        Ok(())
+    }
+}
+
+/// Pick up flag overrides set through another replica's admin API
+struct SyncFlagOverrides;
+
+#[async_trait]
+impl Job for SyncFlagOverrides {
+    fn name(&self) -> &'static str {
+        "sync_flag_overrides"
+    }
+
+    fn default_interval(&self) -> Duration {
+        Duration::from_secs(30)
+    }
+
+    async fn run(&self, state: &FaucetState) -> Result<(), FaucetError> {
+        let overrides = state.ledger.lock().await.flag_overrides()?;
+        state.config.set_overrides(overrides);
+        Ok(())
+    }
+}
+
+/// Seconds since the Unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,172 @@
+//! Rhai scripting hook for custom grant rules
+//!
+//! The script defines `fn decide(req)`, where `req` is a map describing the
+//! request:
+//!
+//! ```text
+//! #{
+//!     address, ip, country, asn, amount,      // amount in whole AXX
+//!     history: #{ drips, total_received, last_drip },
+//!     onchain: #{ balance, nonce },
+//!     labels,                                 // operator labels, e.g. ["partner"]
+//! }
+//! ```
+//!
+//! and returns either a bool (allow/deny) or a map
+//! `#{ allow: bool, amount: int, reason: string }` where `amount` (whole AXX)
+//! and `reason` are optional. `amount` can lower the drip but not raise it
+//! above `req.amount`.
+
+use async_trait::async_trait;
+use axionax_tx::U256;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+use crate::ledger::to_unix;
+use crate::policy::{EligibilityCheck, Grant};
+use crate::{axx_to_wei, FaucetError, FaucetState, RequestContext};
+
+/// Upper bound on operations per script run, so a broken script can't hang requests
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Policy stage delegating the decision to an operator script
+pub struct ScriptStage {
+    engine: Engine,
+    ast: AST,
+}
+
+impl ScriptStage {
+    /// Compile the script at `POLICY_SCRIPT`
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(path) = std::env::var("POLICY_SCRIPT") else {
+            return Ok(None);
+        };
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile_file(path.clone().into())
+            .map_err(|e| anyhow::anyhow!("Invalid POLICY_SCRIPT {}: {}", path, e))?;
+
+        Ok(Some(Self { engine, ast }))
+    }
+
+    /// Build the `req` map passed to `decide`
+    async fn request_map(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &Grant,
+    ) -> Result<Map, FaucetError> {
+        let (drips, labels) = {
+            let ledger = state.ledger.lock().await;
+            (ledger.drips_to(&ctx.address)?, ledger.labels(&ctx.address)?)
+        };
+        let balance = state.rpc.get_balance(&ctx.address).await?;
+        let nonce = state.rpc.get_transaction_count(&ctx.address).await?;
+
+        let mut history = Map::new();
+        history.insert("drips".into(), (drips.len() as i64).into());
+        let received = drips
+            .iter()
+            .fold(U256::zero(), |sum, d| sum.saturating_add(d.amount));
+        history.insert("total_received".into(), axx(received).into());
+        history.insert(
+            "last_drip".into(),
+            drips
+                .last()
+                .map(|d| Dynamic::from(to_unix(d.timestamp)))
+                .unwrap_or(Dynamic::UNIT),
+        );
+
+        let mut onchain = Map::new();
+        onchain.insert("balance".into(), axx(balance).into());
+        onchain.insert("nonce".into(), (nonce as i64).into());
+
+        let mut req = Map::new();
+        req.insert("address".into(), ctx.address.clone().into());
+        req.insert("ip".into(), ctx.ip.to_string().into());
+        req.insert(
+            "country".into(),
+            ctx.geo
+                .country
+                .clone()
+                .map(Dynamic::from)
+                .unwrap_or(Dynamic::UNIT),
+        );
+        req.insert(
+            "asn".into(),
+            ctx.geo
+                .asn
+                .map(|asn| Dynamic::from(i64::from(asn)))
+                .unwrap_or(Dynamic::UNIT),
+        );
+        req.insert("amount".into(), axx(grant.amount).into());
+        req.insert("history".into(), history.into());
+        req.insert("onchain".into(), onchain.into());
+        req.insert(
+            "labels".into(),
+            labels
+                .into_iter()
+                .map(|l| Dynamic::from(l.label))
+                .collect::<Array>()
+                .into(),
+        );
+        Ok(req)
+    }
+}
+
+#[async_trait]
+impl EligibilityCheck for ScriptStage {
+    fn name(&self) -> &'static str {
+        "script"
+    }
+
+    async fn check(
+        &self,
+        state: &FaucetState,
+        ctx: &RequestContext,
+        grant: &mut Grant,
+    ) -> Result<(), FaucetError> {
+        let req = self.request_map(state, ctx, grant).await?;
+        let result: Dynamic = self
+            .engine
+            .call_fn(&mut Scope::new(), &self.ast, "decide", (req,))
+            .map_err(|e| {
+                axionax_errors::Error::internal(format!("policy script: {}", e))
+                    .with_code("policy_script_error")
+            })?;
+
+        if let Some(allow) = result.clone().try_cast::<bool>() {
+            return if allow {
+                Ok(())
+            } else {
+                Err(FaucetError::Denied("denied by policy script".to_string()))
+            };
+        }
+
+        let decision = result.try_cast::<Map>().ok_or_else(|| {
+            axionax_errors::Error::internal("policy script must return a bool or a map")
+                .with_code("policy_script_error")
+        })?;
+        let allow = decision
+            .get("allow")
+            .and_then(|v| v.as_bool().ok())
+            .unwrap_or(false);
+        if !allow {
+            let reason = decision
+                .get("reason")
+                .and_then(|v| v.clone().into_string().ok())
+                .unwrap_or_else(|| "denied by policy script".to_string());
+            return Err(FaucetError::Denied(reason));
+        }
+        if let Some(amount) = decision.get("amount").and_then(|v| v.as_int().ok()) {
+            grant.amount = grant.amount.min(axx_to_wei(amount.max(0) as u64));
+        }
+        Ok(())
+    }
+}
+
+/// Whole AXX in an amount of wei, saturated to Rhai's integer range
+fn axx(wei: U256) -> i64 {
+    i64::try_from(wei / axx_to_wei(1)).unwrap_or(i64::MAX)
+}
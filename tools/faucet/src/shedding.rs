@@ -0,0 +1,167 @@
+//! Load shedding while the RPC endpoint is slow
+//!
+//! Every JSON-RPC call's latency is recorded. While the p95 over the last
+//! `SHED_WINDOW_SECS` exceeds `SHED_RPC_P95_MS`, `/request` turns away
+//! `SHED_PERCENT` percent of requests with `503` and `Retry-After`, instead of
+//! letting all of them queue behind the node and time out. Requests with a
+//! verified partner referral are never shed. Shedding stops as soon as the
+//! p95 is back under the threshold; without `SHED_RPC_P95_MS` nothing is shed.
+
+use axionax_rpc::middleware::Request;
+use axionax_rpc::Middleware;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Most recent calls kept for the p95
+const MAX_SAMPLES: usize = 1024;
+
+/// Calls needed in the window before the p95 is trusted
+const MIN_SAMPLES: usize = 10;
+
+/// Latency samples and shedding state
+pub struct LoadShedder {
+    threshold: Option<Duration>,
+    /// Share of non-priority requests shed, in percent
+    percent: u64,
+    window: Duration,
+    retry_after: Duration,
+    /// Recent calls, oldest first
+    samples: Mutex<VecDeque<(Instant, Duration)>>,
+    shedding: AtomicBool,
+    p95_micros: AtomicU64,
+    /// Requests seen while shedding, to shed an exact share of them
+    seen: AtomicU64,
+    shed: AtomicU64,
+}
+
+/// Shedding state, as reported by `/readyz` and `/metrics`
+#[derive(Debug, Clone, Serialize)]
+pub struct SheddingStatus {
+    pub shedding: bool,
+    /// p95 RPC latency over the window, `null` with too few calls
+    pub rpc_p95_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_ms: Option<u64>,
+    pub percent: u64,
+    /// Requests shed since startup
+    pub shed_total: u64,
+}
+
+impl LoadShedder {
+    /// Load `SHED_RPC_P95_MS`, `SHED_PERCENT`, `SHED_WINDOW_SECS` and `SHED_RETRY_AFTER_SECS`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let var = |name: &str, default: u64| -> anyhow::Result<u64> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid {}: {:?}", name, value)),
+                Err(_) => Ok(default),
+            }
+        };
+        let threshold = match var("SHED_RPC_P95_MS", 0)? {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        };
+        let percent = var("SHED_PERCENT", 50)?;
+        if percent > 100 {
+            anyhow::bail!("Invalid SHED_PERCENT: {}, expected 0-100", percent);
+        }
+        Ok(Self {
+            threshold,
+            percent,
+            window: Duration::from_secs(var("SHED_WINDOW_SECS", 60)?.max(1)),
+            retry_after: Duration::from_secs(var("SHED_RETRY_AFTER_SECS", 30)?.max(1)),
+            samples: Mutex::new(VecDeque::new()),
+            shedding: AtomicBool::new(false),
+            p95_micros: AtomicU64::new(0),
+            seen: AtomicU64::new(0),
+            shed: AtomicU64::new(0),
+        })
+    }
+
+    /// Record the latency of one RPC call and re-evaluate shedding
+    pub fn record(&self, latency: Duration) {
+        let now = Instant::now();
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back((now, latency));
+        if samples.len() > MAX_SAMPLES {
+            samples.pop_front();
+        }
+        self.update(&mut samples, now);
+    }
+
+    /// Whether to turn this request away, and when the client should retry
+    pub fn shed(&self, priority: bool) -> Option<Duration> {
+        self.threshold?;
+        // Let stale samples age out even when no RPC calls are made
+        self.update(&mut self.samples.lock().unwrap(), Instant::now());
+        if priority || !self.shedding.load(Ordering::Relaxed) {
+            return None;
+        }
+        if self.seen.fetch_add(1, Ordering::Relaxed) % 100 >= self.percent {
+            return None;
+        }
+        self.shed.fetch_add(1, Ordering::Relaxed);
+        Some(self.retry_after)
+    }
+
+    /// Current shedding state
+    pub fn status(&self) -> SheddingStatus {
+        let micros = self.p95_micros.load(Ordering::Relaxed);
+        SheddingStatus {
+            shedding: self.shedding.load(Ordering::Relaxed),
+            rpc_p95_ms: (micros > 0).then_some(micros / 1000),
+            threshold_ms: self.threshold.map(|t| t.as_millis() as u64),
+            percent: self.percent,
+            shed_total: self.shed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Drop samples older than the window and recompute the p95
+    fn update(&self, samples: &mut VecDeque<(Instant, Duration)>, now: Instant) {
+        while samples
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > self.window)
+        {
+            samples.pop_front();
+        }
+        let p95 = (samples.len() >= MIN_SAMPLES).then(|| {
+            let mut latencies: Vec<Duration> = samples.iter().map(|(_, l)| *l).collect();
+            latencies.sort_unstable();
+            latencies[(latencies.len() * 95).div_ceil(100) - 1]
+        });
+        self.p95_micros.store(
+            p95.map_or(0, |p95| p95.as_micros().max(1) as u64),
+            Ordering::Relaxed,
+        );
+
+        let shedding = matches!((p95, self.threshold), (Some(p95), Some(t)) if p95 > t);
+        if self.shedding.swap(shedding, Ordering::Relaxed) != shedding {
+            let p95_ms = p95.unwrap_or_default().as_millis();
+            if shedding {
+                warn!(
+                    "RPC p95 latency is {} ms, shedding {}% of /request traffic",
+                    p95_ms, self.percent
+                );
+            } else {
+                info!("RPC p95 latency is {} ms, stopped shedding", p95_ms);
+            }
+        }
+    }
+}
+
+impl Middleware for LoadShedder {
+    fn on_response(
+        &self,
+        _request: &Request,
+        elapsed: Duration,
+        _result: Result<&Value, &axionax_rpc::Error>,
+    ) {
+        self.record(elapsed);
+    }
+}
@@ -0,0 +1,193 @@
+//! On-chain tasks that gate drips
+//!
+//! `TASKS_FILE` lists tasks a requester must have completed before a grant,
+//! each checked against the chain through the faucet's RPC endpoint:
+//!
+//! ```json
+//! [
+//!   { "id": "deploy", "description": "Deploy a contract", "kind": "deploy_contract" },
+//!   { "id": "active", "description": "Send 3 transactions", "kind": "transactions", "min": 3 },
+//!   {
+//!     "id": "swap", "description": "Swap on AxSwap", "kind": "event",
+//!     "contract": "0x...", "event": "Swap(address,uint256,uint256)", "topic": 1
+//!   }
+//! ]
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+
+use crate::rpc::RpcClient;
+use crate::FaucetError;
+use axionax_faucet::validation;
+
+/// Most recent nonces scanned for contract deployments
+///
+/// Only `CREATE` addresses of the requester's own transactions are checked,
+/// so a deployment followed by more than this many transactions, or one made
+/// through a factory or `CREATE2`, doesn't count.
+const MAX_DEPLOY_SCAN: u64 = 16;
+
+/// Qualifying on-chain action
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum TaskKind {
+    /// The requester deployed a contract within its last `MAX_DEPLOY_SCAN` transactions
+    DeployContract,
+    /// The requester sent at least `min` transactions
+    Transactions { min: u64 },
+    /// `contract` emitted `event` with the requester as indexed argument `topic` (1-3)
+    Event {
+        contract: String,
+        event: String,
+        topic: usize,
+        #[serde(default)]
+        from_block: u64,
+    },
+}
+
+/// A task from `TASKS_FILE`
+#[derive(Debug, Deserialize)]
+struct Task {
+    id: String,
+    description: String,
+    #[serde(flatten)]
+    kind: TaskKind,
+}
+
+/// Completion of a task by an address
+#[derive(Debug, Serialize)]
+pub struct TaskStatus {
+    pub id: String,
+    pub description: String,
+    pub completed: bool,
+}
+
+/// Tasks required before a grant
+pub struct Tasks {
+    tasks: Vec<Task>,
+}
+
+impl Tasks {
+    /// Load `TASKS_FILE`, a JSON array of tasks
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(path) = std::env::var("TASKS_FILE") else {
+            return Ok(None);
+        };
+
+        let tasks: Vec<Task> = serde_json::from_slice(&std::fs::read(path)?)?;
+        for task in &tasks {
+            if let TaskKind::Event {
+                contract, topic, ..
+            } = &task.kind
+            {
+                validation::parse_address(contract)
+                    .map_err(|e| anyhow::anyhow!("Invalid contract for task {}: {}", task.id, e))?;
+                if !(1..=3).contains(topic) {
+                    anyhow::bail!("Task {}: topic must be 1, 2 or 3", task.id);
+                }
+            }
+        }
+
+        Ok(Some(Self { tasks }))
+    }
+
+    /// Check every task for `address`
+    pub async fn status(
+        &self,
+        rpc: &RpcClient,
+        address: &str,
+    ) -> Result<Vec<TaskStatus>, FaucetError> {
+        let mut statuses = Vec::with_capacity(self.tasks.len());
+        for task in &self.tasks {
+            statuses.push(TaskStatus {
+                id: task.id.clone(),
+                description: task.description.clone(),
+                completed: task.kind.completed(rpc, address).await?,
+            });
+        }
+        Ok(statuses)
+    }
+}
+
+impl TaskKind {
+    async fn completed(&self, rpc: &RpcClient, address: &str) -> Result<bool, FaucetError> {
+        match self {
+            TaskKind::DeployContract => {
+                let nonce = rpc.get_transaction_count(address).await?;
+                for n in (nonce.saturating_sub(MAX_DEPLOY_SCAN)..nonce).rev() {
+                    if rpc.is_contract(&create_address(address, n)).await? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            TaskKind::Transactions { min } => Ok(rpc.get_transaction_count(address).await? >= *min),
+            TaskKind::Event {
+                contract,
+                event,
+                topic,
+                from_block,
+            } => {
+                let mut topics = vec![Value::Null; topic + 1];
+                topics[0] = json!(format!("0x{}", hex::encode(Keccak256::digest(event))));
+                topics[*topic] = json!(format!("0x{:0>64}", address.trim_start_matches("0x")));
+                let logs = rpc
+                    .get_logs(&json!({
+                        "address": contract,
+                        "topics": topics,
+                        "fromBlock": format!("0x{:x}", from_block),
+                        "toBlock": "latest",
+                    }))
+                    .await?;
+                Ok(!logs.is_empty())
+            }
+        }
+    }
+}
+
+/// Address of the contract `sender` creates with transaction `nonce`
+pub fn create_address(sender: &str, nonce: u64) -> String {
+    let sender = hex::decode(sender.trim_start_matches("0x")).unwrap_or_default();
+    let nonce_bytes: Vec<u8> = nonce
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|b| *b == 0)
+        .collect();
+
+    // rlp([sender, nonce])
+    let mut items = vec![0x80 + sender.len() as u8];
+    items.extend(&sender);
+    match nonce_bytes.as_slice() {
+        [] => items.push(0x80),
+        [b] if *b < 0x80 => items.push(*b),
+        bytes => {
+            items.push(0x80 + bytes.len() as u8);
+            items.extend(bytes);
+        }
+    }
+    let mut rlp = vec![0xc0 + items.len() as u8];
+    rlp.extend(items);
+
+    format!("0x{}", hex::encode(&Keccak256::digest(rlp)[12..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_address_matches_known_vectors() {
+        let sender = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+        let expected = [
+            "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d",
+            "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8",
+            "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91",
+            "0xfffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c",
+        ];
+        for (nonce, address) in expected.into_iter().enumerate() {
+            assert_eq!(create_address(sender, nonce as u64), address);
+        }
+    }
+}
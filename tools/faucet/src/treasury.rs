@@ -0,0 +1,148 @@
+//! Automatic faucet top-ups from a treasury wallet
+
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+use axionax_faucet::validation::parse_amount;
+use axionax_notify::{Event, Severity};
+use axionax_tx::U256;
+
+use crate::ledger::EntryKind;
+use crate::{alerts, branding, cluster, wallet};
+use crate::{axx, send_transaction, FaucetError, FaucetState};
+
+/// Treasury configuration, enabled when `TREASURY_PRIVATE_KEY` is set
+#[derive(Clone)]
+pub struct TreasuryConfig {
+    /// Treasury wallet private key
+    private_key: String,
+    /// Top up when the faucet balance drops below this amount (wei)
+    threshold: U256,
+    /// Amount sent per top-up (wei)
+    amount: U256,
+    /// Interval between balance checks
+    check_interval: Duration,
+    /// Minimum time between two top-ups, so pending transfers aren't repeated
+    cooldown: Duration,
+}
+
+impl TreasuryConfig {
+    /// Load treasury settings from the environment
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(private_key) = std::env::var("TREASURY_PRIVATE_KEY") else {
+            return Ok(None);
+        };
+        let axx_var = |name: &str, default: &str| -> anyhow::Result<U256> {
+            let value = std::env::var(name).unwrap_or_else(|_| default.to_string());
+            parse_amount(&value).map_err(|e| anyhow::anyhow!("Invalid {} {:?}: {}", name, value, e))
+        };
+        let secs_var = |name: &str, default: u64| -> anyhow::Result<u64> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid {}: {:?}", name, value)),
+                Err(_) => Ok(default),
+            }
+        };
+        let threshold = axx_var("TREASURY_THRESHOLD_AXX", "1000")?;
+        let amount = axx_var("TREASURY_TOPUP_AXX", "10000")?;
+        let check_interval = secs_var("TREASURY_CHECK_INTERVAL_SECS", 300)?;
+        let cooldown = secs_var("TREASURY_COOLDOWN_SECS", 3600)?;
+
+        Ok(Some(Self {
+            private_key,
+            threshold,
+            amount,
+            check_interval: Duration::from_secs(check_interval),
+            cooldown: Duration::from_secs(cooldown),
+        }))
+    }
+
+    /// Address of the treasury wallet
+    pub fn address(&self) -> anyhow::Result<String> {
+        wallet::address_from_private_key(&self.private_key)
+    }
+}
+
+/// Spawn the background task watching the faucet balance
+pub fn spawn(state: FaucetState, config: TreasuryConfig) {
+    info!(
+        "   Treasury: top up {} below {}",
+        axx(config.amount),
+        axx(config.threshold)
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.check_interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = check_and_top_up(&state, &config).await {
+                warn!("Treasury top-up failed: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Top up the faucet from the treasury if its balance is below the threshold
+async fn check_and_top_up(state: &FaucetState, config: &TreasuryConfig) -> Result<(), FaucetError> {
+    if !state.chain_verified.load(Ordering::Relaxed) {
+        return Err(FaucetError::ChainMismatch);
+    }
+    if !cluster::is_sender(state) {
+        return Ok(());
+    }
+    let balance = state.rpc.get_balance(&state.address).await?;
+    if balance >= config.threshold {
+        return Ok(());
+    }
+
+    if let Some(last) = state.ledger.lock().await.last(EntryKind::TopUp)? {
+        let elapsed = SystemTime::now()
+            .duration_since(last.timestamp)
+            .unwrap_or(Duration::ZERO);
+        if elapsed < config.cooldown {
+            return Ok(());
+        }
+    }
+
+    info!(
+        "Faucet balance {} below threshold, requesting treasury top-up",
+        axx(balance)
+    );
+
+    let tx_hash = send_transaction(
+        &state.rpc,
+        state.chain_id,
+        &config.private_key,
+        &state.address,
+        config.amount,
+    )
+    .await?;
+    state.ledger.lock().await.record(
+        EntryKind::TopUp,
+        &state.address,
+        config.amount,
+        &tx_hash,
+        None,
+    )?;
+
+    info!(
+        "✓ Treasury sent {} to faucet (tx: {})",
+        axx(config.amount),
+        tx_hash
+    );
+    let mut event = Event::new(
+        alerts::TOP_UP,
+        Severity::Info,
+        "Treasury topped up the faucet",
+    )
+    .with_field("amount", axx(config.amount))
+    .with_field("tx_hash", &tx_hash);
+    if let Some(url) = branding::tx_url(&tx_hash) {
+        event = event.with_field("explorer", url);
+    }
+    alerts::send(state, event);
+
+    Ok(())
+}
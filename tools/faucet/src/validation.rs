@@ -0,0 +1,404 @@
+//! Validation of untrusted input: addresses, amounts and JSON payloads
+//!
+//! Everything here is pure and panic-free on arbitrary input, and is
+//! exercised by the property tests in `tests/validation.rs` and the fuzz
+//! targets in `fuzz/`.
+
+use axionax_tx::amount::{self, AmountError};
+use axionax_tx::U256;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// Largest accepted request body
+pub const MAX_PAYLOAD_BYTES: usize = 16 * 1024;
+
+/// Deepest accepted JSON nesting
+pub const MAX_JSON_DEPTH: usize = 16;
+
+/// Longest raw address input considered at all (before trimming)
+pub const MAX_ADDRESS_INPUT: usize = 128;
+
+/// Longest name accepted for resolution
+pub const MAX_NAME_LEN: usize = 253;
+
+/// Longest amount string accepted (covers any 78-digit wei value with a unit)
+const MAX_AMOUNT_INPUT: usize = 96;
+
+/// Why an input was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    Empty,
+    TooLong,
+    /// Contains non-ASCII characters (e.g. unicode homoglyphs)
+    NonAscii,
+    MissingPrefix,
+    InvalidLength,
+    InvalidHex,
+    /// Mixed-case address whose EIP-55 checksum doesn't match
+    BadChecksum,
+    /// Name with empty, oversized or non `[a-z0-9-_]` labels
+    InvalidName,
+    InvalidAmount,
+    AmountOverflow,
+    PayloadTooLarge,
+    TooDeeplyNested,
+    InvalidJson(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Empty => write!(f, "empty input"),
+            ValidationError::TooLong => write!(f, "input too long"),
+            ValidationError::NonAscii => write!(f, "non-ASCII characters"),
+            ValidationError::MissingPrefix => write!(f, "missing 0x prefix"),
+            ValidationError::InvalidLength => write!(f, "address must be 20 bytes"),
+            ValidationError::InvalidHex => write!(f, "invalid hex digit"),
+            ValidationError::BadChecksum => write!(f, "invalid EIP-55 checksum"),
+            ValidationError::InvalidName => write!(f, "invalid name"),
+            ValidationError::InvalidAmount => write!(f, "invalid amount"),
+            ValidationError::AmountOverflow => write!(f, "amount too large"),
+            ValidationError::PayloadTooLarge => {
+                write!(f, "payload larger than {} bytes", MAX_PAYLOAD_BYTES)
+            }
+            ValidationError::TooDeeplyNested => {
+                write!(f, "JSON nested deeper than {} levels", MAX_JSON_DEPTH)
+            }
+            ValidationError::InvalidJson(e) => write!(f, "invalid JSON: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Parse an Ethereum address, returning it in lowercase `0x` form
+///
+/// Surrounding ASCII whitespace is ignored. All-lowercase and all-uppercase
+/// hex are accepted as is; mixed case must carry a valid EIP-55 checksum.
+pub fn parse_address(input: &str) -> Result<String, ValidationError> {
+    if input.len() > MAX_ADDRESS_INPUT {
+        return Err(ValidationError::TooLong);
+    }
+    if !input.is_ascii() {
+        return Err(ValidationError::NonAscii);
+    }
+    let input = input.trim_matches(|c: char| c.is_ascii_whitespace());
+    if input.is_empty() {
+        return Err(ValidationError::Empty);
+    }
+    let hex = input
+        .strip_prefix("0x")
+        .ok_or(ValidationError::MissingPrefix)?;
+    if hex.len() != 40 {
+        return Err(ValidationError::InvalidLength);
+    }
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ValidationError::InvalidHex);
+    }
+
+    let lower = hex.to_ascii_lowercase();
+    let mixed_case =
+        hex.bytes().any(|b| b.is_ascii_lowercase()) && hex.bytes().any(|b| b.is_ascii_uppercase());
+    if mixed_case && to_checksum_address(&lower)[2..] != *hex {
+        return Err(ValidationError::BadChecksum);
+    }
+    Ok(format!("0x{}", lower))
+}
+
+/// EIP-55 checksummed form of a valid address (with or without `0x`)
+pub fn to_checksum_address(address: &str) -> String {
+    let lower = address.trim_start_matches("0x").to_ascii_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if c.is_ascii_alphabetic() && nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+/// Whether input should be treated as a name (e.g. `alice.axx`) rather than an address
+pub fn looks_like_name(input: &str) -> bool {
+    let input = input.trim_matches(|c: char| c.is_ascii_whitespace());
+    input.contains('.') && !input.starts_with("0x")
+}
+
+/// Parse a dotted name into lowercase form
+///
+/// Only ASCII labels of `[a-z0-9-_]` are accepted, so names can't be spoofed
+/// with unicode lookalikes.
+pub fn parse_name(input: &str) -> Result<String, ValidationError> {
+    if !input.is_ascii() {
+        return Err(ValidationError::NonAscii);
+    }
+    let name = input
+        .trim_matches(|c: char| c.is_ascii_whitespace())
+        .to_ascii_lowercase();
+    if name.is_empty() {
+        return Err(ValidationError::Empty);
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(ValidationError::TooLong);
+    }
+    let valid_label = |label: &str| {
+        (1..=63).contains(&label.len())
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+    };
+    if !name.split('.').all(valid_label) {
+        return Err(ValidationError::InvalidName);
+    }
+    Ok(name)
+}
+
+/// Parse an AXX amount (e.g. `"100"`, `"0.25"` or `"2000 gwei"`) into wei
+pub fn parse_amount(input: &str) -> Result<U256, ValidationError> {
+    if input.len() > MAX_AMOUNT_INPUT {
+        return Err(ValidationError::TooLong);
+    }
+    if input.is_empty() {
+        return Err(ValidationError::Empty);
+    }
+    amount::parse(input).map_err(|e| match e {
+        AmountError::Overflow => ValidationError::AmountOverflow,
+        _ => ValidationError::InvalidAmount,
+    })
+}
+
+/// Deserialize a JSON payload after enforcing size and nesting limits
+pub fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, ValidationError> {
+    if body.len() > MAX_PAYLOAD_BYTES {
+        return Err(ValidationError::PayloadTooLarge);
+    }
+    if json_depth(body) > MAX_JSON_DEPTH {
+        return Err(ValidationError::TooDeeplyNested);
+    }
+    serde_json::from_slice(body).map_err(|e| ValidationError::InvalidJson(e.to_string()))
+}
+
+/// Maximum bracket nesting of a JSON document, ignoring brackets in strings
+pub fn json_depth(body: &[u8]) -> usize {
+    let (mut depth, mut max) = (0usize, 0usize);
+    let (mut in_string, mut escaped) = (false, false);
+    for &b in body {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                max = max.max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    max
+}
+
+/// Invalid field of a request body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// Path of the field, e.g. `pow.nonce`; empty for the body itself
+    pub field: String,
+    /// Machine-readable reason, e.g. `required` or `invalid_address`
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn wrong_type(field: &str, expected: &str, value: &Value) -> Self {
+        Self::new(
+            field,
+            "invalid_type",
+            format!("expected {}, got {}", expected, type_name(value)),
+        )
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.field.as_str() {
+            "" => f.write_str(&self.message),
+            field => write!(f, "{}: {}", field, self.message),
+        }
+    }
+}
+
+/// Faucet settings a `/request` body is checked against
+#[derive(Debug, Clone, Copy)]
+pub struct RequestRules {
+    /// Chain the faucet sends on; a body naming another `chain_id` is rejected
+    pub chain_id: u64,
+    /// Whether names are resolved, so `address` may be a name
+    pub names: bool,
+    /// Whether every request needs a `captcha_token` (or a partner `referral`)
+    pub captcha: bool,
+}
+
+/// Check a `/request` body field by field, returning every invalid field
+///
+/// Unknown fields are ignored. An empty result means the body deserializes
+/// and its address parses.
+pub fn check_request(body: &Value, rules: &RequestRules) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let Some(fields) = body.as_object() else {
+        errors.push(FieldError::wrong_type("", "an object", body));
+        return errors;
+    };
+
+    match fields.get("address") {
+        None | Some(Value::Null) => errors.push(FieldError::new(
+            "address",
+            "required",
+            "address is required",
+        )),
+        Some(Value::String(input)) if rules.names && looks_like_name(input) => {
+            if let Err(e) = parse_name(input) {
+                errors.push(FieldError::new("address", "invalid_name", e.to_string()));
+            }
+        }
+        Some(Value::String(input)) => {
+            if let Err(e) = parse_address(input) {
+                errors.push(FieldError::new("address", "invalid_address", e.to_string()));
+            }
+        }
+        Some(other) => errors.push(FieldError::wrong_type("address", "a string", other)),
+    }
+
+    for field in ["captcha_token", "referral", "fingerprint"] {
+        match fields.get(field) {
+            None | Some(Value::Null | Value::String(_)) => {}
+            Some(other) => errors.push(FieldError::wrong_type(field, "a string", other)),
+        }
+    }
+    match fields.get("allow_contract") {
+        None | Some(Value::Null | Value::Bool(_)) => {}
+        Some(other) => errors.push(FieldError::wrong_type("allow_contract", "a boolean", other)),
+    }
+
+    match fields.get("pow") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(pow)) => check_pow(pow, &mut errors),
+        Some(other) => errors.push(FieldError::wrong_type("pow", "an object", other)),
+    }
+
+    match fields.get("identity") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(identity)) => match identity.get("provider") {
+            Some(Value::String(_)) => {}
+            None | Some(Value::Null) => errors.push(FieldError::new(
+                "identity.provider",
+                "required",
+                "identity.provider is required",
+            )),
+            Some(other) => errors.push(FieldError::wrong_type(
+                "identity.provider",
+                "a string",
+                other,
+            )),
+        },
+        Some(other) => errors.push(FieldError::wrong_type("identity", "an object", other)),
+    }
+
+    match fields.get("chain_id") {
+        None | Some(Value::Null) => {}
+        Some(value) => match value.as_u64() {
+            Some(id) if id == rules.chain_id => {}
+            Some(id) => errors.push(FieldError::new(
+                "chain_id",
+                "unknown_chain",
+                format!("this faucet sends on chain {}, not {}", rules.chain_id, id),
+            )),
+            None => errors.push(FieldError::wrong_type("chain_id", "an integer", value)),
+        },
+    }
+
+    let present = |field| !matches!(fields.get(field), None | Some(Value::Null));
+    if rules.captcha && !present("captcha_token") && !present("referral") {
+        errors.push(FieldError::new(
+            "captcha_token",
+            "required",
+            "captcha_token is required",
+        ));
+    }
+    errors
+}
+
+/// Check a proof-of-work solution object
+fn check_pow(pow: &Map<String, Value>, errors: &mut Vec<FieldError>) {
+    match pow.get("challenge") {
+        None | Some(Value::Null) => errors.push(FieldError::new(
+            "pow.challenge",
+            "required",
+            "pow.challenge is required",
+        )),
+        Some(Value::String(_)) => {}
+        Some(other) => errors.push(FieldError::wrong_type("pow.challenge", "a string", other)),
+    }
+    match pow.get("nonce") {
+        None | Some(Value::Null) => errors.push(FieldError::new(
+            "pow.nonce",
+            "required",
+            "pow.nonce is required",
+        )),
+        Some(value) if value.is_u64() => {}
+        Some(other) => errors.push(FieldError::wrong_type(
+            "pow.nonce",
+            "a non-negative integer",
+            other,
+        )),
+    }
+}
+
+/// JSON type of a value, for error messages
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Printable, length-limited excerpt of untrusted input for logs and storage
+pub fn excerpt(input: &str) -> String {
+    const MAX_CHARS: usize = 64;
+    let mut out: String = input
+        .chars()
+        .take(MAX_CHARS)
+        .flat_map(char::escape_debug)
+        .collect();
+    if input.chars().nth(MAX_CHARS).is_some() {
+        out.push('…');
+    }
+    out
+}
@@ -0,0 +1,38 @@
+//! Faucet wallet helpers
+
+use anyhow::anyhow;
+use k256::ecdsa::SigningKey;
+use sha3::{Digest, Keccak256};
+use std::path::Path;
+
+/// Parse a hex-encoded private key
+pub fn signing_key(private_key: &str) -> anyhow::Result<SigningKey> {
+    let bytes = hex::decode(private_key.trim().trim_start_matches("0x"))?;
+    Ok(SigningKey::from_slice(&bytes)?)
+}
+
+/// Derive the lowercase 0x-prefixed address of a hex-encoded private key
+pub fn address_from_private_key(private_key: &str) -> anyhow::Result<String> {
+    let key = signing_key(private_key)?;
+    let point = key.verifying_key().to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+/// Read a hex-encoded private key from a file
+pub fn read_key_file(path: &Path) -> anyhow::Result<String> {
+    let key = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    Ok(key.trim().to_string())
+}
+
+/// Generate a random hex-encoded private key
+pub fn generate_key() -> String {
+    loop {
+        let bytes: [u8; 32] = rand::random();
+        if SigningKey::from_slice(&bytes).is_ok() {
+            return format!("0x{}", hex::encode(bytes));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Cold-start warmup before a replica reports ready
+//!
+//! A freshly deployed faucet has no open connection to the RPC endpoint and
+//! an empty SQLite page cache, so the first drip paid for every handshake and
+//! cold read in sequence. Right after the server starts listening, warmup
+//! fetches the gas price and the signer's nonces and balance in parallel,
+//! which opens the pooled RPC connection, and reads the ledger tables behind
+//! `/request` and `/stats`. `/readyz` answers 503 until it is done, or until
+//! `WARMUP_TIMEOUT_SECS` (default 10) have passed; cooldowns are already
+//! loaded from the ledger before the server starts.
+
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::{axx, current_stats, gwei, publish_stats, FaucetError, FaucetState};
+
+/// Time warmup may hold back readiness when `WARMUP_TIMEOUT_SECS` is unset
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Warm up in the background, marking the replica warm when done
+pub fn spawn(state: FaucetState) -> anyhow::Result<()> {
+    let timeout = match std::env::var("WARMUP_TIMEOUT_SECS") {
+        Ok(secs) => Duration::from_secs(
+            secs.parse()
+                .map_err(|_| anyhow::anyhow!("Invalid WARMUP_TIMEOUT_SECS"))?,
+        ),
+        Err(_) => DEFAULT_TIMEOUT,
+    };
+    tokio::spawn(async move {
+        let started = Instant::now();
+        match tokio::time::timeout(timeout, warm(&state)).await {
+            Ok(Ok(())) => info!("Warmed up in {}ms", started.elapsed().as_millis()),
+            Ok(Err(e)) => warn!("Warmup failed, serving cold: {:?}", e),
+            Err(_) => warn!(
+                "Warmup timed out after {}s, serving cold",
+                timeout.as_secs()
+            ),
+        }
+        state.warm.store(true, Ordering::Relaxed);
+    });
+    Ok(())
+}
+
+/// Prime the RPC connection, the cached balance and the ledger
+async fn warm(state: &FaucetState) -> Result<(), FaucetError> {
+    let (gas_price, nonce, pending, balance) = tokio::try_join!(
+        state.rpc.get_gas_price(),
+        state.rpc.get_transaction_count(&state.address),
+        state.rpc.get_pending_nonce(&state.address),
+        state.rpc.get_balance(&state.address),
+    )?;
+    *state.balance.write().await = Some(balance);
+    info!(
+        "   Signer nonce {}, balance {}, gas price {}",
+        nonce,
+        axx(balance),
+        gwei(gas_price)
+    );
+    if pending > nonce {
+        warn!(
+            "{} transactions from a previous run are still pending",
+            pending - nonce
+        );
+    }
+
+    state.ledger.lock().await.maintenance()?;
+    publish_stats(state, current_stats(state).await?);
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! Explanation of the grant decision for an address
+//!
+//! `GET /why/:address` runs the service checks and every policy stage the way
+//! `/request` would, without sending or recording anything, so support can see
+//! why a request is rejected instead of guessing from logs. Challenge stages
+//! always report `challenge_required`, since no solution is given.
+//!
+//! The route requires an admin token unless `WHY_PUBLIC=true`. Admins can ask
+//! about any client with `?ip=` and also see the address's labels; everyone
+//! else is explained for their own IP.
+
+use axionax_tx::U256;
+use axum::extract::{ConnectInfo, Json, Path, Query, State};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+
+use crate::admin::AdminAuth;
+use crate::ledger::{to_unix, AddressLabel};
+use crate::{axx, client_ip, cluster, recipient, FaucetError, FaucetState, RequestContext};
+
+/// Query parameters for `/why/:address`
+#[derive(Debug, Deserialize)]
+pub struct WhyQuery {
+    /// Client IP to explain the IP limits for (admins only)
+    ip: Option<IpAddr>,
+}
+
+/// Grant decision for an address and the state behind it
+#[derive(Debug, Serialize)]
+pub struct WhyResponse {
+    address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    ip: String,
+    /// Whether a request now would pass every check
+    eligible: bool,
+    /// First check that would reject a request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rejected_by: Option<CheckOutcome>,
+    /// Amount a grant would send
+    amount: String,
+    /// Whether the address or IP is on the blocklist
+    banned: bool,
+    /// Operator labels of the address (admins only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<AddressLabel>,
+    cooldown: CooldownState,
+    ip_limit: IpLimitState,
+    lifetime: Lifetime,
+    /// Service checks, then policy stages in `POLICY_STAGES` order
+    checks: Vec<CheckOutcome>,
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckOutcome {
+    check: &'static str,
+    passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl CheckOutcome {
+    fn new(check: &'static str, outcome: Result<(), FaucetError>) -> Self {
+        match outcome {
+            Ok(()) => Self {
+                check,
+                passed: true,
+                code: None,
+                message: None,
+            },
+            Err(e) => Self {
+                check,
+                passed: false,
+                code: Some(e.reason()),
+                message: Some(e.message()),
+            },
+        }
+    }
+}
+
+/// Per-address cooldown
+#[derive(Debug, Serialize)]
+pub struct CooldownState {
+    /// Last grant to the address (unix seconds)
+    last_grant: Option<i64>,
+    remaining_secs: u64,
+}
+
+/// Grant limiter bucket of the client IP (or IPv6 prefix)
+#[derive(Debug, Serialize)]
+pub struct IpLimitState {
+    key: String,
+    /// Grants the bucket admits right now
+    available: u32,
+    burst: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after_secs: Option<u64>,
+}
+
+/// Drips ever sent to the address
+#[derive(Debug, Serialize)]
+pub struct Lifetime {
+    drips: usize,
+    total_received: String,
+    first_drip: Option<i64>,
+    last_drip: Option<i64>,
+}
+
+/// Explain the grant decision for an address
+pub async fn why(
+    auth: Option<AdminAuth>,
+    State(state): State<FaucetState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Path(input): Path<String>,
+    Query(query): Query<WhyQuery>,
+) -> Result<Json<WhyResponse>, FaucetError> {
+    let ip = match (&auth, query.ip) {
+        (Some(_), Some(ip)) => client_ip::normalize(ip),
+        (None, _) if !state.why_public => return Err(FaucetError::Unauthorized),
+        _ => client_ip::normalize(peer.ip()),
+    };
+    let (address, name) = recipient(&state, &input).await?;
+    if let Some(auth) = &auth {
+        auth.audit(
+            &state,
+            "why",
+            json!({ "address": address, "ip": ip.to_string() }),
+        )
+        .await?;
+    }
+
+    let ctx = RequestContext {
+        address,
+        name,
+        ip,
+        rate_limit_key: client_ip::rate_limit_key(ip, state.ipv6_prefix_len),
+        geo: state
+            .geoip
+            .as_ref()
+            .map(|geoip| geoip.lookup(ip))
+            .unwrap_or_default(),
+        captcha_token: None,
+        pow: None,
+        partner: None,
+        allow_contract: false,
+        session: None,
+        identity: None,
+    };
+
+    // The checks `/request` makes before the policy
+    let maintenance = state.ledger.lock().await.maintenance()?;
+    let mut checks = vec![
+        CheckOutcome::new(
+            "maintenance",
+            maintenance.map_or(Ok(()), |m| Err(FaucetError::Maintenance(m))),
+        ),
+        CheckOutcome::new(
+            "chain_id",
+            if state.chain_verified.load(Ordering::Relaxed) {
+                Ok(())
+            } else {
+                Err(FaucetError::ChainMismatch)
+            },
+        ),
+        CheckOutcome::new(
+            "leader",
+            if cluster::is_sender(&state) {
+                Ok(())
+            } else {
+                Err(FaucetError::NotLeader)
+            },
+        ),
+    ];
+    let (grant, stages) = state.policy.explain(&state, &ctx).await;
+    checks.extend(
+        stages
+            .into_iter()
+            .map(|(stage, outcome)| CheckOutcome::new(stage, outcome)),
+    );
+
+    let now = SystemTime::now();
+    let last_grant = state
+        .address_requests
+        .read()
+        .await
+        .get(&ctx.address)
+        .copied();
+    let cooldown = grant.cooldown;
+    let remaining = last_grant
+        .map(|last| cooldown.saturating_sub(now.duration_since(last).unwrap_or_default()))
+        .unwrap_or_default();
+
+    let tat = state
+        .ip_requests
+        .read()
+        .await
+        .get(&ctx.rate_limit_key)
+        .copied();
+    let (drips, labels) = {
+        let ledger = state.ledger.lock().await;
+        let labels = match auth.is_some() {
+            true => ledger.labels(&ctx.address)?,
+            false => Vec::new(),
+        };
+        (ledger.drips_to(&ctx.address)?, labels)
+    };
+
+    let rejected_by = checks.iter().find(|c| !c.passed).cloned();
+    let ip_limit = state.config.ip_limit();
+    Ok(Json(WhyResponse {
+        eligible: rejected_by.is_none(),
+        banned: checks
+            .iter()
+            .any(|c| c.check == "blocklist" && c.code == Some("blocked")),
+        rejected_by,
+        labels,
+        amount: axx(grant.amount),
+        cooldown: CooldownState {
+            last_grant: last_grant.map(to_unix),
+            remaining_secs: remaining.as_secs(),
+        },
+        ip_limit: IpLimitState {
+            available: ip_limit.available(tat, now),
+            burst: ip_limit.burst(),
+            retry_after_secs: ip_limit
+                .check(tat, now)
+                .err()
+                .map(|wait| wait.as_secs().max(1)),
+            key: ctx.rate_limit_key.clone(),
+        },
+        lifetime: Lifetime {
+            drips: drips.len(),
+            total_received: axx(drips
+                .iter()
+                .fold(U256::zero(), |sum, d| sum.saturating_add(d.amount))),
+            first_drip: drips.first().map(|d| to_unix(d.timestamp)),
+            last_drip: drips.last().map(|d| to_unix(d.timestamp)),
+        },
+        address: ctx.address,
+        name: ctx.name,
+        ip: ctx.ip.to_string(),
+        checks,
+    }))
+}
@@ -0,0 +1,541 @@
+//! End-to-end harness: runs the faucet binary and talks to it over HTTP
+//!
+//! By default the faucet runs with `--dry-run` against its in-process mock
+//! chain. Set `E2E_RPC_URL` and `E2E_PRIVATE_KEY` (a funded key) to run it
+//! against a real devnet or anvil node instead.
+
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+pub const WEI_PER_AXX: u128 = 1_000_000_000_000_000_000;
+
+/// Client side of the faucet's WebSocket
+pub type WebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Node the faucet talks to
+pub enum Backend {
+    /// `--dry-run` mock chain
+    Mock,
+    /// External JSON-RPC node with a funded faucet key
+    Node {
+        rpc_url: String,
+        private_key: String,
+    },
+}
+
+impl Backend {
+    pub fn from_env() -> Self {
+        match (
+            std::env::var("E2E_RPC_URL"),
+            std::env::var("E2E_PRIVATE_KEY"),
+        ) {
+            (Ok(rpc_url), Ok(private_key)) => Backend::Node {
+                rpc_url,
+                private_key,
+            },
+            _ => Backend::Mock,
+        }
+    }
+}
+
+/// A running faucet process, killed on drop
+pub struct Faucet {
+    child: Child,
+    pub database: PathBuf,
+    pub url: String,
+    pub http: reqwest::Client,
+}
+
+impl Faucet {
+    /// Start the faucet with extra environment variables and wait until healthy
+    pub async fn start(backend: &Backend, env: &[(&str, &str)]) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let database =
+            std::env::temp_dir().join(format!("faucet-e2e-{}-{}.db", std::process::id(), port));
+
+        let mut command = Command::new(env!("CARGO_BIN_EXE_axionax-faucet"));
+        command
+            .arg("--listen")
+            .arg(format!("127.0.0.1:{}", port))
+            .env("DATABASE_PATH", &database)
+            .env("JOB_REFRESH_BALANCE_SECS", "1")
+            .env("RUST_LOG", "warn")
+            .env_remove("PORT")
+            .env_remove("LISTEN_UNIX_SOCKET")
+            .envs(env.iter().copied())
+            .stdout(Stdio::null());
+        match backend {
+            Backend::Mock => {
+                command.arg("--dry-run").env_remove("FAUCET_PRIVATE_KEY");
+            }
+            Backend::Node {
+                rpc_url,
+                private_key,
+            } => {
+                command
+                    .env("RPC_URL", rpc_url)
+                    .env("FAUCET_PRIVATE_KEY", private_key);
+            }
+        }
+
+        let faucet = Self {
+            child: command.spawn().expect("failed to start faucet"),
+            database,
+            url: format!("http://127.0.0.1:{}", port),
+            http: reqwest::Client::new(),
+        };
+        faucet.wait_healthy().await;
+        faucet
+    }
+
+    async fn wait_healthy(&self) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if let Ok(response) = self.http.get(format!("{}/health", self.url)).send().await {
+                if response.status().is_success() {
+                    return;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("faucet did not become healthy");
+    }
+
+    /// `POST /request`, returning the status code and body
+    pub async fn request(&self, address: &str) -> (u16, Value) {
+        self.request_with(json!({ "address": address })).await
+    }
+
+    /// `POST /request` with a full request body
+    pub async fn request_with(&self, body: Value) -> (u16, Value) {
+        let response = self
+            .http
+            .post(format!("{}/v1/request", self.url))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        (response.status().as_u16(), response.json().await.unwrap())
+    }
+
+    /// GET `path` and return the status and JSON body
+    pub async fn get(&self, path: &str) -> (u16, Value) {
+        let response = self
+            .http
+            .get(format!("{}{}", self.url, path))
+            .send()
+            .await
+            .unwrap();
+        (response.status().as_u16(), response.json().await.unwrap())
+    }
+
+    /// Open a WebSocket on `/ws`
+    pub async fn ws(&self) -> WebSocket {
+        let url = format!("{}/v1/ws", self.url.replacen("http", "ws", 1));
+        tokio_tungstenite::connect_async(url).await.unwrap().0
+    }
+
+    /// GET an admin `path` with a bearer `token`
+    pub async fn admin_get(&self, path: &str, token: &str) -> (u16, Value) {
+        self.admin(reqwest::Method::GET, path, token, None).await
+    }
+
+    /// Call an admin `path` with a bearer `token` and an optional JSON body
+    pub async fn admin(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        token: &str,
+        body: Option<Value>,
+    ) -> (u16, Value) {
+        let mut request = self
+            .http
+            .request(method, format!("{}{}", self.url, path))
+            .bearer_auth(token);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.unwrap();
+        (response.status().as_u16(), response.json().await.unwrap())
+    }
+
+    /// `GET /stats`
+    pub async fn stats(&self) -> Value {
+        self.http
+            .get(format!("{}/v1/stats", self.url))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    }
+}
+
+impl Drop for Faucet {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.database);
+    }
+}
+
+/// Serve `router` on a free local port and return its base URL
+pub async fn serve(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    url
+}
+
+/// Minimal JSON-RPC calls against an external node
+pub struct Node {
+    pub rpc_url: String,
+    http: reqwest::Client,
+}
+
+impl Node {
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn call(&self, method: &str, params: Value) -> Value {
+        let response: Value = self
+            .http
+            .post(&self.rpc_url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(
+            response.get("error").is_none(),
+            "{} failed: {}",
+            method,
+            response
+        );
+        response["result"].clone()
+    }
+
+    pub async fn balance(&self, address: &str) -> u128 {
+        let balance = self
+            .call("eth_getBalance", json!([address, "latest"]))
+            .await;
+        u128::from_str_radix(balance.as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+    }
+
+    /// Wait for a transaction receipt and return it
+    pub async fn wait_for_receipt(&self, tx_hash: &str, timeout: Duration) -> Value {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            let receipt = self
+                .call("eth_getTransactionReceipt", json!([tx_hash]))
+                .await;
+            if !receipt.is_null() {
+                return receipt;
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+        panic!("transaction {} was not confirmed", tx_hash);
+    }
+}
+
+/// JSON-RPC node stub that mines every faucet transaction at once and lets
+/// tests set the nonces, deployed contracts, logs and `eth_call` results of
+/// other accounts
+pub struct StubChain {
+    pub url: String,
+    accounts: Arc<Mutex<StubAccounts>>,
+}
+
+#[derive(Default)]
+struct StubAccounts {
+    nonces: HashMap<String, u64>,
+    contracts: HashSet<String>,
+    /// `eth_call` results by call data; other calls return a zero word
+    calls: HashMap<String, String>,
+    /// Emitted logs as `(contract, topics)`
+    logs: Vec<(String, Vec<String>)>,
+    /// Requests served per method
+    served: HashMap<String, u64>,
+    /// Methods answered with a JSON-RPC error
+    failing: HashSet<String>,
+    sent: u64,
+}
+
+impl StubChain {
+    /// A well-known development key, funded with 1,000,000 AXX
+    pub const FAUCET_KEY: &'static str =
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    pub const FAUCET_ADDRESS: &'static str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+    pub async fn start() -> Self {
+        let accounts = Arc::new(Mutex::new(StubAccounts::default()));
+        let chain = accounts.clone();
+        let url = serve(axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(call): axum::Json<Value>| async move {
+                let result = stub_call(&mut chain.lock().unwrap(), &call);
+                axum::Json(match result {
+                    Some(result) => json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+                    None => json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "error": { "code": -32601, "message": "method not found" },
+                    }),
+                })
+            }),
+        ))
+        .await;
+        Self { url, accounts }
+    }
+
+    /// Faucet backend signing with [`Self::FAUCET_KEY`] against this node
+    pub fn backend(&self) -> Backend {
+        Backend::Node {
+            rpc_url: self.url.clone(),
+            private_key: Self::FAUCET_KEY.to_string(),
+        }
+    }
+
+    pub fn set_nonce(&self, address: &str, nonce: u64) {
+        let mut accounts = self.accounts.lock().unwrap();
+        accounts.nonces.insert(address.to_lowercase(), nonce);
+    }
+
+    /// Give `address` contract code
+    pub fn deploy(&self, address: &str) {
+        let mut accounts = self.accounts.lock().unwrap();
+        accounts.contracts.insert(address.to_lowercase());
+    }
+
+    /// Answer `eth_call` with `data` by `result`
+    pub fn answer_call(&self, data: &str, result: &str) {
+        let mut accounts = self.accounts.lock().unwrap();
+        accounts
+            .calls
+            .insert(data.to_lowercase(), result.to_string());
+    }
+
+    /// Record a log emitted by `contract`
+    pub fn emit(&self, contract: &str, topics: &[String]) {
+        let mut accounts = self.accounts.lock().unwrap();
+        let topics = topics.iter().map(|t| t.to_lowercase()).collect();
+        accounts.logs.push((contract.to_lowercase(), topics));
+    }
+
+    /// Answer every `method` request with a JSON-RPC error
+    pub fn fail(&self, method: &str) {
+        let mut accounts = self.accounts.lock().unwrap();
+        accounts.failing.insert(method.to_string());
+    }
+
+    /// Number of `method` requests served so far
+    pub fn served(&self, method: &str) -> u64 {
+        let accounts = self.accounts.lock().unwrap();
+        accounts.served.get(method).copied().unwrap_or_default()
+    }
+}
+
+fn stub_call(accounts: &mut StubAccounts, call: &Value) -> Option<Value> {
+    let quantity = |n: u128| json!(format!("0x{:x}", n));
+    let address = call["params"][0]
+        .as_str()
+        .unwrap_or_default()
+        .to_lowercase();
+    let method = call["method"].as_str()?;
+    *accounts.served.entry(method.to_string()).or_default() += 1;
+    if accounts.failing.contains(method) {
+        return None;
+    }
+    let result = match method {
+        "eth_chainId" => quantity(86137),
+        "eth_blockNumber" => quantity(16),
+        "eth_gasPrice" => quantity(1_000_000_000),
+        "eth_estimateGas" => quantity(21_000),
+        "eth_getBalance" if address == StubChain::FAUCET_ADDRESS => {
+            quantity(1_000_000 * WEI_PER_AXX)
+        }
+        "eth_getBalance" => quantity(0),
+        "eth_getTransactionCount" if address == StubChain::FAUCET_ADDRESS => {
+            quantity(accounts.sent.into())
+        }
+        "eth_getTransactionCount" => quantity(
+            accounts
+                .nonces
+                .get(&address)
+                .copied()
+                .unwrap_or_default()
+                .into(),
+        ),
+        "eth_getCode" if accounts.contracts.contains(&address) => json!("0x6080"),
+        "eth_getCode" => json!("0x"),
+        "eth_call" => {
+            let data = call["params"][0]["data"].as_str().unwrap_or_default();
+            match accounts.calls.get(&data.to_lowercase()) {
+                Some(result) => json!(result),
+                None => json!(format!("0x{}", "0".repeat(64))),
+            }
+        }
+        "eth_getLogs" => {
+            let filter = &call["params"][0];
+            let address = filter["address"]
+                .as_str()
+                .unwrap_or_default()
+                .to_lowercase();
+            let wanted = filter["topics"].as_array().cloned().unwrap_or_default();
+            let logs: Vec<Value> = accounts
+                .logs
+                .iter()
+                .filter(|(contract, topics)| {
+                    *contract == address
+                        && wanted.iter().enumerate().all(|(i, topic)| {
+                            topic.is_null()
+                                || topic.as_str().map(str::to_lowercase).as_ref() == topics.get(i)
+                        })
+                })
+                .map(|(contract, topics)| json!({ "address": contract, "topics": topics }))
+                .collect();
+            json!(logs)
+        }
+        "eth_getBlockByNumber" => json!({ "number": quantity(16), "transactions": [] }),
+        "eth_sendRawTransaction" => {
+            accounts.sent += 1;
+            json!(format!("0x{:064x}", accounts.sent))
+        }
+        "eth_getTransactionByHash" => json!({ "hash": address, "blockNumber": quantity(16) }),
+        "eth_getTransactionReceipt" => json!({
+            "transactionHash": address,
+            "blockNumber": quantity(16),
+            "status": "0x1",
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Write an IPv4 MaxMind DB with 24-bit records mapping each network
+/// (e.g. `127.0.0.0/8`) to its record
+pub fn write_mmdb(path: &Path, networks: &[(&str, Value)]) {
+    #[derive(Clone, Copy)]
+    enum Record {
+        Empty,
+        Node(usize),
+        Data(usize),
+    }
+
+    let mut data = Vec::new();
+    let mut nodes = vec![[Record::Empty; 2]];
+    for (network, record) in networks {
+        let (ip, prefix) = network.split_once('/').unwrap();
+        let (ip, prefix) = (
+            u32::from(ip.parse::<Ipv4Addr>().unwrap()),
+            prefix.parse().unwrap(),
+        );
+        let mut node = 0;
+        for depth in 0..prefix {
+            let bit = (ip >> (31 - depth) & 1) as usize;
+            if depth + 1 == prefix {
+                nodes[node][bit] = Record::Data(data.len());
+            } else if let Record::Node(next) = nodes[node][bit] {
+                node = next;
+            } else {
+                nodes.push([Record::Empty; 2]);
+                nodes[node][bit] = Record::Node(nodes.len() - 1);
+                node = nodes.len() - 1;
+            }
+        }
+        encode_mmdb(&mut data, record);
+    }
+
+    let node_count = nodes.len();
+    let mut file = Vec::new();
+    for record in nodes.iter().flatten() {
+        let value = match *record {
+            Record::Empty => node_count,
+            Record::Node(node) => node,
+            Record::Data(offset) => node_count + 16 + offset,
+        };
+        file.extend(&(value as u32).to_be_bytes()[1..]);
+    }
+    file.extend([0; 16]);
+    file.extend(data);
+    file.extend(b"\xab\xcd\xefMaxMind.com");
+    let metadata = json!({
+        "binary_format_major_version": 2,
+        "binary_format_minor_version": 0,
+        "build_epoch": 0,
+        "database_type": "faucet-e2e",
+        "description": {},
+        "ip_version": 4,
+        "languages": [],
+        "node_count": node_count,
+        "record_size": 24,
+    });
+    encode_mmdb(&mut file, &metadata);
+    std::fs::write(path, file).unwrap();
+}
+
+/// MaxMind DB encoding of objects, arrays, strings and 32-bit unsigned numbers
+fn encode_mmdb(out: &mut Vec<u8>, value: &Value) {
+    let control = |out: &mut Vec<u8>, kind: u8, size: usize| {
+        assert!(size < 29 + 256, "mmdb value too long");
+        let head = size.min(29) as u8;
+        match kind {
+            0..=7 => out.push(kind << 5 | head),
+            _ => out.extend([head, kind - 7]),
+        }
+        if size >= 29 {
+            out.push((size - 29) as u8);
+        }
+    };
+    match value {
+        Value::String(s) => {
+            control(out, 2, s.len());
+            out.extend(s.as_bytes());
+        }
+        Value::Number(n) => {
+            let n = u32::try_from(n.as_u64().unwrap()).unwrap().to_be_bytes();
+            let bytes = &n[n.iter().take_while(|b| **b == 0).count()..];
+            control(out, 6, bytes.len());
+            out.extend(bytes);
+        }
+        Value::Object(map) => {
+            control(out, 7, map.len());
+            for (key, value) in map {
+                encode_mmdb(out, &json!(key));
+                encode_mmdb(out, value);
+            }
+        }
+        Value::Array(items) => {
+            control(out, 11, items.len());
+            for item in items {
+                encode_mmdb(out, item);
+            }
+        }
+        other => panic!("can't encode {} in an mmdb", other),
+    }
+}
+
+/// A fresh random-looking recipient address
+pub fn recipient(seed: u8) -> String {
+    format!("0x{:02x}{}", seed, "5".repeat(38))
+}
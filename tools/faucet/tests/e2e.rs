@@ -0,0 +1,2539 @@
+//! End-to-end tests of the request flow against a running faucet
+
+mod common;
+
+use axionax_faucet::receipt::SignedReceipt;
+use common::{recipient, Backend, Faucet, Node, StubChain, WebSocket, WEI_PER_AXX};
+use ed25519_dalek::{Signer, SigningKey};
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use reqwest::Method;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256, Sha3_256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn drip_then_cooldown() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    let address = recipient(1);
+
+    let (status, body) = faucet.request(&address).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["success"], true);
+    assert_eq!(body["amount"], "100 AXX");
+    assert_eq!(body["tx_hash"].as_str().unwrap().len(), 66);
+    assert!(body.get("code").is_none());
+
+    let (status, body) = faucet.request(&address).await;
+    assert_eq!(status, 429, "{}", body);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["kind"], "rate_limit");
+    assert_eq!(body["code"], "too_soon");
+}
+
+#[tokio::test]
+async fn drip_returns_verifiable_receipt() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    let address = recipient(1);
+
+    let (status, body) = faucet.request(&address).await;
+    assert_eq!(status, 200, "{}", body);
+    let receipt: SignedReceipt = serde_json::from_value(body["receipt"].clone()).unwrap();
+    assert!(receipt.verify().is_ok());
+    assert_eq!(receipt.receipt.recipient, address.to_lowercase());
+    assert_eq!(receipt.receipt.tx_hash, body["tx_hash"]);
+
+    let (status, stored) = faucet
+        .get(&format!("/v1/receipt/{}", receipt.receipt.claim_id))
+        .await;
+    assert_eq!(status, 200, "{}", stored);
+    assert_eq!(stored, body["receipt"]);
+
+    let (status, _) = faucet
+        .get(&format!("/v1/receipt/0x{}", "00".repeat(32)))
+        .await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn first_drip_mints_a_badge() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            (
+                "BADGE_CONTRACT",
+                "0x00000000000000000000000000000000000b4d6e",
+            ),
+            ("JOB_MINT_BADGES_SECS", "1"),
+        ],
+    )
+    .await;
+    let address = recipient(1);
+
+    let (status, body) = faucet.request(&address).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let badge = loop {
+        let (status, badge) = faucet.get(&format!("/v1/badges/{}", address)).await;
+        assert_eq!(status, 200, "{}", badge);
+        if badge["status"] == "minted" {
+            break badge;
+        }
+        assert_eq!(badge["status"], "queued");
+        assert!(Instant::now() < deadline, "never minted: {}", badge);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    assert_eq!(badge["address"], address.to_lowercase());
+    assert_eq!(badge["tx_hash"].as_str().unwrap().len(), 66);
+    assert_ne!(badge["tx_hash"], body["tx_hash"]);
+
+    let (status, _) = faucet.get(&format!("/v1/badges/{}", recipient(2))).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn websocket_streams_claim_confirmation() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    let receipt: SignedReceipt = serde_json::from_value(body["receipt"].clone()).unwrap();
+
+    let mut ws = faucet.ws().await;
+    let subscribe = |id: u64, claim_id: &str| json!({ "jsonrpc": "2.0", "id": id, "method": "faucet_subscribe", "params": [claim_id] });
+    let unknown = format!("0x{}", "00".repeat(32));
+    ws.send(Message::text(subscribe(1, &unknown).to_string()))
+        .await
+        .unwrap();
+    assert_eq!(next_message(&mut ws).await["error"]["code"], -32602);
+
+    ws.send(Message::text(
+        subscribe(2, &receipt.receipt.claim_id).to_string(),
+    ))
+    .await
+    .unwrap();
+    let reply = next_message(&mut ws).await;
+    assert_eq!(reply["id"], 2, "{}", reply);
+    let subscription = reply["result"].clone();
+
+    let notification = next_message(&mut ws).await;
+    assert_eq!(notification["method"], "faucet_subscription");
+    assert_eq!(notification["params"]["subscription"], subscription);
+    let event = &notification["params"]["result"];
+    assert_eq!(event["claim_id"], receipt.receipt.claim_id);
+    assert_eq!(event["tx_hash"], body["tx_hash"]);
+    assert_eq!(event["status"], "confirmed");
+    assert!(event["block_number"].is_u64(), "{}", event);
+}
+
+/// Next JSON text message on a WebSocket, waiting up to 15 seconds
+async fn next_message(ws: &mut WebSocket) -> Value {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(15), ws.next())
+            .await
+            .expect("no WebSocket message")
+            .unwrap()
+            .unwrap();
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn request_command_solves_pow_and_waits_for_funds() {
+    // Flags every IP, so the faucet asks for proof of work, and serves a
+    // balance that rises after the first read
+    let reads = Arc::new(AtomicU64::new(0));
+    let stub = common::serve(
+        axum::Router::new()
+            .route(
+                "/reputation/:ip",
+                axum::routing::get(|| async { axum::Json(json!({ "proxy": true })) }),
+            )
+            .route(
+                "/rpc",
+                axum::routing::post(move |axum::Json(call): axum::Json<Value>| async move {
+                    let balance = match reads.fetch_add(1, Ordering::SeqCst) {
+                        0 => 0,
+                        _ => 100 * WEI_PER_AXX,
+                    };
+                    axum::Json(json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": format!("0x{:x}", balance),
+                    }))
+                }),
+            ),
+    )
+    .await;
+    let reputation_url = format!("{}/reputation/{{ip}}", stub);
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("REPUTATION_API_URL", &reputation_url),
+            ("POW_DIFFICULTY", "8"),
+        ],
+    )
+    .await;
+    let (status, body) = faucet.request(&recipient(2)).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["challenge"], "pow");
+
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet"))
+        .args(["request", &recipient(1), "--wait", "--timeout", "20"])
+        .args(["--url", &faucet.url, "--rpc-url", &format!("{}/rpc", stub)])
+        .env("RUST_LOG", "warn")
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        stdout.contains("Solving a 8-bit proof-of-work challenge"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Sent 100 AXX"), "{}", stdout);
+    assert!(stdout.contains("Balance: 100 AXX"), "{}", stdout);
+}
+
+#[tokio::test]
+async fn export_lists_recipients_that_transacted() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    for seed in [1, 2] {
+        let (status, body) = faucet.request(&recipient(seed)).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+
+    // Recipient 1 has sent three transactions since its drip, recipient 2 none
+    let active = recipient(1).to_lowercase();
+    let nonce_of = active.clone();
+    let rpc = common::serve(axum::Router::new().route(
+        "/",
+        axum::routing::post(move |axum::Json(call): axum::Json<Value>| async move {
+            let nonce = if call["params"][0] == nonce_of.as_str() {
+                3
+            } else {
+                0
+            };
+            axum::Json(
+                json!({ "jsonrpc": "2.0", "id": call["id"], "result": format!("0x{:x}", nonce) }),
+            )
+        }),
+    ))
+    .await;
+
+    let file = faucet.database.with_extension("alloc.json");
+    let export = |min_activity: &str| {
+        tokio::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet"))
+            .args([
+                "export-recipients",
+                file.to_str().unwrap(),
+                "--rpc-url",
+                &rpc,
+            ])
+            .args(["--min-activity", min_activity, "--amount", "250"])
+            .env("DATABASE_PATH", &faucet.database)
+            .env("RUST_LOG", "warn")
+            .output()
+    };
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let allocations = loop {
+        let output = export("0").await.unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let allocations: Value = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+        // Recipients are tracked in the background after their drip
+        if allocations.as_array().unwrap().len() == 2 || Instant::now() > deadline {
+            break allocations;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    assert_eq!(allocations.as_array().unwrap().len(), 2, "{}", allocations);
+
+    let output = export("2").await.unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let allocations: Value = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+    let _ = std::fs::remove_file(&file);
+    assert_eq!(
+        allocations,
+        json!([{ "address": active, "balance": (250 * WEI_PER_AXX).to_string() }])
+    );
+}
+
+#[tokio::test]
+async fn slo_counts_requests_per_route() {
+    let faucet = Faucet::start(&Backend::Mock, &[("SLO_OBJECTIVES", "/request=60000@99")]).await;
+    faucet.request(&recipient(5)).await;
+    faucet.request("not-an-address").await;
+
+    let (status, slo) = faucet.get("/v1/slo").await;
+    assert_eq!(status, 200, "{}", slo);
+    let objective = &slo["objectives"][0];
+    assert_eq!(objective["route"], "/request");
+    assert_eq!(objective["requests"], 2);
+    assert_eq!(objective["good"], 2);
+    assert_eq!(objective["met"], true);
+}
+
+#[tokio::test]
+async fn ready_after_warmup() {
+    let faucet = Faucet::start(&Backend::Mock, &[("DRY_RUN_RPC_LATENCY_MS", "300")]).await;
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let (status, ready) = faucet.get("/readyz").await;
+        if status == 200 {
+            break;
+        }
+        assert_eq!(ready["ready"], false);
+        assert!(Instant::now() < deadline, "never ready: {}", ready);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let (_, stats) = faucet.get("/v1/stats").await;
+    assert_ne!(stats["faucet_balance"], "unknown", "{}", stats);
+}
+
+#[tokio::test]
+async fn follower_takes_over_when_leader_dies() {
+    let database =
+        std::env::temp_dir().join(format!("faucet-e2e-{}-cluster.db", std::process::id()));
+    let database = database.to_str().unwrap();
+    let replica = |id| {
+        [
+            ("DATABASE_PATH", database),
+            ("REPLICA_ID", id),
+            ("LEADER_LEASE_SECS", "3"),
+        ]
+    };
+    let leader = Faucet::start(&Backend::Mock, &replica("a")).await;
+    let follower = Faucet::start(&Backend::Mock, &replica("b")).await;
+
+    let (_, ready) = follower.get("/readyz").await;
+    assert_eq!(ready["role"], "follower");
+    assert_eq!(ready["leader"], "a");
+    let (status, body) = follower.request(&recipient(6)).await;
+    assert_eq!(status, 503, "{}", body);
+
+    // Killed without releasing the lease, so the follower waits for it to expire
+    drop(leader);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let (_, ready) = follower.get("/readyz").await;
+        if ready["role"] == "leader" {
+            break;
+        }
+        assert!(Instant::now() < deadline, "no failover: {}", ready);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    let (status, body) = follower.request(&recipient(6)).await;
+    assert_eq!(status, 200, "{}", body);
+}
+
+#[tokio::test]
+async fn admin_actions_are_audited() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("ADMIN_TOKEN", "root"),
+            ("ADMIN_TOKENS", "alice:alice-token"),
+        ],
+    )
+    .await;
+
+    let (status, _) = faucet
+        .admin_get("/v1/admin/stats?hours=6", "alice-token")
+        .await;
+    assert_eq!(status, 200);
+    let (status, _) = faucet.admin_get("/v1/admin/audit", "wrong").await;
+    assert_eq!(status, 401);
+
+    let (status, audit) = faucet.admin_get("/v1/admin/audit", "root").await;
+    assert_eq!(status, 200, "{}", audit);
+    let entries = audit["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2, "{}", audit);
+    assert_eq!(entries[0]["actor"], "admin");
+    assert_eq!(entries[0]["action"], "audit");
+    assert_eq!(entries[1]["actor"], "alice");
+    assert_eq!(entries[1]["action"], "stats");
+    assert_eq!(entries[1]["payload"]["hours"], 6);
+
+    let (_, audit) = faucet
+        .admin_get("/v1/admin/audit?actor=alice", "root")
+        .await;
+    assert_eq!(audit["entries"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn why_explains_the_grant_decision() {
+    let faucet = Faucet::start(&Backend::Mock, &[("ADMIN_TOKEN", "root")]).await;
+    let address = recipient(7);
+    let path = format!("/v1/why/{}", address);
+
+    let (status, _) = faucet.get(&path).await;
+    assert_eq!(status, 401);
+    let (status, why) = faucet.admin_get(&path, "root").await;
+    assert_eq!(status, 200, "{}", why);
+    assert_eq!(why["eligible"], true, "{}", why);
+    assert_eq!(why["amount"], "100 AXX");
+    assert_eq!(why["ip_limit"]["available"], 3);
+
+    let (status, body) = faucet.request(&address).await;
+    assert_eq!(status, 200, "{}", body);
+    let (_, why) = faucet.admin_get(&path, "root").await;
+    assert_eq!(why["eligible"], false, "{}", why);
+    assert_eq!(why["rejected_by"]["code"], "too_soon", "{}", why);
+    assert_eq!(why["banned"], false);
+    assert!(why["cooldown"]["remaining_secs"].as_u64().unwrap() > 0);
+    assert_eq!(why["ip_limit"]["available"], 2);
+    assert_eq!(why["lifetime"]["drips"], 1);
+    assert_eq!(why["lifetime"]["total_received"], "100 AXX");
+    let cooldown = why["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["check"] == "cooldown")
+        .unwrap();
+    assert_eq!(cooldown["passed"], false);
+
+    // Admins can look at another client's IP bucket
+    let (_, why) = faucet
+        .admin_get(&format!("{}?ip=10.1.2.3", path), "root")
+        .await;
+    assert_eq!(why["ip"], "10.1.2.3");
+    assert_eq!(why["ip_limit"]["available"], 3);
+}
+
+#[tokio::test]
+async fn ipv6_clients_share_a_prefix_bucket() {
+    let faucet = Faucet::start(&Backend::Mock, &[("ADMIN_TOKEN", "root")]).await;
+
+    // Hosts of one /64 share a bucket, the next /64 gets its own
+    let why = why_from(&faucet, "2001:db8:1:2:aaaa::1").await;
+    assert_eq!(why["ip_limit"]["key"], "2001:db8:1:2::/64", "{}", why);
+    let why = why_from(&faucet, "2001:db8:1:2:ffff::9").await;
+    assert_eq!(why["ip_limit"]["key"], "2001:db8:1:2::/64", "{}", why);
+    let why = why_from(&faucet, "2001:db8:1:3::1").await;
+    assert_eq!(why["ip_limit"]["key"], "2001:db8:1:3::/64", "{}", why);
+
+    // IPv4-mapped clients are limited as the IPv4 address
+    let why = why_from(&faucet, "::ffff:203.0.113.9").await;
+    assert_eq!(why["ip"], "203.0.113.9", "{}", why);
+    assert_eq!(why["ip_limit"]["key"], "203.0.113.9", "{}", why);
+
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[("ADMIN_TOKEN", "root"), ("IPV6_PREFIX_LEN", "48")],
+    )
+    .await;
+    let why = why_from(&faucet, "2001:db8:1:2::1").await;
+    assert_eq!(why["ip_limit"]["key"], "2001:db8:1::/48", "{}", why);
+}
+
+/// `/why` as seen from another client IP
+async fn why_from(faucet: &Faucet, ip: &str) -> Value {
+    let path = format!("/v1/why/{}?ip={}", recipient(1), ip);
+    let (status, why) = faucet.admin_get(&path, "root").await;
+    assert_eq!(status, 200, "{}", why);
+    why
+}
+
+#[tokio::test]
+async fn invalid_ipv6_prefix_len_is_rejected() {
+    let database =
+        std::env::temp_dir().join(format!("faucet-e2e-prefix-{}.db", std::process::id()));
+    for value in ["0", "200", "wide"] {
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet"))
+            .arg("--dry-run")
+            .env("DATABASE_PATH", &database)
+            .env("IPV6_PREFIX_LEN", value)
+            .env("RUST_LOG", "warn")
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(Duration::from_secs(30), output)
+            .await
+            .expect("faucet started with an invalid IPV6_PREFIX_LEN")
+            .unwrap();
+        assert!(!output.status.success(), "{}: {:?}", value, output);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Invalid IPV6_PREFIX_LEN"), "{}", stderr);
+    }
+    let _ = std::fs::remove_file(&database);
+}
+
+#[tokio::test]
+async fn session_aliases_share_a_cooldown() {
+    let faucet = Faucet::start(&Backend::Mock, &[("ADMIN_TOKEN", "root")]).await;
+    let request =
+        |seed, fingerprint| json!({ "address": recipient(seed), "fingerprint": fingerprint });
+
+    let (status, body) = faucet.request_with(request(1, "laptop")).await;
+    assert_eq!(status, 200, "{}", body);
+    let (status, body) = faucet.request_with(request(2, "laptop")).await;
+    assert_eq!(status, 429, "{}", body);
+    let (status, body) = faucet.request_with(request(3, "phone")).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // Only granted requests link their session
+    let (status, stats) = faucet.admin_get("/v1/admin/stats", "root").await;
+    assert_eq!(status, 200, "{}", stats);
+    assert_eq!(stats["alias_clusters"], json!([]), "{}", stats);
+}
+
+#[tokio::test]
+async fn rejected_requests_link_no_session() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    let request =
+        |seed, fingerprint| json!({ "address": recipient(seed), "fingerprint": fingerprint });
+
+    let (status, body) = faucet.request_with(request(1, "attacker")).await;
+    assert_eq!(status, 200, "{}", body);
+    // Sending the victim's address from the attacker's session is rejected...
+    let (status, body) = faucet.request_with(request(2, "attacker")).await;
+    assert_eq!(status, 429, "{}", body);
+    // ...and doesn't tie the victim into the attacker's cooldown
+    let (status, body) = faucet.request_with(request(2, "victim")).await;
+    assert_eq!(status, 200, "{}", body);
+}
+
+#[tokio::test]
+async fn maintenance_pauses_drips_only() {
+    let faucet = Faucet::start(&Backend::Mock, &[("ADMIN_TOKEN", "root")]).await;
+    let body = json!({ "message": "Upgrading the testnet", "eta": 4102444800u64 });
+
+    let (status, state) = faucet
+        .admin(Method::PUT, "/v1/admin/maintenance", "root", Some(body))
+        .await;
+    assert_eq!(status, 200, "{}", state);
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 503, "{}", body);
+    assert_eq!(body["error"], "Upgrading the testnet");
+    assert_eq!(body["kind"], "unavailable");
+    assert_eq!(body["code"], "maintenance");
+    assert_eq!(body["eta"], 4102444800u64);
+
+    let (status, info) = faucet.get("/v1/info").await;
+    assert_eq!(status, 200);
+    assert_eq!(info["maintenance"]["message"], "Upgrading the testnet");
+    assert_eq!(faucet.get("/v1/stats").await.0, 200);
+    assert_eq!(faucet.get("/health").await.0, 200);
+
+    let (status, _) = faucet
+        .admin(Method::DELETE, "/v1/admin/maintenance", "root", None)
+        .await;
+    assert_eq!(status, 200);
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (_, audit) = faucet.admin_get("/v1/admin/audit", "root").await;
+    let actions: Vec<&str> = audit["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["audit", "end_maintenance", "start_maintenance"]);
+}
+
+#[tokio::test]
+async fn flags_gate_policy_stages() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("ADMIN_TOKEN", "root"),
+            ("POLICY_STAGES", "pow@pow_rollout,cooldown"),
+            ("FEATURE_FLAGS", "pow_rollout=0"),
+        ],
+    )
+    .await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, flags) = faucet
+        .admin(
+            Method::PUT,
+            "/v1/admin/flags/pow_rollout",
+            "root",
+            Some(json!({ "percent": 100 })),
+        )
+        .await;
+    assert_eq!(status, 200, "{}", flags);
+    assert_eq!(flags["flags"][0]["source"], "admin");
+    let (status, body) = faucet.request(&recipient(2)).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["challenge"], "pow");
+
+    let (status, flags) = faucet
+        .admin(Method::DELETE, "/v1/admin/flags/pow_rollout", "root", None)
+        .await;
+    assert_eq!(status, 200, "{}", flags);
+    assert_eq!(flags["flags"][0]["source"], "env");
+    assert_eq!(flags["flags"][0]["percent"], 0);
+    let (status, body) = faucet.request(&recipient(2)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, _) = faucet
+        .admin(
+            Method::PUT,
+            "/v1/admin/flags/pow_rollout",
+            "root",
+            Some(json!({ "percent": 101 })),
+        )
+        .await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn labels_feed_the_policy() {
+    let faucet = Faucet::start(&Backend::Mock, &[("ADMIN_TOKEN", "root")]).await;
+    let bot = recipient(1);
+    let path = format!("/v1/admin/labels/{}/suspected_bot", bot);
+
+    let (status, labels) = faucet
+        .admin(
+            Method::PUT,
+            &path,
+            "root",
+            Some(json!({ "note": "farm pattern" })),
+        )
+        .await;
+    assert_eq!(status, 200, "{}", labels);
+    assert_eq!(labels["labels"][0]["label"], "suspected_bot");
+    assert_eq!(labels["labels"][0]["note"], "farm pattern");
+    assert_eq!(labels["labels"][0]["added_by"], "admin");
+    let (status, body) = faucet.request(&bot).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["code"], "blocked");
+
+    // Labels outside LABEL_DENY don't change the grant
+    let (status, _) = faucet
+        .admin(
+            Method::PUT,
+            &format!("/v1/admin/labels/{}/partner", recipient(2)),
+            "root",
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, 200);
+    let (status, body) = faucet.request(&recipient(2)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (_, why) = faucet.admin_get(&format!("/v1/why/{}", bot), "root").await;
+    assert_eq!(why["labels"][0]["label"], "suspected_bot", "{}", why);
+    assert_eq!(why["rejected_by"]["check"], "labels", "{}", why);
+    let (status, labeled) = faucet
+        .admin_get("/v1/admin/labels?label=partner", "root")
+        .await;
+    assert_eq!(status, 200, "{}", labeled);
+    assert_eq!(labeled["labels"].as_array().unwrap().len(), 1);
+    assert_eq!(labeled["labels"][0]["address"], recipient(2));
+
+    let (status, labels) = faucet.admin(Method::DELETE, &path, "root", None).await;
+    assert_eq!(status, 200, "{}", labels);
+    assert_eq!(labels["labels"], json!([]));
+    let (status, body) = faucet.request(&bot).await;
+    assert_eq!(status, 200, "{}", body);
+    let (status, _) = faucet.admin(Method::DELETE, &path, "root", None).await;
+    assert_eq!(status, 404);
+
+    let (status, _) = faucet
+        .admin(
+            Method::PUT,
+            &format!("/v1/admin/labels/{}/Bad-Label", bot),
+            "root",
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn compat_mode_shapes_legacy_responses() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[("COMPAT_API_KEYS", "acme-key:eth-faucet")],
+    )
+    .await;
+    let http = reqwest::Client::new();
+    let claim = |query: &'static str, key: Option<&'static str>| {
+        let mut request = http
+            .post(format!("{}/v1/request{}", faucet.url, query))
+            .json(&json!({ "address": recipient(1) }));
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        async move {
+            let response = request.send().await.unwrap();
+            let status = response.status().as_u16();
+            (status, response.json::<Value>().await.unwrap())
+        }
+    };
+
+    let (status, body) = claim("?compat=eth-faucet", None).await;
+    assert_eq!(status, 200, "{}", body);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.starts_with("Txhash: 0x"), "{}", body);
+    assert_eq!(body.as_object().unwrap().len(), 1, "{}", body);
+
+    let (status, body) = claim("", Some("acme-key")).await;
+    assert_eq!(status, 429, "{}", body);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("wait"), "{}", body);
+    assert!(body.get("code").is_none(), "{}", body);
+
+    // Unknown keys get the native schema
+    let (status, body) = claim("", Some("other")).await;
+    assert_eq!(status, 429);
+    assert_eq!(body["code"], "too_soon");
+    let (status, body) = claim("?compat=nope", None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "invalid_payload");
+
+    let (status, info) = faucet.get("/v1/info?compat=eth-faucet").await;
+    assert_eq!(status, 200);
+    assert_eq!(info["payout"], "100");
+    assert_eq!(info["symbol"], "AXX");
+    assert_eq!(info["account"].as_str().unwrap().len(), 42, "{}", info);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn serves_over_a_unix_socket() {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+
+    /// Stops the faucet and removes its files
+    struct Server(std::process::Child, std::path::PathBuf);
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+            let _ = std::fs::remove_dir_all(&self.1);
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("faucet-e2e-unix-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let socket = dir.join("faucet.sock");
+    let database = dir.join("faucet.db");
+    let _server = Server(
+        std::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet"))
+            .arg("--dry-run")
+            .arg("--unix-socket")
+            .arg(&socket)
+            .env("DATABASE_PATH", &database)
+            .env("ADMIN_TOKEN", "root")
+            .env("RUST_LOG", "warn")
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap(),
+        dir.clone(),
+    );
+
+    let get = |path: String| {
+        let socket = socket.clone();
+        async move {
+            let mut stream = UnixStream::connect(&socket).await.ok()?;
+            let request = format!(
+                "GET {} HTTP/1.1\r\nHost: faucet\r\nAuthorization: Bearer root\r\n\
+                 X-Forwarded-For: 198.51.100.1, 203.0.113.9\r\nConnection: close\r\n\r\n",
+                path
+            );
+            stream.write_all(request.as_bytes()).await.ok()?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.ok()?;
+            let (head, body) = response.split_once("\r\n\r\n")?;
+            Some((head.to_string(), serde_json::from_str::<Value>(body).ok()?))
+        }
+    };
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let (head, _) = loop {
+        if let Some(response) = get("/health".to_string()).await {
+            break response;
+        }
+        assert!(Instant::now() < deadline, "faucet did not listen");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+
+    // The client is the address the proxy appended
+    let (_, why) = get(format!("/v1/why/{}", recipient(1))).await.unwrap();
+    assert_eq!(why["ip"], "203.0.113.9", "{}", why);
+
+    let mode = |path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(&socket), 0o660);
+    assert_eq!(mode(&database), 0o600);
+}
+
+#[tokio::test]
+async fn chaos_mode_injects_faults() {
+    let chaos = |rpc: &'static str, storage: &'static str| {
+        [
+            ("CHAOS", "true"),
+            ("ADMIN_TOKEN", "root"),
+            ("CHAOS_RPC_ERROR_RATE", rpc),
+            ("CHAOS_RPC_SLOW_RATE", "0"),
+            ("CHAOS_STORAGE_ERROR_RATE", storage),
+        ]
+    };
+
+    // A drip that went out is reported as sent even when it can't be recorded
+    let faucet = Faucet::start(&Backend::Mock, &chaos("0", "1")).await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert!(body["tx_hash"].is_string(), "{}", body);
+    let body = json!({ "message": "Upgrading the testnet" });
+    let (status, body) = faucet
+        .admin(Method::PUT, "/v1/admin/maintenance", "root", Some(body))
+        .await;
+    assert_eq!(status, 500, "{}", body);
+    assert_eq!(body["kind"], "storage");
+
+    // The chain ID can't be verified, so drips stay paused
+    let faucet = Faucet::start(&Backend::Mock, &chaos("1", "0")).await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 503, "{}", body);
+    assert_eq!(faucet.get("/health").await.0, 200);
+}
+
+#[tokio::test]
+async fn soak_reports_grants_and_rejections() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    let report = faucet.database.with_extension("soak.json");
+
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet-soak"))
+        .args(["--url", &faucet.url, "--duration", "2", "--rate", "20"])
+        .args(["--burst-size", "5", "--json"])
+        .arg(&report)
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let json = std::fs::read(&report).unwrap();
+    let _ = std::fs::remove_file(&report);
+    let report: Value = serde_json::from_slice(&json).unwrap();
+    let granted = report["total"]["granted"].as_u64().unwrap();
+    assert!(granted >= 1, "{}", report);
+    let invalid = &report["kinds"][2];
+    assert_eq!(invalid["kind"], "invalid");
+    assert_eq!(invalid["granted"], 0);
+    assert_eq!(invalid["rejected"], invalid["sent"]);
+}
+
+/// Run a faucet subcommand against `database`
+async fn faucet_command(database: &Path, args: &[&str]) -> std::process::Output {
+    tokio::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet"))
+        .args(args)
+        .env("DATABASE_PATH", database)
+        .env("RUST_LOG", "warn")
+        .output()
+        .await
+        .unwrap()
+}
+
+/// A faucet with a grant, a label and a maintenance window, backed up to a file
+async fn backed_up_faucet() -> (Faucet, PathBuf) {
+    let faucet = Faucet::start(&Backend::Mock, &[("ADMIN_TOKEN", "root")]).await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    let path = format!("/v1/admin/labels/{}/partner", recipient(2));
+    let (status, _) = faucet
+        .admin(Method::PUT, &path, "root", Some(json!({})))
+        .await;
+    assert_eq!(status, 200);
+    let body = json!({ "message": "Upgrading the testnet" });
+    let (status, _) = faucet
+        .admin(Method::PUT, "/v1/admin/maintenance", "root", Some(body))
+        .await;
+    assert_eq!(status, 200);
+
+    let file = faucet.database.with_extension("backup.json");
+    let output = faucet_command(&faucet.database, &["backup", file.to_str().unwrap()]).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (faucet, file)
+}
+
+#[tokio::test]
+async fn backup_round_trips() {
+    let (faucet, file) = backed_up_faucet().await;
+    let restored = faucet.database.with_extension("restored.db");
+    let copy = faucet.database.with_extension("copy.json");
+
+    let output = faucet_command(&restored, &["restore", file.to_str().unwrap()]).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = faucet_command(&restored, &["backup", copy.to_str().unwrap()]).await;
+    assert!(output.status.success());
+
+    let snapshot = |file: &Path| {
+        let mut contents: Value = serde_json::from_slice(&std::fs::read(file).unwrap()).unwrap();
+        contents["snapshot"]["created_at"].take();
+        contents["snapshot"].take()
+    };
+    let (original, copy_of) = (snapshot(&file), snapshot(&copy));
+    assert_eq!(original["transfers"].as_array().unwrap().len(), 1);
+    assert_eq!(original["address_labels"][0]["label"], "partner");
+    assert_eq!(
+        original["maintenance"][0]["message"],
+        "Upgrading the testnet"
+    );
+    assert_eq!(original, copy_of);
+
+    for path in [file, copy, restored] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[tokio::test]
+async fn restore_rejects_a_tampered_backup() {
+    let (faucet, file) = backed_up_faucet().await;
+    let restored = faucet.database.with_extension("restored.db");
+
+    let mut contents: Value = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+    contents["snapshot"]["transfers"][0]["amount"] = json!("1");
+    std::fs::write(&file, serde_json::to_vec(&contents).unwrap()).unwrap();
+    let output = faucet_command(&restored, &["restore", file.to_str().unwrap()]).await;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("checksum mismatch"), "{}", stderr);
+
+    for path in [file, restored] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[tokio::test]
+async fn restore_needs_force_to_replace_records() {
+    let (faucet, file) = backed_up_faucet().await;
+
+    let output = faucet_command(&faucet.database, &["restore", file.to_str().unwrap()]).await;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("pass --force"), "{}", stderr);
+
+    let args = ["restore", file.to_str().unwrap(), "--force"];
+    let output = faucet_command(&faucet.database, &args).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let _ = std::fs::remove_file(file);
+}
+
+/// Names of the embedded migrations, e.g. `V1__initial_schema`, in order
+fn migrations() -> Vec<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations");
+    let mut names: Vec<(u32, String)> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            let name = name.trim_end_matches(".sql").to_string();
+            let version = name[1..name.find("__").unwrap()].parse().unwrap();
+            (version, name)
+        })
+        .collect();
+    names.sort();
+    names.into_iter().map(|(_, name)| name).collect()
+}
+
+#[tokio::test]
+async fn db_commands_migrate_a_fresh_database() {
+    let database =
+        std::env::temp_dir().join(format!("faucet-e2e-migrate-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&database);
+    let run = |args: &'static [&'static str]| {
+        let database = database.clone();
+        async move {
+            let output = faucet_command(&database, args).await;
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            String::from_utf8(output.stdout).unwrap()
+        }
+    };
+    let migrations = migrations();
+
+    let status = run(&["db", "status"]).await;
+    let lines: Vec<&str> = status.lines().collect();
+    assert_eq!(lines.len(), migrations.len(), "{}", status);
+    for (line, migration) in lines.iter().zip(&migrations) {
+        assert_eq!(*line, format!("{}  pending", migration));
+    }
+
+    let applied = run(&["db", "migrate"]).await;
+    let expected: Vec<String> = migrations
+        .iter()
+        .map(|m| format!("Applied {}", m))
+        .collect();
+    assert_eq!(applied.lines().collect::<Vec<_>>(), expected);
+    let again = run(&["db", "migrate"]).await;
+    assert!(again.contains("schema is up to date"), "{}", again);
+
+    let status = run(&["db", "status"]).await;
+    let lines: Vec<&str> = status.lines().collect();
+    assert_eq!(lines.len(), migrations.len(), "{}", status);
+    for (line, migration) in lines.iter().zip(&migrations) {
+        assert!(
+            line.starts_with(&format!("{}  applied", migration)),
+            "{}",
+            line
+        );
+    }
+    let _ = std::fs::remove_file(&database);
+}
+
+#[tokio::test]
+async fn migrations_upgrade_an_unversioned_database() {
+    let database =
+        std::env::temp_dir().join(format!("faucet-e2e-unversioned-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&database);
+
+    // The schema the ledger created before migrations were versioned
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE transfers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            address TEXT NOT NULL,
+            amount TEXT NOT NULL,
+            tx_hash TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE TABLE requests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            address TEXT NOT NULL,
+            ip TEXT NOT NULL,
+            granted INTEGER NOT NULL,
+            reason TEXT,
+            timestamp INTEGER NOT NULL
+        );
+        INSERT INTO transfers (kind, address, amount, tx_hash, timestamp)
+            VALUES ('drip', '0x01', '100', '0xaa', 1);
+        INSERT INTO requests (address, ip, granted, timestamp)
+            VALUES ('0x01', '127.0.0.1', 1, 1);",
+    )
+    .unwrap();
+    drop(conn);
+
+    let output = faucet_command(&database, &["db", "migrate"]).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let applied = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(applied.lines().count(), migrations().len(), "{}", applied);
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    let columns: Vec<String> = conn
+        .prepare("SELECT name FROM pragma_table_info('requests')")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    for column in ["country", "asn", "as_org"] {
+        assert!(columns.iter().any(|c| c == column), "{:?}", columns);
+    }
+    let count = |table: &str| -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    };
+    assert_eq!(count("transfers"), 1);
+    assert_eq!(count("requests"), 1);
+    drop(conn);
+    let _ = std::fs::remove_file(&database);
+}
+
+/// Items of an RLP-encoded list of strings, such as a legacy transaction
+fn rlp_items(raw: &[u8]) -> Vec<Vec<u8>> {
+    let length = |bytes: &[u8]| bytes.iter().fold(0, |n, b| n << 8 | *b as usize);
+    let mut rest = match raw[0] {
+        0xc0..=0xf7 => &raw[1..],
+        b => &raw[1 + (b - 0xf7) as usize..],
+    };
+    let mut items = Vec::new();
+    while let Some(&b) = rest.first() {
+        let (start, len) = match b {
+            0x00..=0x7f => (0, 1),
+            0x80..=0xb7 => (1, (b - 0x80) as usize),
+            _ => {
+                let n = (b - 0xb7) as usize;
+                (1 + n, length(&rest[1..1 + n]))
+            }
+        };
+        items.push(rest[start..start + len].to_vec());
+        rest = &rest[start + len..];
+    }
+    items
+}
+
+#[tokio::test]
+async fn rotate_key_sweeps_to_the_new_signer_and_resumes() {
+    // A well-known development key, holding 10 AXX on the stub chain
+    let old_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let old_address = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    let (gas_price, gas) = (1_000_000_000u128, 21_000u128);
+    let balances = Arc::new(Mutex::new(
+        [(old_address.to_string(), 10 * WEI_PER_AXX)]
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+    ));
+    let sent = Arc::new(AtomicU64::new(0));
+    let mined = Arc::new(AtomicU64::new(0));
+    let (chain_balances, chain_sent, chain_mined) = (balances.clone(), sent.clone(), mined.clone());
+    let tx_hash = format!("0x{}", "ab".repeat(32));
+    let chain_hash = tx_hash.clone();
+    let stub = common::serve(axum::Router::new().route(
+        "/",
+        axum::routing::post(move |axum::Json(call): axum::Json<Value>| async move {
+            let quantity = |n: u128| json!(format!("0x{:x}", n));
+            let mut balances = chain_balances.lock().unwrap();
+            let result = match call["method"].as_str().unwrap() {
+                "eth_chainId" => quantity(86137),
+                "eth_gasPrice" => quantity(gas_price),
+                "eth_estimateGas" => quantity(gas),
+                "eth_getTransactionCount" => quantity(0),
+                "eth_getBalance" => {
+                    let address = call["params"][0].as_str().unwrap().to_lowercase();
+                    quantity(balances.get(&address).copied().unwrap_or_default())
+                }
+                "eth_sendRawTransaction" => {
+                    let raw = hex::decode(&call["params"][0].as_str().unwrap()[2..]).unwrap();
+                    let items = rlp_items(&raw);
+                    let to = format!("0x{}", hex::encode(&items[3]));
+                    let value = items[4].iter().fold(0u128, |n, b| n << 8 | *b as u128);
+                    let fee = gas_price * gas;
+                    let from = balances.get_mut(old_address).unwrap();
+                    assert!(*from >= value + fee, "sweep overdraws the old signer");
+                    *from -= value + fee;
+                    *balances.entry(to).or_default() += value;
+                    chain_sent.fetch_add(1, Ordering::SeqCst);
+                    json!(chain_hash)
+                }
+                "eth_getTransactionReceipt" => match chain_mined.load(Ordering::SeqCst) {
+                    0 => Value::Null,
+                    _ => json!({ "transactionHash": call["params"][0], "status": "0x1" }),
+                },
+                method => panic!("unexpected call to {}", method),
+            };
+            axum::Json(json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }))
+        }),
+    ))
+    .await;
+
+    let faucet = Faucet::start(&Backend::Mock, &[("ADMIN_TOKEN", "root")]).await;
+    let key_file = faucet.database.with_extension("key");
+    let next_file = faucet.database.with_extension("key.next");
+    let sweep_file = faucet.database.with_extension("key.next.tx");
+    std::fs::write(&key_file, old_key).unwrap();
+    let rotate = |timeout: &'static str| {
+        let key_file = key_file.to_str().unwrap().to_string();
+        let database = faucet.database.clone();
+        let stub = stub.clone();
+        async move {
+            let args = ["rotate-key", "--key-file", &key_file, "--rpc-url", &stub];
+            faucet_command(&database, &[&args[..], &["--timeout", timeout]].concat()).await
+        }
+    };
+
+    // The sweep isn't mined in time, so the rotation stops half-way
+    let output = rotate("0").await;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("rerun to resume"), "{}", stderr);
+    let new_key = std::fs::read_to_string(&next_file).unwrap();
+    let sweep = std::fs::read_to_string(&sweep_file).unwrap();
+    assert!(sweep.starts_with(&tx_hash), "{}", sweep);
+    assert_eq!(std::fs::read_to_string(&key_file).unwrap(), old_key);
+
+    // Rerunning waits for the same sweep with the same key
+    mined.store(1, Ordering::SeqCst);
+    let output = rotate("20").await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(sent.load(Ordering::SeqCst), 1);
+    assert_eq!(std::fs::read_to_string(&key_file).unwrap(), new_key);
+    assert!(!next_file.exists() && !sweep_file.exists());
+
+    let (_, audit) = faucet.admin_get("/v1/admin/audit", "root").await;
+    let entries = audit["entries"].as_array().unwrap();
+    let entry = entries
+        .iter()
+        .find(|e| e["action"] == "rotate_key")
+        .expect("rotation not audited");
+    assert_eq!(entry["payload"]["old_address"], old_address);
+    assert_eq!(entry["payload"]["tx_hash"], tx_hash);
+    let new_address = entry["payload"]["new_address"].as_str().unwrap();
+    let swept = 10 * WEI_PER_AXX - gas_price * gas;
+    assert_eq!(entry["payload"]["amount"], swept.to_string());
+    let balances = balances.lock().unwrap();
+    assert_eq!(balances[new_address], swept);
+    assert_eq!(balances[old_address], 0);
+
+    let _ = std::fs::remove_file(key_file);
+}
+
+#[tokio::test]
+async fn hung_reputation_api_times_out() {
+    let stub = common::serve(axum::Router::new().route(
+        "/reputation/:ip",
+        axum::routing::get(|| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            axum::Json(json!({ "proxy": true }))
+        }),
+    ))
+    .await;
+    let reputation_url = format!("{}/reputation/{{ip}}", stub);
+
+    let faucet = Faucet::start(&Backend::Mock, &[("REPUTATION_API_URL", &reputation_url)]).await;
+    let started = Instant::now();
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("REPUTATION_API_URL", &reputation_url),
+            ("REPUTATION_ON_ERROR", "challenge"),
+        ],
+    )
+    .await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["challenge"], "pow");
+}
+
+#[tokio::test]
+async fn slow_checks_run_concurrently_but_grants_stay_single() {
+    // Every reputation lookup takes a second
+    let stub = common::serve(axum::Router::new().route(
+        "/reputation/:ip",
+        axum::routing::get(|| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            axum::Json(json!({ "proxy": false }))
+        }),
+    ))
+    .await;
+    let reputation_url = format!("{}/reputation/{{ip}}", stub);
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("REPUTATION_API_URL", &reputation_url),
+            ("IP_LIMIT", "100/1h@100"),
+        ],
+    )
+    .await;
+
+    let started = Instant::now();
+    let requests = (1..=4).map(|seed| faucet.request_with(json!({ "address": recipient(seed) })));
+    for (status, body) in futures_util::future::join_all(requests).await {
+        assert_eq!(status, 200, "{}", body);
+    }
+    let elapsed = started.elapsed();
+    assert!(elapsed < Duration::from_secs(3), "{:?}", elapsed);
+
+    // Requests for one address all pass the first look, but only one is sent
+    let address = recipient(5);
+    let requests = (0..4).map(|_| faucet.request(&address));
+    let mut statuses: Vec<u16> = futures_util::future::join_all(requests)
+        .await
+        .into_iter()
+        .map(|(status, _)| status)
+        .collect();
+    statuses.sort();
+    assert_eq!(statuses, [200, 429, 429, 429]);
+}
+
+#[tokio::test]
+async fn branding_renames_the_network_and_token() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("NETWORK_NAME", "Acme Devnet"),
+            ("TOKEN_SYMBOL", "tAXX"),
+            ("SUPPORT_URL", "https://support.acme.dev"),
+            ("EXPLORER_TX_URL", "https://explorer.acme.dev/tx/{tx}"),
+        ],
+    )
+    .await;
+    let (_, info) = faucet.get("/v1/info").await;
+    assert_eq!(info["network"], "Acme Devnet");
+    assert_eq!(info["symbol"], "tAXX");
+    assert_eq!(info["amount"], "100 tAXX");
+    assert_eq!(info["explorer_tx_url"], "https://explorer.acme.dev/tx/{tx}");
+
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "100 tAXX");
+    assert!(body.get("support_url").is_none());
+    let tx_hash = body["tx_hash"].as_str().unwrap();
+    assert_eq!(
+        body["explorer_url"],
+        format!("https://explorer.acme.dev/tx/{}", tx_hash)
+    );
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 429, "{}", body);
+    assert_eq!(body["support_url"], "https://support.acme.dev");
+}
+
+#[tokio::test]
+async fn verified_humans_get_identity_perks() {
+    // BrightID node where recipient 2 and 3 are linked to the same person
+    let app = axum::Router::new().route(
+        "/verifications/:context/:address",
+        axum::routing::get(
+            |axum::extract::Path((_, address)): axum::extract::Path<(String, String)>| async move {
+                axum::Json(json!({
+                    "data": { "unique": true, "contextIds": [address, recipient(2)] }
+                }))
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let node = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("BRIGHTID_CONTEXT", "faucet-e2e"),
+            ("BRIGHTID_NODE_URL", &node),
+            ("IDENTITY_AMOUNT_AXX", "250"),
+            ("IDENTITY_COOLDOWN_HOURS", "0"),
+            ("IP_LIMIT", "60/1m@10"),
+        ],
+    )
+    .await;
+    let verified =
+        |seed| json!({ "address": recipient(seed), "identity": { "provider": "brightid" } });
+
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "100 AXX");
+
+    let (status, body) = faucet.request_with(verified(2)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "250 AXX");
+
+    // The verification is remembered, and the cooldown is waived
+    let (status, body) = faucet.request(&recipient(2)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "250 AXX");
+
+    // One person, one verified address
+    let (status, body) = faucet.request_with(verified(3)).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["code"], "identity_rejected");
+}
+
+#[tokio::test]
+async fn reports_invalid_fields() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+
+    let (status, body) = faucet.request("0x1234").await;
+    assert_eq!(status, 422, "{}", body);
+    assert_eq!(body["code"], "invalid_request");
+    assert_eq!(body["errors"][0]["field"], "address");
+    assert_eq!(body["errors"][0]["code"], "invalid_address");
+
+    let (status, body) = faucet
+        .request_with(json!({ "address": recipient(1), "pow": { "nonce": "1" }, "chain_id": 1 }))
+        .await;
+    assert_eq!(status, 422, "{}", body);
+    let fields: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["pow.challenge", "pow.nonce", "chain_id"]);
+
+    // Broken JSON is still a plain bad request
+    let response = reqwest::Client::new()
+        .post(format!("{}/v1/request", faucet.url))
+        .header("content-type", "application/json")
+        .body("{\"address\":")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn ip_limit_applies_across_addresses() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+
+    for seed in 1..=3 {
+        let (status, body) = faucet.request(&recipient(seed)).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+    let (status, body) = faucet.request(&recipient(4)).await;
+    assert_eq!(status, 429, "{}", body);
+}
+
+#[tokio::test]
+async fn ip_limit_refills_at_sustained_rate() {
+    let faucet = Faucet::start(&Backend::Mock, &[("IP_LIMIT", "60/1m@2")]).await;
+
+    for seed in 1..=2 {
+        let (status, body) = faucet.request(&recipient(seed)).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+    let (status, body) = faucet.request(&recipient(3)).await;
+    assert_eq!(status, 429, "{}", body);
+
+    // One grant per second at the sustained rate
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let (status, body) = faucet.request(&recipient(3)).await;
+    assert_eq!(status, 200, "{}", body);
+}
+
+#[tokio::test]
+async fn route_limits_apply_per_route() {
+    let faucet = Faucet::start(&Backend::Mock, &[("RATE_LIMITS", "/info=1/1h@2")]).await;
+
+    // The deprecated path shares the limit of its /v1 successor
+    assert_eq!(faucet.get("/v1/info").await.0, 200);
+    assert_eq!(faucet.get("/info").await.0, 200);
+    let (status, body) = faucet.get("/v1/info").await;
+    assert_eq!(status, 429, "{}", body);
+    assert_eq!(faucet.get("/v1/stats").await.0, 200);
+}
+
+#[tokio::test]
+async fn legacy_paths_are_deprecated() {
+    let sunset = "Sat, 01 May 2027 00:00:00 GMT";
+    let faucet = Faucet::start(&Backend::Mock, &[("LEGACY_API_SUNSET", sunset)]).await;
+    let get = |path: &str| faucet.http.get(format!("{}{}", faucet.url, path)).send();
+
+    let legacy = get("/stats").await.unwrap();
+    assert_eq!(legacy.status(), 200);
+    assert_eq!(legacy.headers()["deprecation"], "@1792108800");
+    assert_eq!(legacy.headers()["sunset"], sunset);
+    assert_eq!(
+        legacy.headers()["link"],
+        "</v1/stats>; rel=\"successor-version\""
+    );
+    let legacy: Value = legacy.json().await.unwrap();
+    assert_eq!(legacy, faucet.get("/v1/stats").await.1);
+
+    let current = get("/v1/stats").await.unwrap();
+    assert!(current.headers().get("deprecation").is_none());
+    let metrics = get("/metrics").await.unwrap().text().await.unwrap();
+    assert!(
+        metrics.contains("faucet_http_deprecated_requests_total{route=\"/stats\"} 1"),
+        "{}",
+        metrics
+    );
+}
+
+#[tokio::test]
+async fn slow_rpc_sheds_requests() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("DRY_RUN_RPC_LATENCY_MS", "50"),
+            ("SHED_RPC_P95_MS", "20"),
+            ("SHED_PERCENT", "100"),
+            ("SHED_RETRY_AFTER_SECS", "15"),
+        ],
+    )
+    .await;
+
+    // Drips make enough slow RPC calls to push the p95 over the threshold
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut seed = 1;
+    loop {
+        let (_, ready) = faucet.get("/readyz").await;
+        if ready["load_shedding"]["shedding"] == true {
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "never started shedding: {}",
+            ready
+        );
+        faucet.request(&recipient(seed)).await;
+        seed += 1;
+    }
+
+    let response = faucet
+        .http
+        .post(format!("{}/v1/request", faucet.url))
+        .json(&json!({ "address": recipient(100) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 503);
+    assert_eq!(response.headers()["retry-after"], "15");
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "overloaded");
+
+    let (status, ready) = faucet.get("/readyz").await;
+    assert_eq!(status, 200);
+    assert!(ready["load_shedding"]["rpc_p95_ms"].as_u64().unwrap() >= 50);
+    assert_eq!(ready["load_shedding"]["shed_total"], 1);
+    let metrics = faucet
+        .http
+        .get(format!("{}/metrics", faucet.url))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains("faucet_load_shedding 1"), "{}", metrics);
+    assert!(
+        metrics.contains("faucet_requests_shed_total 1"),
+        "{}",
+        metrics
+    );
+}
+
+#[tokio::test]
+async fn drip_reduces_faucet_balance() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    wait_for_balance(&faucet, "1000000 AXX").await;
+
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    wait_for_balance(&faucet, "999900 AXX").await;
+    let stats = faucet.stats().await;
+    assert_eq!(stats["total_requests"], 1);
+    assert_eq!(stats["total_distributed"], "100 AXX");
+}
+
+#[tokio::test]
+async fn stats_stream_pushes_changes() {
+    let faucet = Faucet::start(&Backend::Mock, &[("JOB_PUBLISH_STATS_SECS", "1")]).await;
+    let mut stream = faucet
+        .http
+        .get(format!("{}/v1/stats/stream", faucet.url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        stream.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let mut buffer = String::new();
+
+    let stats = next_stats(&mut stream, &mut buffer).await;
+    assert_eq!(stats["total_requests"], 0);
+
+    let (status, body) = faucet.request(&recipient(9)).await;
+    assert_eq!(status, 200, "{}", body);
+    // A balance refresh may be pushed before the drip is counted
+    loop {
+        let stats = next_stats(&mut stream, &mut buffer).await;
+        if stats["total_requests"] == 1 {
+            assert_eq!(stats["total_distributed"], "100 AXX");
+            break;
+        }
+    }
+}
+
+/// Data of the next `stats` event on a server-sent event stream
+async fn next_stats(stream: &mut reqwest::Response, buffer: &mut String) -> Value {
+    loop {
+        if let Some(end) = buffer.find("\n\n") {
+            let event: String = buffer.drain(..end + 2).collect();
+            if !event.lines().any(|line| line == "event: stats") {
+                continue;
+            }
+            let data = event
+                .lines()
+                .find_map(|line| line.strip_prefix("data: "))
+                .unwrap();
+            return serde_json::from_str(data).unwrap();
+        }
+        let chunk = tokio::time::timeout(Duration::from_secs(10), stream.chunk())
+            .await
+            .expect("no stats event")
+            .unwrap()
+            .expect("stream ended");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn low_balance_is_notified() {
+    // Slack-style webhook recording what it receives
+    let (sender, mut received) = tokio::sync::mpsc::unbounded_channel::<Value>();
+    let app = axum::Router::new().route(
+        "/hook",
+        axum::routing::post(move |axum::Json(body): axum::Json<Value>| {
+            let sender = sender.clone();
+            async move {
+                let _ = sender.send(body);
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hook = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let config = std::env::temp_dir().join(format!("faucet-notify-{}.json", std::process::id()));
+    let routes = json!({
+        "source": "faucet-e2e",
+        "channels": { "ops": { "type": "slack", "webhook_url": hook } },
+        "routes": { "low_balance": ["ops"] },
+    });
+    std::fs::write(&config, routes.to_string()).unwrap();
+
+    // The mock chain funds the faucet with 1,000,000 AXX
+    let _faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("NOTIFICATIONS_FILE", config.to_str().unwrap()),
+            ("LOW_BALANCE_AXX", "2000000"),
+        ],
+    )
+    .await;
+    let body = tokio::time::timeout(Duration::from_secs(10), received.recv())
+        .await
+        .expect("no notification")
+        .unwrap();
+    let text = body["text"].as_str().unwrap();
+    assert!(
+        text.starts_with("[WARNING] faucet-e2e: Faucet balance low"),
+        "{}",
+        text
+    );
+    assert!(text.contains("balance: 1000000 AXX"), "{}", text);
+    let _ = std::fs::remove_file(config);
+}
+
+#[tokio::test]
+async fn runway_projects_from_recent_drips() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+
+    let (status, runway) = faucet.get("/v1/stats/runway").await;
+    assert_eq!(status, 200, "{}", runway);
+    assert_eq!(runway["window_days"], 7);
+    assert_eq!(runway["burn_per_day"], "0 AXX");
+    assert!(runway.get("runway_days").is_none(), "{}", runway);
+
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // 100 AXX a day against the mock chain's 1,000,000 AXX
+    let (status, runway) = faucet.get("/v1/stats/runway?days=1").await;
+    assert_eq!(status, 200, "{}", runway);
+    assert_eq!(runway["distributed"], "100 AXX");
+    assert_eq!(runway["burn_per_day"], "100 AXX");
+    let days = runway["runway_days"].as_f64().unwrap();
+    assert!((9999.0..=10000.0).contains(&days), "{}", runway);
+    assert!(runway["depletes_at"].as_i64().is_some(), "{}", runway);
+}
+
+#[tokio::test]
+async fn timeseries_buckets_requests_and_drips() {
+    let faucet = Faucet::start(&Backend::Mock, &[]).await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 429, "{}", body);
+
+    let (status, stats) = faucet
+        .get("/v1/stats/timeseries?bucket=hour&buckets=3")
+        .await;
+    assert_eq!(status, 200, "{}", stats);
+    assert_eq!(stats["bucket"], "hour");
+    let series = stats["series"].as_array().unwrap();
+    assert_eq!(series.len(), 3, "{}", stats);
+    let starts: Vec<i64> = series
+        .iter()
+        .map(|b| b["start"].as_i64().unwrap())
+        .collect();
+    assert!(starts.iter().all(|start| start % 3600 == 0), "{}", stats);
+    assert_eq!(starts[2] - starts[0], 2 * 3600, "{}", stats);
+    // Both requests may straddle an hour boundary, so count across buckets
+    let total = |field: &str| {
+        series
+            .iter()
+            .map(|b| b[field].as_u64().unwrap())
+            .sum::<u64>()
+    };
+    assert_eq!(total("requests"), 2, "{}", stats);
+    assert_eq!(total("grants"), 1, "{}", stats);
+    assert_eq!(total("rejects"), 1, "{}", stats);
+    assert_eq!(series[0]["distributed"], "0 AXX", "{}", stats);
+    assert!(
+        series.iter().any(|b| b["distributed"] == "100 AXX"),
+        "{}",
+        stats
+    );
+
+    let (status, stats) = faucet
+        .get("/v1/stats/timeseries?bucket=day&buckets=2")
+        .await;
+    assert_eq!(status, 200, "{}", stats);
+    let series = stats["series"].as_array().unwrap();
+    assert_eq!(series[1]["start"].as_i64().unwrap() % 86400, 0, "{}", stats);
+    let total = |field: &str| {
+        series
+            .iter()
+            .map(|b| b[field].as_u64().unwrap())
+            .sum::<u64>()
+    };
+    assert_eq!(total("requests"), 2, "{}", stats);
+    assert_eq!(total("grants"), 1, "{}", stats);
+}
+
+#[tokio::test]
+async fn compaction_prunes_requests_but_keeps_drips() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("LEDGER_RETENTION_DAYS", "0"),
+            ("JOB_COMPACT_LEDGER_SECS", "1"),
+        ],
+    )
+    .await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let series = loop {
+        let (status, stats) = faucet
+            .get("/v1/stats/timeseries?bucket=day&buckets=2")
+            .await;
+        assert_eq!(status, 200, "{}", stats);
+        let series = stats["series"].as_array().unwrap().clone();
+        if series.iter().all(|b| b["requests"] == 0) {
+            break series;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "requests were not pruned: {}",
+            stats
+        );
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    // The drip itself is a transfer, which is kept
+    assert!(
+        series.iter().any(|b| b["distributed"] == "100 AXX"),
+        "{:?}",
+        series
+    );
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 429, "{}", body);
+}
+
+#[tokio::test]
+async fn leaderboard_counts_activity_since_the_drip() {
+    // Known CREATE vector: this sender deploys 0xf778…0c91 with nonce 2
+    let builder = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+    let (sender, idle) = (recipient(2).to_lowercase(), recipient(3).to_lowercase());
+    let chain = StubChain::start().await;
+    // The builder had already deployed a contract before its drip
+    chain.set_nonce(builder, 1);
+    chain.deploy("0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d");
+    let faucet = Faucet::start(&chain.backend(), &[("JOB_REFRESH_LEADERBOARD_SECS", "1")]).await;
+    for address in [builder, &sender, &idle] {
+        let (status, body) = faucet.request(address).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+
+    // Three transactions after the drip, one of them deploying a contract
+    chain.set_nonce(builder, 4);
+    chain.deploy("0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91");
+    chain.set_nonce(&sender, 5);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let entries = loop {
+        let (status, board) = faucet.get("/v1/leaderboard").await;
+        assert_eq!(status, 200, "{}", board);
+        let entries = board["entries"].as_array().unwrap().clone();
+        if entries.len() == 2 {
+            break entries;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "leaderboard not refreshed: {}",
+            board
+        );
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    // Recipients without activity aren't ranked
+    assert_eq!(entries[0]["rank"], 1);
+    assert_eq!(entries[0]["address"], builder);
+    assert_eq!(entries[0]["transactions"], 3, "{:?}", entries);
+    assert_eq!(entries[0]["contracts"], 1, "{:?}", entries);
+    assert_eq!(entries[1]["address"], sender);
+    assert_eq!(entries[1]["transactions"], 5, "{:?}", entries);
+    assert_eq!(entries[1]["contracts"], 0, "{:?}", entries);
+
+    let (_, board) = faucet.get("/v1/leaderboard?by=transactions&limit=1").await;
+    assert_eq!(board["by"], "transactions");
+    assert_eq!(board["entries"].as_array().unwrap().len(), 1, "{}", board);
+    assert_eq!(board["entries"][0]["address"], sender, "{}", board);
+}
+
+#[tokio::test]
+async fn names_resolve_through_the_resolver() {
+    let resolver = "0x00000000000000000000000000000000000e4500";
+    let owner = recipient(9).to_lowercase();
+    let chain = StubChain::start().await;
+    chain.answer_call(
+        &format!("0x3b3b57de{}", hex::encode(namehash("alice.axx"))),
+        &format!("0x{:0>64}", owner.trim_start_matches("0x")),
+    );
+    let faucet = Faucet::start(&chain.backend(), &[("NAME_RESOLVER", resolver)]).await;
+
+    let (status, body) = faucet.request("Alice.axx").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["resolved_address"], owner, "{}", body);
+    let (status, body) = faucet.request(&owner).await;
+    assert_eq!(status, 429, "{}", body);
+
+    // The second lookup is served from the cache
+    let calls = chain.served("eth_call");
+    assert!(calls > 0);
+    let (status, body) = faucet.request("alice.axx").await;
+    assert_eq!(status, 429, "{}", body);
+    assert_eq!(chain.served("eth_call"), calls);
+
+    // Unknown names resolve to the zero address, other suffixes aren't tried
+    for name in ["bob.axx", "alice.eth"] {
+        let (status, body) = faucet.request(name).await;
+        assert_eq!(status, 400, "{}", body);
+        assert_eq!(body["code"], "unresolved_name", "{}", body);
+    }
+}
+
+/// ENS namehash of `name`
+fn namehash(name: &str) -> [u8; 32] {
+    name.rsplit('.').fold([0; 32], |node, label| {
+        Keccak256::new()
+            .chain_update(node)
+            .chain_update(Keccak256::digest(label))
+            .finalize()
+            .into()
+    })
+}
+
+#[tokio::test]
+async fn treasury_tops_up_below_the_threshold() {
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            // A well-known development key, funded on the mock chain
+            (
+                "TREASURY_PRIVATE_KEY",
+                "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+            ),
+            ("TREASURY_THRESHOLD_AXX", "999950"),
+            ("TREASURY_TOPUP_AXX", "500"),
+            ("TREASURY_CHECK_INTERVAL_SECS", "1"),
+        ],
+    )
+    .await;
+    // The starting balance of 1,000,000 AXX is above the threshold
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(faucet.stats().await["total_top_ups"], 0);
+
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let stats = loop {
+        let stats = faucet.stats().await;
+        if stats["total_top_ups"] != 0 && stats["faucet_balance"] == "1000400 AXX" {
+            break stats;
+        }
+        assert!(Instant::now() < deadline, "no top-up: {}", stats);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    // The cooldown holds back a second top-up
+    assert_eq!(stats["total_top_ups"], 1, "{}", stats);
+    assert_eq!(stats["total_topped_up"], "500 AXX", "{}", stats);
+}
+
+#[tokio::test]
+async fn tasks_gate_drips_until_completed() {
+    // Known CREATE vector: this sender deploys 0xf778…0c91 with nonce 2
+    let builder = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+    let dex = "0x00000000000000000000000000000000000d0e00";
+    let chain = StubChain::start().await;
+    let tasks_file =
+        std::env::temp_dir().join(format!("faucet-e2e-tasks-{}.json", std::process::id()));
+    let tasks = json!([
+        { "id": "active", "description": "Send 3 transactions", "kind": "transactions", "min": 3 },
+        { "id": "deploy", "description": "Deploy a contract", "kind": "deploy_contract" },
+        {
+            "id": "swap", "description": "Swap on AxSwap", "kind": "event",
+            "contract": dex, "event": "Swap(address,uint256,uint256)", "topic": 1
+        }
+    ]);
+    std::fs::write(&tasks_file, tasks.to_string()).unwrap();
+    let faucet = Faucet::start(
+        &chain.backend(),
+        &[("TASKS_FILE", tasks_file.to_str().unwrap())],
+    )
+    .await;
+    let progress = || async {
+        let (status, tasks) = faucet.get(&format!("/v1/tasks/{}", builder)).await;
+        assert_eq!(status, 200, "{}", tasks);
+        tasks
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["completed"].as_bool().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(progress().await, [false, false, false]);
+    let (status, body) = faucet.request(builder).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["code"], "task_incomplete", "{}", body);
+    assert!(
+        body["error"]
+            .as_str()
+            .unwrap()
+            .contains("Send 3 transactions"),
+        "{}",
+        body
+    );
+
+    // Three transactions, the last of which deployed a contract
+    chain.set_nonce(builder, 3);
+    assert_eq!(progress().await, [true, false, false]);
+    chain.deploy("0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91");
+    assert_eq!(progress().await, [true, true, false]);
+
+    // A swap by someone else doesn't count
+    let swap = format!(
+        "0x{}",
+        hex::encode(Keccak256::digest("Swap(address,uint256,uint256)"))
+    );
+    let trader = |address: &str| format!("0x{:0>64}", address.trim_start_matches("0x"));
+    chain.emit(dex, &[swap.clone(), trader(&recipient(4))]);
+    assert_eq!(progress().await, [true, true, false]);
+    chain.emit(dex, &[swap, trader(builder)]);
+    assert_eq!(progress().await, [true, true, true]);
+
+    let (status, body) = faucet.request(builder).await;
+    assert_eq!(status, 200, "{}", body);
+    let _ = std::fs::remove_file(&tasks_file);
+}
+
+#[tokio::test]
+async fn referrals_need_a_valid_token_from_the_partner_origin() {
+    let partners_file =
+        std::env::temp_dir().join(format!("faucet-e2e-partners-{}.json", std::process::id()));
+    let partners = json!([{
+        "id": "acme",
+        "origins": ["https://app.acme.xyz"],
+        "secret": "change-me",
+        "daily_budget_axx": "100"
+    }]);
+    std::fs::write(&partners_file, partners.to_string()).unwrap();
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[("PARTNERS_FILE", partners_file.to_str().unwrap())],
+    )
+    .await;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let token = |address: &str, expires: i64| {
+        let mut mac = Hmac::<Sha3_256>::new_from_slice(b"change-me").unwrap();
+        mac.update(format!("acme.{}.{}", address, expires).as_bytes());
+        format!(
+            "acme.{}.{}",
+            expires,
+            hex::encode(mac.finalize().into_bytes())
+        )
+    };
+    let refer = |address: String, token: String, origin: &'static str| {
+        let request = faucet
+            .http
+            .post(format!("{}/v1/request", faucet.url))
+            .header("Origin", origin)
+            .json(&json!({ "address": address, "referral": token }));
+        async move {
+            let response = request.send().await.unwrap();
+            let status = response.status().as_u16();
+            (status, response.json::<Value>().await.unwrap())
+        }
+    };
+    let (first, second) = (recipient(1).to_lowercase(), recipient(2).to_lowercase());
+    let origin = "https://app.acme.xyz";
+
+    let rejected = [
+        // Another site replaying the token
+        refer(
+            first.clone(),
+            token(&first, now + 600),
+            "https://evil.example",
+        )
+        .await,
+        refer(first.clone(), token(&first, now - 1), origin).await,
+        // Signed for another recipient
+        refer(first.clone(), token(&second, now + 600), origin).await,
+        // Signed for a later expiry
+        refer(
+            first.clone(),
+            token(&first, now + 600).replacen(
+                &(now + 600).to_string(),
+                &(now + 9999).to_string(),
+                1,
+            ),
+            origin,
+        )
+        .await,
+        // Signed with the old unkeyed SHA3 scheme
+        refer(
+            first.clone(),
+            format!(
+                "acme.{}.{}",
+                now + 600,
+                hex::encode(Sha3_256::digest(format!(
+                    "change-meacme.{}.{}",
+                    first,
+                    now + 600
+                )))
+            ),
+            origin,
+        )
+        .await,
+    ];
+    for (status, body) in rejected {
+        assert_eq!(status, 403, "{}", body);
+        assert_eq!(body["code"], "invalid_referral", "{}", body);
+    }
+
+    let (status, body) = refer(first.clone(), token(&first, now + 600), origin).await;
+    assert_eq!(status, 200, "{}", body);
+    // The 100 AXX budget is spent on the first referral
+    let (status, body) = refer(second.clone(), token(&second, now + 600), origin).await;
+    assert_eq!(status, 429, "{}", body);
+    assert_eq!(body["code"], "partner_budget_exhausted", "{}", body);
+    let _ = std::fs::remove_file(&partners_file);
+}
+
+#[tokio::test]
+async fn geo_limits_cap_grants_per_country_and_asn() {
+    let dir = std::env::temp_dir().join(format!("faucet-e2e-geoip-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (country_db, asn_db) = (dir.join("country.mmdb"), dir.join("asn.mmdb"));
+    // Test clients connect from loopback
+    common::write_mmdb(
+        &country_db,
+        &[
+            ("127.0.0.0/8", json!({ "country": { "iso_code": "DE" } })),
+            (
+                "198.51.100.0/24",
+                json!({ "country": { "iso_code": "US" } }),
+            ),
+        ],
+    );
+    common::write_mmdb(
+        &asn_db,
+        &[(
+            "127.0.0.0/8",
+            json!({
+                "autonomous_system_number": 64500,
+                "autonomous_system_organization": "Loopback Networks",
+            }),
+        )],
+    );
+    let databases = [
+        ("ADMIN_TOKEN", "root"),
+        ("GEOIP_COUNTRY_DB", country_db.to_str().unwrap()),
+        ("GEOIP_ASN_DB", asn_db.to_str().unwrap()),
+    ];
+
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            databases.as_slice(),
+            &[("GEOIP_COUNTRY_LIMITS", "de:2,US:0")],
+        ]
+        .concat(),
+    )
+    .await;
+    for seed in [1, 2] {
+        let (status, body) = faucet.request(&recipient(seed)).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+    let (status, body) = faucet.request(&recipient(3)).await;
+    assert_eq!(status, 429, "{}", body);
+    assert_eq!(body["code"], "geo_limited", "{}", body);
+
+    let (status, stats) = faucet.admin_get("/v1/admin/stats", "root").await;
+    assert_eq!(status, 200, "{}", stats);
+    assert_eq!(
+        stats["countries"],
+        json!([{ "country": "DE", "requests": 3, "grants": 2 }])
+    );
+    assert_eq!(
+        stats["asns"],
+        json!([{ "asn": 64500, "as_org": "Loopback Networks", "requests": 3, "grants": 2 }])
+    );
+    // Another country has its own limit
+    let why = why_from(&faucet, "198.51.100.7").await;
+    let geo = why["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["check"] == "geo_limit")
+        .unwrap();
+    assert_eq!(geo["code"], "geo_limited", "{}", why);
+
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[databases.as_slice(), &[("GEOIP_ASN_LIMITS", "AS64500:1")]].concat(),
+    )
+    .await;
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    let (status, body) = faucet.request(&recipient(2)).await;
+    assert_eq!(status, 429, "{}", body);
+    assert_eq!(body["code"], "geo_limited", "{}", body);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn policy_script_decides_grants() {
+    let script =
+        std::env::temp_dir().join(format!("faucet-e2e-policy-{}.rhai", std::process::id()));
+    std::fs::write(
+        &script,
+        r#"
+        fn decide(req) {
+            switch req.address.sub_string(2, 2) {
+                "01" => true,
+                "02" => false,
+                "03" => #{ allow: true, amount: 20 },
+                "04" => #{ allow: false, reason: "lifetime limit reached" },
+                "05" => #{ allow: true, amount: 1000 },
+                _ => { let n = 0; loop { n += 1; } }
+            }
+        }
+        "#,
+    )
+    .unwrap();
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("POLICY_SCRIPT", script.to_str().unwrap()),
+            ("IP_LIMIT", "100/1h@100"),
+        ],
+    )
+    .await;
+
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "100 AXX");
+    let (status, body) = faucet.request(&recipient(2)).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["code"], "denied", "{}", body);
+    assert_eq!(body["error"], "Request denied: denied by policy script");
+
+    let (status, body) = faucet.request(&recipient(3)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "20 AXX");
+    let (status, body) = faucet.request(&recipient(4)).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["error"], "Request denied: lifetime limit reached");
+    // Scripts can't raise the drip
+    let (status, body) = faucet.request(&recipient(5)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "100 AXX");
+
+    // A runaway script is stopped by the operation limit
+    let started = Instant::now();
+    let (status, body) = faucet.request(&recipient(6)).await;
+    assert_eq!(status, 500, "{}", body);
+    assert_eq!(body["code"], "policy_script_error", "{}", body);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let _ = std::fs::remove_file(&script);
+}
+
+#[tokio::test]
+async fn json_logs_carry_the_request_id() {
+    let log = std::env::temp_dir().join(format!("faucet-e2e-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&log);
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("LOG_FORMAT", "json"),
+            ("LOG_FILE", log.to_str().unwrap()),
+            ("LOG_ROTATION", "never"),
+            ("RUST_LOG", "info"),
+        ],
+    )
+    .await;
+    let response = faucet
+        .http
+        .post(format!("{}/v1/request", faucet.url))
+        .header("x-request-id", "e2e-log-1")
+        .json(&json!({ "address": recipient(1) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["x-request-id"], "e2e-log-1");
+
+    // The file writer is non-blocking, so wait for the drip to be flushed
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let lines: Vec<Value> = loop {
+        let contents = std::fs::read_to_string(&log).unwrap_or_default();
+        if contents.contains("Sending") || Instant::now() > deadline {
+            break contents
+                .lines()
+                .map(|line| serde_json::from_str(line).expect(line))
+                .collect();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    drop(faucet);
+    let _ = std::fs::remove_file(&log);
+
+    assert!(lines.iter().all(|line| line["timestamp"].is_string()
+        && line["level"].is_string()
+        && line["target"].is_string()
+        && line["fields"]["message"].is_string()));
+    let logged = |prefix: &str| {
+        lines.iter().find(|line| {
+            line["fields"]["message"]
+                .as_str()
+                .is_some_and(|m| m.starts_with(prefix))
+        })
+    };
+    assert!(logged("🚰 Faucet server starting").is_some());
+    let sending = logged("Sending").expect("no drip logged");
+    assert_eq!(sending["level"], "INFO");
+    assert_eq!(sending["span"]["request_id"], "e2e-log-1");
+    assert_eq!(sending["span"]["method"], "POST");
+    assert_eq!(sending["span"]["path"], "/v1/request");
+}
+
+#[cfg(feature = "error-report")]
+#[tokio::test]
+async fn server_errors_are_reported() {
+    let reports = Arc::new(Mutex::new(Vec::<Value>::new()));
+    let received = reports.clone();
+    let webhook = common::serve(axum::Router::new().route(
+        "/",
+        axum::routing::post(move |axum::Json(report): axum::Json<Value>| async move {
+            received.lock().unwrap().push(report);
+        }),
+    ))
+    .await;
+    let chain = StubChain::start().await;
+    chain.fail("eth_sendRawTransaction");
+    let faucet = Faucet::start(
+        &chain.backend(),
+        &[("ERROR_REPORT_URL", &webhook), ("ERROR_REPORT_ENV", "e2e")],
+    )
+    .await;
+
+    let response = faucet
+        .http
+        .post(format!("{}/v1/request", faucet.url))
+        .header("x-request-id", "e2e-report-1")
+        .json(&json!({ "address": recipient(1) }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_server_error(), "{:?}", response);
+    let status = response.status().as_u16();
+
+    // Reports are posted in the background
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let report = loop {
+        let found = reports
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|r| r["kind"] == "server_error")
+            .cloned();
+        if let Some(report) = found {
+            break report;
+        }
+        assert!(Instant::now() < deadline, "{:?}", reports.lock().unwrap());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    assert_eq!(report["status"], status);
+    assert_eq!(report["request_id"], "e2e-report-1");
+    assert_eq!(report["method"], "POST");
+    assert_eq!(report["path"], "/v1/request");
+    assert_eq!(report["environment"], "e2e");
+    assert_eq!(report["service"], "axionax-faucet");
+    assert!(report["message"].as_str().unwrap().contains("RPC error"));
+    assert!(reports
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|r| r["kind"] == "rpc_error"));
+}
+
+#[tokio::test]
+async fn invalid_sqlite_settings_are_rejected() {
+    let database =
+        std::env::temp_dir().join(format!("faucet-e2e-sqlite-{}.db", std::process::id()));
+    let cases = [
+        (
+            "SQLITE_JOURNAL_MODE",
+            "memory",
+            "SQLITE_JOURNAL_MODE must be one of",
+        ),
+        (
+            "SQLITE_SYNCHRONOUS",
+            "extra",
+            "SQLITE_SYNCHRONOUS must be one of",
+        ),
+        (
+            "SQLITE_BUSY_TIMEOUT_MS",
+            "-1",
+            "Invalid SQLITE_BUSY_TIMEOUT_MS",
+        ),
+        ("SQLITE_CACHE_MB", "lots", "Invalid SQLITE_CACHE_MB"),
+    ];
+    for (var, value, error) in cases {
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet"))
+            .arg("--dry-run")
+            .env("DATABASE_PATH", &database)
+            .env(var, value)
+            .env("RUST_LOG", "warn")
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(Duration::from_secs(30), output)
+            .await
+            .unwrap_or_else(|_| panic!("faucet started with {}={}", var, value))
+            .unwrap();
+        assert!(!output.status.success(), "{}: {:?}", var, output);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(error), "{}", stderr);
+    }
+    let _ = std::fs::remove_file(&database);
+
+    // The journal mode is kept in the file, so it reads back after startup
+    let faucet = Faucet::start(&Backend::Mock, &[("SQLITE_JOURNAL_MODE", "WAL")]).await;
+    let conn = rusqlite::Connection::open(&faucet.database).unwrap();
+    let mode: String = conn
+        .pragma_query_value(None, "journal_mode", |row| row.get(0))
+        .unwrap();
+    assert_eq!(mode, "wal");
+}
+
+#[tokio::test]
+async fn heartbeat_fails_when_worker_stalls() {
+    // Healthchecks-style endpoint recording which URL was hit
+    let (sender, mut pings) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let ok = sender.clone();
+    let app = axum::Router::new()
+        .route(
+            "/check",
+            axum::routing::get(move || {
+                let _ = ok.send("ok".to_string());
+                async {}
+            }),
+        )
+        .route(
+            "/check/fail",
+            axum::routing::post(move |body: String| {
+                let _ = sender.send(format!("fail: {}", body));
+                async {}
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/check", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    // With the confirmation loop disabled it never completes another cycle
+    let _faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("HEARTBEAT_URL", url.as_str()),
+            ("HEARTBEAT_MAX_STALL_SECS", "1"),
+            ("JOB_HEARTBEAT_SECS", "1"),
+            ("JOB_CONFIRM_CLAIMS_SECS", "0"),
+        ],
+    )
+    .await;
+    let mut seen = Vec::new();
+    let ping = loop {
+        let ping = tokio::time::timeout(Duration::from_secs(10), pings.recv())
+            .await
+            .expect("no heartbeat")
+            .unwrap();
+        if ping != "ok" {
+            break ping;
+        }
+        seen.push(ping);
+    };
+    assert!(!seen.is_empty(), "first heartbeat should report healthy");
+    assert!(
+        ping.starts_with("fail: claim confirmation loop idle for"),
+        "{}",
+        ping
+    );
+}
+
+#[tokio::test]
+async fn config_bundles_apply_only_when_signed() {
+    let key = SigningKey::from_bytes(&[7; 32]);
+    let public_key = hex::encode(key.verifying_key().to_bytes());
+    let bundle = Arc::new(Mutex::new(Value::Null));
+    let served = bundle.clone();
+    let stub = common::serve(axum::Router::new().route(
+        "/bundle.json",
+        axum::routing::get(move || async move { axum::Json(served.lock().unwrap().clone()) }),
+    ))
+    .await;
+
+    // The first bundle is signed with the CLI, the way operators publish them
+    let payload = std::env::temp_dir().join(format!("bundle-{}.json", std::process::id()));
+    let blocked = recipient(2);
+    std::fs::write(
+        &payload,
+        json!({ "version": 1, "chain_id": 86137, "amount": "50", "blocklist": [blocked] })
+            .to_string(),
+    )
+    .unwrap();
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_axionax-faucet"))
+        .args(["bundle", "sign"])
+        .arg(&payload)
+        .env("CONFIG_BUNDLE_SIGNING_KEY", hex::encode(key.to_bytes()))
+        .output()
+        .await
+        .unwrap();
+    let _ = std::fs::remove_file(&payload);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    *bundle.lock().unwrap() = serde_json::from_slice(&output.stdout).unwrap();
+
+    let faucet = Faucet::start(
+        &Backend::Mock,
+        &[
+            ("CONFIG_BUNDLE_URL", &format!("{}/bundle.json", stub)),
+            ("CONFIG_BUNDLE_KEY", &public_key),
+            ("JOB_FETCH_CONFIG_BUNDLE_SECS", "1"),
+        ],
+    )
+    .await;
+    let (_, info) = faucet.get("/v1/info").await;
+    assert_eq!(info["config_version"], 1);
+    assert_eq!(info["amount"], "50 AXX");
+    let (status, body) = faucet.request(&recipient(1)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["amount"], "50 AXX");
+    let (status, body) = faucet.request(&blocked).await;
+    assert_eq!(status, 403, "{}", body);
+    assert_eq!(body["code"], "blocked");
+
+    // A tampered payload keeps the applied bundle
+    let signature = bundle.lock().unwrap()["signature"].clone();
+    let tampered = json!({ "version": 2, "amount": "1000" }).to_string();
+    *bundle.lock().unwrap() = json!({ "payload": tampered, "signature": signature });
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert_eq!(faucet.get("/v1/info").await.1["amount"], "50 AXX");
+
+    // Omitted settings fall back to the local configuration
+    let payload = json!({ "version": 2, "amount": "75" }).to_string();
+    let signature = hex::encode(key.sign(payload.as_bytes()).to_bytes());
+    *bundle.lock().unwrap() = json!({ "payload": payload, "signature": signature });
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let (_, info) = faucet.get("/v1/info").await;
+        if info["config_version"] == 2 {
+            assert_eq!(info["amount"], "75 AXX");
+            break;
+        }
+        assert!(Instant::now() < deadline, "bundle not applied: {}", info);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    let (status, body) = faucet.request(&blocked).await;
+    assert_eq!(status, 200, "{}", body);
+}
+
+/// Full request → sign → confirm flow against a real node
+///
+/// Run with `E2E_RPC_URL=... E2E_PRIVATE_KEY=... cargo test -- --ignored`.
+#[tokio::test]
+#[ignore = "requires a devnet or anvil node (E2E_RPC_URL, E2E_PRIVATE_KEY)"]
+async fn node_transfer_changes_balances() {
+    let backend = Backend::from_env();
+    let Backend::Node { rpc_url, .. } = &backend else {
+        panic!("E2E_RPC_URL and E2E_PRIVATE_KEY must be set");
+    };
+    let node = Node::new(rpc_url);
+    let faucet = Faucet::start(&backend, &[]).await;
+    let address = recipient(rand_seed());
+
+    let before = node.balance(&address).await;
+    let (status, body) = faucet.request(&address).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let receipt = node
+        .wait_for_receipt(body["tx_hash"].as_str().unwrap(), Duration::from_secs(60))
+        .await;
+    assert_eq!(receipt["status"], "0x1");
+    assert_eq!(node.balance(&address).await, before + 100 * WEI_PER_AXX);
+}
+
+async fn wait_for_balance(faucet: &Faucet, expected: &str) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let stats = faucet.stats().await;
+        if stats["faucet_balance"] == expected {
+            return;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "faucet balance stuck at {}",
+            stats["faucet_balance"]
+        );
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+fn rand_seed() -> u8 {
+    (std::process::id() % 251) as u8
+}
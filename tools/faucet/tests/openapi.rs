@@ -0,0 +1,82 @@
+//! Consistency of `openapi.json` with the router
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Paths registered with `.route(...)` in `source`, in OpenAPI syntax
+fn route_paths(source: &str) -> Vec<String> {
+    source
+        .split(".route(")
+        .skip(1)
+        .filter_map(|call| {
+            let path = call.trim_start().strip_prefix('"')?.split('"').next()?;
+            let segments: Vec<String> = path
+                .split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                })
+                .collect();
+            Some(segments.join("/"))
+        })
+        .collect()
+}
+
+/// Unversioned routes of `src/main.rs` and the `/v1` routes of `src/api.rs`
+fn router_paths() -> BTreeSet<String> {
+    let api = include_str!("../src/api.rs");
+    let v1 = api
+        .split("pub fn v1()")
+        .nth(1)
+        .and_then(|rest| rest.split("\n}\n").next())
+        .unwrap();
+    route_paths(include_str!("../src/main.rs"))
+        .into_iter()
+        .chain(
+            route_paths(v1)
+                .into_iter()
+                .map(|path| format!("/v1{}", path)),
+        )
+        .collect()
+}
+
+fn spec() -> Value {
+    serde_json::from_str(include_str!("../openapi.json")).unwrap()
+}
+
+#[test]
+fn spec_documents_every_route() {
+    let documented: BTreeSet<String> = spec()["paths"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect();
+    assert_eq!(documented, router_paths());
+}
+
+#[test]
+fn spec_refs_resolve() {
+    let spec = spec();
+    let schemas = spec["components"]["schemas"].as_object().unwrap();
+
+    fn refs(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(r)) = map.get("$ref") {
+                    out.push(r.clone());
+                }
+                map.values().for_each(|v| refs(v, out));
+            }
+            Value::Array(items) => items.iter().for_each(|v| refs(v, out)),
+            _ => {}
+        }
+    }
+
+    let mut found = Vec::new();
+    refs(&spec, &mut found);
+    for r in found {
+        let name = r.strip_prefix("#/components/schemas/").unwrap();
+        assert!(schemas.contains_key(name), "unresolved {}", r);
+    }
+}
@@ -0,0 +1,98 @@
+//! Signing and verification of EIP-712 drip receipts
+
+use axionax_faucet::receipt::{Receipt, ReceiptError};
+use k256::ecdsa::SigningKey;
+
+const CHAIN_ID: u64 = 86137;
+
+fn key() -> SigningKey {
+    SigningKey::from_slice(&[1u8; 32]).unwrap()
+}
+
+fn receipt() -> Receipt {
+    Receipt {
+        claim_id: format!("0x{}", "ab".repeat(32)),
+        recipient: "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".to_string(),
+        amount: "100000000000000000000".to_string(),
+        timestamp: 1_700_000_000,
+        tx_hash: format!("0x{}", "cd".repeat(32)),
+    }
+}
+
+#[test]
+fn signed_receipts_verify() {
+    let signed = receipt().sign(CHAIN_ID, &key()).unwrap();
+    assert_eq!(signed.signer, "0x1a642f0e3c3af545e7acbd38b07251b3990914f1");
+    assert_eq!(signed.signature.len(), 2 + 65 * 2);
+    assert!(signed.verify().is_ok());
+}
+
+#[test]
+fn amounts_beyond_u128_are_signed() {
+    let mut large = receipt();
+    large.amount = format!("1{}", "0".repeat(40));
+    assert!(large.sign(CHAIN_ID, &key()).unwrap().verify().is_ok());
+}
+
+#[test]
+fn tampered_receipts_do_not_verify() {
+    let signed = receipt().sign(CHAIN_ID, &key()).unwrap();
+
+    let mut tampered = signed.clone();
+    tampered.receipt.amount = "200000000000000000000".to_string();
+    assert_eq!(tampered.verify(), Err(ReceiptError::InvalidSignature));
+
+    let mut tampered = signed.clone();
+    tampered.receipt.recipient = "0x0000000000000000000000000000000000000001".to_string();
+    assert_eq!(tampered.verify(), Err(ReceiptError::InvalidSignature));
+
+    let mut replayed = signed.clone();
+    replayed.chain_id = 1;
+    assert_eq!(replayed.verify(), Err(ReceiptError::InvalidSignature));
+}
+
+#[test]
+fn malformed_receipts_are_rejected() {
+    let mut bad = receipt();
+    bad.tx_hash = "0x1234".to_string();
+    assert_eq!(
+        bad.digest(CHAIN_ID),
+        Err(ReceiptError::InvalidField("tx_hash"))
+    );
+
+    for amount in ["-1", "", "0x10", &format!("1{}", "0".repeat(78))] {
+        let mut bad = receipt();
+        bad.amount = amount.to_string();
+        assert_eq!(
+            bad.digest(CHAIN_ID),
+            Err(ReceiptError::InvalidField("amount")),
+            "{:?}",
+            amount
+        );
+    }
+
+    for signature in ["", "0x00", &format!("0x{}", "ff".repeat(65))] {
+        assert_eq!(
+            receipt().recover_signer(CHAIN_ID, signature),
+            Err(ReceiptError::InvalidSignature)
+        );
+    }
+}
+
+#[test]
+fn receipts_serialize_flat() {
+    let signed = receipt().sign(CHAIN_ID, &key()).unwrap();
+    let json = serde_json::to_value(&signed).unwrap();
+    for field in [
+        "claim_id",
+        "recipient",
+        "amount",
+        "timestamp",
+        "tx_hash",
+        "chain_id",
+        "signer",
+        "signature",
+    ] {
+        assert!(json.get(field).is_some(), "{}", field);
+    }
+}
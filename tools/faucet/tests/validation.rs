@@ -0,0 +1,295 @@
+//! Property tests for the input validation layer
+
+use axionax_faucet::validation::{
+    check_request, excerpt, json_depth, looks_like_name, parse_address, parse_amount, parse_json,
+    parse_name, to_checksum_address, RequestRules, ValidationError, MAX_JSON_DEPTH,
+    MAX_PAYLOAD_BYTES,
+};
+use axionax_tx::U256;
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+const WEI_PER_AXX: u128 = 1_000_000_000_000_000_000;
+
+fn wei(value: u128) -> Result<U256, ValidationError> {
+    Ok(U256::from(value))
+}
+
+const RULES: RequestRules = RequestRules {
+    chain_id: 86137,
+    names: false,
+    captcha: false,
+};
+
+fn hex_address() -> impl Strategy<Value = String> {
+    "[0-9a-f]{40}".prop_map(|hex| format!("0x{}", hex))
+}
+
+proptest! {
+    #[test]
+    fn address_parsing_never_panics(input in any::<String>()) {
+        let _ = parse_address(&input);
+        let _ = excerpt(&input);
+    }
+
+    #[test]
+    fn lowercase_addresses_roundtrip(address in hex_address()) {
+        prop_assert_eq!(parse_address(&address), Ok(address.clone()));
+        prop_assert_eq!(parse_address(&format!("  {}\n", address)), Ok(address));
+    }
+
+    #[test]
+    fn checksummed_addresses_roundtrip(address in hex_address()) {
+        let checksummed = to_checksum_address(&address);
+        prop_assert_eq!(parse_address(&checksummed), Ok(address.clone()));
+        prop_assert_eq!(parse_address(&address.to_uppercase().replacen("0X", "0x", 1)), Ok(address));
+    }
+
+    #[test]
+    fn broken_checksums_are_rejected(address in hex_address(), index in 0usize..40) {
+        let checksummed = to_checksum_address(&address);
+        let mut chars: Vec<char> = checksummed.chars().collect();
+        let c = chars[index + 2];
+        prop_assume!(c.is_ascii_alphabetic());
+        chars[index + 2] = if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() };
+        let broken: String = chars.into_iter().collect();
+        // Flipping one letter can only produce a valid address if the result is all one case
+        let hex = &broken[2..];
+        let single_case = !hex.bytes().any(|b| b.is_ascii_uppercase()) || !hex.bytes().any(|b| b.is_ascii_lowercase());
+        prop_assume!(!single_case);
+        prop_assert_eq!(parse_address(&broken), Err(ValidationError::BadChecksum));
+    }
+
+    #[test]
+    fn homoglyphs_are_rejected(address in hex_address(), index in 2usize..42, glyph in prop::sample::select(vec!['а', 'е', 'о', 'с', '０', 'Ａ', 'ｆ', '\u{200b}', '\u{212a}'])) {
+        let mut spoofed = address.clone();
+        spoofed.replace_range(index..index + 1, &glyph.to_string());
+        prop_assert_eq!(parse_address(&spoofed), Err(ValidationError::NonAscii));
+    }
+
+    #[test]
+    fn huge_inputs_are_rejected_early(address in hex_address(), padding in 100usize..10_000) {
+        let input = format!("{}{}", " ".repeat(padding), address);
+        prop_assert_eq!(parse_address(&input), Err(ValidationError::TooLong));
+    }
+
+    #[test]
+    fn name_parsing_never_panics(input in any::<String>()) {
+        let _ = parse_name(&input);
+    }
+
+    #[test]
+    fn addresses_are_not_names(address in hex_address()) {
+        prop_assert!(!looks_like_name(&address));
+    }
+
+    #[test]
+    fn amount_parsing_never_panics(input in any::<String>()) {
+        let _ = parse_amount(&input);
+    }
+
+    #[test]
+    fn amounts_roundtrip(wei in any::<u128>()) {
+        let formatted = format!("{}.{:018}", wei / WEI_PER_AXX, wei % WEI_PER_AXX);
+        prop_assert_eq!(parse_amount(&formatted), Ok(U256::from(wei)));
+    }
+
+    #[test]
+    fn whole_amounts_beyond_u128_are_exact(axx in any::<u128>()) {
+        let expected = U256::from(axx) * U256::from(WEI_PER_AXX);
+        prop_assert_eq!(parse_amount(&axx.to_string()), Ok(expected));
+    }
+
+    #[test]
+    fn json_parsing_never_panics(body in prop::collection::vec(any::<u8>(), 0..4096)) {
+        let _ = parse_json::<Value>(&body);
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected(depth in (MAX_JSON_DEPTH + 1)..100_000, open in prop::sample::select(vec!['[', '{'])) {
+        let body = if open == '[' {
+            format!("{}{}", "[".repeat(depth), "]".repeat(depth))
+        } else {
+            format!("{}1{}", "{\"a\":".repeat(depth), "}".repeat(depth))
+        };
+        let result = parse_json::<Value>(body.as_bytes());
+        prop_assert!(matches!(result, Err(ValidationError::TooDeeplyNested | ValidationError::PayloadTooLarge)));
+    }
+
+    #[test]
+    fn request_checks_never_panic(body in prop::collection::vec(any::<u8>(), 0..4096)) {
+        if let Ok(body) = parse_json::<Value>(&body) {
+            let _ = check_request(&body, &RULES);
+        }
+    }
+
+    #[test]
+    fn valid_requests_have_no_field_errors(address in hex_address(), nonce in any::<u64>()) {
+        let body = json!({ "address": address, "pow": { "challenge": "c", "nonce": nonce } });
+        prop_assert_eq!(check_request(&body, &RULES), vec![]);
+    }
+
+    #[test]
+    fn brackets_inside_strings_do_not_count(s in "[\\[\\]{}\"\\\\a]{0,200}") {
+        let body = serde_json::to_string(&serde_json::json!({ "address": s })).unwrap();
+        prop_assert_eq!(json_depth(body.as_bytes()), 1);
+    }
+}
+
+#[test]
+fn oversized_payloads_are_rejected() {
+    let body = format!(r#"{{"address":"{}"}}"#, "0".repeat(MAX_PAYLOAD_BYTES));
+    assert_eq!(
+        parse_json::<Value>(body.as_bytes()),
+        Err(ValidationError::PayloadTooLarge)
+    );
+}
+
+#[test]
+fn known_checksum_vectors() {
+    // From EIP-55
+    for address in [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ] {
+        assert_eq!(to_checksum_address(address), address);
+        assert_eq!(parse_address(address), Ok(address.to_lowercase()));
+    }
+}
+
+#[test]
+fn amount_edge_cases() {
+    assert_eq!(parse_amount("100"), wei(100 * WEI_PER_AXX));
+    assert_eq!(parse_amount("0.25"), wei(WEI_PER_AXX / 4));
+    assert_eq!(parse_amount(".5"), wei(WEI_PER_AXX / 2));
+    assert_eq!(parse_amount("0.000000000000000001"), wei(1));
+    assert_eq!(parse_amount("1.5 AXX"), wei(WEI_PER_AXX * 3 / 2));
+    assert_eq!(parse_amount("2000 gwei"), wei(2_000_000_000_000));
+    assert_eq!(parse_amount(&format!("{} wei", U256::MAX)), Ok(U256::MAX));
+    assert_eq!(
+        parse_amount(&format!("{}1 wei", U256::MAX)),
+        Err(ValidationError::AmountOverflow)
+    );
+    for bad in [
+        "",
+        ".",
+        "1.",
+        "-1",
+        "+1",
+        "1e18",
+        "1.0000000000000000001",
+        " 1",
+        "１",
+        "1 eth",
+        "1.5 wei",
+    ] {
+        assert!(parse_amount(bad).is_err(), "{:?}", bad);
+    }
+}
+
+#[test]
+fn name_edge_cases() {
+    assert_eq!(parse_name(" Alice.AXX "), Ok("alice.axx".to_string()));
+    assert_eq!(
+        parse_name("my-wallet_1.axx"),
+        Ok("my-wallet_1.axx".to_string())
+    );
+    assert_eq!(parse_name(&"a.".repeat(200)), Err(ValidationError::TooLong));
+    assert_eq!(parse_name("аlice.axx"), Err(ValidationError::NonAscii));
+    for bad in [
+        "",
+        ".axx",
+        "alice..axx",
+        "alice.axx.",
+        "al ice.axx",
+        "alice/.axx",
+    ] {
+        assert!(parse_name(bad).is_err(), "{:?}", bad);
+    }
+    assert_eq!(
+        parse_name(&format!("{}.axx", "a".repeat(64))),
+        Err(ValidationError::InvalidName)
+    );
+}
+
+#[test]
+fn request_field_errors() {
+    let fields = |body: Value, rules: &RequestRules| -> Vec<(String, &'static str)> {
+        check_request(&body, rules)
+            .into_iter()
+            .map(|e| (e.field, e.code))
+            .collect()
+    };
+    let address = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+
+    assert_eq!(
+        fields(json!([address]), &RULES),
+        [("".into(), "invalid_type")]
+    );
+    assert_eq!(fields(json!({}), &RULES), [("address".into(), "required")]);
+    assert_eq!(
+        fields(json!({ "address": "0x1234" }), &RULES),
+        [("address".into(), "invalid_address")]
+    );
+    // Every invalid field is reported, not just the first
+    assert_eq!(
+        fields(
+            json!({
+                "address": 42,
+                "captcha_token": false,
+                "allow_contract": "yes",
+                "pow": { "nonce": -1 },
+                "identity": { "proof": {} },
+                "chain_id": 1,
+            }),
+            &RULES
+        ),
+        [
+            ("address".into(), "invalid_type"),
+            ("captcha_token".into(), "invalid_type"),
+            ("allow_contract".into(), "invalid_type"),
+            ("pow.challenge".into(), "required"),
+            ("pow.nonce".into(), "invalid_type"),
+            ("identity.provider".into(), "required"),
+            ("chain_id".into(), "unknown_chain"),
+        ]
+    );
+    assert_eq!(
+        fields(
+            json!({ "address": address, "chain_id": 86137, "extra": 1 }),
+            &RULES
+        ),
+        []
+    );
+
+    // Names are only addresses when a resolver is configured
+    let names = RequestRules {
+        names: true,
+        ..RULES
+    };
+    assert_eq!(
+        fields(json!({ "address": "alice.axx" }), &RULES),
+        [("address".into(), "invalid_address")]
+    );
+    assert_eq!(fields(json!({ "address": "alice.axx" }), &names), []);
+    assert_eq!(
+        fields(json!({ "address": "alice..axx" }), &names),
+        [("address".into(), "invalid_name")]
+    );
+
+    // A partner referral stands in for the captcha
+    let captcha = RequestRules {
+        captcha: true,
+        ..RULES
+    };
+    assert_eq!(
+        fields(json!({ "address": address }), &captcha),
+        [("captcha_token".into(), "required")]
+    );
+    assert_eq!(
+        fields(json!({ "address": address, "referral": "token" }), &captcha),
+        []
+    );
+}
@@ -0,0 +1,103 @@
+//! HTTP server scaffolding shared by the axionax services
+//!
+//! Every service serves the same middleware stack, so requests look alike in
+//! logs and to clients whichever tool answers them:
+//!
+//! - [`stack`]: permissive CORS, an `x-request-id` on every request and
+//!   response, and a tracing span per request tagged with it
+//! - [`health`]: a `/health` liveness answer naming the service
+//! - [`shutdown_signal`]: resolves on Ctrl-C or `SIGTERM`, for graceful
+//!   shutdown
+//!
+//! Metrics and readiness depend on each service's own state, so services
+//! serve them themselves.
+
+use axum::body::Body;
+use axum::http::{HeaderName, Request};
+use axum::routing::{get, MethodRouter};
+use axum::{Json, Router};
+use serde_json::json;
+use tower_http::cors::{Any, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+use tracing::{info, info_span, Span};
+
+/// Header carrying the per-request id
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Wrap `router` in the shared middleware stack
+///
+/// Add it after the service's own layers, so they run inside the request's
+/// span and see its id.
+pub fn stack<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(cors())
+        .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
+}
+
+/// CORS allowing any origin, method and header
+pub fn cors() -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any)
+}
+
+/// Span for an HTTP request, tagged with its `x-request-id`
+///
+/// Must run inside `SetRequestIdLayer` so the header is already present.
+pub fn request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    )
+}
+
+/// Liveness endpoint answering `{ status, service, version }`
+pub fn health<S>(service: &'static str, version: &'static str) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    get(move || async move {
+        Json(json!({
+            "status": "ok",
+            "service": service,
+            "version": version,
+        }))
+    })
+}
+
+/// Resolve on Ctrl-C or, on unix, `SIGTERM`
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        if let Ok(mut signal) =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        {
+            signal.recv().await;
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("Shutting down");
+}
@@ -0,0 +1,53 @@
+//! The shared middleware stack and health endpoint
+
+use axionax_http::{health, stack, REQUEST_ID_HEADER};
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    stack(
+        Router::new()
+            .route("/health", health("axionax-test", "1.2.3"))
+            .route("/echo", get(|| async { "ok" })),
+    )
+}
+
+#[tokio::test]
+async fn health_names_the_service() {
+    let response = app()
+        .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({ "status": "ok", "service": "axionax-test", "version": "1.2.3" })
+    );
+}
+
+#[tokio::test]
+async fn responses_carry_a_request_id() {
+    let response = app()
+        .oneshot(Request::get("/echo").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let id = response.headers().get(REQUEST_ID_HEADER).unwrap();
+    assert_eq!(id.len(), 36, "{:?}", id);
+
+    // A caller's id is kept
+    let request = Request::get("/echo")
+        .header(REQUEST_ID_HEADER, "trace-me")
+        .header(header::ORIGIN, "https://example.org")
+        .body(Body::empty())
+        .unwrap();
+    let response = app().oneshot(request).await.unwrap();
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "trace-me");
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+}
@@ -0,0 +1,165 @@
+//! Built-in channels
+
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::{json, Map, Value};
+
+use crate::{Error, Event, Notifier};
+
+/// Default PagerDuty Events API v2 endpoint
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+async fn post(http: &reqwest::Client, url: &str, body: &Value) -> Result<(), Error> {
+    let response = http
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| Error::Http(e.to_string()))?;
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        status => Err(Error::Status(status.as_u16())),
+    }
+}
+
+/// Slack incoming webhook
+pub struct Slack {
+    pub webhook_url: String,
+    pub http: reqwest::Client,
+}
+
+#[async_trait]
+impl Notifier for Slack {
+    async fn notify(&self, event: &Event) -> Result<(), Error> {
+        post(
+            &self.http,
+            &self.webhook_url,
+            &json!({ "text": event.text() }),
+        )
+        .await
+    }
+}
+
+/// Discord webhook
+pub struct Discord {
+    pub webhook_url: String,
+    pub http: reqwest::Client,
+}
+
+#[async_trait]
+impl Notifier for Discord {
+    async fn notify(&self, event: &Event) -> Result<(), Error> {
+        // Discord rejects messages over 2000 characters
+        let content: String = event.text().chars().take(2000).collect();
+        post(
+            &self.http,
+            &self.webhook_url,
+            &json!({ "content": content }),
+        )
+        .await
+    }
+}
+
+/// PagerDuty service, through the Events API v2
+///
+/// Events of one kind and source share a dedup key, so a repeated alert
+/// updates the open incident instead of paging again.
+pub struct PagerDuty {
+    pub routing_key: String,
+    pub url: String,
+    pub http: reqwest::Client,
+}
+
+#[async_trait]
+impl Notifier for PagerDuty {
+    async fn notify(&self, event: &Event) -> Result<(), Error> {
+        let details: Map<String, Value> = event
+            .fields
+            .iter()
+            .map(|(name, value)| (name.clone(), json!(value)))
+            .collect();
+        let body = json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "dedup_key": format!("{}/{}", event.source, event.kind),
+            "payload": {
+                "summary": event.title,
+                "source": event.source,
+                "severity": event.severity.as_str(),
+                "custom_details": details,
+            },
+        });
+        post(&self.http, &self.url, &body).await
+    }
+}
+
+/// Email over SMTP
+pub struct Smtp {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl Smtp {
+    /// Mailer for `host:port`, using STARTTLS unless `tls` is false
+    pub fn new(
+        host: &str,
+        port: u16,
+        tls: bool,
+        credentials: Option<(String, String)>,
+        from: &str,
+        to: &[String],
+    ) -> Result<Self, Error> {
+        let mut builder = if tls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| Error::Config(e.to_string()))?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        }
+        .port(port);
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let mailbox = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| Error::Config(format!("{}: {}", address, e)))
+        };
+        if to.is_empty() {
+            return Err(Error::Config(
+                "email channel without recipients".to_string(),
+            ));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from: mailbox(from)?,
+            to: to.iter().map(|a| mailbox(a)).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Smtp {
+    async fn notify(&self, event: &Event) -> Result<(), Error> {
+        let mut message = Message::builder().from(self.from.clone()).subject(format!(
+            "[{}] {}: {}",
+            event.severity.as_str().to_uppercase(),
+            event.source,
+            event.title
+        ));
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message
+            .body(event.text())
+            .map_err(|e| Error::Smtp(e.to_string()))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| Error::Smtp(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Declarative channel and routing configuration
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::Severity;
+
+/// Notification config, usually read from a JSON file
+///
+/// ```json
+/// {
+///   "source": "faucet-testnet",
+///   "channels": {
+///     "ops": { "type": "slack", "webhook_url": "https://hooks.slack.com/services/..." },
+///     "oncall": { "type": "pagerduty", "routing_key": "..." }
+///   },
+///   "routes": {
+///     "low_balance": ["ops"],
+///     "send_failed": ["ops", "oncall"],
+///     "*": ["ops"]
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Name events are reported under (default `axionax`)
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Channels by name
+    #[serde(default)]
+    pub channels: BTreeMap<String, ChannelConfig>,
+    /// Channel names per event kind; `*` catches kinds without a route
+    #[serde(default)]
+    pub routes: BTreeMap<String, Vec<String>>,
+    /// Events below this severity are dropped (default `info`)
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+}
+
+/// A configured channel
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ChannelConfig {
+    Slack {
+        webhook_url: String,
+    },
+    Discord {
+        webhook_url: String,
+    },
+    #[serde(rename = "pagerduty")]
+    PagerDuty {
+        routing_key: String,
+        /// Events API endpoint (default the public one)
+        #[serde(default)]
+        url: Option<String>,
+    },
+    Smtp {
+        host: String,
+        #[serde(default = "default_smtp_port")]
+        port: u16,
+        /// STARTTLS; disable only for local relays
+        #[serde(default = "default_true")]
+        tls: bool,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_true() -> bool {
+    true
+}
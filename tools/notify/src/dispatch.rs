@@ -0,0 +1,157 @@
+//! Routing events to channels
+
+use futures_util::future::join_all;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::channels::{Discord, PagerDuty, Slack, Smtp, PAGERDUTY_EVENTS_URL};
+use crate::{ChannelConfig, Config, Error, Event, Notifier, Severity};
+
+/// Route for event kinds without one of their own
+const FALLBACK_ROUTE: &str = "*";
+
+/// Configured channels and the routes from event kinds to them
+///
+/// The default value has no channels and drops every event.
+#[derive(Clone)]
+pub struct Notifications {
+    source: String,
+    min_severity: Severity,
+    channels: HashMap<String, Arc<dyn Notifier>>,
+    routes: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            source: "axionax".to_string(),
+            min_severity: Severity::Info,
+            channels: HashMap::new(),
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+impl Notifications {
+    /// Read a JSON [`Config`] file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes =
+            std::fs::read(path).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        let config: Config = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        Self::from_config(config)
+    }
+
+    /// Build the channels of `config` and check its routes
+    pub fn from_config(config: Config) -> Result<Self, Error> {
+        let http = reqwest::Client::new();
+        let mut notifications = Self {
+            min_severity: config.min_severity.unwrap_or(Severity::Info),
+            ..Self::default()
+        };
+        if let Some(source) = config.source {
+            notifications.source = source;
+        }
+        for (name, channel) in config.channels {
+            let notifier: Arc<dyn Notifier> = match channel {
+                ChannelConfig::Slack { webhook_url } => Arc::new(Slack {
+                    webhook_url,
+                    http: http.clone(),
+                }),
+                ChannelConfig::Discord { webhook_url } => Arc::new(Discord {
+                    webhook_url,
+                    http: http.clone(),
+                }),
+                ChannelConfig::PagerDuty { routing_key, url } => Arc::new(PagerDuty {
+                    routing_key,
+                    url: url.unwrap_or_else(|| PAGERDUTY_EVENTS_URL.to_string()),
+                    http: http.clone(),
+                }),
+                ChannelConfig::Smtp {
+                    host,
+                    port,
+                    tls,
+                    username,
+                    password,
+                    from,
+                    to,
+                } => Arc::new(Smtp::new(
+                    &host,
+                    port,
+                    tls,
+                    username.zip(password),
+                    &from,
+                    &to,
+                )?),
+            };
+            notifications = notifications.with_channel(&name, notifier);
+        }
+        for (kind, channels) in config.routes {
+            notifications = notifications.route(&kind, &channels)?;
+        }
+        Ok(notifications)
+    }
+
+    /// Add or replace a channel
+    pub fn with_channel(mut self, name: &str, notifier: Arc<dyn Notifier>) -> Self {
+        self.channels.insert(name.to_string(), notifier);
+        self
+    }
+
+    /// Send events of `kind` (or `*`) to the named channels
+    pub fn route(mut self, kind: &str, channels: &[String]) -> Result<Self, Error> {
+        if let Some(unknown) = channels.iter().find(|c| !self.channels.contains_key(*c)) {
+            return Err(Error::Config(format!(
+                "route {} uses unknown channel {}",
+                kind, unknown
+            )));
+        }
+        self.routes.insert(kind.to_string(), channels.to_vec());
+        Ok(self)
+    }
+
+    /// Whether any event would be delivered anywhere
+    pub fn is_empty(&self) -> bool {
+        self.routes.values().all(Vec::is_empty)
+    }
+
+    /// Deliver `event` to every channel routed for its kind, concurrently
+    ///
+    /// Failures are logged and returned by channel name; they never stop
+    /// delivery to the other channels.
+    pub async fn notify(&self, event: Event) -> Vec<(String, Error)> {
+        if event.severity < self.min_severity {
+            return Vec::new();
+        }
+        let Some(names) = self
+            .routes
+            .get(&event.kind)
+            .or_else(|| self.routes.get(FALLBACK_ROUTE))
+        else {
+            return Vec::new();
+        };
+        let event = Event {
+            source: match event.source.as_str() {
+                "" => self.source.clone(),
+                _ => event.source,
+            },
+            ..event
+        };
+
+        let deliveries = names.iter().map(|name| {
+            let event = &event;
+            async move { (name, self.channels[name].notify(event).await) }
+        });
+        let mut failures = Vec::new();
+        for (name, result) in join_all(deliveries).await {
+            if let Err(e) = result {
+                warn!("Failed to notify {} of {}: {}", name, event.kind, e);
+                failures.push((name.clone(), e));
+            }
+        }
+        failures
+    }
+}
@@ -0,0 +1,75 @@
+//! What tools notify about
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How urgent an event is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Something worth telling an operator about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Event {
+    /// Routing key, e.g. `low_balance`
+    pub kind: String,
+    pub severity: Severity,
+    /// Tool or deployment raising the event, e.g. `faucet-testnet`
+    pub source: String,
+    /// One-line summary
+    pub title: String,
+    /// Details as `(name, value)` pairs, in order
+    pub fields: Vec<(String, String)>,
+}
+
+impl Event {
+    /// Event with no fields; the source is filled in by [`Notifications`](crate::Notifications)
+    pub fn new(kind: &str, severity: Severity, title: impl Into<String>) -> Self {
+        Self {
+            kind: kind.to_string(),
+            severity,
+            source: String::new(),
+            title: title.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_field(mut self, name: &str, value: impl fmt::Display) -> Self {
+        self.fields.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Plain-text rendering used by chat and email channels
+    pub fn text(&self) -> String {
+        let mut text = format!(
+            "[{}] {}: {}",
+            self.severity.as_str().to_uppercase(),
+            self.source,
+            self.title
+        );
+        for (name, value) in &self.fields {
+            text.push_str(&format!("\n{}: {}", name, value));
+        }
+        text
+    }
+}
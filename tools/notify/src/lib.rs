@@ -0,0 +1,50 @@
+//! Notifications shared by the axionax tools
+//!
+//! Tools raise [`Event`]s such as `low_balance` or `chain_stall`;
+//! [`Notifications`] routes each one, by its kind, to the channels
+//! configured for it. Built-in channels post to Slack and Discord webhooks,
+//! trigger PagerDuty incidents and send email over SMTP. Other channels
+//! implement [`Notifier`].
+
+pub mod channels;
+mod config;
+mod dispatch;
+mod event;
+
+pub use config::{ChannelConfig, Config};
+pub use dispatch::Notifications;
+pub use event::{Event, Severity};
+
+use async_trait::async_trait;
+use std::fmt;
+
+/// Why a notification couldn't be delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Invalid configuration, such as a route to an unknown channel
+    Config(String),
+    /// The request didn't reach the service
+    Http(String),
+    /// The service answered with a non-success status
+    Status(u16),
+    Smtp(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(e) => write!(f, "invalid notification config: {}", e),
+            Error::Http(e) => write!(f, "request failed: {}", e),
+            Error::Status(status) => write!(f, "service answered with status {}", status),
+            Error::Smtp(e) => write!(f, "smtp: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A channel events are delivered to
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, event: &Event) -> Result<(), Error>;
+}
@@ -0,0 +1,234 @@
+//! Routing and delivery of notifications
+
+use axionax_notify::{Config, Error, Event, Notifications, Notifier, Severity};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+type Received = Arc<Mutex<Vec<(String, Value)>>>;
+
+/// Webhook endpoint recording `POST /<name>` bodies; `/down` answers 500
+async fn webhooks() -> (String, Received) {
+    let received = Received::default();
+    let app = Router::new()
+        .route(
+            "/:name",
+            post(
+                |State(received): State<Received>,
+                 Path(name): Path<String>,
+                 Json(body): Json<Value>| async move {
+                    if name == "down" {
+                        return StatusCode::INTERNAL_SERVER_ERROR;
+                    }
+                    received.lock().unwrap().push((name, body));
+                    StatusCode::OK
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, received)
+}
+
+fn config(value: Value) -> Config {
+    serde_json::from_value(value).unwrap()
+}
+
+fn low_balance() -> Event {
+    Event::new("low_balance", Severity::Warning, "Faucet balance low")
+        .with_field("balance", "12.5 AXX")
+}
+
+#[tokio::test]
+async fn events_reach_the_channels_routed_for_their_kind() {
+    let (url, received) = webhooks().await;
+    let notifications = Notifications::from_config(config(json!({
+        "source": "faucet-testnet",
+        "channels": {
+            "ops": { "type": "slack", "webhook_url": format!("{}/slack", url) },
+            "chat": { "type": "discord", "webhook_url": format!("{}/discord", url) },
+            "oncall": {
+                "type": "pagerduty",
+                "routing_key": "key",
+                "url": format!("{}/pagerduty", url),
+            },
+        },
+        "routes": {
+            "low_balance": ["ops", "chat"],
+            "send_failed": ["oncall"],
+        },
+    })))
+    .unwrap();
+
+    assert!(notifications.notify(low_balance()).await.is_empty());
+    let mut bodies = received.lock().unwrap().drain(..).collect::<Vec<_>>();
+    bodies.sort_by(|a, b| a.0.cmp(&b.0));
+    let text = "[WARNING] faucet-testnet: Faucet balance low\nbalance: 12.5 AXX";
+    assert_eq!(
+        bodies,
+        vec![
+            ("discord".to_string(), json!({ "content": text })),
+            ("slack".to_string(), json!({ "text": text })),
+        ]
+    );
+
+    let failed = Event::new("send_failed", Severity::Critical, "Drip failed")
+        .with_field("error", "nonce too low");
+    assert!(notifications.notify(failed).await.is_empty());
+    let (name, body) = received.lock().unwrap().pop().unwrap();
+    assert_eq!(name, "pagerduty");
+    assert_eq!(body["routing_key"], "key");
+    assert_eq!(body["dedup_key"], "faucet-testnet/send_failed");
+    assert_eq!(body["payload"]["severity"], "critical");
+    assert_eq!(body["payload"]["custom_details"]["error"], "nonce too low");
+
+    // No route and no fallback: dropped
+    let other = Event::new("chain_stall", Severity::Critical, "No new blocks");
+    assert!(notifications.notify(other).await.is_empty());
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn fallback_route_and_minimum_severity() {
+    let (url, received) = webhooks().await;
+    let notifications = Notifications::from_config(config(json!({
+        "channels": { "ops": { "type": "slack", "webhook_url": format!("{}/slack", url) } },
+        "routes": { "*": ["ops"] },
+        "min_severity": "warning",
+    })))
+    .unwrap();
+
+    notifications
+        .notify(Event::new("daily", Severity::Info, "Summary"))
+        .await;
+    assert!(received.lock().unwrap().is_empty());
+
+    notifications
+        .notify(Event::new(
+            "chain_stall",
+            Severity::Critical,
+            "No new blocks",
+        ))
+        .await;
+    let (_, body) = received.lock().unwrap().pop().unwrap();
+    assert_eq!(body["text"], "[CRITICAL] axionax: No new blocks");
+}
+
+#[tokio::test]
+async fn failing_channels_do_not_block_the_others() {
+    let (url, received) = webhooks().await;
+    let notifications = Notifications::from_config(config(json!({
+        "channels": {
+            "broken": { "type": "slack", "webhook_url": format!("{}/down", url) },
+            "ops": { "type": "slack", "webhook_url": format!("{}/slack", url) },
+        },
+        "routes": { "low_balance": ["broken", "ops"] },
+    })))
+    .unwrap();
+
+    let failures = notifications.notify(low_balance()).await;
+    assert_eq!(failures, vec![("broken".to_string(), Error::Status(500))]);
+    assert_eq!(received.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn routes_must_name_configured_channels() {
+    let result = Notifications::from_config(config(json!({
+        "routes": { "low_balance": ["ops"] },
+    })));
+    assert!(matches!(result, Err(Error::Config(_))));
+    assert!(serde_json::from_value::<Config>(json!({
+        "channels": { "ops": { "type": "pager", "routing_key": "key" } },
+    }))
+    .is_err());
+    assert!(Notifications::default().is_empty());
+}
+
+#[tokio::test]
+async fn custom_notifiers_can_be_routed() {
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait::async_trait]
+    impl Notifier for Recorder {
+        async fn notify(&self, event: &Event) -> Result<(), Error> {
+            self.0.lock().unwrap().push(event.title.clone());
+            Ok(())
+        }
+    }
+
+    let recorder = Arc::new(Recorder(Mutex::default()));
+    let notifications = Notifications::default()
+        .with_channel("log", recorder.clone())
+        .route("low_balance", &["log".to_string()])
+        .unwrap();
+    notifications.notify(low_balance()).await;
+    assert_eq!(*recorder.0.lock().unwrap(), ["Faucet balance low"]);
+}
+
+/// Accept one SMTP session and return the message data
+async fn smtp_server() -> (u16, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let session = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (read, mut write) = socket.into_split();
+        let mut lines = BufReader::new(read).lines();
+        write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+        let mut data = String::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let reply: &[u8] = match line.split(' ').next().unwrap().to_uppercase().as_str() {
+                "DATA" => {
+                    write.write_all(b"354 end with .\r\n").await.unwrap();
+                    while let Some(line) = lines.next_line().await.unwrap() {
+                        if line == "." {
+                            break;
+                        }
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    b"250 queued\r\n"
+                }
+                "QUIT" => {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                }
+                _ => b"250 OK\r\n",
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        data
+    });
+    (port, session)
+}
+
+#[tokio::test]
+async fn email_is_sent_over_smtp() {
+    let (port, session) = smtp_server().await;
+    let notifications = Notifications::from_config(config(json!({
+        "source": "faucet-testnet",
+        "channels": {
+            "mail": {
+                "type": "smtp",
+                "host": "127.0.0.1",
+                "port": port,
+                "tls": false,
+                "from": "faucet@example.org",
+                "to": ["ops@example.org"],
+            },
+        },
+        "routes": { "*": ["mail"] },
+    })))
+    .unwrap();
+
+    assert!(notifications.notify(low_balance()).await.is_empty());
+    let data = session.await.unwrap();
+    assert!(data.contains("To: ops@example.org"), "{}", data);
+    assert!(data.contains("Subject: [WARNING] faucet-testnet: Faucet balance low"));
+    assert!(data.contains("balance: 12.5 AXX"));
+}
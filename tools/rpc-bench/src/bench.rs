@@ -0,0 +1,70 @@
+//! Sends a method to an endpoint with bounded concurrency and times each call
+
+use axionax_rpc::Client;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::stats::{self, Summary};
+use crate::workload::{Method, Workload};
+
+/// How many requests of a method to send, and how
+#[derive(Debug, Clone, Copy)]
+pub struct Plan {
+    pub requests: usize,
+    pub concurrency: usize,
+    /// Untimed requests sent first to open connections and warm caches
+    pub warmup: usize,
+}
+
+/// Result of benchmarking one method
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodReport {
+    pub method: &'static str,
+    #[serde(flatten)]
+    pub summary: Summary,
+    /// First error seen, to tell a rate limit from an unsupported method
+    pub first_error: Option<String>,
+}
+
+/// Benchmark `method` against `rpc`, which should not retry
+pub async fn run(rpc: &Client, method: Method, workload: &Workload, plan: Plan) -> MethodReport {
+    let concurrency = plan.concurrency.max(1);
+    stream::iter(0..plan.warmup)
+        .map(|i| rpc.request(method.name(), workload.params(method, i)))
+        .buffer_unordered(concurrency)
+        .for_each(|_| async {})
+        .await;
+
+    let started = Instant::now();
+    let results: Vec<Result<Duration, String>> = stream::iter(0..plan.requests)
+        .map(|i| async move {
+            let sent = Instant::now();
+            rpc.request(method.name(), workload.params(method, i))
+                .await
+                .map(|_| sent.elapsed())
+                .map_err(|e| e.to_string())
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+    let elapsed = started.elapsed();
+
+    let mut latencies = Vec::with_capacity(results.len());
+    let mut errors = 0;
+    let mut first_error = None;
+    for result in results {
+        match result {
+            Ok(latency) => latencies.push(latency),
+            Err(e) => {
+                errors += 1;
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    MethodReport {
+        method: method.name(),
+        summary: stats::summarize(latencies, errors, elapsed),
+        first_error,
+    }
+}
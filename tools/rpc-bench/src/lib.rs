@@ -0,0 +1,5 @@
+//! Library surface of the RPC benchmark, shared with its tests
+
+pub mod bench;
+pub mod stats;
+pub mod workload;
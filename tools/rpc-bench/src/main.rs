@@ -0,0 +1,172 @@
+//! Measures JSON-RPC latency and throughput of one or more endpoints
+
+use axionax_rpc::Client;
+use axionax_rpc_bench::bench::{self, MethodReport, Plan};
+use axionax_rpc_bench::workload::{Method, Workload};
+use clap::Parser;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Command-line options
+#[derive(Debug, Parser)]
+#[command(version, about = "Benchmark JSON-RPC endpoints per method")]
+struct Cli {
+    /// Endpoint to benchmark; repeat to compare nodes or providers
+    #[arg(
+        long = "rpc-url",
+        env = "RPC_URL",
+        default_value = "http://localhost:8545"
+    )]
+    rpc_urls: Vec<String>,
+
+    /// Methods to benchmark, comma-separated
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "eth_blockNumber,eth_call,eth_getBlockByNumber,eth_getLogs"
+    )]
+    methods: Vec<Method>,
+
+    /// Timed requests per method
+    #[arg(long, default_value_t = 200)]
+    requests: usize,
+
+    /// Requests in flight at once
+    #[arg(long, default_value_t = 8)]
+    concurrency: usize,
+
+    /// Untimed requests per method sent before measuring
+    #[arg(long, default_value_t = 10)]
+    warmup: usize,
+
+    /// Per-request timeout in seconds; a timeout counts as an error
+    #[arg(long, default_value_t = 30)]
+    timeout: u64,
+
+    /// Blocks covered by each eth_getLogs query
+    #[arg(long, default_value_t = 10_000)]
+    log_range: u64,
+
+    /// Only query logs of this contract
+    #[arg(long)]
+    log_address: Option<String>,
+
+    /// eth_getBlockByNumber requests spread over this many recent blocks
+    #[arg(long, default_value_t = 1_000)]
+    block_span: u64,
+
+    /// Fetch full transactions with eth_getBlockByNumber
+    #[arg(long)]
+    full_blocks: bool,
+
+    /// Target of eth_call
+    #[arg(long, default_value = "0x0000000000000000000000000000000000000000")]
+    call_to: String,
+
+    /// Calldata of eth_call
+    #[arg(long, default_value = "0x")]
+    call_data: String,
+
+    /// Also write the results as JSON to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+/// JSON report of a run
+#[derive(Serialize)]
+struct Report {
+    timestamp: u64,
+    requests: usize,
+    concurrency: usize,
+    log_range: u64,
+    endpoints: Vec<EndpointReport>,
+}
+
+#[derive(Serialize)]
+struct EndpointReport {
+    url: String,
+    head: u64,
+    methods: Vec<MethodReport>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let plan = Plan {
+        requests: cli.requests,
+        concurrency: cli.concurrency,
+        warmup: cli.warmup,
+    };
+
+    let mut endpoints = Vec::new();
+    for url in &cli.rpc_urls {
+        let rpc = Client::builder()
+            .endpoint(url)
+            .timeout(Duration::from_secs(cli.timeout))
+            .build();
+        let head = rpc
+            .get_block_number()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}: {}", url, e))?;
+        let workload = Workload {
+            head,
+            log_range: cli.log_range,
+            log_address: cli.log_address.clone(),
+            block_span: cli.block_span,
+            full_blocks: cli.full_blocks,
+            call_to: cli.call_to.clone(),
+            call_data: cli.call_data.clone(),
+        };
+
+        println!("{} (head {})", url, head);
+        println!(
+            "{:<22} {:>8} {:>7} {:>9} {:>9} {:>9} {:>9} {:>9}",
+            "method", "requests", "errors", "req/s", "p50 ms", "p90 ms", "p99 ms", "max ms"
+        );
+        let mut methods = Vec::new();
+        for method in &cli.methods {
+            let report = bench::run(&rpc, *method, &workload, plan).await;
+            let s = &report.summary;
+            println!(
+                "{:<22} {:>8} {:>7} {:>9.1} {:>9.1} {:>9.1} {:>9.1} {:>9.1}",
+                report.method,
+                s.requests,
+                s.errors,
+                s.throughput,
+                s.latency.p50,
+                s.latency.p90,
+                s.latency.p99,
+                s.latency.max
+            );
+            if let Some(error) = &report.first_error {
+                eprintln!("  {}: first error: {}", report.method, error);
+            }
+            methods.push(report);
+        }
+        println!();
+
+        endpoints.push(EndpointReport {
+            url: url.clone(),
+            head,
+            methods,
+        });
+    }
+
+    if let Some(path) = &cli.json {
+        let report = Report {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            requests: cli.requests,
+            concurrency: cli.concurrency,
+            log_range: cli.log_range,
+            endpoints,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
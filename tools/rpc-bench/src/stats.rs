@@ -0,0 +1,62 @@
+//! Latency percentiles and throughput of a benchmark run
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// Latencies of successful requests, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Latency {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+/// Outcome of sending one method to one endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub requests: usize,
+    pub errors: usize,
+    /// Successful requests per second of wall time
+    pub throughput: f64,
+    pub latency: Latency,
+}
+
+/// Nearest-rank percentile of sorted latencies; zero when there are none
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Summarize the latencies of successful requests and the error count
+pub fn summarize(mut latencies: Vec<Duration>, errors: usize, elapsed: Duration) -> Summary {
+    latencies.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let total: Duration = latencies.iter().sum();
+    let latency = Latency {
+        min: ms(latencies.first().copied().unwrap_or_default()),
+        mean: match latencies.len() {
+            0 => 0.0,
+            n => ms(total) / n as f64,
+        },
+        p50: ms(percentile(&latencies, 50.0)),
+        p90: ms(percentile(&latencies, 90.0)),
+        p99: ms(percentile(&latencies, 99.0)),
+        max: ms(latencies.last().copied().unwrap_or_default()),
+    };
+    let throughput = match elapsed.as_secs_f64() {
+        secs if secs > 0.0 => latencies.len() as f64 / secs,
+        _ => 0.0,
+    };
+    Summary {
+        requests: latencies.len() + errors,
+        errors,
+        throughput,
+        latency,
+    }
+}
@@ -0,0 +1,73 @@
+//! Request parameters for each benchmarked method
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+/// A JSON-RPC method the benchmark knows how to call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Method {
+    #[value(name = "eth_blockNumber")]
+    BlockNumber,
+    #[value(name = "eth_call")]
+    Call,
+    #[value(name = "eth_getBlockByNumber")]
+    GetBlockByNumber,
+    #[value(name = "eth_getLogs")]
+    GetLogs,
+}
+
+impl Method {
+    pub fn name(self) -> &'static str {
+        match self {
+            Method::BlockNumber => "eth_blockNumber",
+            Method::Call => "eth_call",
+            Method::GetBlockByNumber => "eth_getBlockByNumber",
+            Method::GetLogs => "eth_getLogs",
+        }
+    }
+}
+
+/// What the requests of a run ask for, relative to the chain head at the start
+#[derive(Debug, Clone)]
+pub struct Workload {
+    pub head: u64,
+    /// Blocks covered by each `eth_getLogs` query, ending at the head
+    pub log_range: u64,
+    /// Optional contract whose logs are queried; all logs otherwise
+    pub log_address: Option<String>,
+    /// `eth_getBlockByNumber` picks blocks among this many below the head
+    pub block_span: u64,
+    /// Return full transactions from `eth_getBlockByNumber`
+    pub full_blocks: bool,
+    pub call_to: String,
+    pub call_data: String,
+}
+
+impl Workload {
+    /// Parameters of the `i`th request of `method`
+    ///
+    /// Block lookups spread over `block_span` so a node's block cache doesn't
+    /// answer every request; the spread is deterministic so runs compare.
+    pub fn params(&self, method: Method, i: usize) -> Value {
+        let tag = |number: u64| format!("0x{:x}", number);
+        match method {
+            Method::BlockNumber => json!([]),
+            Method::Call => json!([{ "to": self.call_to, "data": self.call_data }, "latest"]),
+            Method::GetBlockByNumber => {
+                let span = self.block_span.clamp(1, self.head + 1);
+                let offset = (i as u64).wrapping_mul(7919) % span;
+                json!([tag(self.head - offset), self.full_blocks])
+            }
+            Method::GetLogs => {
+                let mut filter = json!({
+                    "fromBlock": tag(self.head.saturating_sub(self.log_range)),
+                    "toBlock": tag(self.head),
+                });
+                if let Some(address) = &self.log_address {
+                    filter["address"] = json!(address);
+                }
+                json!([filter])
+            }
+        }
+    }
+}
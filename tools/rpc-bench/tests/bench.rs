@@ -0,0 +1,118 @@
+//! Benchmark runs against a stub node
+
+use axionax_rpc::Client;
+use axionax_rpc_bench::bench::{self, Plan};
+use axionax_rpc_bench::workload::{Method, Workload};
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Serve a node that records every request; eth_getLogs fails like a busy node
+async fn stub_node() -> (Client, Arc<Mutex<Vec<Value>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    let app = Router::new().route(
+        "/",
+        post(move |Json(request): Json<Value>| {
+            let seen = recorded.clone();
+            async move {
+                seen.lock().unwrap().push(request.clone());
+                Json(match request["method"].as_str() {
+                    Some("eth_blockNumber") => json!({ "jsonrpc": "2.0", "id": 1, "result": "0x3e8" }),
+                    Some("eth_getLogs") => json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "error": { "code": -32005, "message": "query returned more than 10000 results" },
+                    }),
+                    _ => json!({ "jsonrpc": "2.0", "id": 1, "result": null }),
+                })
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (
+        Client::builder()
+            .endpoint(&url)
+            .timeout(Duration::from_secs(5))
+            .build(),
+        seen,
+    )
+}
+
+fn workload(head: u64) -> Workload {
+    Workload {
+        head,
+        log_range: 5_000,
+        log_address: Some("0x3535353535353535353535353535353535353535".to_string()),
+        block_span: 100,
+        full_blocks: false,
+        call_to: "0x0000000000000000000000000000000000000000".to_string(),
+        call_data: "0x".to_string(),
+    }
+}
+
+const PLAN: Plan = Plan {
+    requests: 20,
+    concurrency: 4,
+    warmup: 3,
+};
+
+#[tokio::test]
+async fn successful_requests_are_timed() {
+    let (rpc, seen) = stub_node().await;
+    let head = rpc.get_block_number().await.unwrap();
+    assert_eq!(head, 1000);
+
+    let report = bench::run(&rpc, Method::GetBlockByNumber, &workload(head), PLAN).await;
+    assert_eq!(report.method, "eth_getBlockByNumber");
+    assert_eq!(report.summary.requests, 20);
+    assert_eq!(report.summary.errors, 0);
+    assert!(report.first_error.is_none());
+    assert!(report.summary.throughput > 0.0);
+    assert!(report.summary.latency.p50 <= report.summary.latency.max);
+
+    // The head lookup, warmup and timed requests all reach the node
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1 + 3 + 20);
+    for request in &seen[1..] {
+        let block = request["params"][0].as_str().unwrap();
+        let number = u64::from_str_radix(&block[2..], 16).unwrap();
+        assert!((901..=1000).contains(&number), "{}", block);
+    }
+}
+
+#[tokio::test]
+async fn rpc_errors_are_counted() {
+    let (rpc, seen) = stub_node().await;
+    let report = bench::run(&rpc, Method::GetLogs, &workload(1000), PLAN).await;
+    assert_eq!(report.summary.requests, 20);
+    assert_eq!(report.summary.errors, 20);
+    assert_eq!(report.summary.throughput, 0.0);
+    assert!(report
+        .first_error
+        .unwrap()
+        .contains("more than 10000 results"));
+
+    let filter = &seen.lock().unwrap()[0]["params"][0];
+    // The range is clamped at genesis
+    assert_eq!(filter["fromBlock"], "0x0");
+    assert_eq!(filter["toBlock"], "0x3e8");
+    assert_eq!(
+        filter["address"],
+        "0x3535353535353535353535353535353535353535"
+    );
+}
+
+#[test]
+fn block_lookups_stay_within_the_chain() {
+    let workload = workload(10);
+    for i in 0..50 {
+        let params = workload.params(Method::GetBlockByNumber, i);
+        let number = u64::from_str_radix(&params[0].as_str().unwrap()[2..], 16).unwrap();
+        assert!(number <= 10);
+        assert_eq!(params[1], false);
+    }
+}
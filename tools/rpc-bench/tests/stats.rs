@@ -0,0 +1,42 @@
+//! Percentiles and summaries
+
+use axionax_rpc_bench::stats::{percentile, summarize};
+use std::time::Duration;
+
+fn ms(values: impl IntoIterator<Item = u64>) -> Vec<Duration> {
+    values.into_iter().map(Duration::from_millis).collect()
+}
+
+#[test]
+fn percentiles_use_nearest_rank() {
+    let sorted = ms(1..=100);
+    assert_eq!(percentile(&sorted, 50.0), Duration::from_millis(50));
+    assert_eq!(percentile(&sorted, 90.0), Duration::from_millis(90));
+    assert_eq!(percentile(&sorted, 99.0), Duration::from_millis(99));
+    assert_eq!(percentile(&sorted, 100.0), Duration::from_millis(100));
+    assert_eq!(percentile(&sorted, 0.0), Duration::from_millis(1));
+
+    assert_eq!(percentile(&ms([7]), 99.0), Duration::from_millis(7));
+    assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+}
+
+#[test]
+fn summary_sorts_latencies_and_counts_errors() {
+    let summary = summarize(ms([30, 10, 20, 40]), 2, Duration::from_secs(2));
+    assert_eq!(summary.requests, 6);
+    assert_eq!(summary.errors, 2);
+    assert_eq!(summary.throughput, 2.0);
+    assert_eq!(summary.latency.min, 10.0);
+    assert_eq!(summary.latency.mean, 25.0);
+    assert_eq!(summary.latency.p50, 20.0);
+    assert_eq!(summary.latency.max, 40.0);
+}
+
+#[test]
+fn summary_of_failed_run_is_zero() {
+    let summary = summarize(Vec::new(), 5, Duration::from_secs(1));
+    assert_eq!(summary.requests, 5);
+    assert_eq!(summary.throughput, 0.0);
+    assert_eq!(summary.latency.mean, 0.0);
+    assert_eq!(summary.latency.p99, 0.0);
+}
@@ -0,0 +1,201 @@
+//! Client with endpoint failover, retries and middleware
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::middleware::Request;
+use crate::{Error, Http, Middleware, Transport};
+
+/// How often and how patiently to retry a failed call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// Extra rounds over the endpoints after the first one fails
+    pub retries: usize,
+    /// Delay before the first retry round; doubled each round
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Retry {
+    /// Try each endpoint once
+    pub const NONE: Retry = Retry {
+        retries: 0,
+        backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    /// Delay before retry round `round`, counting from 1
+    pub fn delay(&self, round: usize) -> Duration {
+        let factor = 1u32 << (round.saturating_sub(1)).min(16);
+        self.backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry::NONE
+    }
+}
+
+/// JSON-RPC client over one or more endpoints
+///
+/// Calls go to the active endpoint. An attempt failing with a retryable
+/// error moves the client to the next endpoint straight away; once every
+/// endpoint failed in a round, the client waits out the backoff and starts
+/// another round, up to `Retry::retries` times. Clones share the active
+/// endpoint.
+#[derive(Clone)]
+pub struct Client {
+    endpoints: Arc<[Arc<dyn Transport>]>,
+    active: Arc<AtomicUsize>,
+    retry: Retry,
+    middleware: Arc<[Arc<dyn Middleware>]>,
+}
+
+/// Configures a [`Client`]
+#[derive(Default)]
+pub struct ClientBuilder {
+    urls: Vec<String>,
+    transports: Vec<Arc<dyn Transport>>,
+    timeout: Option<Duration>,
+    retry: Retry,
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl ClientBuilder {
+    /// Add an HTTP endpoint; endpoints are tried in the order added
+    pub fn endpoint(mut self, url: &str) -> Self {
+        self.urls.push(url.to_string());
+        self
+    }
+
+    /// Add an endpoint served by a custom transport, after the HTTP ones
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transports.push(transport);
+        self
+    }
+
+    /// Per-attempt timeout of HTTP endpoints
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Run `middleware` around every attempt, after those added before it
+    pub fn middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Build the client
+    ///
+    /// # Panics
+    ///
+    /// When no endpoint was added.
+    pub fn build(self) -> Client {
+        let http = reqwest::Client::new();
+        let mut endpoints: Vec<Arc<dyn Transport>> = self
+            .urls
+            .iter()
+            .map(|url| Arc::new(Http::new(url, http.clone(), self.timeout)) as Arc<dyn Transport>)
+            .collect();
+        endpoints.extend(self.transports);
+        assert!(!endpoints.is_empty(), "RPC client needs an endpoint");
+
+        Client {
+            endpoints: endpoints.into(),
+            active: Arc::new(AtomicUsize::new(0)),
+            retry: self.retry,
+            middleware: self.middleware.into(),
+        }
+    }
+}
+
+impl Client {
+    /// Client for a single HTTP endpoint, without retries
+    pub fn new(url: &str) -> Self {
+        Self::builder().endpoint(url).build()
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Endpoint calls currently go to
+    pub fn endpoint(&self) -> &str {
+        self.endpoints[self.active.load(Ordering::Relaxed) % self.endpoints.len()].endpoint()
+    }
+
+    /// Call a method and return its raw result, which may be `null`
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let count = self.endpoints.len();
+        let mut attempt = 0;
+        for round in 0..=self.retry.retries {
+            if round > 0 {
+                tokio::time::sleep(self.retry.delay(round)).await;
+            }
+            for _ in 0..count {
+                let index = self.active.load(Ordering::Relaxed);
+                let transport = &self.endpoints[index % count];
+                let request = Request {
+                    endpoint: transport.endpoint(),
+                    method,
+                    params: &params,
+                    attempt,
+                };
+                self.middleware.iter().for_each(|m| m.on_request(&request));
+                let started = Instant::now();
+                let result = transport.send(method, &params).await;
+                let elapsed = started.elapsed();
+                self.middleware
+                    .iter()
+                    .for_each(|m| m.on_response(&request, elapsed, result.as_ref()));
+
+                match result {
+                    Err(e) if e.is_retryable() && attempt < self.attempts() - 1 => {
+                        // Another call may have failed over already
+                        let _ = self.active.compare_exchange(
+                            index,
+                            (index + 1) % count,
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                        );
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        }
+        unreachable!("the last attempt always returns")
+    }
+
+    /// Call a method and decode its result, which must not be `null`
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, Error> {
+        self.call_optional(method, params)
+            .await?
+            .ok_or_else(|| Error::EmptyResult(method.to_string()))
+    }
+
+    /// Call a method whose result may be `null`, such as a lookup of a
+    /// transaction that isn't mined yet
+    pub async fn call_optional<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Option<T>, Error> {
+        serde_json::from_value(self.request(method, params).await?)
+            .map_err(|e| Error::Decode(format!("{}: {}", method, e)))
+    }
+
+    fn attempts(&self) -> usize {
+        (self.retry.retries + 1) * self.endpoints.len()
+    }
+}
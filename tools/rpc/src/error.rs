@@ -0,0 +1,69 @@
+//! Errors of JSON-RPC calls
+
+use axionax_errors::Kind;
+use std::fmt;
+
+/// JSON-RPC error code for methods a node doesn't implement
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code nodes use for rate and query limits
+pub const LIMIT_EXCEEDED: i64 = -32005;
+
+/// Why a JSON-RPC call failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The request didn't get a JSON-RPC response: connection failures,
+    /// timeouts, non-2xx statuses and bodies that aren't JSON
+    Transport(String),
+    /// The node answered with a JSON-RPC error object
+    Rpc { code: i64, message: String },
+    /// The node returned `null` for a method that must have a result
+    EmptyResult(String),
+    /// The result doesn't have the expected shape
+    Decode(String),
+}
+
+impl Error {
+    /// Whether another attempt, possibly on another endpoint, may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Transport(_) => true,
+            Error::Rpc { code, .. } => *code == LIMIT_EXCEEDED,
+            Error::EmptyResult(_) | Error::Decode(_) => false,
+        }
+    }
+
+    /// JSON-RPC error code, when the node returned one
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Error::Rpc { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(message) | Error::Decode(message) => write!(f, "{}", message),
+            Error::Rpc { code, message } => write!(f, "{} (code {})", message, code),
+            Error::EmptyResult(method) => write!(f, "empty result for {}", method),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Shared error of kind `rpc`, with codes `rpc_unavailable`, `rpc_error`,
+/// `rpc_empty_result` and `rpc_invalid_response`
+impl From<Error> for axionax_errors::Error {
+    fn from(err: Error) -> Self {
+        let code = match &err {
+            Error::Transport(_) => "rpc_unavailable",
+            Error::Rpc { .. } => "rpc_error",
+            Error::EmptyResult(_) => "rpc_empty_result",
+            Error::Decode(_) => "rpc_invalid_response",
+        };
+        axionax_errors::Error::new(Kind::Rpc, code, err.to_string())
+    }
+}
@@ -0,0 +1,20 @@
+//! JSON-RPC client shared by the axionax tools
+//!
+//! [`Client`] sends requests over one or more [`Transport`]s, failing over
+//! between endpoints and retrying with backoff, and runs every attempt
+//! through its [`Middleware`]. Typed wrappers for the methods the tools use
+//! live in [`methods`]; [`ws`] streams `eth_subscribe` notifications.
+
+mod client;
+mod error;
+pub mod methods;
+pub mod middleware;
+mod transport;
+pub mod ws;
+
+pub use client::{Client, ClientBuilder, Retry};
+pub use error::{Error, LIMIT_EXCEEDED, METHOD_NOT_FOUND};
+pub use methods::{parse_quantity, parse_u256};
+pub use middleware::Middleware;
+pub use primitive_types::U256;
+pub use transport::{Http, Transport};
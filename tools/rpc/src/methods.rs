@@ -0,0 +1,143 @@
+//! Typed wrappers of the methods the tools call
+
+use primitive_types::U256;
+use serde_json::{json, Value};
+
+use crate::{Client, Error, METHOD_NOT_FOUND};
+
+/// Parse a hex-encoded JSON-RPC quantity
+pub fn parse_quantity(value: &str) -> Result<u128, Error> {
+    u128::from_str_radix(value.trim_start_matches("0x"), 16)
+        .map_err(|_| Error::Decode(format!("invalid quantity: {}", value)))
+}
+
+/// Parse a hex-encoded JSON-RPC quantity of up to 256 bits, such as a balance
+pub fn parse_u256(value: &str) -> Result<U256, Error> {
+    U256::from_str_radix(value.trim_start_matches("0x"), 16)
+        .map_err(|_| Error::Decode(format!("invalid quantity: {}", value)))
+}
+
+fn block_tag(number: u64) -> String {
+    format!("0x{:x}", number)
+}
+
+impl Client {
+    async fn quantity(&self, method: &str, params: Value) -> Result<u128, Error> {
+        let value: String = self.call(method, params).await?;
+        parse_quantity(&value)
+    }
+
+    async fn wei(&self, method: &str, params: Value) -> Result<U256, Error> {
+        let value: String = self.call(method, params).await?;
+        parse_u256(&value)
+    }
+
+    /// Get the chain ID reported by the endpoint
+    pub async fn get_chain_id(&self) -> Result<u64, Error> {
+        Ok(self.quantity("eth_chainId", json!([])).await? as u64)
+    }
+
+    /// Get the number of the latest block
+    pub async fn get_block_number(&self) -> Result<u64, Error> {
+        Ok(self.quantity("eth_blockNumber", json!([])).await? as u64)
+    }
+
+    /// Get the balance of an address in wei
+    pub async fn get_balance(&self, address: &str) -> Result<U256, Error> {
+        self.wei("eth_getBalance", json!([address, "latest"])).await
+    }
+
+    /// Get the number of transactions sent from an address
+    pub async fn get_transaction_count(&self, address: &str) -> Result<u64, Error> {
+        Ok(self
+            .quantity("eth_getTransactionCount", json!([address, "latest"]))
+            .await? as u64)
+    }
+
+    /// Next nonce of an address, counting its transactions still in the pool
+    pub async fn get_pending_nonce(&self, address: &str) -> Result<u64, Error> {
+        Ok(self
+            .quantity("eth_getTransactionCount", json!([address, "pending"]))
+            .await? as u64)
+    }
+
+    /// Get the current gas price in wei
+    pub async fn get_gas_price(&self) -> Result<U256, Error> {
+        self.wei("eth_gasPrice", json!([])).await
+    }
+
+    /// Estimate the gas used by a plain value transfer
+    pub async fn estimate_gas(&self, from: &str, to: &str, value: U256) -> Result<u64, Error> {
+        let tx = json!({ "from": from, "to": to, "value": format!("0x{:x}", value) });
+        Ok(self.quantity("eth_estimateGas", json!([tx])).await? as u64)
+    }
+
+    /// Whether an address has contract code deployed
+    pub async fn is_contract(&self, address: &str) -> Result<bool, Error> {
+        let code: String = self.call("eth_getCode", json!([address, "latest"])).await?;
+        Ok(code.len() > 2)
+    }
+
+    /// Submit a signed transaction and return its hash
+    pub async fn send_raw_transaction(&self, raw: &str) -> Result<String, Error> {
+        self.call("eth_sendRawTransaction", json!([raw])).await
+    }
+
+    /// Transaction by hash; `None` when the node doesn't know it
+    pub async fn get_transaction_by_hash(&self, hash: &str) -> Result<Option<Value>, Error> {
+        self.call_optional("eth_getTransactionByHash", json!([hash]))
+            .await
+    }
+
+    /// Receipt of a transaction; `None` until it is mined
+    pub async fn get_transaction_receipt(&self, hash: &str) -> Result<Option<Value>, Error> {
+        self.call_optional("eth_getTransactionReceipt", json!([hash]))
+            .await
+    }
+
+    /// Block by number, with full transactions or only their hashes
+    pub async fn get_block_by_number(
+        &self,
+        number: u64,
+        full: bool,
+    ) -> Result<Option<Value>, Error> {
+        self.call_optional("eth_getBlockByNumber", json!([block_tag(number), full]))
+            .await
+    }
+
+    /// Receipts of a block in transaction order
+    ///
+    /// Uses `eth_getBlockReceipts`, falling back to one
+    /// `eth_getTransactionReceipt` per transaction on nodes without it.
+    pub async fn get_block_receipts(&self, number: u64) -> Result<Vec<Value>, Error> {
+        match self
+            .call("eth_getBlockReceipts", json!([block_tag(number)]))
+            .await
+        {
+            Err(e) if e.code() == Some(METHOD_NOT_FOUND) => {}
+            result => return result,
+        }
+
+        let block = self
+            .get_block_by_number(number, false)
+            .await?
+            .ok_or_else(|| Error::EmptyResult("eth_getBlockByNumber".to_string()))?;
+        let mut receipts = Vec::new();
+        for hash in block["transactions"].as_array().into_iter().flatten() {
+            let hash = hash
+                .as_str()
+                .ok_or_else(|| Error::Decode(format!("invalid transaction hash {}", hash)))?;
+            receipts.push(
+                self.get_transaction_receipt(hash)
+                    .await?
+                    .ok_or_else(|| Error::EmptyResult("eth_getTransactionReceipt".to_string()))?,
+            );
+        }
+        Ok(receipts)
+    }
+
+    /// Logs matching an `eth_getLogs` filter object
+    pub async fn get_logs(&self, filter: &Value) -> Result<Vec<Value>, Error> {
+        self.call("eth_getLogs", json!([filter])).await
+    }
+}
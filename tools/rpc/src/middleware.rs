@@ -0,0 +1,88 @@
+//! Hooks run around every attempt of a call
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::Error;
+
+/// One attempt of a call, as seen by middleware
+#[derive(Debug, Clone, Copy)]
+pub struct Request<'a> {
+    pub endpoint: &'a str,
+    pub method: &'a str,
+    pub params: &'a Value,
+    /// 0 for the first attempt, counting up through retries and failovers
+    pub attempt: usize,
+}
+
+/// Observes calls made by a [`Client`](crate::Client)
+pub trait Middleware: Send + Sync {
+    fn on_request(&self, _request: &Request) {}
+
+    fn on_response(&self, _request: &Request, _elapsed: Duration, _result: Result<&Value, &Error>) {
+    }
+}
+
+/// Logs every attempt at debug level and failed attempts at warn
+pub struct Tracing;
+
+impl Middleware for Tracing {
+    fn on_response(&self, request: &Request, elapsed: Duration, result: Result<&Value, &Error>) {
+        let elapsed_ms = elapsed.as_millis() as u64;
+        match result {
+            Ok(_) => tracing::debug!(
+                endpoint = request.endpoint,
+                method = request.method,
+                attempt = request.attempt,
+                elapsed_ms,
+                "RPC call"
+            ),
+            Err(error) => tracing::warn!(
+                endpoint = request.endpoint,
+                method = request.method,
+                attempt = request.attempt,
+                elapsed_ms,
+                %error,
+                "RPC call failed"
+            ),
+        }
+    }
+}
+
+/// Counts of calls to one method
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodStats {
+    pub calls: u64,
+    pub errors: u64,
+    /// Summed latency of every attempt, successful or not
+    pub latency: Duration,
+}
+
+/// Per-method call counts and latency, for a tool to export
+#[derive(Default)]
+pub struct Metrics {
+    methods: Mutex<BTreeMap<String, MethodStats>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stats of every method called so far, by name
+    pub fn snapshot(&self) -> BTreeMap<String, MethodStats> {
+        self.methods.lock().unwrap().clone()
+    }
+}
+
+impl Middleware for Metrics {
+    fn on_response(&self, request: &Request, elapsed: Duration, result: Result<&Value, &Error>) {
+        let mut methods = self.methods.lock().unwrap();
+        let stats = methods.entry(request.method.to_string()).or_default();
+        stats.calls += 1;
+        stats.errors += u64::from(result.is_err());
+        stats.latency += elapsed;
+    }
+}
@@ -0,0 +1,71 @@
+//! How requests reach an endpoint
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::Error;
+
+/// Sends one JSON-RPC request and returns its `result`
+///
+/// Implemented over HTTP by [`Http`]; tools implement it for in-process
+/// chains, such as the faucet's dry-run mode.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Endpoint name used in logs and metrics
+    fn endpoint(&self) -> &str;
+
+    async fn send(&self, method: &str, params: &Value) -> Result<Value, Error>;
+}
+
+/// JSON-RPC over HTTP POST
+pub struct Http {
+    url: String,
+    http: reqwest::Client,
+    timeout: Option<Duration>,
+}
+
+impl Http {
+    pub fn new(url: &str, http: reqwest::Client, timeout: Option<Duration>) -> Self {
+        Self {
+            url: url.to_string(),
+            http,
+            timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for Http {
+    fn endpoint(&self) -> &str {
+        &self.url
+    }
+
+    async fn send(&self, method: &str, params: &Value) -> Result<Value, Error> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut request = self.http.post(&self.url).json(&body);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+
+        let mut response: Value = async { request.send().await?.error_for_status()?.json().await }
+            .await
+            .map_err(|e| Error::Transport(e.to_string()))?;
+
+        match response.get("error") {
+            Some(error) if !error.is_null() => Err(Error::Rpc {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string()),
+            }),
+            _ => Ok(response["result"].take()),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! `eth_subscribe` notifications over WebSocket
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::Error;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// An open `eth_subscribe` subscription
+pub struct Subscription {
+    socket: Socket,
+    id: Value,
+}
+
+fn transport(e: impl std::fmt::Display) -> Error {
+    Error::Transport(e.to_string())
+}
+
+impl Subscription {
+    /// Subscribe at `url` with `eth_subscribe` params, e.g. `["newHeads"]`
+    pub async fn open(url: &str, params: Value) -> Result<Self, Error> {
+        let (mut socket, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(transport)?;
+        let request =
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_subscribe", "params": params });
+        socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .map_err(transport)?;
+
+        // Notifications can't arrive before the reply carrying the id
+        loop {
+            let reply = match next_json(&mut socket).await {
+                Some(reply) => reply?,
+                None => return Err(transport("connection closed before eth_subscribe reply")),
+            };
+            if reply["id"] != json!(1) {
+                continue;
+            }
+            if let Some(error) = reply.get("error").filter(|e| !e.is_null()) {
+                return Err(Error::Rpc {
+                    code: error["code"].as_i64().unwrap_or_default(),
+                    message: error["message"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+            return Ok(Self {
+                socket,
+                id: reply["result"].clone(),
+            });
+        }
+    }
+
+    /// Subscription to new block headers
+    pub async fn new_heads(url: &str) -> Result<Self, Error> {
+        Self::open(url, json!(["newHeads"])).await
+    }
+
+    /// Subscription to logs matching an `eth_getLogs`-style filter
+    pub async fn logs(url: &str, filter: Value) -> Result<Self, Error> {
+        Self::open(url, json!(["logs", filter])).await
+    }
+
+    /// Subscription id assigned by the node
+    pub fn id(&self) -> &Value {
+        &self.id
+    }
+
+    /// Next notification; `None` once the node closes the connection
+    pub async fn next(&mut self) -> Option<Result<Value, Error>> {
+        loop {
+            let mut message = match next_json(&mut self.socket).await? {
+                Ok(message) => message,
+                Err(e) => return Some(Err(e)),
+            };
+            if message["method"] == "eth_subscription"
+                && message["params"]["subscription"] == self.id
+            {
+                return Some(Ok(message["params"]["result"].take()));
+            }
+        }
+    }
+
+    /// Cancel the subscription and close the connection
+    pub async fn unsubscribe(mut self) -> Result<(), Error> {
+        let request =
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "eth_unsubscribe", "params": [self.id] });
+        self.socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .map_err(transport)?;
+        self.socket.close(None).await.map_err(transport)
+    }
+}
+
+/// Next text message as JSON, skipping pings and other frames
+async fn next_json(socket: &mut Socket) -> Option<Result<Value, Error>> {
+    while let Some(message) = socket.next().await {
+        match message {
+            Ok(Message::Text(text)) => {
+                return Some(serde_json::from_str(&text).map_err(|e| Error::Decode(e.to_string())))
+            }
+            Ok(Message::Close(_)) => return None,
+            Ok(_) => continue,
+            Err(e) => return Some(Err(transport(e))),
+        }
+    }
+    None
+}
@@ -0,0 +1,177 @@
+//! Client behaviour against stub nodes
+
+use axionax_rpc::middleware::Metrics;
+use axionax_rpc::{Client, Error, Retry, U256};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Serve a node answering each request with `answer(method, params)`
+async fn node(answer: impl Fn(&str, &Value) -> Response + Clone + Send + Sync + 'static) -> String {
+    let app = Router::new().route(
+        "/",
+        post(move |Json(request): Json<Value>| {
+            let answer = answer.clone();
+            async move { answer(request["method"].as_str().unwrap(), &request["params"]) }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+fn result(value: Value) -> Response {
+    Json(json!({ "jsonrpc": "2.0", "id": 1, "result": value })).into_response()
+}
+
+fn error(code: i64, message: &str) -> Response {
+    Json(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": code, "message": message } }))
+        .into_response()
+}
+
+fn unavailable() -> Response {
+    StatusCode::SERVICE_UNAVAILABLE.into_response()
+}
+
+/// A healthy node with one block holding two transactions
+async fn healthy(with_block_receipts: bool) -> String {
+    node(move |method, params| match method {
+        "eth_chainId" => result(json!("0x15b38")),
+        "eth_getBalance" => result(json!("0xde0b6b3a7640000")),
+        "eth_getCode" => result(json!(if params[0] == "0xc0de" {
+            "0x6080"
+        } else {
+            "0x"
+        })),
+        "eth_getTransactionReceipt" if params[0] == "0xpending" => result(Value::Null),
+        "eth_getTransactionReceipt" => result(json!({ "transactionHash": params[0] })),
+        "eth_getBlockByNumber" => result(json!({ "transactions": ["0xa", "0xb"] })),
+        "eth_getBlockReceipts" if with_block_receipts => {
+            result(json!([{ "transactionHash": "0xa" }, { "transactionHash": "0xb" }]))
+        }
+        "eth_call" => error(3, "execution reverted"),
+        _ => error(-32601, "the method does not exist"),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn typed_methods_decode_results() {
+    let client = Client::new(&healthy(true).await);
+    assert_eq!(client.get_chain_id().await.unwrap(), 88888);
+    assert_eq!(client.get_balance("0x01").await.unwrap(), U256::exp10(18));
+    assert!(client.is_contract("0xc0de").await.unwrap());
+    assert!(!client.is_contract("0x01").await.unwrap());
+    assert_eq!(
+        client.get_transaction_receipt("0xpending").await.unwrap(),
+        None
+    );
+
+    // A null result is only an error where one is required
+    let empty = client
+        .call::<Value>("eth_getTransactionReceipt", json!(["0xpending"]))
+        .await;
+    assert_eq!(
+        empty,
+        Err(Error::EmptyResult("eth_getTransactionReceipt".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn rpc_errors_keep_their_code() {
+    let client = Client::new(&healthy(true).await);
+    let e = client
+        .call::<String>("eth_call", json!([]))
+        .await
+        .unwrap_err();
+    assert_eq!(e.code(), Some(3));
+    assert_eq!(e.to_string(), "execution reverted (code 3)");
+    assert!(!e.is_retryable());
+
+    let shared = axionax_errors::Error::from(e);
+    assert_eq!((shared.kind.as_str(), shared.code), ("rpc", "rpc_error"));
+
+    let e = client
+        .call::<u64>("eth_chainId", json!([]))
+        .await
+        .unwrap_err();
+    assert!(matches!(e, Error::Decode(message) if message.starts_with("eth_chainId: ")));
+}
+
+#[tokio::test]
+async fn block_receipts_fall_back_to_single_receipts() {
+    for with_block_receipts in [true, false] {
+        let client = Client::new(&healthy(with_block_receipts).await);
+        let receipts = client.get_block_receipts(7).await.unwrap();
+        let hashes: Vec<&Value> = receipts.iter().map(|r| &r["transactionHash"]).collect();
+        assert_eq!(hashes, [&json!("0xa"), &json!("0xb")]);
+    }
+}
+
+#[tokio::test]
+async fn failing_endpoint_fails_over_to_the_next() {
+    let down = node(|_, _| unavailable()).await;
+    let up = healthy(true).await;
+    let metrics = Arc::new(Metrics::new());
+    let client = Client::builder()
+        .endpoint(&down)
+        .endpoint(&up)
+        .middleware(metrics.clone())
+        .build();
+
+    assert_eq!(client.get_chain_id().await.unwrap(), 88888);
+    assert_eq!(client.endpoint(), up);
+
+    // Later calls stay on the working endpoint
+    client.get_chain_id().await.unwrap();
+    let stats = &metrics.snapshot()["eth_chainId"];
+    assert_eq!((stats.calls, stats.errors), (3, 1));
+
+    // Errors from the node itself are the same on every endpoint
+    assert!(client.call::<Value>("eth_call", json!([])).await.is_err());
+    assert_eq!(metrics.snapshot()["eth_call"].calls, 1);
+}
+
+#[tokio::test]
+async fn retries_back_off_until_the_node_recovers() {
+    let failures = Arc::new(AtomicUsize::new(0));
+    let counter = failures.clone();
+    let flaky = node(move |_, _| match counter.fetch_add(1, Ordering::SeqCst) {
+        0 | 1 => error(-32005, "rate limited"),
+        _ => result(json!("0x1")),
+    })
+    .await;
+
+    let once = Client::new(&flaky);
+    let e = once.get_block_number().await.unwrap_err();
+    assert_eq!(e.code(), Some(-32005));
+
+    let patient = Client::builder()
+        .endpoint(&flaky)
+        .retry(Retry {
+            retries: 3,
+            backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        })
+        .build();
+    assert_eq!(patient.get_block_number().await.unwrap(), 1);
+    assert_eq!(failures.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn backoff_doubles_up_to_the_maximum() {
+    let retry = Retry {
+        retries: 10,
+        backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_secs(1),
+    };
+    let delays: Vec<u128> = (1..=6)
+        .map(|round| retry.delay(round).as_millis())
+        .collect();
+    assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+}
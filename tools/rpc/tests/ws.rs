@@ -0,0 +1,63 @@
+//! Subscriptions against a stub WebSocket node
+
+use axionax_rpc::ws::Subscription;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::{routing::get, Router};
+use serde_json::{json, Value};
+
+/// Reply to eth_subscribe, send a notification for another subscription and
+/// two heads, then close
+async fn stub(mut socket: WebSocket) {
+    let Some(Ok(Message::Text(text))) = socket.recv().await else {
+        return;
+    };
+    let request: Value = serde_json::from_str(&text).unwrap();
+    let reply = if request["params"][0] == "newHeads" {
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0xabc" })
+    } else {
+        json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32602, "message": "unsupported" } })
+    };
+    socket.send(Message::Text(reply.to_string())).await.unwrap();
+
+    for (subscription, number) in [("0xother", "0x9"), ("0xabc", "0x10"), ("0xabc", "0x11")] {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "eth_subscription",
+            "params": { "subscription": subscription, "result": { "number": number } },
+        });
+        socket
+            .send(Message::Text(notification.to_string()))
+            .await
+            .unwrap();
+    }
+    let _ = socket.close().await;
+}
+
+async fn serve() -> String {
+    let app = Router::new().route(
+        "/",
+        get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(stub) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+#[tokio::test]
+async fn new_heads_are_streamed_until_close() {
+    let mut heads = Subscription::new_heads(&serve().await).await.unwrap();
+    assert_eq!(heads.id(), "0xabc");
+    assert_eq!(heads.next().await.unwrap().unwrap()["number"], "0x10");
+    assert_eq!(heads.next().await.unwrap().unwrap()["number"], "0x11");
+    assert!(heads.next().await.is_none());
+}
+
+#[tokio::test]
+async fn rejected_subscription_is_an_rpc_error() {
+    let e = Subscription::logs(&serve().await, json!({ "address": "0x01" }))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(e.code(), Some(-32602));
+}
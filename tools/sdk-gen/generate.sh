@@ -0,0 +1,10 @@
+#!/bin/bash
+# Regenerate the client SDKs from the service OpenAPI specs.
+# Pass --check to fail instead when the committed SDKs are out of date.
+
+set -e
+
+ROOT="$(cd "$(dirname "$0")/../.." && pwd)"
+cd "$ROOT"
+
+python3 tools/sdk-gen/sdk_gen.py tools/faucet/openapi.json --name faucet --out sdk/faucet "$@"
@@ -0,0 +1,565 @@
+#!/usr/bin/env python3
+"""
+SDK Generator
+Emits TypeScript and Python client packages from an OpenAPI 3 spec
+"""
+
+import argparse
+import filecmp
+import json
+import re
+import sys
+import tempfile
+from pathlib import Path
+from typing import Dict, List, Optional, Tuple
+
+HTTP_METHODS = ("get", "post", "put", "patch", "delete")
+
+HEADER = "Generated by tools/sdk-gen from {spec}. Do not edit."
+
+# Build artifacts that live next to the generated sources
+IGNORED = ["node_modules", "dist", "build", "__pycache__"]
+
+
+def pascal_case(name: str) -> str:
+    return "".join(part[:1].upper() + part[1:] for part in re.split(r"[^A-Za-z0-9]+", name) if part)
+
+
+def camel_case(name: str) -> str:
+    pascal = pascal_case(name)
+    return pascal[:1].lower() + pascal[1:]
+
+
+def snake_case(name: str) -> str:
+    name = re.sub(r"([a-z0-9])([A-Z])", r"\1_\2", name)
+    return re.sub(r"[^A-Za-z0-9]+", "_", name).lower().strip("_")
+
+
+def ref_name(ref: str) -> str:
+    if not ref.startswith("#/components/schemas/"):
+        raise ValueError(f"Unsupported $ref: {ref}")
+    return ref.rsplit("/", 1)[1]
+
+
+class Operation:
+    """A single HTTP operation from the spec"""
+
+    def __init__(self, path: str, method: str, spec: Dict, security: List[str]):
+        self.path = path
+        self.method = method.upper()
+        if "operationId" not in spec:
+            raise ValueError(f"{method.upper()} {path} has no operationId")
+        self.operation_id = spec["operationId"]
+        self.summary = spec.get("summary", "")
+        params = spec.get("parameters", [])
+        self.path_params = [p for p in params if p["in"] == "path"]
+        self.query_params = [p for p in params if p["in"] == "query"]
+        body = spec.get("requestBody", {}).get("content", {}).get("application/json")
+        self.body_schema = body["schema"] if body else None
+        self.body_required = spec.get("requestBody", {}).get("required", False)
+        self.response_schema = self._success_schema(spec.get("responses", {}))
+        self.auth = bool(spec.get("security", security))
+
+    @staticmethod
+    def _success_schema(responses: Dict) -> Optional[Dict]:
+        for status in sorted(responses):
+            if status.startswith("2"):
+                content = responses[status].get("content", {})
+                if "application/json" in content:
+                    return content["application/json"]["schema"]
+                if "text/plain" in content:
+                    return {"type": "string"}
+                return None
+        return None
+
+
+def is_stream(spec: Dict) -> bool:
+    """Whether an operation is a WebSocket upgrade (101) or a server-sent event stream"""
+    responses = spec.get("responses", {})
+    return "101" in responses or any(
+        "text/event-stream" in response.get("content", {}) for response in responses.values()
+    )
+
+
+class Spec:
+    """Schemas and operations of an OpenAPI document"""
+
+    def __init__(self, document: Dict):
+        if not str(document.get("openapi", "")).startswith("3."):
+            raise ValueError("Only OpenAPI 3 specs are supported")
+        self.title = document["info"]["title"]
+        self.version = document["info"]["version"]
+        self.schemas: Dict[str, Dict] = document.get("components", {}).get("schemas", {})
+        default_security = [name for req in document.get("security", []) for name in req]
+        self.operations: List[Operation] = []
+        for path, item in document["paths"].items():
+            for method in HTTP_METHODS:
+                # WebSocket upgrades and event streams don't fit request/response clients
+                if method in item and not is_stream(item[method]):
+                    self.operations.append(Operation(path, method, item[method], default_security))
+
+    @property
+    def needs_auth(self) -> bool:
+        return any(op.auth for op in self.operations)
+
+
+class TypeScriptGenerator:
+    """Emits an npm package with interfaces and a fetch-based client"""
+
+    def __init__(self, spec: Spec, name: str, spec_path: str):
+        self.spec = spec
+        self.name = name
+        self.spec_path = spec_path
+        self.client = f"{pascal_case(name)}Client"
+
+    def type_of(self, schema: Dict) -> str:
+        if schema.get("nullable"):
+            return f"{self.type_of(dict(schema, nullable=False))} | null"
+        if "$ref" in schema:
+            return ref_name(schema["$ref"])
+        if "enum" in schema:
+            return " | ".join(json.dumps(value) for value in schema["enum"])
+        kind = schema.get("type")
+        if kind == "array":
+            item = self.type_of(schema["items"])
+            return f"({item})[]" if "|" in item else f"{item}[]"
+        if kind in ("integer", "number"):
+            return "number"
+        if kind == "boolean":
+            return "boolean"
+        if kind == "string":
+            return "string"
+        if kind == "object" and "properties" in schema:
+            return self.inline_object(schema)
+        return "unknown"
+
+    def inline_object(self, schema: Dict) -> str:
+        required = set(schema.get("required", []))
+        fields = [
+            f"{name}{'' if name in required else '?'}: {self.type_of(prop)}"
+            for name, prop in schema["properties"].items()
+        ]
+        return "{ " + "; ".join(fields) + " }"
+
+    def declaration(self, name: str, schema: Dict) -> str:
+        lines = []
+        if schema.get("description"):
+            lines.append(f"/** {schema['description']} */")
+        if schema.get("type") == "object" and "properties" in schema:
+            required = set(schema.get("required", []))
+            lines.append(f"export interface {name} {{")
+            for field, prop in schema["properties"].items():
+                if prop.get("description"):
+                    lines.append(f"  /** {prop['description']} */")
+                optional = "" if field in required else "?"
+                lines.append(f"  {field}{optional}: {self.type_of(prop)};")
+            lines.append("}")
+        else:
+            lines.append(f"export type {name} = {self.type_of(schema)};")
+        return "\n".join(lines)
+
+    def method(self, op: Operation) -> str:
+        args = [f"{camel_case(p['name'])}: string" for p in op.path_params]
+        if op.body_schema:
+            optional = "" if op.body_required else "?"
+            args.append(f"body{optional}: {self.type_of(op.body_schema)}")
+        if op.query_params:
+            query = self.inline_object({
+                "type": "object",
+                "properties": {p["name"]: p["schema"] for p in op.query_params},
+                "required": [p["name"] for p in op.query_params if p.get("required")],
+            })
+            args.append(f"query: {query} = {{}}")
+        result = self.type_of(op.response_schema) if op.response_schema else "void"
+
+        path = op.path
+        for p in op.path_params:
+            path = path.replace(
+                "{" + p["name"] + "}", "${encodeURIComponent(" + camel_case(p["name"]) + ")}"
+            )
+
+        lines = [f"  /** {op.summary} */"] if op.summary else []
+        lines.append(f"  {op.operation_id}({', '.join(args)}): Promise<{result}> {{")
+        call = [f'"{op.method}"', f"`{path}`"]
+        call.append("query" if op.query_params else "undefined")
+        call.append("body" if op.body_schema else "undefined")
+        call.append("true" if op.auth else "false")
+        lines.append(f"    return this.request<{result}>({', '.join(call)});")
+        lines.append("  }")
+        return "\n".join(lines)
+
+    def index(self) -> str:
+        parts = [f"// {HEADER.format(spec=self.spec_path)}", ""]
+        for name, schema in self.spec.schemas.items():
+            parts.append(self.declaration(name, schema))
+            parts.append("")
+
+        auth_option = [
+            "  /** Bearer token for authenticated endpoints */",
+            "  token?: string;",
+        ] if self.spec.needs_auth else []
+        auth_header = [
+            "    if (auth && this.options.token) {",
+            "      headers.authorization = `Bearer ${this.options.token}`;",
+            "    }",
+        ] if self.spec.needs_auth else []
+
+        parts += [
+            "/** Non-2xx response; `body` holds the decoded error payload */",
+            "export class ApiError extends Error {",
+            "  constructor(public readonly status: number, public readonly body: unknown) {",
+            "    super(`HTTP ${status}`);",
+            '    this.name = "ApiError";',
+            "  }",
+            "}",
+            "",
+            f"export interface {self.client}Options {{",
+            *auth_option,
+            "  /** Extra headers sent with every request */",
+            "  headers?: Record<string, string>;",
+            "  /** fetch implementation, defaults to the global one */",
+            "  fetch?: typeof fetch;",
+            "}",
+            "",
+            f"/** Client for the {self.spec.title} */",
+            f"export class {self.client} {{",
+            "  constructor(",
+            "    private readonly baseUrl: string,",
+            f"    private readonly options: {self.client}Options = {{}},",
+            "  ) {}",
+            "",
+        ]
+        for op in self.spec.operations:
+            parts.append(self.method(op))
+            parts.append("")
+        parts += [
+            "  private async request<T>(",
+            "    method: string,",
+            "    path: string,",
+            "    query: Record<string, string | number | boolean | undefined> | undefined,",
+            "    body: unknown,",
+            "    auth: boolean,",
+            "  ): Promise<T> {",
+            '    const url = new URL(this.baseUrl.replace(/\\/$/, "") + path);',
+            "    for (const [key, value] of Object.entries(query ?? {})) {",
+            "      if (value !== undefined) url.searchParams.set(key, String(value));",
+            "    }",
+            "    const headers: Record<string, string> = { ...this.options.headers };",
+            "    if (body !== undefined) headers[\"content-type\"] = \"application/json\";",
+            *auth_header,
+            "    const response = await (this.options.fetch ?? fetch)(url, {",
+            "      method,",
+            "      headers,",
+            "      body: body === undefined ? undefined : JSON.stringify(body),",
+            "    });",
+            "    const text = await response.text();",
+            '    const json = response.headers.get("content-type")?.includes("json");',
+            "    const payload = text && json ? JSON.parse(text) : text || undefined;",
+            "    if (!response.ok) throw new ApiError(response.status, payload);",
+            "    return payload as T;",
+            "  }",
+            "}",
+            "",
+        ]
+        return "\n".join(parts)
+
+    def files(self) -> Dict[str, str]:
+        package = {
+            "name": f"@axionax/{self.name}-client",
+            "version": self.spec.version,
+            "description": f"Client for the {self.spec.title}",
+            "license": "MIT",
+            "main": "dist/index.js",
+            "types": "dist/index.d.ts",
+            "files": ["dist"],
+            "scripts": {"build": "tsc"},
+            "devDependencies": {"typescript": "^5.0.0"},
+        }
+        tsconfig = {
+            "compilerOptions": {
+                "target": "ES2020",
+                "module": "commonjs",
+                "lib": ["ES2020", "DOM"],
+                "declaration": True,
+                "strict": True,
+                "outDir": "dist",
+            },
+            "include": ["src"],
+        }
+        return {
+            "package.json": json.dumps(package, indent=2) + "\n",
+            "tsconfig.json": json.dumps(tsconfig, indent=2) + "\n",
+            "src/index.ts": self.index(),
+        }
+
+
+class PythonGenerator:
+    """Emits a Python package with TypedDicts and a urllib-based client"""
+
+    def __init__(self, spec: Spec, name: str, spec_path: str):
+        self.spec = spec
+        self.name = name
+        self.spec_path = spec_path
+        self.package = f"axionax_{snake_case(name)}_client"
+        self.client = f"{pascal_case(name)}Client"
+
+    def type_of(self, schema: Dict) -> str:
+        if schema.get("nullable"):
+            return f"Optional[{self.type_of(dict(schema, nullable=False))}]"
+        if "$ref" in schema:
+            return f'"{ref_name(schema["$ref"])}"'
+        if "enum" in schema:
+            return "Literal[" + ", ".join(json.dumps(value) for value in schema["enum"]) + "]"
+        kind = schema.get("type")
+        if kind == "array":
+            return f"List[{self.type_of(schema['items'])}]"
+        if kind == "integer":
+            return "int"
+        if kind == "number":
+            return "float"
+        if kind == "boolean":
+            return "bool"
+        if kind == "string":
+            return "str"
+        if kind == "object":
+            return "Dict[str, Any]"
+        return "Any"
+
+    def declaration(self, name: str, schema: Dict) -> str:
+        if not (schema.get("type") == "object" and "properties" in schema):
+            return f"{name} = {self.type_of(schema)}"
+
+        required = set(schema.get("required", []))
+        props = schema["properties"]
+        groups: List[Tuple[str, str, List[str]]] = []
+        required_fields = [f for f in props if f in required]
+        optional_fields = [f for f in props if f not in required]
+        if optional_fields and required_fields:
+            groups.append((f"_{name}Required", "TypedDict", required_fields))
+            groups.append((name, f"_{name}Required, total=False", optional_fields))
+        elif optional_fields:
+            groups.append((name, "TypedDict, total=False", optional_fields))
+        else:
+            groups.append((name, "TypedDict", required_fields))
+
+        blocks = []
+        for class_name, bases, fields in groups:
+            lines = [f"class {class_name}({bases}):"]
+            if class_name == name and schema.get("description"):
+                lines.append(f'    """{schema["description"]}"""')
+                lines.append("")
+            for field in fields:
+                lines.append(f"    {field}: {self.type_of(props[field])}")
+            blocks.append("\n".join(lines))
+        return "\n\n\n".join(blocks)
+
+    def method(self, op: Operation) -> str:
+        args = ["self"] + [f"{snake_case(p['name'])}: str" for p in op.path_params]
+        if op.body_schema:
+            if op.body_required:
+                args.append(f"body: {self.type_of(op.body_schema)}")
+            else:
+                args.append(f"body: Optional[{self.type_of(op.body_schema)}] = None")
+        query_args = []
+        for p in op.query_params:
+            arg = snake_case(p["name"])
+            query_args.append((p["name"], arg))
+            args.append(f"{arg}: Optional[{self.type_of(p['schema'])}] = None")
+        result = self.type_of(op.response_schema) if op.response_schema else "None"
+
+        path = op.path
+        for p in op.path_params:
+            path = path.replace(
+                "{" + p["name"] + "}", "{quote(" + snake_case(p["name"]) + ", safe='')}"
+            )
+        path_expr = f'f"{path}"' if op.path_params else f'"{path}"'
+        query = "{" + ", ".join(f'"{name}": {arg}' for name, arg in query_args) + "}"
+
+        lines = [f"    def {snake_case(op.operation_id)}({', '.join(args)}) -> {result}:"]
+        if op.summary:
+            lines.append(f'        """{op.summary}"""')
+        call = [f'"{op.method}"', path_expr]
+        call.append(query if query_args else "None")
+        call.append("body" if op.body_schema else "None")
+        call.append("True" if op.auth else "False")
+        lines.append(f"        return self._request({', '.join(call)})")
+        return "\n".join(lines)
+
+    def module(self) -> str:
+        parts = [
+            f'"""Client for the {self.spec.title}',
+            "",
+            HEADER.format(spec=self.spec_path),
+            '"""',
+            "",
+            "import json",
+            "import urllib.error",
+            "import urllib.request",
+            "from typing import Any, Dict, List, Literal, Optional, TypedDict",
+            "from urllib.parse import quote, urlencode",
+            "",
+            f'__version__ = "{self.spec.version}"',
+            "",
+            "",
+        ]
+        for name, schema in self.spec.schemas.items():
+            parts.append(self.declaration(name, schema))
+            parts.append("")
+            parts.append("")
+
+        token_arg = ", token: Optional[str] = None" if self.spec.needs_auth else ""
+        token_init = ["        self.token = token"] if self.spec.needs_auth else []
+        token_header = [
+            "        if auth and self.token:",
+            '            headers["Authorization"] = f"Bearer {self.token}"',
+        ] if self.spec.needs_auth else []
+
+        parts += [
+            "class ApiError(Exception):",
+            '    """Non-2xx response; `body` holds the decoded error payload"""',
+            "",
+            "    def __init__(self, status: int, body: Any):",
+            '        super().__init__(f"HTTP {status}")',
+            "        self.status = status",
+            "        self.body = body",
+            "",
+            "",
+            f"class {self.client}:",
+            f'    """Client for the {self.spec.title}"""',
+            "",
+            f"    def __init__(self, base_url: str{token_arg}, timeout: float = 30.0):",
+            '        self.base_url = base_url.rstrip("/")',
+            *token_init,
+            "        self.timeout = timeout",
+            "",
+        ]
+        for op in self.spec.operations:
+            parts.append(self.method(op))
+            parts.append("")
+        parts += [
+            "    def _request(self, method: str, path: str, query: Optional[Dict[str, Any]],",
+            "                 body: Any, auth: bool) -> Any:",
+            "        url = self.base_url + path",
+            "        params = {k: v for k, v in (query or {}).items() if v is not None}",
+            "        if params:",
+            '            url += "?" + urlencode(params)',
+            '        headers = {"Accept": "application/json"}',
+            "        data = None",
+            "        if body is not None:",
+            '            headers["Content-Type"] = "application/json"',
+            "            data = json.dumps(body).encode()",
+            *token_header,
+            "        request = urllib.request.Request(url, data=data, headers=headers, method=method)",
+            "        try:",
+            "            with urllib.request.urlopen(request, timeout=self.timeout) as response:",
+            "                return _decode(response.headers.get(\"Content-Type\", \"\"), response.read())",
+            "        except urllib.error.HTTPError as e:",
+            "            raise ApiError(e.code, _decode(e.headers.get(\"Content-Type\", \"\"), e.read())) from None",
+            "",
+            "",
+            "def _decode(content_type: str, data: bytes) -> Any:",
+            "    text = data.decode()",
+            "    if not text:",
+            "        return None",
+            '    return json.loads(text) if "json" in content_type else text',
+            "",
+        ]
+        return "\n".join(parts)
+
+    def files(self) -> Dict[str, str]:
+        dist = f"axionax-{self.name}-client"
+        pyproject = "\n".join([
+            "[build-system]",
+            'requires = ["setuptools>=61"]',
+            'build-backend = "setuptools.build_meta"',
+            "",
+            "[project]",
+            f'name = "{dist}"',
+            f'version = "{self.spec.version}"',
+            f'description = "Client for the {self.spec.title}"',
+            'license = { text = "MIT" }',
+            'requires-python = ">=3.8"',
+            "",
+            "[tool.setuptools.package-data]",
+            f'{self.package} = ["py.typed"]',
+            "",
+        ])
+        return {
+            "pyproject.toml": pyproject,
+            f"{self.package}/__init__.py": self.module(),
+            f"{self.package}/py.typed": "",
+        }
+
+
+GENERATORS = {
+    "typescript": TypeScriptGenerator,
+    "python": PythonGenerator,
+}
+
+
+def write_files(root: Path, files: Dict[str, str]):
+    for relative, content in files.items():
+        path = root / relative
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(content)
+
+
+def differences(expected: Path, actual: Path) -> List[str]:
+    """Files that differ between two directory trees"""
+    if not actual.exists():
+        return [str(actual)]
+    compare = filecmp.dircmp(expected, actual, ignore=IGNORED)
+    extra = [name for name in compare.right_only if not name.endswith(".egg-info")]
+    stale = [str(actual / name) for name in compare.left_only + extra + compare.diff_files]
+    for name in compare.common_dirs:
+        stale += differences(expected / name, actual / name)
+    return stale
+
+
+def main():
+    """Main function"""
+    parser = argparse.ArgumentParser(description="Generate client SDKs from an OpenAPI spec")
+    parser.add_argument("spec", help="OpenAPI 3 spec (JSON)")
+    parser.add_argument("--name", required=True, help="Service name, e.g. faucet")
+    parser.add_argument("--out", required=True, help="Output directory, one subdirectory per language")
+    parser.add_argument("--lang", default=",".join(GENERATORS),
+                        help="Comma-separated languages (default: all)")
+    parser.add_argument("--check", action="store_true",
+                        help="Fail if the output directory is not up to date instead of writing it")
+    args = parser.parse_args()
+
+    langs = [lang.strip() for lang in args.lang.split(",") if lang.strip()]
+    unknown = [lang for lang in langs if lang not in GENERATORS]
+    if unknown:
+        parser.error(f"unknown language(s): {', '.join(unknown)}")
+
+    spec_path = Path(args.spec)
+    try:
+        spec = Spec(json.loads(spec_path.read_text()))
+    except (OSError, ValueError, KeyError) as e:
+        print(f"❌ Invalid spec {spec_path}: {e}", file=sys.stderr)
+        sys.exit(1)
+
+    out = Path(args.out)
+    stale = []
+    for lang in langs:
+        files = GENERATORS[lang](spec, args.name, spec_path.as_posix()).files()
+        if args.check:
+            with tempfile.TemporaryDirectory() as tmp:
+                write_files(Path(tmp), files)
+                stale += differences(Path(tmp), out / lang)
+        else:
+            target = out / lang
+            write_files(target, files)
+            print(f"✅ {lang}: {target}")
+
+    if stale:
+        print("❌ Generated SDKs are out of date:", file=sys.stderr)
+        for path in stale:
+            print(f"   {path}", file=sys.stderr)
+        print("   Run tools/sdk-gen/generate.sh", file=sys.stderr)
+        sys.exit(1)
+
+
+if __name__ == "__main__":
+    main()
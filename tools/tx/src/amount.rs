@@ -0,0 +1,134 @@
+//! Parsing and formatting of token amounts
+
+use primitive_types::U256;
+use std::fmt;
+use std::str::FromStr;
+
+/// Denomination of an amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Wei,
+    Gwei,
+    Axx,
+}
+
+impl Unit {
+    /// Decimal places between this unit and wei
+    pub fn decimals(self) -> usize {
+        match self {
+            Unit::Wei => 0,
+            Unit::Gwei => 9,
+            Unit::Axx => 18,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Wei => "wei",
+            Unit::Gwei => "gwei",
+            Unit::Axx => "AXX",
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = AmountError;
+
+    /// Unit by symbol, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wei" => Ok(Unit::Wei),
+            "gwei" => Ok(Unit::Gwei),
+            "axx" => Ok(Unit::Axx),
+            _ => Err(AmountError::UnknownUnit(s.to_string())),
+        }
+    }
+}
+
+/// Why an amount couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountError {
+    /// Not a decimal number, optionally followed by a space and a unit
+    Invalid,
+    UnknownUnit(String),
+    /// More decimal places than the unit has
+    TooPrecise,
+    /// Larger than 2^256 - 1 wei
+    Overflow,
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::Invalid => write!(f, "invalid amount"),
+            AmountError::UnknownUnit(unit) => write!(f, "unknown unit {}", unit),
+            AmountError::TooPrecise => write!(f, "too many decimal places"),
+            AmountError::Overflow => write!(f, "amount too large"),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+/// Parse an amount such as `"1.5 AXX"`, `"2000 gwei"` or `"100"` into wei
+///
+/// Amounts without a unit are in AXX.
+pub fn parse(input: &str) -> Result<U256, AmountError> {
+    parse_in(input, Unit::Axx)
+}
+
+/// Parse an amount into wei, reading amounts without a unit in `default`
+///
+/// The number is decimal with an optional fraction (`"0.25"`, `".5"`), and
+/// the unit follows after at most one space. Signs, exponents and
+/// surrounding whitespace are rejected.
+pub fn parse_in(input: &str, default: Unit) -> Result<U256, AmountError> {
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, rest) = input.split_at(split);
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if (whole.is_empty() && fraction.is_empty()) || fraction.contains('.') || number.ends_with('.')
+    {
+        return Err(AmountError::Invalid);
+    }
+    let unit = match rest.strip_prefix(' ').unwrap_or(rest) {
+        "" if rest.is_empty() => default,
+        symbol if !symbol.is_empty() && symbol.bytes().all(|b| b.is_ascii_alphabetic()) => {
+            symbol.parse()?
+        }
+        _ => return Err(AmountError::Invalid),
+    };
+    if fraction.len() > unit.decimals() {
+        return Err(AmountError::TooPrecise);
+    }
+
+    let decimal = |digits: &str| match digits {
+        "" => Ok(U256::zero()),
+        digits => U256::from_dec_str(digits).map_err(|_| AmountError::Overflow),
+    };
+    let scale = U256::exp10(unit.decimals() - fraction.len());
+    let fraction = decimal(fraction)?
+        .checked_mul(scale)
+        .ok_or(AmountError::Overflow)?;
+    decimal(whole)?
+        .checked_mul(U256::exp10(unit.decimals()))
+        .and_then(|wei| wei.checked_add(fraction))
+        .ok_or(AmountError::Overflow)
+}
+
+/// Format wei in `unit` without trailing zeros, e.g. `"1.5 AXX"`
+pub fn format(wei: U256, unit: Unit) -> String {
+    let scale = U256::exp10(unit.decimals());
+    let (whole, fraction) = wei.div_mod(scale);
+    if fraction.is_zero() {
+        return format!("{} {}", whole, unit.symbol());
+    }
+    let fraction = format!("{:0>width$}", fraction, width = unit.decimals());
+    format!(
+        "{}.{} {}",
+        whole,
+        fraction.trim_end_matches('0'),
+        unit.symbol()
+    )
+}
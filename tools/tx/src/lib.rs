@@ -0,0 +1,14 @@
+//! Transactions and amounts shared by the axionax tools
+//!
+//! [`Transaction`] builds and signs legacy, EIP-2930 and EIP-1559
+//! transactions from high-level inputs; [`amount`] parses and formats
+//! amounts such as `"1.5 AXX"` or `"2000 gwei"`. All values are [`U256`].
+
+pub mod amount;
+pub mod rlp;
+mod transaction;
+
+pub use primitive_types::U256;
+pub use transaction::{
+    parse_address, AccessListItem, Address, Fees, SignedTransaction, Transaction, TxError,
+};
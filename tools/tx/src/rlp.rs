@@ -0,0 +1,52 @@
+//! Recursive length prefix (RLP) encoding
+
+/// An RLP item: a byte string or a list of items
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Bytes(Vec<u8>),
+    List(Vec<Item>),
+}
+
+impl Item {
+    /// Big-endian integer bytes, with leading zeros stripped
+    pub fn uint(bytes: &[u8]) -> Self {
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        Item::Bytes(bytes[start..].to_vec())
+    }
+
+    /// Encode the item
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Item::Bytes(bytes) if bytes.len() == 1 && bytes[0] < 0x80 => bytes.clone(),
+            Item::Bytes(bytes) => {
+                let mut out = length_prefix(bytes.len(), 0x80);
+                out.extend_from_slice(bytes);
+                out
+            }
+            Item::List(items) => {
+                let payload: Vec<u8> = items.iter().flat_map(Item::encode).collect();
+                let mut out = length_prefix(payload.len(), 0xc0);
+                out.extend(payload);
+                out
+            }
+        }
+    }
+}
+
+impl From<u64> for Item {
+    fn from(value: u64) -> Self {
+        Item::uint(&value.to_be_bytes())
+    }
+}
+
+/// Prefix of a string (`offset` 0x80) or list (0xc0) payload of `len` bytes
+fn length_prefix(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes = (len as u64).to_be_bytes();
+    let start = len_bytes.iter().position(|b| *b != 0).unwrap_or(7);
+    let mut out = vec![offset + 55 + (8 - start) as u8];
+    out.extend_from_slice(&len_bytes[start..]);
+    out
+}
@@ -0,0 +1,320 @@
+//! Building and signing transactions
+
+use k256::ecdsa::SigningKey;
+use primitive_types::U256;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+use crate::rlp::Item;
+
+/// 20-byte account address
+pub type Address = [u8; 20];
+
+/// Gas limit of a plain value transfer to an account without code
+const TRANSFER_GAS: u64 = 21_000;
+
+/// Why a transaction couldn't be built or signed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxError {
+    InvalidAddress,
+    Signing(String),
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxError::InvalidAddress => write!(f, "invalid address"),
+            TxError::Signing(e) => write!(f, "signing failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TxError {}
+
+/// Parse a `0x`-prefixed hex address; the checksum is not verified
+pub fn parse_address(address: &str) -> Result<Address, TxError> {
+    let hex = address.strip_prefix("0x").ok_or(TxError::InvalidAddress)?;
+    let mut out = [0u8; 20];
+    hex::decode_to_slice(hex, &mut out).map_err(|_| TxError::InvalidAddress)?;
+    Ok(out)
+}
+
+/// Storage an EIP-2930 transaction declares it will access
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<[u8; 32]>,
+}
+
+/// Fee fields, which also select the transaction type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fees {
+    /// Type 0, signed with EIP-155 replay protection
+    Legacy { gas_price: U256 },
+    /// Type 1 (EIP-2930)
+    AccessList {
+        gas_price: U256,
+        access_list: Vec<AccessListItem>,
+    },
+    /// Type 2 (EIP-1559)
+    DynamicFee {
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        access_list: Vec<AccessListItem>,
+    },
+}
+
+/// An unsigned transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    /// `None` deploys a contract
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Vec<u8>,
+    pub fees: Fees,
+}
+
+/// A signed transaction, ready for `eth_sendRawTransaction`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTransaction {
+    pub raw: Vec<u8>,
+    pub hash: [u8; 32],
+}
+
+impl SignedTransaction {
+    /// `0x`-prefixed raw transaction
+    pub fn raw_hex(&self) -> String {
+        format!("0x{}", hex::encode(&self.raw))
+    }
+
+    /// `0x`-prefixed transaction hash
+    pub fn hash_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.hash))
+    }
+}
+
+fn uint(value: U256) -> Item {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    Item::uint(&bytes)
+}
+
+fn access_list(list: &[AccessListItem]) -> Item {
+    Item::List(
+        list.iter()
+            .map(|item| {
+                Item::List(vec![
+                    Item::Bytes(item.address.to_vec()),
+                    Item::List(
+                        item.storage_keys
+                            .iter()
+                            .map(|key| Item::Bytes(key.to_vec()))
+                            .collect(),
+                    ),
+                ])
+            })
+            .collect(),
+    )
+}
+
+impl Transaction {
+    /// Value transfer of `value` wei to `to`, with nonce 0 and legacy fees of
+    /// zero; set the nonce and fees before signing
+    pub fn transfer(chain_id: u64, to: Address, value: U256) -> Self {
+        Self {
+            chain_id,
+            nonce: 0,
+            gas_limit: TRANSFER_GAS,
+            to: Some(to),
+            value,
+            input: Vec::new(),
+            fees: Fees::Legacy {
+                gas_price: U256::zero(),
+            },
+        }
+    }
+
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    pub fn with_input(mut self, input: Vec<u8>) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Pay a fixed gas price: a legacy transaction, or an EIP-2930 one when
+    /// it has an access list
+    pub fn with_gas_price(mut self, gas_price: U256) -> Self {
+        self.fees = match self.take_access_list() {
+            access_list if access_list.is_empty() => Fees::Legacy { gas_price },
+            access_list => Fees::AccessList {
+                gas_price,
+                access_list,
+            },
+        };
+        self
+    }
+
+    /// Pay EIP-1559 fees, making this a type 2 transaction
+    pub fn with_dynamic_fee(
+        mut self,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    ) -> Self {
+        let access_list = self.take_access_list();
+        self.fees = Fees::DynamicFee {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            access_list,
+        };
+        self
+    }
+
+    /// Declare accessed storage, turning a legacy transaction into EIP-2930
+    pub fn with_access_list(mut self, list: Vec<AccessListItem>) -> Self {
+        self.fees = match self.fees {
+            Fees::Legacy { gas_price } | Fees::AccessList { gas_price, .. } => Fees::AccessList {
+                gas_price,
+                access_list: list,
+            },
+            Fees::DynamicFee {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                ..
+            } => Fees::DynamicFee {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                access_list: list,
+            },
+        };
+        self
+    }
+
+    /// EIP-2718 transaction type
+    pub fn tx_type(&self) -> u8 {
+        match self.fees {
+            Fees::Legacy { .. } => 0,
+            Fees::AccessList { .. } => 1,
+            Fees::DynamicFee { .. } => 2,
+        }
+    }
+
+    /// Highest fee the transaction can cost, in wei
+    pub fn max_fee(&self) -> U256 {
+        let price = match &self.fees {
+            Fees::Legacy { gas_price } | Fees::AccessList { gas_price, .. } => *gas_price,
+            Fees::DynamicFee {
+                max_fee_per_gas, ..
+            } => *max_fee_per_gas,
+        };
+        price.saturating_mul(U256::from(self.gas_limit))
+    }
+
+    /// Hash the sender signs
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut fields = self.fields();
+        if let Fees::Legacy { .. } = self.fees {
+            // EIP-155: commit to the chain ID in place of the signature
+            fields.extend([
+                Item::from(self.chain_id),
+                Item::Bytes(Vec::new()),
+                Item::Bytes(Vec::new()),
+            ]);
+        }
+        Keccak256::digest(self.envelope(fields)).into()
+    }
+
+    /// Sign with `key`
+    pub fn sign(&self, key: &SigningKey) -> Result<SignedTransaction, TxError> {
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(&self.signing_hash())
+            .map_err(|e| TxError::Signing(e.to_string()))?;
+        let parity = u64::from(recovery_id.to_byte());
+        let v = match self.fees {
+            Fees::Legacy { .. } => parity + 35 + 2 * self.chain_id,
+            _ => parity,
+        };
+
+        let (r, s) = signature.split_bytes();
+        let mut fields = self.fields();
+        fields.extend([Item::from(v), Item::uint(&r), Item::uint(&s)]);
+        let raw = self.envelope(fields);
+        Ok(SignedTransaction {
+            hash: Keccak256::digest(&raw).into(),
+            raw,
+        })
+    }
+
+    /// Fields shared by the signing payload and the signed transaction
+    fn fields(&self) -> Vec<Item> {
+        let to = Item::Bytes(self.to.map(|to| to.to_vec()).unwrap_or_default());
+        let common = |fees: Vec<Item>| {
+            let mut fields = fees;
+            fields.extend([
+                Item::from(self.gas_limit),
+                to.clone(),
+                uint(self.value),
+                Item::Bytes(self.input.clone()),
+            ]);
+            fields
+        };
+        match &self.fees {
+            Fees::Legacy { gas_price } => {
+                let mut fields = vec![Item::from(self.nonce)];
+                fields.extend(common(vec![uint(*gas_price)]));
+                fields
+            }
+            Fees::AccessList {
+                gas_price,
+                access_list: list,
+            } => {
+                let mut fields = vec![Item::from(self.chain_id), Item::from(self.nonce)];
+                fields.extend(common(vec![uint(*gas_price)]));
+                fields.push(access_list(list));
+                fields
+            }
+            Fees::DynamicFee {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                access_list: list,
+            } => {
+                let mut fields = vec![Item::from(self.chain_id), Item::from(self.nonce)];
+                fields.extend(common(vec![
+                    uint(*max_priority_fee_per_gas),
+                    uint(*max_fee_per_gas),
+                ]));
+                fields.push(access_list(list));
+                fields
+            }
+        }
+    }
+
+    /// RLP list of `fields`, prefixed with the type for typed transactions
+    fn envelope(&self, fields: Vec<Item>) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.tx_type() > 0 {
+            out.push(self.tx_type());
+        }
+        out.extend(Item::List(fields).encode());
+        out
+    }
+
+    fn take_access_list(&mut self) -> Vec<AccessListItem> {
+        match &mut self.fees {
+            Fees::Legacy { .. } => Vec::new(),
+            Fees::AccessList { access_list, .. } | Fees::DynamicFee { access_list, .. } => {
+                std::mem::take(access_list)
+            }
+        }
+    }
+}
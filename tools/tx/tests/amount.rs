@@ -0,0 +1,81 @@
+//! Amount parsing and formatting
+
+use axionax_tx::amount::{self, AmountError, Unit};
+use axionax_tx::U256;
+use proptest::prelude::*;
+
+fn wei(digits: &str) -> U256 {
+    U256::from_dec_str(digits).unwrap()
+}
+
+#[test]
+fn parses_amounts_in_each_unit() {
+    assert_eq!(amount::parse("1.5 AXX"), Ok(wei("1500000000000000000")));
+    assert_eq!(amount::parse("1.5axx"), Ok(wei("1500000000000000000")));
+    assert_eq!(amount::parse("2000 gwei"), Ok(wei("2000000000000")));
+    assert_eq!(amount::parse("7 wei"), Ok(U256::from(7)));
+    assert_eq!(amount::parse("100"), Ok(wei("100000000000000000000")));
+    assert_eq!(amount::parse(".5"), Ok(wei("500000000000000000")));
+    assert_eq!(amount::parse("0.000000000000000001"), Ok(U256::one()));
+    assert_eq!(amount::parse_in("3", Unit::Gwei), Ok(wei("3000000000")));
+}
+
+#[test]
+fn rejects_malformed_amounts() {
+    for input in [
+        "", ".", "1.", "1..2", "-1", "+1", "1e18", " 1", "1 ", "1  AXX", "١",
+    ] {
+        assert_eq!(
+            amount::parse(input),
+            Err(AmountError::Invalid),
+            "{:?}",
+            input
+        );
+    }
+    assert_eq!(
+        amount::parse("1 eth"),
+        Err(AmountError::UnknownUnit("eth".to_string()))
+    );
+    assert_eq!(amount::parse("1.5 wei"), Err(AmountError::TooPrecise));
+    assert_eq!(
+        amount::parse("0.0000000001 gwei"),
+        Err(AmountError::TooPrecise)
+    );
+}
+
+#[test]
+fn rejects_amounts_beyond_u256() {
+    let max = U256::MAX.to_string();
+    assert_eq!(amount::parse(&format!("{} wei", max)), Ok(U256::MAX));
+    assert_eq!(
+        amount::parse(&format!("{}0 wei", max)),
+        Err(AmountError::Overflow)
+    );
+    assert_eq!(amount::parse(&max), Err(AmountError::Overflow));
+}
+
+#[test]
+fn formats_without_trailing_zeros() {
+    assert_eq!(
+        amount::format(wei("1500000000000000000"), Unit::Axx),
+        "1.5 AXX"
+    );
+    assert_eq!(
+        amount::format(wei("2000000000000"), Unit::Gwei),
+        "2000 gwei"
+    );
+    assert_eq!(
+        amount::format(U256::one(), Unit::Axx),
+        "0.000000000000000001 AXX"
+    );
+    assert_eq!(amount::format(U256::zero(), Unit::Wei), "0 wei");
+}
+
+proptest! {
+    #[test]
+    fn formatted_amounts_parse_back(value in any::<u128>(), unit in 0usize..3) {
+        let unit = [Unit::Wei, Unit::Gwei, Unit::Axx][unit];
+        let value = U256::from(value);
+        prop_assert_eq!(amount::parse(&amount::format(value, unit)), Ok(value));
+    }
+}
@@ -0,0 +1,96 @@
+//! Building and signing transactions
+
+use axionax_tx::rlp::Item;
+use axionax_tx::{parse_address, AccessListItem, Transaction, TxError, U256};
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+
+fn key() -> SigningKey {
+    SigningKey::from_slice(&[0x46; 32]).unwrap()
+}
+
+/// The transfer from the EIP-155 example
+fn transfer() -> Transaction {
+    Transaction::transfer(1, [0x35; 20], U256::exp10(18))
+        .with_nonce(9)
+        .with_gas_price(U256::from(20_000_000_000u64))
+}
+
+/// Check that the signature at the end of `raw` recovers to `key`
+fn assert_signed_by(tx: &Transaction, raw: &[u8], key: &SigningKey) {
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&tx.signing_hash()).unwrap();
+    let (r, s) = signature.split_bytes();
+    let mut tail = Item::from(u64::from(recovery_id.to_byte())).encode();
+    tail.extend(Item::uint(&r).encode());
+    tail.extend(Item::uint(&s).encode());
+    assert!(raw.ends_with(&tail));
+
+    let signature = Signature::from_scalars(r, s).unwrap();
+    let recovered = VerifyingKey::recover_from_prehash(
+        &tx.signing_hash(),
+        &signature,
+        RecoveryId::from_byte(recovery_id.to_byte()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(&recovered, key.verifying_key());
+}
+
+#[test]
+fn signs_legacy_transactions_with_eip155() {
+    let tx = transfer();
+    assert_eq!(
+        format!("0x{}", hex::encode(tx.signing_hash())),
+        "0xdaf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+    );
+    let signed = tx.sign(&key()).unwrap();
+    assert_eq!(
+        signed.raw_hex(),
+        "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000\
+         8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f\
+         761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+    );
+    assert_eq!(
+        signed.hash_hex(),
+        "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788"
+    );
+}
+
+#[test]
+fn access_list_makes_an_eip2930_transaction() {
+    let tx = transfer().with_access_list(vec![AccessListItem {
+        address: [0x11; 20],
+        storage_keys: vec![[0x22; 32]],
+    }]);
+    assert_eq!(tx.tx_type(), 1);
+
+    let signed = tx.sign(&key()).unwrap();
+    assert_eq!(signed.raw[0], 0x01);
+    assert_signed_by(&tx, &signed.raw, &key());
+}
+
+#[test]
+fn dynamic_fee_makes_an_eip1559_transaction() {
+    let tx = transfer()
+        .with_dynamic_fee(U256::from(30_000_000_000u64), U256::from(1_000_000_000u64))
+        .with_gas_limit(50_000)
+        .with_input(vec![0xde, 0xad]);
+    assert_eq!(tx.tx_type(), 2);
+    assert_eq!(tx.max_fee(), U256::from(1_500_000_000_000_000u64));
+
+    let signed = tx.sign(&key()).unwrap();
+    assert_eq!(signed.raw[0], 0x02);
+    assert_signed_by(&tx, &signed.raw, &key());
+
+    // Switching back to a gas price keeps the (empty) access list
+    assert_eq!(tx.with_gas_price(U256::one()).tx_type(), 0);
+}
+
+#[test]
+fn parses_addresses() {
+    assert_eq!(
+        parse_address("0x3535353535353535353535353535353535353535"),
+        Ok([0x35; 20])
+    );
+    for address in ["3535353535353535353535353535353535353535", "0x35", "0xzz"] {
+        assert_eq!(parse_address(address), Err(TxError::InvalidAddress));
+    }
+}